@@ -10,5 +10,6 @@ mod process;
 
 pub use self::{
     parse::{parse, ParseError},
+    print::value as print_value,
     process::{process, ProcessError},
 };
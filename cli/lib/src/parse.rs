@@ -55,7 +55,7 @@ impl From<RequestBuilderError> for ParseError {
     }
 }
 
-pub fn parse(input: &str) -> Result<Request, ParseError> {
+pub fn parse(input: &str) -> Result<Request<'_>, ParseError> {
     let mut split = input.split(' ');
 
     let cmd_name = match split.next() {
@@ -104,13 +104,13 @@ fn input_args<'a>(
     match cmd_id.key_notation() {
         KeyNotation::None => {}
         KeyNotation::One => {
-            let arg = iter.next().ok_or_else(|| ParseError::KeyUnspecified)?;
+            let arg = iter.next().ok_or(ParseError::KeyUnspecified)?;
 
             args.push(arg.as_bytes().to_vec());
         }
         KeyNotation::Two => {
             for _ in 0..2 {
-                let arg = iter.next().ok_or_else(|| ParseError::KeyUnspecified)?;
+                let arg = iter.next().ok_or(ParseError::KeyUnspecified)?;
 
                 args.push(arg.as_bytes().to_vec());
             }
@@ -168,6 +168,21 @@ fn input_args<'a>(
             KeyType::Set => {
                 args.push(arg.as_bytes().to_vec());
             }
+            KeyType::SortedSet => {
+                let score = arg
+                    .parse::<f64>()
+                    .map_err(|_| ParseError::ArgumentInvalid {
+                        argument: arg.to_owned(),
+                        key_type: KeyType::SortedSet,
+                    })?;
+                let member = match iter.next() {
+                    Some(arg) => arg.as_bytes().to_vec(),
+                    None => break,
+                };
+
+                args.push(score.to_be_bytes().to_vec());
+                args.push(member);
+            }
             KeyType::String => {
                 args.push(arg.as_bytes().to_vec());
             }
@@ -200,6 +215,7 @@ fn key_type(key_type: &str) -> Option<KeyType> {
         "list" => KeyType::List,
         "map" => KeyType::Map,
         "set" => KeyType::Set,
+        "sortedset" | "zset" => KeyType::SortedSet,
         "string" | "str" => KeyType::String,
         _ => return None,
     })
@@ -214,6 +230,7 @@ fn key_type_name(key_type: KeyType) -> &'static str {
         KeyType::List => "list",
         KeyType::Map => "map",
         KeyType::Set => "set",
+        KeyType::SortedSet => "zset",
         KeyType::String => "str",
     }
 }
@@ -270,6 +287,8 @@ mod tests {
         assert_eq!(Some(KeyType::List), super::key_type("list"));
         assert_eq!(Some(KeyType::Map), super::key_type("map"));
         assert_eq!(Some(KeyType::Set), super::key_type("set"));
+        assert_eq!(Some(KeyType::SortedSet), super::key_type("sortedset"));
+        assert_eq!(Some(KeyType::SortedSet), super::key_type("zset"));
         assert_eq!(Some(KeyType::String), super::key_type("string"));
         assert_eq!(Some(KeyType::String), super::key_type("str"));
     }
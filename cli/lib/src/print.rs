@@ -9,6 +9,7 @@ pub fn key_type_name(key_type: KeyType) -> &'static str {
         KeyType::List => "list",
         KeyType::Map => "map",
         KeyType::Set => "set",
+        KeyType::SortedSet => "zset",
         KeyType::String => "str",
     }
 }
@@ -21,7 +22,8 @@ pub fn list<T: IntoIterator<Item = U>, U: AsRef<[u8]>>(items: T) -> String {
         output.push('\n');
     }
 
-    output.truncate(output.len() - 2);
+    // remove the trailing newline
+    output.pop();
 
     output
 }
@@ -36,7 +38,8 @@ pub fn map<T: IntoIterator<Item = (U, V)>, U: AsRef<[u8]>, V: AsRef<[u8]>>(pairs
         output.push('\n');
     }
 
-    output.truncate(output.len() - 2);
+    // remove the trailing newline
+    output.pop();
 
     output
 }
@@ -47,9 +50,13 @@ pub fn value(value: Value) -> String {
         Value::Bytes(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
         Value::Float(float) => float.to_string(),
         Value::Integer(int) => int.to_string(),
+        Value::IntSet(set) => list(set.members()),
         Value::List(value_list) => list(value_list),
-        Value::Map(value_map) => map(value_map),
+        Value::Map(value_map) => map(value_map.into_iter().map(|(k, v)| (k, self::value(v)))),
         Value::Set(set) => list(set),
+        Value::SortedSet(set) => map(set
+            .iter()
+            .map(|(member, score)| (member, score.to_string()))),
         Value::String(string) => string,
     }
 }
@@ -77,7 +77,9 @@ enum InnerProcessError<B: Backend> {
     BadRequest,
     BuildingRequest,
     Dispatching,
+    InvalidUtf8,
     KeyDestinationRequired,
+    KeyExists,
     KeyNonexistent,
     KeyRequiredMinimum,
     KeySourceRequired,
@@ -86,9 +88,14 @@ enum InnerProcessError<B: Backend> {
     KeyTypeRequired,
     KeyTypeUnexpected,
     KeyUnspecified,
+    MalformedNumber,
+    OutOfMemory,
     PreconditionFailed,
+    RequestPending,
+    ResponseTooLarge,
     TooFewArguments,
     TooManyArguments,
+    ValueTooLarge,
     WritingOutput { source: FmtError },
 }
 
@@ -128,9 +135,11 @@ where
         Err(InnerProcessError::Dispatching) => {
             "The engine failed to properly run the request.".into()
         }
+        Err(InnerProcessError::InvalidUtf8) => "A string argument isn't valid UTF-8.".into(),
         Err(InnerProcessError::KeyDestinationRequired) => {
             "The destination key name is required.".into()
         }
+        Err(InnerProcessError::KeyExists) => "The specified key already exists.".into(),
         Err(InnerProcessError::KeyNonexistent) => "The specified key does not exist.".into(),
         Err(InnerProcessError::KeyRequiredMinimum) => {
             "A minimum of at least one key or more is required.".into()
@@ -149,15 +158,30 @@ where
             "A key type was specified when the command can't be given one.".into()
         }
         Err(InnerProcessError::KeyUnspecified) => "Specifying a key is required.".into(),
+        Err(InnerProcessError::MalformedNumber) => {
+            "A number argument wasn't encoded as 8 bytes.".into()
+        }
+        Err(InnerProcessError::OutOfMemory) => {
+            "The write doesn't fit within the maximum memory, even after evicting keys.".into()
+        }
         Err(InnerProcessError::PreconditionFailed) => {
             "A precondition failed, such as the key not existing.".into()
         }
+        Err(InnerProcessError::RequestPending) => {
+            "A request with the same idempotency token is still being run.".into()
+        }
+        Err(InnerProcessError::ResponseTooLarge) => {
+            "The response has more items than can be written.".into()
+        }
         Err(InnerProcessError::TooFewArguments) => {
             "Too few arguments were provided for this command.".into()
         }
         Err(InnerProcessError::TooManyArguments) => {
             "You may only provide at most 255 arguments.".into()
         }
+        Err(InnerProcessError::ValueTooLarge) => {
+            "The write would grow a value past its maximum size.".into()
+        }
         Err(InnerProcessError::WritingOutput { source }) => {
             format!("Failed to write the response: {}", source).into()
         }
@@ -180,13 +204,20 @@ where
                 MemoryError::KeyTypeUnsupported { .. } => InnerProcessError::KeyTypeInvalid,
                 MemoryError::RunningCommand { source } => match source {
                     DispatchError::ArgumentRetrieval => InnerProcessError::TooFewArguments,
+                    DispatchError::InvalidUtf8 => InnerProcessError::InvalidUtf8,
+                    DispatchError::KeyExists => InnerProcessError::KeyExists,
                     DispatchError::KeyNonexistent => InnerProcessError::KeyNonexistent,
                     DispatchError::KeyTypeDifferent => InnerProcessError::KeyTypeDifferent,
                     DispatchError::KeyTypeInvalid => InnerProcessError::KeyTypeInvalid,
                     DispatchError::KeyTypeRequired => InnerProcessError::KeyTypeRequired,
                     DispatchError::KeyTypeUnexpected => InnerProcessError::KeyTypeUnexpected,
                     DispatchError::KeyUnspecified => InnerProcessError::KeyUnspecified,
+                    DispatchError::MalformedNumber => InnerProcessError::MalformedNumber,
+                    DispatchError::OutOfMemory => InnerProcessError::OutOfMemory,
                     DispatchError::PreconditionFailed => InnerProcessError::PreconditionFailed,
+                    DispatchError::RequestPending => InnerProcessError::RequestPending,
+                    DispatchError::ResponseTooLarge => InnerProcessError::ResponseTooLarge,
+                    DispatchError::ValueTooLarge => InnerProcessError::ValueTooLarge,
                 },
             }
         }
@@ -262,7 +293,9 @@ where
 
             let value = client.get(key).await.map_err(backend_err)?;
 
-            Ok(print::value(value).into())
+            Ok(value
+                .map_or_else(|| "(nil)".to_owned(), print::value)
+                .into())
         }
         CommandId::Increment => {
             let key = req.key().ok_or_else(|| InnerProcessError::KeyUnspecified)?;
@@ -301,7 +334,7 @@ where
 
             let v = client.rename(from, to).await.map_err(backend_err)?;
 
-            Ok(String::from_utf8_lossy(&v).into_owned().into())
+            Ok(print::value(v).into())
         }
         CommandId::Stats => {
             let stats = client.stats().await.map_err(backend_err)?;
@@ -315,6 +348,16 @@ where
             .map_err(|source| InnerProcessError::WritingOutput { source })?;
             writeln!(output, "Commands errored: {}", stats.commands_errored())
                 .map_err(|source| InnerProcessError::WritingOutput { source })?;
+            writeln!(output, "Connections opened: {}", stats.connections_opened())
+                .map_err(|source| InnerProcessError::WritingOutput { source })?;
+            writeln!(output, "Connections active: {}", stats.connections_active())
+                .map_err(|source| InnerProcessError::WritingOutput { source })?;
+            writeln!(output, "Bytes read: {}", stats.bytes_read())
+                .map_err(|source| InnerProcessError::WritingOutput { source })?;
+            writeln!(output, "Bytes written: {}", stats.bytes_written())
+                .map_err(|source| InnerProcessError::WritingOutput { source })?;
+            writeln!(output, "Keys evicted: {}", stats.keys_evicted())
+                .map_err(|source| InnerProcessError::WritingOutput { source })?;
             writeln!(output, "Sessions started: {}", stats.sessions_started())
                 .map_err(|source| InnerProcessError::WritingOutput { source })?;
             write!(output, "Sessions ended: {}", stats.sessions_ended())
@@ -322,6 +365,15 @@ where
 
             Ok(output.into())
         }
+        CommandId::Touch => {
+            let args = req
+                .args(..)
+                .ok_or_else(|| InnerProcessError::KeyUnspecified)?;
+
+            let touched = client.touch().keys(args)?.await.map_err(backend_err)?;
+
+            Ok(touched.to_string().into())
+        }
         CommandId::Type => {
             let key = req.key().ok_or_else(|| InnerProcessError::KeyUnspecified)?;
 
@@ -1,18 +1,19 @@
-use super::Backend;
-use crate::model::StatsData;
-use alloc::{boxed::Box, vec::Vec};
+use super::{Backend, KeyspaceEvents};
+use crate::model::{CommandsData, StatsData};
+use alloc::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
 use async_trait::async_trait;
 use core::{
     convert::TryInto,
     fmt::{Display, Formatter, Result as FmtResult},
 };
+use futures_util::stream::StreamExt;
 use hop_engine::{
     command::{
         request::{ParseError as RequestParseError, RequestBuilder, RequestBuilderError},
-        response::{Context, Instruction, Response},
+        response::Response,
         CommandId, DispatchError, Request,
     },
-    state::{KeyType, Value},
+    state::{KeyType, ListEnd, Value},
     Hop,
 };
 
@@ -51,7 +52,7 @@ impl Display for Error {
     }
 }
 
-#[cfg(all(not(no_std), feature = "std"))]
+#[cfg(feature = "std")]
 mod if_std {
     use super::Error;
     use std::error::Error as StdError;
@@ -92,22 +93,49 @@ impl MemoryBackend {
         Default::default()
     }
 
+    /// Create a backend using an existing engine instance.
+    ///
+    /// Hop instances are cheap to clone and clones share their state, so
+    /// multiple backends created from clones of one instance see each other's
+    /// writes, like clients of the same server.
+    pub fn from_hop(hop: Hop) -> Self {
+        Self { hop }
+    }
+
     fn send<'a>(&self, req: impl Into<Request<'a>>) -> Result<Value, Error> {
+        // Only commands sent with `send_optional` respond with nil.
+        self.send_optional(req)
+            .map(|value| value.expect("response isn't nil"))
+    }
+
+    /// Send a request whose response may be nil, such as a get of a key that
+    /// doesn't exist.
+    fn send_optional<'a>(&self, req: impl Into<Request<'a>>) -> Result<Option<Value>, Error> {
         let mut resp = Vec::new();
 
         self.hop.dispatch(&req.into(), &mut resp)?;
 
-        let mut ctx = Context::new();
+        // The engine always writes a complete, valid response.
+        match Response::parse(&resp).unwrap() {
+            Response::Value(value) => Ok(Some(value)),
+            Response::Nil => Ok(None),
+            Response::Array(_) => unreachable!("arrays are only requested with `send_array`"),
+            Response::DispatchError(source) => Err(Error::Dispatching { source }),
+            Response::ParseError(source) => Err(Error::BadRequest { source }),
+        }
+    }
 
-        match ctx.feed(&resp).unwrap() {
-            Instruction::Concluded(Response::Value(value)) => Ok(value),
-            Instruction::Concluded(Response::DispatchError(source)) => {
-                Err(Error::Dispatching { source })
-            }
-            Instruction::Concluded(Response::ParseError(source)) => {
-                Err(Error::BadRequest { source })
-            }
-            Instruction::ReadBytes(_) => unreachable!(),
+    /// Send a request that responds with an array, such as a set scan.
+    fn send_array<'a>(&self, req: impl Into<Request<'a>>) -> Result<Vec<Response>, Error> {
+        let mut resp = Vec::new();
+
+        self.hop.dispatch(&req.into(), &mut resp)?;
+
+        match Response::parse(&resp).unwrap() {
+            Response::Array(items) => Ok(items),
+            Response::DispatchError(source) => Err(Error::Dispatching { source }),
+            Response::ParseError(source) => Err(Error::BadRequest { source }),
+            other => panic!("Other response: {:?}", other),
         }
     }
 }
@@ -145,6 +173,15 @@ impl Backend for MemoryBackend {
         self.send(builder)
     }
 
+    async fn commands(&self) -> Result<CommandsData, Self::Error> {
+        let builder = RequestBuilder::new(CommandId::Command);
+
+        match self.send(builder)? {
+            Value::List(list) => Ok(CommandsData::from_list(&list).unwrap()),
+            _ => panic!(),
+        }
+    }
+
     async fn decrement_by<T: Into<Value> + Send>(
         &self,
         key: &[u8],
@@ -195,7 +232,7 @@ impl Backend for MemoryBackend {
         builder.bytes(content)?;
 
         match self.send(builder)? {
-            Value::List(list) => Ok(list),
+            Value::List(list) => Ok(list.into()),
             _ => panic!(),
         }
     }
@@ -216,11 +253,27 @@ impl Backend for MemoryBackend {
         }
     }
 
-    async fn get(&self, key: &[u8]) -> Result<Value, Self::Error> {
-        let mut builder = RequestBuilder::new(CommandId::Get);
+    async fn get(
+        &self,
+        key: &[u8],
+        key_type: Option<KeyType>,
+    ) -> Result<Option<Value>, Self::Error> {
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Get, key_type);
         builder.bytes(key)?;
 
-        self.send(builder)
+        self.send_optional(builder)
+    }
+
+    async fn get_range(&self, key: &[u8], offset: u64, len: u64) -> Result<Vec<u8>, Self::Error> {
+        let mut builder = RequestBuilder::new(CommandId::GetRange);
+        builder.bytes(key)?;
+        builder.bytes(super::range_position(offset).to_be_bytes().as_ref())?;
+        builder.bytes(super::range_position(len).to_be_bytes().as_ref())?;
+
+        match self.send(builder)? {
+            Value::Bytes(bytes) => Ok(bytes),
+            other => panic!("Other response: {:?}", other),
+        }
     }
 
     async fn increment_by<T: Into<Value> + Send>(
@@ -275,6 +328,12 @@ impl Backend for MemoryBackend {
         }
     }
 
+    async fn info(&self) -> Result<BTreeMap<String, String>, Self::Error> {
+        let builder = RequestBuilder::new(CommandId::Info);
+
+        Ok(super::parse_info(self.send(builder)?).unwrap())
+    }
+
     async fn key_type(&self, key: &[u8]) -> Result<KeyType, Self::Error> {
         let mut builder = RequestBuilder::new(CommandId::Type);
         builder.bytes(key)?;
@@ -296,7 +355,7 @@ impl Backend for MemoryBackend {
         builder.bytes(key)?;
 
         match self.send(builder)? {
-            Value::List(list) => Ok(list),
+            Value::List(list) => Ok(list.into()),
             _ => panic!(),
         }
     }
@@ -311,14 +370,52 @@ impl Backend for MemoryBackend {
         }
     }
 
-    async fn rename(&self, from: &[u8], to: &[u8]) -> Result<Vec<u8>, Self::Error> {
+    async fn list_peek(&self, key: &[u8], end: ListEnd) -> Result<Option<Vec<u8>>, Self::Error> {
+        let mut builder = RequestBuilder::new(CommandId::ListPeek);
+        builder.bytes(key)?;
+        builder.bytes([end as u8].as_ref())?;
+
+        match self.send_optional(builder)? {
+            Some(Value::Bytes(item)) => Ok(Some(item)),
+            None => Ok(None),
+            Some(other) => panic!("Other response: {:?}", other),
+        }
+    }
+
+    async fn map_get_all(&self, key: &[u8]) -> Result<BTreeMap<Vec<u8>, Value>, Self::Error> {
+        let mut builder = RequestBuilder::new(CommandId::MapGetAll);
+        builder.bytes(key)?;
+
+        match self.send(builder)? {
+            Value::Map(map) => Ok(map.into_iter().collect()),
+            other => panic!("Other response: {:?}", other),
+        }
+    }
+
+    async fn rename(&self, from: &[u8], to: &[u8]) -> Result<Value, Self::Error> {
         let mut builder = RequestBuilder::new(CommandId::Rename);
         builder.bytes(from)?;
         builder.bytes(to)?;
 
-        match self.send(builder)? {
-            Value::Bytes(bytes) => Ok(bytes),
-            _ => panic!(),
+        self.send(builder)
+    }
+
+    async fn scan(
+        &self,
+        pattern: &[u8],
+        cursor: u64,
+        count: u64,
+    ) -> Result<(u64, Vec<Vec<u8>>), Self::Error> {
+        let mut builder = RequestBuilder::new(CommandId::Scan);
+        builder.value(cursor as i64)?;
+        builder.bytes(pattern)?;
+        builder.value(count as i64)?;
+
+        match self.send_array(builder)?.as_slice() {
+            [Response::Value(Value::Integer(next)), Response::Value(Value::List(keys))] => {
+                Ok((*next as u64, keys.iter().cloned().collect()))
+            }
+            other => panic!("Other response: {:?}", other),
         }
     }
 
@@ -333,6 +430,25 @@ impl Backend for MemoryBackend {
         self.send(builder)
     }
 
+    async fn set_scan(
+        &self,
+        key: &[u8],
+        cursor: u64,
+        count: u64,
+    ) -> Result<(u64, Vec<Vec<u8>>), Self::Error> {
+        let mut builder = RequestBuilder::new(CommandId::SetScan);
+        builder.bytes(key)?;
+        builder.value(cursor as i64)?;
+        builder.value(count as i64)?;
+
+        match self.send_array(builder)?.as_slice() {
+            [Response::Value(Value::Integer(next)), Response::Value(Value::List(members))] => {
+                Ok((*next as u64, members.iter().cloned().collect()))
+            }
+            other => panic!("Other response: {:?}", other),
+        }
+    }
+
     async fn stats(&self) -> Result<StatsData, Self::Error> {
         let builder = RequestBuilder::new(CommandId::Stats);
 
@@ -341,16 +457,58 @@ impl Backend for MemoryBackend {
             _ => panic!(),
         };
 
-        Ok(StatsData::new(stats.into_iter().collect()))
+        Ok(StatsData::new(stats))
+    }
+
+    async fn time(&self) -> Result<(u64, u32), Self::Error> {
+        let builder = RequestBuilder::new(CommandId::Time);
+
+        Ok(super::parse_time(self.send(builder)?).unwrap())
+    }
+
+    async fn touch<T: IntoIterator<Item = U> + Send, U: AsRef<[u8]> + Send>(
+        &self,
+        keys: T,
+    ) -> Result<i64, Self::Error> {
+        let mut builder = RequestBuilder::new(CommandId::Touch);
+
+        for key in keys {
+            builder.bytes(key.as_ref())?;
+        }
+
+        match self.send(builder)? {
+            Value::Integer(touched) => Ok(touched),
+            other => panic!("Other response: {:?}", other),
+        }
+    }
+
+    async fn watch_keyspace(
+        &self,
+        pattern: &[u8],
+    ) -> Result<KeyspaceEvents<Self::Error>, Self::Error> {
+        let mut builder = RequestBuilder::new(CommandId::Watch);
+        builder.bytes(pattern)?;
+
+        match self.send(builder)? {
+            Value::Boolean(true) => {}
+            other => panic!("Other response: {:?}", other),
+        }
+
+        let rx = self.hop.pubsub().watch_keyspace(pattern.to_vec());
+
+        Ok(rx.into_stream().map(Ok).boxed())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{Backend, Error, MemoryBackend};
+    use futures_util::stream::StreamExt;
     use hop_engine::{
+        command::{CommandId, DispatchError},
         dashmap::{DashMap, DashSet},
         state::{KeyType, Value},
+        Hop,
     };
     use static_assertions::assert_impl_all;
     use std::fmt::Debug;
@@ -358,6 +516,47 @@ mod tests {
     assert_impl_all!(Error: Debug, Send, Sync);
     assert_impl_all!(MemoryBackend: Debug, Default, Send, Sync);
 
+    #[tokio::test]
+    async fn test_from_hop_shared() {
+        let hop = Hop::new();
+        let a = MemoryBackend::from_hop(hop.clone());
+        let b = MemoryBackend::from_hop(hop);
+
+        assert!(a.set(b"foo", Value::Integer(1)).await.is_ok());
+        assert_eq!(
+            Some(&1),
+            b.get(b"foo", None).await.unwrap().unwrap().as_integer_ref()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename() {
+        let backend = MemoryBackend::new();
+        assert!(backend.set(b"foo", Value::Integer(1)).await.is_ok());
+        assert!(backend.set(b"bar", Value::Integer(2)).await.is_ok());
+
+        // the moved value is returned and the destination is overwritten
+        let moved = backend.rename(b"foo", b"bar").await.unwrap();
+        assert_eq!(Some(&1), moved.as_integer_ref());
+        assert_eq!(
+            Some(&1),
+            backend
+                .get(b"bar", None)
+                .await
+                .unwrap()
+                .unwrap()
+                .as_integer_ref()
+        );
+        assert!(backend.get(b"foo", None).await.unwrap().is_none());
+
+        assert!(matches!(
+            backend.rename(b"foo", b"bar").await,
+            Err(Error::RunningCommand {
+                source: DispatchError::KeyNonexistent
+            })
+        ));
+    }
+
     #[tokio::test]
     async fn test_append() {
         let backend = MemoryBackend::new();
@@ -388,6 +587,29 @@ mod tests {
         assert!(matches!(backend.echo(b"test").await, Ok(vec) if vec == vec![b"test"]));
     }
 
+    #[tokio::test]
+    async fn test_touch() {
+        let backend = MemoryBackend::new();
+        backend.set(b"foo", 1).await.unwrap();
+        backend.set(b"bar", 2).await.unwrap();
+
+        assert_eq!(1, backend.touch(["foo", "baz"]).await.unwrap());
+        assert_eq!(2, backend.touch(["foo", "bar"]).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_watch_keyspace() {
+        let backend = MemoryBackend::new();
+        let mut events = backend.watch_keyspace(b"foo*").await.unwrap();
+
+        backend.set(b"bar", 1).await.unwrap();
+        backend.set(b"foobar", 1).await.unwrap();
+
+        let event = events.next().await.unwrap().unwrap();
+        assert_eq!(CommandId::Set, event.command_id());
+        assert_eq!(b"foobar", event.key());
+    }
+
     #[tokio::test]
     async fn test_is() {
         let backend = MemoryBackend::new();
@@ -411,11 +633,9 @@ mod tests {
 
     #[tokio::test]
     async fn test_length_list() {
-        let mut list = Vec::new();
-        list.push(b"foo".to_vec());
-        list.push(b"bar".to_vec());
+        let list = vec![b"foo".to_vec(), b"bar".to_vec()];
         let backend = MemoryBackend::new();
-        assert!(backend.set(b"foo", Value::List(list)).await.is_ok());
+        assert!(backend.set(b"foo", Value::from(list)).await.is_ok());
         assert_eq!(
             2,
             backend.length(b"foo", Some(KeyType::List)).await.unwrap()
@@ -425,8 +645,8 @@ mod tests {
     #[tokio::test]
     async fn test_length_map() {
         let map = DashMap::new();
-        map.insert(b"foo".to_vec(), b"value".to_vec());
-        map.insert(b"bar".to_vec(), b"value".to_vec());
+        map.insert(b"foo".to_vec(), Value::Bytes(b"value".to_vec()));
+        map.insert(b"bar".to_vec(), Value::Bytes(b"value".to_vec()));
         let backend = MemoryBackend::new();
         assert!(backend.set(b"foo", Value::Map(map)).await.is_ok());
         assert_eq!(2, backend.length(b"foo", Some(KeyType::Map)).await.unwrap());
@@ -1,22 +1,62 @@
 pub mod memory;
+pub mod retry;
 
 #[cfg(all(not(target_arch = "wasm32"), feature = "tokio"))]
 pub mod server;
 
-pub use self::memory::MemoryBackend;
+#[cfg(all(test, not(target_arch = "wasm32"), feature = "tokio"))]
+pub(crate) mod test_server;
+
+pub use self::{
+    memory::MemoryBackend,
+    retry::{RetryBackend, RetryPolicy},
+};
 
 #[cfg(all(not(target_arch = "wasm32"), feature = "tokio"))]
 pub use self::server::ServerBackend;
 
-use crate::model::StatsData;
-use alloc::{boxed::Box, vec::Vec};
+use crate::model::{CommandsData, StatsData};
+use alloc::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
 use async_trait::async_trait;
-use hop_engine::state::{KeyType, Value};
+use core::convert::{TryFrom, TryInto};
+use futures_util::stream::BoxStream;
+use hop_engine::{
+    pubsub::KeyspaceEvent,
+    state::{KeyType, ListEnd, Value},
+};
+
+/// Stream of keyspace events returned by [`Backend::watch_keyspace`].
+///
+/// [`Backend::watch_keyspace`]: trait.Backend.html#tymethod.watch_keyspace
+pub type KeyspaceEvents<E> = BoxStream<'static, Result<KeyspaceEvent, E>>;
 
 #[async_trait]
 pub trait Backend: Send + Sync {
     type Error;
 
+    /// Whether an error was caused by the connection to the instance, such
+    /// that [reconnecting] and trying again may succeed.
+    ///
+    /// By default no errors are connection errors.
+    ///
+    /// [reconnecting]: #method.reconnect
+    fn is_connection_error(&self, _error: &Self::Error) -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    /// Re-establish the connection to the instance.
+    ///
+    /// By default this does nothing.
+    async fn reconnect(&self) -> Result<(), Self::Error>
+    where
+        Self: Sized,
+    {
+        Ok(())
+    }
+
     async fn append<T: Into<Value> + Send>(
         &self,
         key: &[u8],
@@ -25,6 +65,11 @@ pub trait Backend: Send + Sync {
     where
         Self: Sized;
 
+    /// Retrieve the protocol version and commands supported by the instance.
+    async fn commands(&self) -> Result<CommandsData, Self::Error>
+    where
+        Self: Sized;
+
     async fn decrement_by<T: Into<Value> + Send>(
         &self,
         key: &[u8],
@@ -52,7 +97,20 @@ pub trait Backend: Send + Sync {
     where
         Self: Sized;
 
-    async fn get(&self, key: &[u8]) -> Result<Value, Self::Error>
+    /// Retrieve a key's value, which is `None` if the key doesn't exist.
+    ///
+    /// If a key type is given then the value is guaranteed to be of that
+    /// type, and an error is returned if the key is of another type.
+    async fn get(
+        &self,
+        key: &[u8],
+        key_type: Option<KeyType>,
+    ) -> Result<Option<Value>, Self::Error>
+    where
+        Self: Sized;
+
+    /// Retrieve up to `len` bytes of a bytes key starting at `offset`.
+    async fn get_range(&self, key: &[u8], offset: u64, len: u64) -> Result<Vec<u8>, Self::Error>
     where
         Self: Sized;
 
@@ -76,6 +134,11 @@ pub trait Backend: Send + Sync {
     where
         Self: Sized;
 
+    /// Retrieve the instance's metadata, such as its version, by name.
+    async fn info(&self) -> Result<BTreeMap<String, String>, Self::Error>
+    where
+        Self: Sized;
+
     async fn key_type(&self, key: &[u8]) -> Result<KeyType, Self::Error>
     where
         Self: Sized;
@@ -88,7 +151,36 @@ pub trait Backend: Send + Sync {
     where
         Self: Sized;
 
-    async fn rename(&self, from: &[u8], to: &[u8]) -> Result<Vec<u8>, Self::Error>
+    /// Retrieve the item at an end of a list without removing it, which is
+    /// `None` if the list is empty or doesn't exist.
+    async fn list_peek(&self, key: &[u8], end: ListEnd) -> Result<Option<Vec<u8>>, Self::Error>
+    where
+        Self: Sized;
+
+    /// Retrieve every field of a map and its value, which is empty if the map
+    /// doesn't exist.
+    async fn map_get_all(&self, key: &[u8]) -> Result<BTreeMap<Vec<u8>, Value>, Self::Error>
+    where
+        Self: Sized;
+
+    /// Move a key's value and expiry to another key, returning the moved
+    /// value, which was the source key's value before the move.
+    ///
+    /// A destination key that already exists is overwritten. Errors if the
+    /// source key doesn't exist.
+    async fn rename(&self, from: &[u8], to: &[u8]) -> Result<Value, Self::Error>
+    where
+        Self: Sized;
+
+    /// Retrieve a page of up to `count` of the keys matching a glob-style
+    /// pattern starting at a cursor, along with the cursor of the next page,
+    /// which is 0 once every matching key has been retrieved.
+    async fn scan(
+        &self,
+        pattern: &[u8],
+        cursor: u64,
+        count: u64,
+    ) -> Result<(u64, Vec<Vec<u8>>), Self::Error>
     where
         Self: Sized;
 
@@ -96,7 +188,68 @@ pub trait Backend: Send + Sync {
     where
         Self: Sized;
 
+    /// Retrieve a page of up to `count` of a set's members starting at a
+    /// cursor, along with the cursor of the next page, which is 0 once every
+    /// member has been retrieved.
+    async fn set_scan(
+        &self,
+        key: &[u8],
+        cursor: u64,
+        count: u64,
+    ) -> Result<(u64, Vec<Vec<u8>>), Self::Error>
+    where
+        Self: Sized;
+
     async fn stats(&self) -> Result<StatsData, Self::Error>
     where
         Self: Sized;
+
+    /// Retrieve the instance's current Unix time as seconds and microseconds.
+    async fn time(&self) -> Result<(u64, u32), Self::Error>
+    where
+        Self: Sized;
+
+    async fn touch<T: IntoIterator<Item = U> + Send, U: AsRef<[u8]> + Send>(
+        &self,
+        keys: T,
+    ) -> Result<i64, Self::Error>
+    where
+        Self: Sized;
+
+    async fn watch_keyspace(
+        &self,
+        pattern: &[u8],
+    ) -> Result<KeyspaceEvents<Self::Error>, Self::Error>
+    where
+        Self: Sized;
+}
+
+/// Parse the response of a time command, a list of the seconds and
+/// microseconds as big endian integers.
+fn parse_time(value: Value) -> Option<(u64, u32)> {
+    let list = value.as_list_ref()?;
+    let secs = u64::from_be_bytes(list.front()?.as_slice().try_into().ok()?);
+    let micros = u64::from_be_bytes(list.get(1)?.as_slice().try_into().ok()?);
+
+    Some((secs, micros.try_into().ok()?))
+}
+
+/// Parse the response of an info command, a string of `key:value` lines.
+fn parse_info(value: Value) -> Option<BTreeMap<String, String>> {
+    value
+        .as_string_ref()?
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let idx = line.find(':')?;
+
+            Some((line[..idx].into(), line[idx + 1..].into()))
+        })
+        .collect()
+}
+
+/// Convert a position within a value to a range request argument, saturating
+/// positions the server can't represent.
+fn range_position(position: u64) -> i64 {
+    i64::try_from(position).unwrap_or(i64::MAX)
 }
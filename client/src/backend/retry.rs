@@ -0,0 +1,700 @@
+use super::{Backend, KeyspaceEvents};
+use crate::model::{CommandsData, StatsData};
+use alloc::{boxed::Box, collections::BTreeMap, string::String, sync::Arc, vec::Vec};
+use async_trait::async_trait;
+use core::future::Future;
+use hop_engine::{
+    command::CommandId,
+    state::{KeyType, ListEnd, Value},
+};
+
+/// Policy deciding how many times to retry a command that failed due to the
+/// connection.
+///
+/// Only commands that don't modify keys are retried; refer to
+/// [`CommandId::is_mutating`]. Mutating commands such as increments may have
+/// been applied before the connection failed, so retrying them could apply
+/// them twice.
+///
+/// [`CommandId::is_mutating`]: ../../hop_engine/command/enum.CommandId.html#method.is_mutating
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct RetryPolicy {
+    retries: usize,
+}
+
+impl RetryPolicy {
+    /// Create a policy retrying commands up to a number of times.
+    pub fn new(retries: usize) -> Self {
+        Self { retries }
+    }
+
+    /// Retrieve the maximum number of times a command is retried.
+    pub fn retries(&self) -> usize {
+        self.retries
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Create a policy retrying commands up to 3 times.
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+/// Backend wrapping another to retry commands that failed due to the
+/// connection, reconnecting before each retry.
+///
+/// Construct one via [`Client::with_retry`].
+///
+/// [`Client::with_retry`]: ../struct.Client.html#method.with_retry
+#[derive(Debug)]
+pub struct RetryBackend<B> {
+    inner: Arc<B>,
+    policy: RetryPolicy,
+}
+
+impl<B: Backend> RetryBackend<B> {
+    pub fn new(inner: Arc<B>, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    /// Retrieve the retry policy.
+    pub fn policy(&self) -> RetryPolicy {
+        self.policy
+    }
+
+    /// Run a command, retrying it according to the policy if it doesn't
+    /// modify keys.
+    ///
+    /// If reconnecting fails then its error is returned.
+    async fn retry<T, F, Fut>(&self, command_id: CommandId, mut f: F) -> Result<T, B::Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, B::Error>>,
+    {
+        let mut retries = if command_id.is_mutating() {
+            0
+        } else {
+            self.policy.retries
+        };
+
+        loop {
+            match f().await {
+                Err(why) if retries > 0 && self.inner.is_connection_error(&why) => retries -= 1,
+                other => return other,
+            }
+
+            self.inner.reconnect().await?;
+        }
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Backend for RetryBackend<B> {
+    type Error = B::Error;
+
+    fn is_connection_error(&self, error: &Self::Error) -> bool {
+        self.inner.is_connection_error(error)
+    }
+
+    async fn reconnect(&self) -> Result<(), Self::Error> {
+        self.inner.reconnect().await
+    }
+
+    // Commands given a value all modify keys, so they're never retried and
+    // the value doesn't need to be kept around for another attempt.
+    async fn append<T: Into<Value> + Send>(
+        &self,
+        key: &[u8],
+        value: T,
+    ) -> Result<Value, Self::Error> {
+        self.inner.append(key, value).await
+    }
+
+    async fn commands(&self) -> Result<CommandsData, Self::Error> {
+        self.retry(CommandId::Command, || self.inner.commands())
+            .await
+    }
+
+    async fn decrement_by<T: Into<Value> + Send>(
+        &self,
+        key: &[u8],
+        value: T,
+    ) -> Result<Value, Self::Error> {
+        self.inner.decrement_by(key, value).await
+    }
+
+    async fn decrement(&self, key: &[u8], key_type: Option<KeyType>) -> Result<Value, Self::Error> {
+        self.retry(CommandId::Decrement, || self.inner.decrement(key, key_type))
+            .await
+    }
+
+    async fn delete(&self, key: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        self.retry(CommandId::Delete, || self.inner.delete(key))
+            .await
+    }
+
+    async fn echo(&self, content: &[u8]) -> Result<Vec<Vec<u8>>, Self::Error> {
+        self.retry(CommandId::Echo, || self.inner.echo(content))
+            .await
+    }
+
+    async fn exists<T: IntoIterator<Item = U> + Send, U: AsRef<[u8]> + Send>(
+        &self,
+        keys: T,
+    ) -> Result<bool, Self::Error> {
+        let keys = collect_keys(keys);
+
+        self.retry(CommandId::Exists, || self.inner.exists(keys.iter()))
+            .await
+    }
+
+    async fn get(
+        &self,
+        key: &[u8],
+        key_type: Option<KeyType>,
+    ) -> Result<Option<Value>, Self::Error> {
+        self.retry(CommandId::Get, || self.inner.get(key, key_type))
+            .await
+    }
+
+    async fn get_range(&self, key: &[u8], offset: u64, len: u64) -> Result<Vec<u8>, Self::Error> {
+        self.retry(CommandId::GetRange, || {
+            self.inner.get_range(key, offset, len)
+        })
+        .await
+    }
+
+    async fn increment_by<T: Into<Value> + Send>(
+        &self,
+        key: &[u8],
+        value: T,
+    ) -> Result<Value, Self::Error> {
+        self.inner.increment_by(key, value).await
+    }
+
+    async fn increment(&self, key: &[u8], key_type: Option<KeyType>) -> Result<Value, Self::Error> {
+        self.retry(CommandId::Increment, || self.inner.increment(key, key_type))
+            .await
+    }
+
+    async fn is<T: IntoIterator<Item = U> + Send, U: AsRef<[u8]> + Send>(
+        &self,
+        key_type: KeyType,
+        keys: T,
+    ) -> Result<bool, Self::Error> {
+        let keys = collect_keys(keys);
+
+        self.retry(CommandId::Is, || self.inner.is(key_type, keys.iter()))
+            .await
+    }
+
+    async fn info(&self) -> Result<BTreeMap<String, String>, Self::Error> {
+        self.retry(CommandId::Info, || self.inner.info()).await
+    }
+
+    async fn key_type(&self, key: &[u8]) -> Result<KeyType, Self::Error> {
+        self.retry(CommandId::Type, || self.inner.key_type(key))
+            .await
+    }
+
+    async fn keys(&self, key: &[u8]) -> Result<Vec<Vec<u8>>, Self::Error> {
+        self.retry(CommandId::Keys, || self.inner.keys(key)).await
+    }
+
+    async fn length(&self, key: &[u8], key_type: Option<KeyType>) -> Result<i64, Self::Error> {
+        self.retry(CommandId::Length, || self.inner.length(key, key_type))
+            .await
+    }
+
+    async fn list_peek(&self, key: &[u8], end: ListEnd) -> Result<Option<Vec<u8>>, Self::Error> {
+        self.retry(CommandId::ListPeek, || self.inner.list_peek(key, end))
+            .await
+    }
+
+    async fn map_get_all(&self, key: &[u8]) -> Result<BTreeMap<Vec<u8>, Value>, Self::Error> {
+        self.retry(CommandId::MapGetAll, || self.inner.map_get_all(key))
+            .await
+    }
+
+    async fn rename(&self, from: &[u8], to: &[u8]) -> Result<Value, Self::Error> {
+        self.retry(CommandId::Rename, || self.inner.rename(from, to))
+            .await
+    }
+
+    async fn scan(
+        &self,
+        pattern: &[u8],
+        cursor: u64,
+        count: u64,
+    ) -> Result<(u64, Vec<Vec<u8>>), Self::Error> {
+        self.retry(CommandId::Scan, || self.inner.scan(pattern, cursor, count))
+            .await
+    }
+
+    async fn set<T: Into<Value> + Send>(&self, key: &[u8], value: T) -> Result<Value, Self::Error> {
+        self.inner.set(key, value).await
+    }
+
+    async fn set_scan(
+        &self,
+        key: &[u8],
+        cursor: u64,
+        count: u64,
+    ) -> Result<(u64, Vec<Vec<u8>>), Self::Error> {
+        self.retry(CommandId::SetScan, || {
+            self.inner.set_scan(key, cursor, count)
+        })
+        .await
+    }
+
+    async fn stats(&self) -> Result<StatsData, Self::Error> {
+        self.retry(CommandId::Stats, || self.inner.stats()).await
+    }
+
+    async fn time(&self) -> Result<(u64, u32), Self::Error> {
+        self.retry(CommandId::Time, || self.inner.time()).await
+    }
+
+    async fn touch<T: IntoIterator<Item = U> + Send, U: AsRef<[u8]> + Send>(
+        &self,
+        keys: T,
+    ) -> Result<i64, Self::Error> {
+        let keys = collect_keys(keys);
+
+        self.retry(CommandId::Touch, || self.inner.touch(keys.iter()))
+            .await
+    }
+
+    async fn watch_keyspace(
+        &self,
+        pattern: &[u8],
+    ) -> Result<KeyspaceEvents<Self::Error>, Self::Error> {
+        self.retry(CommandId::Watch, || self.inner.watch_keyspace(pattern))
+            .await
+    }
+}
+
+/// Collect keys so that they can be given to each attempt of a command.
+fn collect_keys<T: IntoIterator<Item = U>, U: AsRef<[u8]>>(keys: T) -> Vec<Vec<u8>> {
+    keys.into_iter().map(|key| key.as_ref().to_vec()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::memory::Error as MemoryError, RetryBackend, RetryPolicy};
+    use crate::{
+        backend::{Backend, KeyspaceEvents, MemoryBackend},
+        model::{CommandsData, StatsData},
+        Client,
+    };
+    use async_trait::async_trait;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use futures_util::stream::StreamExt;
+    use hop_engine::{
+        command::DispatchError,
+        dashmap::DashMap,
+        state::{KeyType, ListEnd, Value},
+    };
+    use static_assertions::assert_impl_all;
+    use std::{collections::BTreeMap, fmt::Debug, hash::Hash, sync::Arc};
+
+    assert_impl_all!(RetryBackend<MemoryBackend>: Backend, Debug, Send, Sync);
+    assert_impl_all!(
+        RetryPolicy: Clone,
+        Copy,
+        Debug,
+        Default,
+        Eq,
+        Hash,
+        PartialEq
+    );
+
+    #[derive(Debug)]
+    enum FlakyError {
+        Connection,
+        Memory(MemoryError),
+    }
+
+    /// Backend failing a number of requests with a connection error before
+    /// passing them on to a memory backend.
+    #[derive(Debug, Default)]
+    struct FlakyBackend {
+        attempts: AtomicUsize,
+        failures: AtomicUsize,
+        inner: MemoryBackend,
+        reconnects: AtomicUsize,
+    }
+
+    impl FlakyBackend {
+        fn new(failures: usize) -> Self {
+            Self {
+                failures: AtomicUsize::new(failures),
+                ..Self::default()
+            }
+        }
+
+        fn attempt(&self) -> Result<(), FlakyError> {
+            self.attempts.fetch_add(1, Ordering::SeqCst);
+
+            let failed = self
+                .failures
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |failures| {
+                    failures.checked_sub(1)
+                })
+                .is_ok();
+
+            if failed {
+                Err(FlakyError::Connection)
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Backend for FlakyBackend {
+        type Error = FlakyError;
+
+        fn is_connection_error(&self, error: &Self::Error) -> bool {
+            matches!(error, FlakyError::Connection)
+        }
+
+        async fn reconnect(&self) -> Result<(), Self::Error> {
+            self.reconnects.fetch_add(1, Ordering::SeqCst);
+
+            Ok(())
+        }
+
+        async fn append<T: Into<Value> + Send>(
+            &self,
+            key: &[u8],
+            value: T,
+        ) -> Result<Value, Self::Error> {
+            self.attempt()?;
+            self.inner
+                .append(key, value)
+                .await
+                .map_err(FlakyError::Memory)
+        }
+
+        async fn commands(&self) -> Result<CommandsData, Self::Error> {
+            self.attempt()?;
+            self.inner.commands().await.map_err(FlakyError::Memory)
+        }
+
+        async fn decrement_by<T: Into<Value> + Send>(
+            &self,
+            key: &[u8],
+            value: T,
+        ) -> Result<Value, Self::Error> {
+            self.attempt()?;
+            self.inner
+                .decrement_by(key, value)
+                .await
+                .map_err(FlakyError::Memory)
+        }
+
+        async fn decrement(
+            &self,
+            key: &[u8],
+            key_type: Option<KeyType>,
+        ) -> Result<Value, Self::Error> {
+            self.attempt()?;
+            self.inner
+                .decrement(key, key_type)
+                .await
+                .map_err(FlakyError::Memory)
+        }
+
+        async fn delete(&self, key: &[u8]) -> Result<Vec<u8>, Self::Error> {
+            self.attempt()?;
+            self.inner.delete(key).await.map_err(FlakyError::Memory)
+        }
+
+        async fn echo(&self, content: &[u8]) -> Result<Vec<Vec<u8>>, Self::Error> {
+            self.attempt()?;
+            self.inner.echo(content).await.map_err(FlakyError::Memory)
+        }
+
+        async fn exists<T: IntoIterator<Item = U> + Send, U: AsRef<[u8]> + Send>(
+            &self,
+            keys: T,
+        ) -> Result<bool, Self::Error> {
+            self.attempt()?;
+            self.inner.exists(keys).await.map_err(FlakyError::Memory)
+        }
+
+        async fn get(
+            &self,
+            key: &[u8],
+            key_type: Option<KeyType>,
+        ) -> Result<Option<Value>, Self::Error> {
+            self.attempt()?;
+            self.inner
+                .get(key, key_type)
+                .await
+                .map_err(FlakyError::Memory)
+        }
+
+        async fn get_range(
+            &self,
+            key: &[u8],
+            offset: u64,
+            len: u64,
+        ) -> Result<Vec<u8>, Self::Error> {
+            self.attempt()?;
+            self.inner
+                .get_range(key, offset, len)
+                .await
+                .map_err(FlakyError::Memory)
+        }
+
+        async fn increment_by<T: Into<Value> + Send>(
+            &self,
+            key: &[u8],
+            value: T,
+        ) -> Result<Value, Self::Error> {
+            self.attempt()?;
+            self.inner
+                .increment_by(key, value)
+                .await
+                .map_err(FlakyError::Memory)
+        }
+
+        async fn increment(
+            &self,
+            key: &[u8],
+            key_type: Option<KeyType>,
+        ) -> Result<Value, Self::Error> {
+            self.attempt()?;
+            self.inner
+                .increment(key, key_type)
+                .await
+                .map_err(FlakyError::Memory)
+        }
+
+        async fn is<T: IntoIterator<Item = U> + Send, U: AsRef<[u8]> + Send>(
+            &self,
+            key_type: KeyType,
+            keys: T,
+        ) -> Result<bool, Self::Error> {
+            self.attempt()?;
+            self.inner
+                .is(key_type, keys)
+                .await
+                .map_err(FlakyError::Memory)
+        }
+
+        async fn info(&self) -> Result<BTreeMap<String, String>, Self::Error> {
+            self.attempt()?;
+            self.inner.info().await.map_err(FlakyError::Memory)
+        }
+
+        async fn key_type(&self, key: &[u8]) -> Result<KeyType, Self::Error> {
+            self.attempt()?;
+            self.inner.key_type(key).await.map_err(FlakyError::Memory)
+        }
+
+        async fn keys(&self, key: &[u8]) -> Result<Vec<Vec<u8>>, Self::Error> {
+            self.attempt()?;
+            self.inner.keys(key).await.map_err(FlakyError::Memory)
+        }
+
+        async fn length(&self, key: &[u8], key_type: Option<KeyType>) -> Result<i64, Self::Error> {
+            self.attempt()?;
+            self.inner
+                .length(key, key_type)
+                .await
+                .map_err(FlakyError::Memory)
+        }
+
+        async fn list_peek(
+            &self,
+            key: &[u8],
+            end: ListEnd,
+        ) -> Result<Option<Vec<u8>>, Self::Error> {
+            self.attempt()?;
+            self.inner
+                .list_peek(key, end)
+                .await
+                .map_err(FlakyError::Memory)
+        }
+
+        async fn map_get_all(&self, key: &[u8]) -> Result<BTreeMap<Vec<u8>, Value>, Self::Error> {
+            self.attempt()?;
+            self.inner
+                .map_get_all(key)
+                .await
+                .map_err(FlakyError::Memory)
+        }
+
+        async fn rename(&self, from: &[u8], to: &[u8]) -> Result<Value, Self::Error> {
+            self.attempt()?;
+            self.inner
+                .rename(from, to)
+                .await
+                .map_err(FlakyError::Memory)
+        }
+
+        async fn scan(
+            &self,
+            pattern: &[u8],
+            cursor: u64,
+            count: u64,
+        ) -> Result<(u64, Vec<Vec<u8>>), Self::Error> {
+            self.attempt()?;
+            self.inner
+                .scan(pattern, cursor, count)
+                .await
+                .map_err(FlakyError::Memory)
+        }
+
+        async fn set<T: Into<Value> + Send>(
+            &self,
+            key: &[u8],
+            value: T,
+        ) -> Result<Value, Self::Error> {
+            self.attempt()?;
+            self.inner.set(key, value).await.map_err(FlakyError::Memory)
+        }
+
+        async fn set_scan(
+            &self,
+            key: &[u8],
+            cursor: u64,
+            count: u64,
+        ) -> Result<(u64, Vec<Vec<u8>>), Self::Error> {
+            self.attempt()?;
+            self.inner
+                .set_scan(key, cursor, count)
+                .await
+                .map_err(FlakyError::Memory)
+        }
+
+        async fn stats(&self) -> Result<StatsData, Self::Error> {
+            self.attempt()?;
+            self.inner.stats().await.map_err(FlakyError::Memory)
+        }
+
+        async fn time(&self) -> Result<(u64, u32), Self::Error> {
+            self.attempt()?;
+            self.inner.time().await.map_err(FlakyError::Memory)
+        }
+
+        async fn touch<T: IntoIterator<Item = U> + Send, U: AsRef<[u8]> + Send>(
+            &self,
+            keys: T,
+        ) -> Result<i64, Self::Error> {
+            self.attempt()?;
+            self.inner.touch(keys).await.map_err(FlakyError::Memory)
+        }
+
+        async fn watch_keyspace(
+            &self,
+            pattern: &[u8],
+        ) -> Result<KeyspaceEvents<Self::Error>, Self::Error> {
+            self.attempt()?;
+            let events = self
+                .inner
+                .watch_keyspace(pattern)
+                .await
+                .map_err(FlakyError::Memory)?;
+
+            Ok(events
+                .map(|event| event.map_err(FlakyError::Memory))
+                .boxed())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_retried() {
+        let backend = Arc::new(FlakyBackend::new(1));
+        backend.inner.set(b"foo", Value::Integer(1)).await.unwrap();
+        let retry = RetryBackend::new(Arc::clone(&backend), RetryPolicy::new(1));
+
+        assert_eq!(
+            Some(&1),
+            retry
+                .get(b"foo", None)
+                .await
+                .unwrap()
+                .unwrap()
+                .as_integer_ref()
+        );
+        assert_eq!(2, backend.attempts.load(Ordering::SeqCst));
+        assert_eq!(1, backend.reconnects.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_reads_retried() {
+        let backend = Arc::new(FlakyBackend::new(0));
+        backend.inner.set(b"foo", Value::Integer(1)).await.unwrap();
+        let retry = RetryBackend::new(Arc::clone(&backend), RetryPolicy::new(1));
+
+        backend.failures.store(1, Ordering::SeqCst);
+        assert!(retry.exists([b"foo"]).await.unwrap());
+        let map = DashMap::new();
+        map.insert(b"bar".to_vec(), Value::Bytes(b"baz".to_vec()));
+        backend.inner.set(b"map", Value::Map(map)).await.unwrap();
+        backend.failures.store(1, Ordering::SeqCst);
+        assert_eq!(vec![b"bar".to_vec()], retry.keys(b"map").await.unwrap());
+        backend.failures.store(1, Ordering::SeqCst);
+        assert_eq!(KeyType::Integer, retry.key_type(b"foo").await.unwrap());
+
+        assert_eq!(3, backend.reconnects.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_retries_exhausted() {
+        let backend = Arc::new(FlakyBackend::new(3));
+        let retry = RetryBackend::new(Arc::clone(&backend), RetryPolicy::new(2));
+
+        assert!(matches!(
+            retry.get(b"foo", None).await,
+            Err(FlakyError::Connection)
+        ));
+        assert_eq!(3, backend.attempts.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_other_errors_not_retried() {
+        let backend = Arc::new(FlakyBackend::new(0));
+        let retry = RetryBackend::new(Arc::clone(&backend), RetryPolicy::new(2));
+
+        assert!(matches!(
+            retry.get_range(b"foo", 0, 1).await,
+            Err(FlakyError::Memory(MemoryError::RunningCommand {
+                source: DispatchError::KeyNonexistent
+            }))
+        ));
+        assert_eq!(1, backend.attempts.load(Ordering::SeqCst));
+        assert_eq!(0, backend.reconnects.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_mutations_not_retried() {
+        let backend = Arc::new(FlakyBackend::new(1));
+        let retry = RetryBackend::new(Arc::clone(&backend), RetryPolicy::new(2));
+
+        assert!(matches!(
+            retry.increment(b"foo", None).await,
+            Err(FlakyError::Connection)
+        ));
+        assert_eq!(1, backend.attempts.load(Ordering::SeqCst));
+        assert_eq!(0, backend.reconnects.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_client_with_retry() {
+        let backend = Arc::new(FlakyBackend::new(1));
+        backend.inner.set(b"foo", Value::Integer(1)).await.unwrap();
+        let client = Client {
+            backend: Arc::clone(&backend),
+        }
+        .with_retry(RetryPolicy::default());
+
+        assert_eq!(Some(1), client.get("foo").int().await.unwrap());
+        assert_eq!(2, backend.attempts.load(Ordering::SeqCst));
+    }
+}
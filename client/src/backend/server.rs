@@ -1,21 +1,27 @@
-use super::Backend;
-use crate::model::StatsData;
-use alloc::{boxed::Box, vec::Vec};
+use super::{Backend, KeyspaceEvents};
+use crate::model::{CommandsData, StatsData};
+use alloc::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
 use async_trait::async_trait;
 use core::{
-    convert::TryInto,
+    convert::{TryFrom, TryInto},
     fmt::{Display, Formatter, Result as FmtResult},
     result::Result as StdResult,
 };
+use futures_util::stream::{self, StreamExt};
 use hop_engine::{
     command::{
         request::{ParseError, Request, RequestBuilder, RequestBuilderError},
-        response::{Context, Instruction, Response},
+        response::Response,
         CommandId, DispatchError,
     },
-    state::{KeyType, Value},
+    pubsub::KeyspaceEvent,
+    state::{KeyType, ListEnd, Value},
+};
+use std::{
+    error::Error as StdError,
+    io::{Error as IoError, ErrorKind},
+    net::SocketAddr,
 };
-use std::{error::Error as StdError, io::Error as IoError};
 use tokio::{
     io::{AsyncReadExt, AsyncWriteExt, BufReader},
     net::{
@@ -35,6 +41,7 @@ pub enum Error {
     Connecting { source: IoError },
     ConnectionClosed,
     Dispatching { reason: DispatchError },
+    FrameTooLarge { len: usize },
     KeyTypeInvalid { number: u8 },
     KeyTypeUnsupported { key_type: KeyType },
     ReadingMessage { source: IoError },
@@ -57,6 +64,11 @@ impl Display for Error {
                 "server couldn't process command: {:?}",
                 reason
             )),
+            Self::FrameTooLarge { len } => f.write_fmt(format_args!(
+                "the response frame ({} bytes) is larger than the maximum ({} bytes)",
+                len,
+                ServerBackend::FRAME_LEN_MAX
+            )),
             Self::KeyTypeInvalid { number } => f.write_fmt(format_args!(
                 "the provided key type ({}) is invalid",
                 number
@@ -80,6 +92,7 @@ impl StdError for Error {
             Self::Connecting { source } => Some(source),
             Self::ConnectionClosed => None,
             Self::Dispatching { .. } => None,
+            Self::FrameTooLarge { .. } => None,
             Self::KeyTypeInvalid { .. } => None,
             Self::KeyTypeUnsupported { .. } => None,
             Self::ReadingMessage { source } => Some(source),
@@ -96,25 +109,69 @@ impl From<RequestBuilderError> for Error {
 
 #[derive(Debug)]
 pub struct ServerBackend {
+    addr: SocketAddr,
     reader: Mutex<BufReader<OwnedReadHalf>>,
     writer: Mutex<OwnedWriteHalf>,
 }
 
 impl ServerBackend {
+    /// Maximum number of bytes of a response frame, excluding its length
+    /// prefix.
+    ///
+    /// A frame claiming to be longer isn't read, so a bad server can't make
+    /// the client allocate whatever length it sends.
+    pub const FRAME_LEN_MAX: usize = 512 * 1024 * 1024;
+
     pub async fn connect(addrs: impl ToSocketAddrs) -> Result<Self> {
         let stream = TcpStream::connect(addrs)
             .await
             .map_err(|source| Error::Connecting { source })?;
+        let addr = stream
+            .peer_addr()
+            .map_err(|source| Error::Connecting { source })?;
 
         let (reader, writer) = stream.into_split();
 
         Ok(Self {
+            addr,
             reader: Mutex::new(BufReader::new(reader)),
             writer: Mutex::new(writer),
         })
     }
 
+    /// Close the connection, asking the server to close its end with a quit
+    /// command and then shutting down the write half.
+    ///
+    /// Servers that predate the quit command fail to parse it, which is fine
+    /// since the connection is closed either way.
+    pub async fn close(self) -> Result<()> {
+        match self
+            .send_and_wait(RequestBuilder::new(CommandId::Quit))
+            .await
+        {
+            Ok(_) | Err(Error::BadRequest { .. }) => {}
+            Err(why) => return Err(why),
+        }
+
+        self.writer
+            .into_inner()
+            .shutdown()
+            .await
+            .map_err(|source| Error::WritingMessage { source })
+    }
+
     async fn send_and_wait(&self, request: impl Into<Request<'_>>) -> Result<Value> {
+        self.send_and_wait_optional(request)
+            .await?
+            .ok_or(Error::BadResponse)
+    }
+
+    /// Send a request whose response may be nil, such as a get of a key that
+    /// doesn't exist.
+    async fn send_and_wait_optional(
+        &self,
+        request: impl Into<Request<'_>>,
+    ) -> Result<Option<Value>> {
         self.writer
             .lock()
             .await
@@ -122,34 +179,155 @@ impl ServerBackend {
             .await
             .map_err(|source| Error::WritingMessage { source })?;
 
-        let mut ctx = Context::new();
-        let mut resp = Vec::with_capacity(1);
+        let mut reader = self.reader.lock().await;
+        let resp = Self::read_frame(&mut reader).await?;
+
+        Self::parse_optional_frame(&resp)
+    }
+
+    /// Send requests in one write and read their responses.
+    ///
+    /// The server may answer a pipeline with one write containing all of the
+    /// responses concatenated, so exactly one response frame is read per
+    /// request. Responses are returned in the order the requests were given.
+    pub async fn pipeline(&self, requests: &[Request<'_>]) -> Result<Vec<Response>> {
+        let mut buf = Vec::new();
+
+        for request in requests {
+            buf.extend_from_slice(request.as_bytes());
+        }
+
+        self.writer
+            .lock()
+            .await
+            .write_all(&buf)
+            .await
+            .map_err(|source| Error::WritingMessage { source })?;
+
+        let mut reader = self.reader.lock().await;
+
+        Self::read_frames(&mut reader, requests.len())
+            .await?
+            .iter()
+            .map(|frame| Response::parse(frame).map_err(|_| Error::BadResponse))
+            .collect()
+    }
+
+    /// Send a request that responds with an array, such as a set scan.
+    async fn send_and_wait_array(&self, request: impl Into<Request<'_>>) -> Result<Vec<Response>> {
+        self.writer
+            .lock()
+            .await
+            .write_all(request.into().as_bytes())
+            .await
+            .map_err(|source| Error::WritingMessage { source })?;
 
         let mut reader = self.reader.lock().await;
+        let resp = Self::read_frame(&mut reader).await?;
 
-        loop {
-            let read_amount = reader
-                .read_exact(&mut resp)
-                .await
-                .map_err(|source| Error::ReadingMessage { source })?;
+        match Response::parse(&resp).map_err(|_| Error::BadResponse)? {
+            Response::Array(items) => Ok(items),
+            Response::DispatchError(reason) => Err(Error::Dispatching { reason }),
+            Response::ParseError(reason) => Err(Error::BadRequest { reason }),
+            Response::Nil | Response::Value(_) => Err(Error::BadResponse),
+        }
+    }
 
-            if read_amount == 0 {
-                return Err(Error::ConnectionClosed);
-            }
+    /// Parse a response frame into its value.
+    fn parse_frame(resp: &[u8]) -> Result<Value> {
+        Self::parse_optional_frame(resp)?.ok_or(Error::BadResponse)
+    }
 
-            match ctx.feed(&resp).unwrap() {
-                Instruction::Concluded(response) => {
-                    return match response {
-                        Response::Value(value) => Ok(value),
-                        Response::DispatchError(reason) => Err(Error::Dispatching { reason }),
-                        Response::ParseError(reason) => Err(Error::BadRequest { reason }),
-                    }
-                }
-                Instruction::ReadBytes(bytes) => {
-                    resp.reserve_exact(bytes);
-                }
-            }
+    /// Parse a response frame into its value, which is `None` if the response
+    /// is nil.
+    fn parse_optional_frame(resp: &[u8]) -> Result<Option<Value>> {
+        match Response::parse(resp).map_err(|_| Error::BadResponse)? {
+            Response::Value(value) => Ok(Some(value)),
+            Response::Nil => Ok(None),
+            // Arrays are only requested with `send_and_wait_array`.
+            Response::Array(_) => Err(Error::BadResponse),
+            Response::DispatchError(reason) => Err(Error::Dispatching { reason }),
+            Response::ParseError(reason) => Err(Error::BadRequest { reason }),
+        }
+    }
+
+    /// Read exactly one response frame, including its 4 byte length prefix.
+    ///
+    /// Responses may contain arbitrary bytes, including newlines, so the
+    /// frame's length prefix is the only reliable way to know where it ends.
+    ///
+    /// Returns [`Error::FrameTooLarge`] if the prefix is above
+    /// [`FRAME_LEN_MAX`]. The rest of the frame is left unread, so the
+    /// connection can't be used afterwards.
+    ///
+    /// [`Error::FrameTooLarge`]: enum.Error.html#variant.FrameTooLarge
+    /// [`FRAME_LEN_MAX`]: #associatedconstant.FRAME_LEN_MAX
+    async fn read_frame(reader: &mut BufReader<OwnedReadHalf>) -> Result<Vec<u8>> {
+        let mut prefix = [0; 4];
+
+        reader.read_exact(&mut prefix).await.map_err(read_error)?;
+
+        let len = u32::from_be_bytes(prefix) as usize;
+
+        if len > Self::FRAME_LEN_MAX {
+            return Err(Error::FrameTooLarge { len });
+        }
+
+        // The buffer grows as the frame arrives instead of up front, so a
+        // truncated frame only costs what was actually sent.
+        let mut resp = prefix.to_vec();
+        let read = (&mut *reader)
+            .take(len as u64)
+            .read_to_end(&mut resp)
+            .await
+            .map_err(read_error)?;
+
+        if read < len {
+            return Err(Error::ConnectionClosed);
         }
+
+        Ok(resp)
+    }
+
+    /// Read exactly `count` response frames.
+    async fn read_frames(
+        reader: &mut BufReader<OwnedReadHalf>,
+        count: usize,
+    ) -> Result<Vec<Vec<u8>>> {
+        let mut frames = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            frames.push(Self::read_frame(reader).await?);
+        }
+
+        Ok(frames)
+    }
+}
+
+/// Parse a keyspace event pushed by the server.
+///
+/// Events are lists of two items: the ID of the command that modified the key
+/// as a single byte, and the key.
+fn parse_keyspace_event(value: Value) -> Result<KeyspaceEvent> {
+    let mut list = match value {
+        Value::List(list) if list.len() == 2 => list,
+        _ => return Err(Error::BadResponse),
+    };
+
+    let key = list.pop_back().ok_or(Error::BadResponse)?;
+    let command_id = match list.pop_back().as_deref() {
+        Some([id]) => CommandId::try_from(*id).map_err(|_| Error::BadResponse)?,
+        _ => return Err(Error::BadResponse),
+    };
+
+    Ok(KeyspaceEvent::new(command_id, key))
+}
+
+fn read_error(source: IoError) -> Error {
+    if source.kind() == ErrorKind::UnexpectedEof {
+        Error::ConnectionClosed
+    } else {
+        Error::ReadingMessage { source }
     }
 }
 
@@ -157,6 +335,32 @@ impl ServerBackend {
 impl Backend for ServerBackend {
     type Error = Error;
 
+    fn is_connection_error(&self, error: &Self::Error) -> bool {
+        matches!(
+            error,
+            Error::Connecting { .. }
+                | Error::ConnectionClosed
+                | Error::ReadingMessage { .. }
+                | Error::WritingMessage { .. }
+        )
+    }
+
+    /// Open a new connection to the address that was originally connected
+    /// to, replacing the current connection.
+    async fn reconnect(&self) -> Result<()> {
+        let stream = TcpStream::connect(self.addr)
+            .await
+            .map_err(|source| Error::Connecting { source })?;
+        let (reader, writer) = stream.into_split();
+
+        let mut current_writer = self.writer.lock().await;
+        let mut current_reader = self.reader.lock().await;
+        *current_writer = writer;
+        *current_reader = BufReader::new(reader);
+
+        Ok(())
+    }
+
     async fn append<T: Into<Value> + Send>(&self, key: &[u8], value: T) -> Result<Value> {
         let value = value.into();
         let key_type = value.kind();
@@ -182,6 +386,17 @@ impl Backend for ServerBackend {
         self.send_and_wait(builder).await
     }
 
+    async fn commands(&self) -> Result<CommandsData> {
+        let builder = RequestBuilder::new(CommandId::Command);
+
+        let value = self.send_and_wait(builder).await?;
+
+        value
+            .as_list_ref()
+            .and_then(CommandsData::from_list)
+            .ok_or(Error::BadResponse)
+    }
+
     async fn decrement_by<T: Into<Value> + Send>(&self, key: &[u8], value: T) -> Result<Value> {
         let value = value.into();
         let key_type = value.kind();
@@ -236,7 +451,7 @@ impl Backend for ServerBackend {
         let value = self.send_and_wait(builder).await?;
 
         match value {
-            Value::List(args) => Ok(args),
+            Value::List(args) => Ok(args.into()),
             _ => Err(Error::BadResponse),
         }
     }
@@ -259,11 +474,28 @@ impl Backend for ServerBackend {
         }
     }
 
-    async fn get(&self, key: &[u8]) -> Result<Value> {
-        let mut builder = RequestBuilder::new(CommandId::Get);
+    async fn get(&self, key: &[u8], key_type: Option<KeyType>) -> Result<Option<Value>> {
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Get, key_type);
         builder.bytes(key)?;
 
-        self.send_and_wait(builder).await
+        match self.send_and_wait_optional(builder).await? {
+            Some(value) if matches!(key_type, Some(kind) if kind != value.kind()) => {
+                Err(Error::BadResponse)
+            }
+            value => Ok(value),
+        }
+    }
+
+    async fn get_range(&self, key: &[u8], offset: u64, len: u64) -> Result<Vec<u8>> {
+        let mut builder = RequestBuilder::new(CommandId::GetRange);
+        builder.bytes(key)?;
+        builder.bytes(super::range_position(offset).to_be_bytes().as_ref())?;
+        builder.bytes(super::range_position(len).to_be_bytes().as_ref())?;
+
+        match self.send_and_wait(builder).await? {
+            Value::Bytes(bytes) => Ok(bytes),
+            _ => Err(Error::BadResponse),
+        }
     }
 
     async fn increment_by<T: Into<Value> + Send>(&self, key: &[u8], value: T) -> Result<Value> {
@@ -319,6 +551,14 @@ impl Backend for ServerBackend {
         }
     }
 
+    async fn info(&self) -> Result<BTreeMap<String, String>> {
+        let builder = RequestBuilder::new(CommandId::Info);
+
+        let value = self.send_and_wait(builder).await?;
+
+        super::parse_info(value).ok_or(Error::BadResponse)
+    }
+
     async fn key_type(&self, key: &[u8]) -> Result<KeyType> {
         let mut builder = RequestBuilder::new(CommandId::Type);
         builder.bytes(key)?;
@@ -344,7 +584,7 @@ impl Backend for ServerBackend {
         let value = self.send_and_wait(builder).await?;
 
         match value {
-            Value::List(list) => Ok(list),
+            Value::List(list) => Ok(list.into()),
             _ => Err(Error::BadResponse),
         }
     }
@@ -361,15 +601,60 @@ impl Backend for ServerBackend {
         }
     }
 
-    async fn rename(&self, from: &[u8], to: &[u8]) -> Result<Vec<u8>> {
+    async fn list_peek(&self, key: &[u8], end: ListEnd) -> Result<Option<Vec<u8>>> {
+        let mut builder = RequestBuilder::new(CommandId::ListPeek);
+        builder.bytes(key)?;
+        builder.bytes([end as u8].as_ref())?;
+
+        match self.send_and_wait_optional(builder).await? {
+            Some(Value::Bytes(item)) => Ok(Some(item)),
+            None => Ok(None),
+            Some(_) => Err(Error::BadResponse),
+        }
+    }
+
+    async fn map_get_all(&self, key: &[u8]) -> Result<BTreeMap<Vec<u8>, Value>> {
+        let mut builder = RequestBuilder::new(CommandId::MapGetAll);
+        builder.bytes(key)?;
+
+        match self.send_and_wait(builder).await? {
+            Value::Map(map) => Ok(map.into_iter().collect()),
+            _ => Err(Error::BadResponse),
+        }
+    }
+
+    async fn rename(&self, from: &[u8], to: &[u8]) -> Result<Value> {
         let mut builder = RequestBuilder::new(CommandId::Rename);
         builder.bytes(from)?;
         builder.bytes(to)?;
 
-        let value = self.send_and_wait(builder).await?;
+        self.send_and_wait(builder).await
+    }
 
-        match value {
-            Value::Bytes(bytes) => Ok(bytes),
+    async fn scan(&self, pattern: &[u8], cursor: u64, count: u64) -> Result<(u64, Vec<Vec<u8>>)> {
+        let mut builder = RequestBuilder::new(CommandId::Scan);
+        builder.value(cursor as i64)?;
+        builder.bytes(pattern)?;
+        builder.value(count as i64)?;
+
+        match self.send_and_wait_array(builder).await?.as_slice() {
+            [Response::Value(Value::Integer(next)), Response::Value(Value::List(keys))] => {
+                Ok((*next as u64, keys.iter().cloned().collect()))
+            }
+            _ => Err(Error::BadResponse),
+        }
+    }
+
+    async fn set_scan(&self, key: &[u8], cursor: u64, count: u64) -> Result<(u64, Vec<Vec<u8>>)> {
+        let mut builder = RequestBuilder::new(CommandId::SetScan);
+        builder.bytes(key)?;
+        builder.value(cursor as i64)?;
+        builder.value(count as i64)?;
+
+        match self.send_and_wait_array(builder).await?.as_slice() {
+            [Response::Value(Value::Integer(next)), Response::Value(Value::List(members))] => {
+                Ok((*next as u64, members.iter().cloned().collect()))
+            }
             _ => Err(Error::BadResponse),
         }
     }
@@ -384,7 +669,15 @@ impl Backend for ServerBackend {
             _ => return Err(Error::BadResponse),
         };
 
-        Ok(StatsData::new(map.into_iter().collect()))
+        Ok(StatsData::new(map))
+    }
+
+    async fn time(&self) -> Result<(u64, u32)> {
+        let builder = RequestBuilder::new(CommandId::Time);
+
+        let value = self.send_and_wait(builder).await?;
+
+        super::parse_time(value).ok_or(Error::BadResponse)
     }
 
     async fn set<T: Into<Value> + Send>(&self, key: &[u8], value: T) -> Result<Value> {
@@ -397,14 +690,366 @@ impl Backend for ServerBackend {
 
         self.send_and_wait(builder).await
     }
+
+    async fn touch<T: IntoIterator<Item = U> + Send, U: AsRef<[u8]> + Send>(
+        &self,
+        keys: T,
+    ) -> Result<i64> {
+        let mut builder = RequestBuilder::new(CommandId::Touch);
+
+        for key in keys {
+            builder.bytes(key.as_ref())?;
+        }
+
+        let value = self.send_and_wait(builder).await?;
+
+        match value {
+            Value::Integer(touched) => Ok(touched),
+            _ => Err(Error::BadResponse),
+        }
+    }
+
+    /// Watch the keyspace over a new connection to the server.
+    ///
+    /// The server only pushes events over a connection once it's watching,
+    /// so a dedicated connection is used. The stream ends when the server
+    /// closes the connection.
+    async fn watch_keyspace(&self, pattern: &[u8]) -> Result<KeyspaceEvents<Error>> {
+        let stream = TcpStream::connect(self.addr)
+            .await
+            .map_err(|source| Error::Connecting { source })?;
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+
+        let mut builder = RequestBuilder::new(CommandId::Watch);
+        builder.bytes(pattern)?;
+
+        writer
+            .write_all(builder.into_request().as_bytes())
+            .await
+            .map_err(|source| Error::WritingMessage { source })?;
+
+        match Self::parse_frame(&Self::read_frame(&mut reader).await?)? {
+            Value::Boolean(true) => {}
+            _ => return Err(Error::BadResponse),
+        }
+
+        // The writer is kept alongside the reader, since dropping it would
+        // shut down the connection's write half and end the watch.
+        let events = stream::unfold((reader, writer), |(mut reader, writer)| async move {
+            let event = match Self::read_frame(&mut reader).await {
+                Ok(resp) => Self::parse_frame(&resp).and_then(parse_keyspace_event),
+                Err(Error::ConnectionClosed) => return None,
+                Err(source) => Err(source),
+            };
+
+            Some((event, (reader, writer)))
+        });
+
+        Ok(events.boxed())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Error, ServerBackend};
+    use super::{super::test_server::spawn_test_server, Backend, Error, ServerBackend};
+    use crate::Client;
     use core::fmt::Debug;
+    use hop_engine::{
+        command::{request::RequestBuilder, CommandId, Response},
+        state::{KeyType, ListEnd, Value},
+    };
     use static_assertions::assert_impl_all;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
 
     assert_impl_all!(Error: Debug, Send, Sync);
     assert_impl_all!(ServerBackend: Debug, Send, Sync);
+
+    #[tokio::test]
+    async fn test_server() {
+        let (addr, server) = spawn_test_server().await;
+        let client = Client::connect(addr).await.unwrap();
+
+        assert_eq!(1, client.increment("foo").int().await.unwrap());
+        assert_eq!(2, client.increment("foo").int().await.unwrap());
+        assert_eq!(
+            Some(&Value::Integer(2)),
+            server.hop().state().key_ref(b"foo").as_deref()
+        );
+
+        client.set("bar").string("baz").await.unwrap();
+        assert_eq!(
+            Some("baz"),
+            client.get("bar").string().await.unwrap().as_deref()
+        );
+        assert_eq!(3, client.length("bar").await.unwrap());
+        assert!(client.get("qux").string().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_server_errors() {
+        let (addr, _server) = spawn_test_server().await;
+        let client = Client::connect(addr).await.unwrap();
+
+        client.set("foo").int(1).await.unwrap();
+        assert!(client.list_peek("foo", ListEnd::Front).await.is_err());
+        assert!(client.map_get_all("foo").await.is_err());
+
+        // the connection is still usable after an error
+        assert_eq!(2, client.increment("foo").int().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_server_connections_share_engine() {
+        let (addr, _server) = spawn_test_server().await;
+        let a = Client::connect(addr).await.unwrap();
+        let b = Client::connect(addr).await.unwrap();
+
+        a.set("foo").map(vec![("name", "hop")]).await.unwrap();
+
+        let fields = b.map_get_all("foo").await.unwrap();
+        assert_eq!(
+            Some(&Value::Bytes(b"hop".to_vec())),
+            fields.get(b"name".as_ref())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_stream() {
+        use futures_util::stream::TryStreamExt;
+
+        let (addr, _server) = spawn_test_server().await;
+        let client = Client::connect(addr).await.unwrap();
+
+        for key in ["b", "a", "other"].iter() {
+            client.set(key).int(1).await.unwrap();
+        }
+
+        let keys = client.scan_stream("?").try_collect::<Vec<_>>().await;
+        assert_eq!(vec![b"a".to_vec(), b"b".to_vec()], keys.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_response_containing_newlines() {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 64];
+            assert!(socket.read(&mut buf).await.unwrap() > 0);
+
+            // split the frame over two writes with a newline right at the end
+            // of the first one
+            let resp = Response::from(b"foo\nbar\n".to_vec()).as_bytes();
+            socket.write_all(&resp[..9]).await.unwrap();
+            socket.write_all(&resp[9..]).await.unwrap();
+        });
+
+        let backend = ServerBackend::connect(addr).await.unwrap();
+        assert!(matches!(
+            backend.get(b"foo", None).await.unwrap(),
+            Some(Value::Bytes(bytes)) if bytes == b"foo\nbar\n"
+        ));
+
+        server.await.unwrap();
+        assert!(matches!(
+            backend.get(b"foo", None).await,
+            Err(Error::ConnectionClosed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_frame_truncated() {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 64];
+            assert!(socket.read(&mut buf).await.unwrap() > 0);
+
+            // claim a longer frame than is sent before closing
+            let resp = Response::from(b"foo".to_vec()).as_bytes();
+            socket.write_all(&100u32.to_be_bytes()).await.unwrap();
+            socket.write_all(&resp[4..]).await.unwrap();
+        });
+
+        let backend = ServerBackend::connect(addr).await.unwrap();
+        assert!(matches!(
+            backend.get(b"foo", None).await,
+            Err(Error::ConnectionClosed)
+        ));
+
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_frame_too_large() {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 64];
+            assert!(socket.read(&mut buf).await.unwrap() > 0);
+
+            socket.write_all(&u32::MAX.to_be_bytes()).await.unwrap();
+
+            // the client gives up without waiting for the rest of the frame
+            assert_eq!(socket.read(&mut buf).await.unwrap(), 0);
+        });
+
+        let backend = ServerBackend::connect(addr).await.unwrap();
+        assert!(matches!(
+            backend.get(b"foo", None).await,
+            Err(Error::FrameTooLarge { len }) if len == u32::MAX as usize
+        ));
+
+        drop(backend);
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_pipeline() {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 64];
+            assert!(socket.read(&mut buf).await.unwrap() > 0);
+
+            // answer all three requests with one write
+            let mut resp = Response::from(1).as_bytes();
+            resp.extend(Response::nil().as_bytes());
+            resp.extend(Response::from(b"bar".to_vec()).as_bytes());
+            socket.write_all(&resp).await.unwrap();
+        });
+
+        let mut increment = RequestBuilder::new(CommandId::Increment);
+        increment.bytes(b"foo".as_ref()).unwrap();
+        let mut get = RequestBuilder::new(CommandId::Get);
+        get.bytes(b"baz".as_ref()).unwrap();
+        let echo = RequestBuilder::new(CommandId::Echo);
+
+        let requests = [
+            increment.into_request(),
+            get.into_request(),
+            echo.into_request(),
+        ];
+
+        let backend = ServerBackend::connect(addr).await.unwrap();
+        let responses = backend.pipeline(&requests).await.unwrap();
+        assert_eq!(responses.len(), 3);
+        assert!(matches!(responses[0], Response::Value(Value::Integer(1))));
+        assert!(matches!(responses[1], Response::Nil));
+        assert!(matches!(
+            &responses[2],
+            Response::Value(Value::Bytes(bytes)) if bytes == b"bar"
+        ));
+
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_close() {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 64];
+            assert_eq!(socket.read(&mut buf).await.unwrap(), 1);
+            assert_eq!(buf[0], CommandId::Quit as u8);
+
+            socket
+                .write_all(&Response::from(true).as_bytes())
+                .await
+                .unwrap();
+
+            // the write half is shut down after the quit is answered
+            assert_eq!(socket.read(&mut buf).await.unwrap(), 0);
+        });
+
+        let backend = ServerBackend::connect(addr).await.unwrap();
+        backend.close().await.unwrap();
+
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_nil_response() {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 64];
+            assert!(socket.read(&mut buf).await.unwrap() > 0);
+
+            socket.write_all(&Response::nil().as_bytes()).await.unwrap();
+        });
+
+        let backend = ServerBackend::connect(addr).await.unwrap();
+        assert!(backend.get(b"foo", None).await.unwrap().is_none());
+
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_type_mismatch() {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 64];
+            assert!(socket.read(&mut buf).await.unwrap() > 0);
+
+            // respond with a type other than the one asked for
+            socket
+                .write_all(&Response::from(1).as_bytes())
+                .await
+                .unwrap();
+        });
+
+        let backend = ServerBackend::connect(addr).await.unwrap();
+        assert!(matches!(
+            backend.get(b"foo", Some(KeyType::String)).await,
+            Err(Error::BadResponse)
+        ));
+
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_increment_decrement_command_ids() {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+
+            for expected in [CommandId::Increment, CommandId::Decrement].iter() {
+                let mut buf = [0; 64];
+                assert!(socket.read(&mut buf).await.unwrap() > 0);
+                assert_eq!(*expected as u8, buf[0]);
+
+                socket
+                    .write_all(&Response::from(1).as_bytes())
+                    .await
+                    .unwrap();
+            }
+        });
+
+        let backend = ServerBackend::connect(addr).await.unwrap();
+        assert!(backend.increment(b"foo", None).await.is_ok());
+        assert!(backend.decrement(b"foo", None).await.is_ok());
+
+        server.await.unwrap();
+    }
 }
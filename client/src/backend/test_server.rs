@@ -0,0 +1,118 @@
+//! An in-process Hop server for testing the [`ServerBackend`] against a real
+//! TCP connection.
+//!
+//! [`ServerBackend`]: ../server/struct.ServerBackend.html
+
+use hop_engine::{
+    command::{request::Context, CommandId, Response},
+    Hop,
+};
+use std::{
+    io::Result as IoResult,
+    net::{Shutdown, SocketAddr},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::oneshot::{self, Receiver, Sender},
+};
+
+/// Handle to a server started by [`spawn_test_server`], which stops accepting
+/// connections when dropped.
+///
+/// [`spawn_test_server`]: fn.spawn_test_server.html
+#[derive(Debug)]
+pub struct TestServer {
+    hop: Hop,
+    _shutdown: Sender<()>,
+}
+
+impl TestServer {
+    /// Retrieve the engine that the server dispatches to.
+    pub fn hop(&self) -> &Hop {
+        &self.hop
+    }
+}
+
+/// Bind a server over a fresh engine to an ephemeral port of localhost,
+/// returning its address and a handle that shuts it down when dropped.
+///
+/// Connections already accepted are served until they're closed.
+pub async fn spawn_test_server() -> (SocketAddr, TestServer) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let hop = Hop::new();
+    let (tx, rx) = oneshot::channel();
+
+    tokio::spawn(accept(listener, hop.clone(), rx));
+
+    let server = TestServer { hop, _shutdown: tx };
+
+    (addr, server)
+}
+
+async fn accept(mut listener: TcpListener, hop: Hop, mut shutdown: Receiver<()>) {
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((socket, _)) => {
+                    tokio::spawn(serve(socket, hop.clone()));
+                }
+                Err(_) => return,
+            },
+            _ = &mut shutdown => return,
+        }
+    }
+}
+
+/// Dispatch each request read from the socket until EOF or a quit command,
+/// like the server's binary protocol loop.
+async fn serve(mut socket: TcpStream, hop: Hop) -> IoResult<()> {
+    let mut ctx = Context::new();
+    let mut input = Vec::new();
+    let mut resp = Vec::new();
+
+    loop {
+        if socket.read_buf(&mut input).await? == 0 {
+            return Ok(());
+        }
+
+        let mut quit = false;
+
+        while !quit && !input.is_empty() {
+            let consumed = match ctx.feed(&input) {
+                Ok(Some(req)) => {
+                    if let Err(why) = hop.dispatch(&req, &mut resp) {
+                        Response::DispatchError(why).copy_to(&mut resp);
+                    }
+
+                    quit = req.command_id() == CommandId::Quit;
+
+                    ctx.consumed()
+                }
+                Ok(None) => break,
+                Err(why) if why.is_recoverable() => {
+                    Response::ParseError(why).copy_to(&mut resp);
+
+                    ctx.consumed()
+                }
+                Err(why) => {
+                    Response::ParseError(why).copy_to(&mut resp);
+
+                    input.len()
+                }
+            };
+
+            input.drain(..consumed);
+        }
+
+        socket.write_all(&resp).await?;
+        resp.clear();
+
+        if quit {
+            socket.shutdown(Shutdown::Write)?;
+
+            return Ok(());
+        }
+    }
+}
@@ -12,12 +12,29 @@ pub mod backend;
 pub mod model;
 pub mod request;
 
-pub use hop_engine::state::{KeyType, Value};
+pub use hop_engine::{
+    command::CommandId,
+    pubsub::KeyspaceEvent,
+    state::{KeyType, ListEnd, Value},
+};
 
-use alloc::sync::Arc;
-use backend::{Backend, MemoryBackend};
+use alloc::{collections::BTreeMap, string::String, sync::Arc, vec::Vec};
+use backend::{Backend, MemoryBackend, RetryBackend, RetryPolicy};
+use futures_util::{
+    future,
+    stream::{self, Stream, StreamExt},
+};
+use hop_engine::Hop;
+use model::CommandsData;
 use request::{append::AppendUnconfigured, get::GetUnconfigured, set::SetUnconfigured, *};
 
+/// The number of bytes requested at a time when streaming a value.
+const STREAM_CHUNK_LEN: u64 = 64 * 1024;
+
+/// The number of keys or members requested at a time when scanning the
+/// keyspace or a set.
+const SCAN_PAGE_LEN: u64 = 100;
+
 /// A client for interfacing over Hop instances.
 #[derive(Clone, Debug)]
 pub struct Client<B: Backend> {
@@ -28,6 +45,32 @@ impl<B: Backend> Client<B> {
     fn backend(&self) -> Arc<B> {
         Arc::clone(&self.backend)
     }
+
+    /// Wrap the client's backend to retry commands that fail due to the
+    /// connection, reconnecting before each retry.
+    ///
+    /// Only commands that don't modify keys, such as gets, are retried. Refer
+    /// to [`RetryPolicy`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use hop::{backend::RetryPolicy, Client};
+    ///
+    /// let client = Client::connect("localhost:14000")
+    ///     .await?
+    ///     .with_retry(RetryPolicy::new(2));
+    /// println!("Value: {:?}", client.get("foo").await?);
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// [`RetryPolicy`]: backend/struct.RetryPolicy.html
+    pub fn with_retry(self, policy: RetryPolicy) -> Client<RetryBackend<B>> {
+        Client {
+            backend: Arc::new(RetryBackend::new(self.backend, policy)),
+        }
+    }
 }
 
 #[cfg(all(not(target_arch = "wasm32"), feature = "tokio"))]
@@ -77,6 +120,32 @@ impl Client<MemoryBackend> {
             backend: Arc::new(MemoryBackend::new()),
         }
     }
+
+    /// Create a memory-backend client using an existing engine instance.
+    ///
+    /// Clients created from clones of the same instance share its state, like
+    /// clients of the same server.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use hop::Client;
+    /// use hop_engine::Hop;
+    ///
+    /// let hop = Hop::new();
+    /// let a = Client::memory_from_hop(hop.clone());
+    /// let b = Client::memory_from_hop(hop);
+    ///
+    /// a.set("foo").int(1).await?;
+    /// assert_eq!(Some(1), b.get("foo").int().await?);
+    /// # Ok(()) }
+    /// ```
+    pub fn memory_from_hop(hop: Hop) -> Self {
+        Self {
+            backend: Arc::new(MemoryBackend::from_hop(hop)),
+        }
+    }
 }
 
 impl<B: Backend> Client<B> {
@@ -108,6 +177,29 @@ impl<B: Backend> Client<B> {
         AppendUnconfigured::new(self.backend(), key)
     }
 
+    /// Retrieve the protocol version and commands supported by the instance.
+    ///
+    /// This can be used to check whether an instance supports a command
+    /// before using it, since the commands available vary between versions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop::{Client, CommandId};
+    ///
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::memory();
+    /// let commands = client.commands().await?;
+    ///
+    /// if commands.supports(CommandId::Time) {
+    ///     println!("server time: {:?}", client.server_time().await?);
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub async fn commands(&self) -> Result<CommandsData, B::Error> {
+        self.backend.commands().await
+    }
+
     /// Decrements a float or integer key by one.
     ///
     /// Returns the new value on success.
@@ -146,6 +238,18 @@ impl<B: Backend> Client<B> {
     /// Echos the provided content back at you.
     ///
     /// Returns the input content.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop::Client;
+    ///
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::memory();
+    ///
+    /// assert_eq!([b"hello".to_vec()].to_vec(), client.echo("hello").await?);
+    /// # Ok(()) }
+    /// ```
     pub fn echo<K: AsRef<[u8]> + Send + Unpin>(&self, content: K) -> Echo<'_, B, K> {
         Echo::new(self.backend(), content)
     }
@@ -202,7 +306,7 @@ impl<B: Backend> Client<B> {
     ///
     /// client.set("foo").int(123).await?;
     ///
-    /// assert_eq!(123, client.get("foo").int().await?);
+    /// assert_eq!(Some(123), client.get("foo").int().await?);
     /// # Ok(()) }
     /// ```
     ///
@@ -218,10 +322,11 @@ impl<B: Backend> Client<B> {
     /// client.set("foo").string("this is a string").await?;
     ///
     /// match client.get("foo").await? {
-    ///     Value::Boolean(boolean) => println!("it's a boolean: {}", boolean),
-    ///     Value::Integer(int) => println!("it's an integer: {}", int),
-    ///     Value::String(string) => println!("it's a string: {}", string),
-    ///     _ => println!("it's something else"),
+    ///     Some(Value::Boolean(boolean)) => println!("it's a boolean: {}", boolean),
+    ///     Some(Value::Integer(int)) => println!("it's an integer: {}", int),
+    ///     Some(Value::String(string)) => println!("it's a string: {}", string),
+    ///     Some(_) => println!("it's something else"),
+    ///     None => println!("it doesn't exist"),
     /// }
     /// # Ok(()) }
     /// ```
@@ -231,6 +336,123 @@ impl<B: Backend> Client<B> {
         GetUnconfigured::new(self.backend(), key)
     }
 
+    /// Get multiple integer keys concurrently, resolving to their values in
+    /// the same order as the keys. Keys that don't exist resolve to `None`.
+    ///
+    /// Resolves to the first error returned by the backend, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop::Client;
+    ///
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::memory();
+    ///
+    /// client.set("foo").int(1).await?;
+    /// client.set("bar").int(2).await?;
+    ///
+    /// assert_eq!(
+    ///     vec![Some(2), None, Some(1)],
+    ///     client.get_many_integers(&["bar", "baz", "foo"]).await?,
+    /// );
+    /// # Ok(()) }
+    /// ```
+    pub async fn get_many_integers<K: AsRef<[u8]> + Send + Unpin>(
+        &self,
+        keys: impl IntoIterator<Item = K>,
+    ) -> Result<Vec<Option<i64>>, B::Error>
+    where
+        B: 'static,
+    {
+        future::try_join_all(keys.into_iter().map(|key| self.get(key).int())).await
+    }
+
+    /// Get multiple string keys concurrently, resolving to their values in the
+    /// same order as the keys. Keys that don't exist resolve to `None`.
+    ///
+    /// Resolves to the first error returned by the backend, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop::Client;
+    ///
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::memory();
+    ///
+    /// client.set("foo").string("a").await?;
+    /// client.set("bar").string("b").await?;
+    ///
+    /// assert_eq!(
+    ///     vec![Some("b".to_owned()), Some("a".to_owned())],
+    ///     client.get_many_strings(&["bar", "foo"]).await?,
+    /// );
+    /// # Ok(()) }
+    /// ```
+    pub async fn get_many_strings<K: AsRef<[u8]> + Send + Unpin>(
+        &self,
+        keys: impl IntoIterator<Item = K>,
+    ) -> Result<Vec<Option<String>>, B::Error>
+    where
+        B: 'static,
+    {
+        future::try_join_all(keys.into_iter().map(|key| self.get(key).string())).await
+    }
+
+    /// Stream the value of a bytes key in chunks, rather than retrieving the
+    /// whole value in a single response.
+    ///
+    /// Each chunk is retrieved with a separate range request, so the memory
+    /// used by both the client and the server is bounded by the chunk size
+    /// regardless of how large the value is. The value isn't locked between
+    /// chunks, so modifying the key while it's being streamed may result in a
+    /// mix of the old and new values.
+    ///
+    /// The stream ends after the first error returned by the backend, such as
+    /// the key not existing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures_util::stream::TryStreamExt;
+    /// use hop::Client;
+    ///
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::memory();
+    /// client.set("foo").bytes(vec![1; 100_000]).await?;
+    ///
+    /// let chunks = client.get_stream("foo").try_collect::<Vec<_>>().await?;
+    /// assert_eq!(100_000, chunks.iter().map(Vec::len).sum::<usize>());
+    /// # Ok(()) }
+    /// ```
+    pub fn get_stream(&self, key: impl AsRef<[u8]>) -> impl Stream<Item = Result<Vec<u8>, B::Error>>
+    where
+        B: 'static,
+    {
+        let initial = (self.backend(), key.as_ref().to_vec(), Some(0));
+
+        stream::unfold(initial, |(backend, key, offset)| async move {
+            let offset = offset?;
+
+            match backend.get_range(&key, offset, STREAM_CHUNK_LEN).await {
+                Ok(chunk) if chunk.is_empty() => None,
+                Ok(chunk) => {
+                    let len = chunk.len() as u64;
+                    // A short chunk means the end of the value was reached.
+                    let next = if len < STREAM_CHUNK_LEN {
+                        None
+                    } else {
+                        Some(offset + len)
+                    };
+
+                    Some((Ok(chunk), (backend, key, next)))
+                }
+                Err(source) => Some((Err(source), (backend, key, None))),
+            }
+        })
+    }
+
     /// Increments a float or integer key by one.
     ///
     /// Returns the new value on success.
@@ -269,6 +491,29 @@ impl<B: Backend> Client<B> {
         Increment::new(self.backend(), key)
     }
 
+    /// Retrieve metadata about the instance by name.
+    ///
+    /// The metadata includes the instance's `version`, its `uptime_seconds`,
+    /// the number of `keys` in the selected database, the number of
+    /// `connected_clients` and its `eviction_policy`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop::Client;
+    ///
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::memory();
+    /// client.set("foo").int(1).await?;
+    ///
+    /// let info = client.info().await?;
+    /// assert_eq!(Some("1"), info.get("keys").map(String::as_str));
+    /// # Ok(()) }
+    /// ```
+    pub async fn info(&self) -> Result<BTreeMap<String, String>, B::Error> {
+        self.backend.info().await
+    }
+
     /// Check if one or more keys is a specified key type.
     ///
     /// Returns `true` if all of the keys both exist and are the specified key
@@ -391,10 +636,66 @@ impl<B: Backend> Client<B> {
         Length::new(self.backend(), key)
     }
 
-    /// Rename a key to a new key name, if the new key name doesn't already
-    /// exist.
+    /// Retrieve the item at an end of a list without removing it.
+    ///
+    /// Returns `None` if the list is empty or doesn't exist. If the key isn't
+    /// a list then an error is returned.
+    ///
+    /// This is an `O(1)` time complexity operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop::{Client, ListEnd};
+    ///
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::memory();
+    /// client.set("foo").list([b"foo".to_vec(), b"bar".to_vec()].to_vec()).await?;
+    ///
+    /// assert_eq!(Some(b"bar".to_vec()), client.list_peek("foo", ListEnd::Back).await?);
+    /// assert_eq!(2, client.length("foo").await?);
+    /// # Ok(()) }
+    /// ```
+    pub async fn list_peek(
+        &self,
+        key: impl AsRef<[u8]>,
+        end: ListEnd,
+    ) -> Result<Option<Vec<u8>>, B::Error> {
+        self.backend.list_peek(key.as_ref(), end).await
+    }
+
+    /// Retrieve every field of a map and its value, ordered by field.
     ///
-    /// Returns the new key value on success as a confirmation.
+    /// Returns an empty map if the key doesn't exist. If the key isn't a map
+    /// then an error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop::{Client, Value};
+    ///
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::memory();
+    /// client.set("foo").map(vec![("name", "hop")]).await?;
+    ///
+    /// let fields = client.map_get_all("foo").await?;
+    /// assert_eq!(Some(&Value::Bytes(b"hop".to_vec())), fields.get(b"name".as_ref()));
+    /// # Ok(()) }
+    /// ```
+    pub async fn map_get_all(
+        &self,
+        key: impl AsRef<[u8]>,
+    ) -> Result<BTreeMap<Vec<u8>, Value>, B::Error> {
+        self.backend.map_get_all(key.as_ref()).await
+    }
+
+    /// Rename a key to a new key name, overwriting the new key name if it
+    /// already exists.
+    ///
+    /// The key keeps its expiry, if it has one.
+    ///
+    /// Returns the moved value on success. If the key doesn't exist then an
+    /// error is returned.
     ///
     /// # Examples
     ///
@@ -404,8 +705,10 @@ impl<B: Backend> Client<B> {
     /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// let client = Client::memory();
     /// client.increment("foo").int().await?;
-    /// println!("New key name: {:?}", client.rename("foo", "bar").await?);
-    /// println!("New incremented value: {}", client.increment("foo").int().await?);
+    ///
+    /// let value = client.rename("foo", "bar").await?;
+    /// assert_eq!(Some(&1), value.as_integer_ref());
+    /// assert_eq!(Some(1), client.get("bar").int().await?);
     /// # Ok(()) }
     /// ```
     pub fn rename<F: AsRef<[u8]> + Send + Unpin, T: AsRef<[u8]> + Send + Unpin>(
@@ -416,6 +719,57 @@ impl<B: Backend> Client<B> {
         Rename::new(self.backend(), from, to)
     }
 
+    /// Stream the keys matching a glob-style pattern, retrieving them a page at
+    /// a time rather than in a single response.
+    ///
+    /// Pages are only requested as the stream is polled, so a consumer that
+    /// stops pulling keys stops the scan. Keys are streamed in byte order. The
+    /// keyspace isn't locked between pages, so keys added or removed while it's
+    /// being streamed may be skipped or streamed twice.
+    ///
+    /// The stream ends after the first error returned by the backend.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures_util::stream::TryStreamExt;
+    /// use hop::Client;
+    ///
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::memory();
+    /// client.set("session:1").int(1).await?;
+    /// client.set("user:1").int(2).await?;
+    ///
+    /// let keys = client.scan_stream("session:*").try_collect::<Vec<_>>().await?;
+    /// assert_eq!(vec![b"session:1".to_vec()], keys);
+    /// # Ok(()) }
+    /// ```
+    pub fn scan_stream(
+        &self,
+        pattern: impl AsRef<[u8]>,
+    ) -> impl Stream<Item = Result<Vec<u8>, B::Error>>
+    where
+        B: 'static,
+    {
+        let initial = (self.backend(), pattern.as_ref().to_vec(), Some(0));
+
+        stream::unfold(initial, |(backend, pattern, cursor)| async move {
+            let cursor = cursor?;
+
+            match backend.scan(&pattern, cursor, SCAN_PAGE_LEN).await {
+                Ok((next, keys)) => {
+                    // A cursor of 0 means every key has been retrieved.
+                    let next = if next == 0 { None } else { Some(next) };
+                    let keys = keys.into_iter().map(Ok).collect::<Vec<_>>();
+
+                    Some((stream::iter(keys), (backend, pattern, next)))
+                }
+                Err(source) => Some((stream::iter(vec![Err(source)]), (backend, pattern, None))),
+            }
+        })
+        .flatten()
+    }
+
     /// Set a key to a new value, overriding it regardless of whether it exists
     /// and its current key type.
     ///
@@ -444,6 +798,56 @@ impl<B: Backend> Client<B> {
         SetUnconfigured::new(self.backend(), key)
     }
 
+    /// Stream the members of a set, retrieving them a page at a time rather
+    /// than in a single response.
+    ///
+    /// Members are streamed in byte order. The set isn't locked between pages,
+    /// so members added or removed while the set is being streamed may be
+    /// skipped or streamed twice.
+    ///
+    /// The stream ends after the first error returned by the backend, such as
+    /// the key not being a set. A set that doesn't exist has no members.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures_util::stream::TryStreamExt;
+    /// use hop::Client;
+    ///
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::memory();
+    /// client.set("foo").set(vec![b"a".to_vec(), b"b".to_vec()]).await?;
+    ///
+    /// let members = client.set_scan_stream("foo").try_collect::<Vec<_>>().await?;
+    /// assert_eq!(vec![b"a".to_vec(), b"b".to_vec()], members);
+    /// # Ok(()) }
+    /// ```
+    pub fn set_scan_stream(
+        &self,
+        key: impl AsRef<[u8]>,
+    ) -> impl Stream<Item = Result<Vec<u8>, B::Error>>
+    where
+        B: 'static,
+    {
+        let initial = (self.backend(), key.as_ref().to_vec(), Some(0));
+
+        stream::unfold(initial, |(backend, key, cursor)| async move {
+            let cursor = cursor?;
+
+            match backend.set_scan(&key, cursor, SCAN_PAGE_LEN).await {
+                Ok((next, members)) => {
+                    // A cursor of 0 means every member has been retrieved.
+                    let next = if next == 0 { None } else { Some(next) };
+                    let members = members.into_iter().map(Ok).collect::<Vec<_>>();
+
+                    Some((stream::iter(members), (backend, key, next)))
+                }
+                Err(source) => Some((stream::iter(vec![Err(source)]), (backend, key, None))),
+            }
+        })
+        .flatten()
+    }
+
     /// Retrieve statistics about the current runtime of Hop.
     ///
     /// When Hop is restarted, many of the statistics - like commands run - are
@@ -463,13 +867,355 @@ impl<B: Backend> Client<B> {
     pub fn stats(&self) -> Stats<'_, B> {
         Stats::new(self.backend())
     }
+
+    /// Retrieve the instance's current Unix time as seconds and microseconds.
+    ///
+    /// This is the clock that the instance uses for expiring keys, so it can
+    /// be used to coordinate TTLs and detect clock skew.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop::Client;
+    ///
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::memory();
+    /// let (secs, micros) = client.server_time().await?;
+    /// println!("Server time: {}.{:06}", secs, micros);
+    /// # Ok(()) }
+    /// ```
+    pub async fn server_time(&self) -> Result<(u64, u32), B::Error> {
+        self.backend.time().await
+    }
+
+    /// Record an access of one or more keys without retrieving them.
+    ///
+    /// Touching a key makes it the most recently used key and increases its
+    /// access frequency, so it's less likely to be evicted.
+    ///
+    /// Returns the number of keys that exist and were touched. Keys that don't
+    /// exist are ignored.
+    ///
+    /// Refer to the documentation for the [`Touch`] request for more
+    /// information on how to use the request struct returned by this method.
+    ///
+    /// This is an `O(n)` time complexity operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop::Client;
+    ///
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::memory();
+    /// client.increment("foo").await?;
+    ///
+    /// assert_eq!(1, client.touch().keys(&["foo", "bar"])?.await?);
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// [`Touch`]: request/touch/struct.Touch.html
+    pub fn touch(&self) -> Touch<B> {
+        Touch::new(self.backend())
+    }
+
+    /// Watch for modifications to keys matching a glob-style pattern.
+    ///
+    /// Returns a stream of [`KeyspaceEvent`]s on success, each containing the
+    /// ID of the command that modified a key and the key itself. Patterns
+    /// support `*` to match any number of bytes, `?` to match a single byte,
+    /// and `\` to escape the next byte.
+    ///
+    /// Events that aren't consumed quickly enough may be dropped.
+    ///
+    /// # Examples
+    ///
+    /// Watch for modifications to keys starting with "user:":
+    ///
+    /// ```
+    /// use futures_util::stream::StreamExt;
+    /// use hop::Client;
+    ///
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::memory();
+    /// let mut events = client.watch_keyspace("user:*").await?;
+    ///
+    /// client.set("user:1").int(123).await?;
+    ///
+    /// let event = events.next().await.expect("stream open")?;
+    /// assert_eq!(b"user:1", event.key());
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// [`KeyspaceEvent`]: struct.KeyspaceEvent.html
+    pub fn watch_keyspace<P: AsRef<[u8]> + Send + Unpin>(
+        &self,
+        pattern: P,
+    ) -> WatchKeyspace<'_, B, P> {
+        WatchKeyspace::new(self.backend(), pattern)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{backend::MemoryBackend, Client};
+    use super::{backend::MemoryBackend, Client, ListEnd, Value, SCAN_PAGE_LEN};
     use core::fmt::Debug;
+    use hop_engine::{
+        command::{CommandId, PROTOCOL_VERSION},
+        metrics::Metric,
+        Hop,
+    };
     use static_assertions::assert_impl_all;
 
     assert_impl_all!(Client<MemoryBackend>: Debug, Send, Sync);
+
+    #[tokio::test]
+    async fn test_memory_from_hop_shared() {
+        let hop = Hop::new();
+        let a = Client::memory_from_hop(hop.clone());
+        let b = Client::memory_from_hop(hop);
+
+        a.set("foo").string("bar").await.unwrap();
+        assert_eq!(Some("bar"), b.get("foo").string().await.unwrap().as_deref());
+
+        b.increment("baz").int().await.unwrap();
+        assert_eq!(2, a.increment("baz").int().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_commands() {
+        let commands = Client::memory().commands().await.unwrap();
+
+        assert_eq!(PROTOCOL_VERSION, commands.version());
+        assert!(!commands.commands().is_empty());
+        assert!(commands.supports(CommandId::Get));
+        assert!(commands.supports(CommandId::Command));
+    }
+
+    #[tokio::test]
+    async fn test_server_time() {
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        let client = Client::memory();
+        let (secs, micros) = client.server_time().await.unwrap();
+        let server = secs * 1_000_000 + u64::from(micros);
+        let local = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_micros() as u64;
+
+        assert!(micros < 1_000_000);
+        assert!(local.saturating_sub(server) < 5_000_000);
+        assert!(server.saturating_sub(local) < 5_000_000);
+    }
+
+    #[tokio::test]
+    async fn test_list_peek() {
+        let client = Client::memory();
+        assert!(client
+            .list_peek("foo", ListEnd::Front)
+            .await
+            .unwrap()
+            .is_none());
+
+        let list = [b"a".to_vec(), b"b".to_vec(), b"c".to_vec()];
+        client.set("foo").list(list.to_vec()).await.unwrap();
+
+        assert_eq!(
+            Some(b"a".to_vec()),
+            client.list_peek("foo", ListEnd::Front).await.unwrap()
+        );
+        assert_eq!(
+            Some(b"c".to_vec()),
+            client.list_peek("foo", ListEnd::Back).await.unwrap()
+        );
+        assert_eq!(3, client.length("foo").await.unwrap());
+
+        client.set("bar").int(1).await.unwrap();
+        assert!(client.list_peek("bar", ListEnd::Front).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_map_get_all() {
+        let client = Client::memory();
+        assert!(client.map_get_all("foo").await.unwrap().is_empty());
+
+        client
+            .set("foo")
+            .map(vec![("b", "two"), ("a", "one")])
+            .await
+            .unwrap();
+
+        let fields = client.map_get_all("foo").await.unwrap();
+        assert_eq!(
+            vec![
+                (b"a".to_vec(), Value::Bytes(b"one".to_vec())),
+                (b"b".to_vec(), Value::Bytes(b"two".to_vec())),
+            ],
+            fields.into_iter().collect::<Vec<_>>()
+        );
+
+        client.set("bar").int(1).await.unwrap();
+        assert!(client.map_get_all("bar").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_stream() {
+        use futures_util::stream::{StreamExt, TryStreamExt};
+
+        let value = (0..5 * 1024 * 1024)
+            .map(|idx: u32| (idx % 251) as u8)
+            .collect::<Vec<_>>();
+
+        let client = Client::memory();
+        client.set("foo").bytes(value.clone()).await.unwrap();
+
+        let chunks = client
+            .get_stream("foo")
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert!(chunks
+            .iter()
+            .all(|chunk| chunk.len() as u64 <= super::STREAM_CHUNK_LEN));
+        assert_eq!(value, chunks.concat());
+
+        // a missing key ends the stream with a single error
+        let results = client.get_stream("bar").collect::<Vec<_>>().await;
+        assert_eq!(1, results.len());
+        assert!(results[0].is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scan_stream() {
+        use futures_util::stream::{StreamExt, TryStreamExt};
+
+        let mut keys = (0..250)
+            .map(|idx| format!("key{}", idx).into_bytes())
+            .collect::<Vec<_>>();
+
+        let hop = Hop::new();
+        let client = Client::memory_from_hop(hop.clone());
+
+        for key in &keys {
+            client.set(key).int(1).await.unwrap();
+        }
+
+        client.set("other").int(1).await.unwrap();
+
+        // the keys span several pages
+        let streamed = client
+            .scan_stream("key*")
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        keys.sort();
+        assert_eq!(keys, streamed);
+
+        // a page is only fetched once the stream is polled for it
+        let commands = || hop.metrics().counter(&Metric::CommandsSuccessful).unwrap();
+        let before = commands();
+        let mut stream = Box::pin(client.scan_stream("*"));
+        assert_eq!(before, commands());
+
+        for _ in 0..SCAN_PAGE_LEN {
+            assert!(stream.next().await.unwrap().is_ok());
+        }
+
+        assert_eq!(before + 1, commands());
+
+        assert_eq!(0, client.scan_stream("missing*").count().await);
+    }
+
+    #[tokio::test]
+    async fn test_set_scan_stream() {
+        use futures_util::stream::{StreamExt, TryStreamExt};
+
+        let mut members = (0..250)
+            .map(|idx| format!("member{}", idx).into_bytes())
+            .collect::<Vec<_>>();
+
+        let client = Client::memory();
+        client.set("foo").set(members.clone()).await.unwrap();
+
+        let streamed = client
+            .set_scan_stream("foo")
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        members.sort();
+        assert_eq!(members, streamed);
+
+        assert_eq!(0, client.set_scan_stream("bar").count().await);
+
+        // a key that isn't a set ends the stream with a single error
+        client.set("baz").int(1).await.unwrap();
+        let results = client.set_scan_stream("baz").collect::<Vec<_>>().await;
+        assert_eq!(1, results.len());
+        assert!(results[0].is_err());
+    }
+
+    #[tokio::test]
+    async fn test_info() {
+        let client = Client::memory();
+        client.set("foo").int(1).await.unwrap();
+
+        let info = client.info().await.unwrap();
+
+        for field in &[
+            "version",
+            "uptime_seconds",
+            "keys",
+            "connected_clients",
+            "eviction_policy",
+        ] {
+            assert!(info.contains_key(*field), "missing {}", field);
+        }
+
+        assert_eq!("1", info["keys"]);
+        assert_eq!("lru", info["eviction_policy"]);
+    }
+
+    #[tokio::test]
+    async fn test_get_many_integers() {
+        let client = Client::memory();
+        client.set("foo").int(1).await.unwrap();
+        client.set("bar").int(2).await.unwrap();
+        client.set("baz").int(3).await.unwrap();
+
+        assert_eq!(
+            vec![Some(3), Some(1), Some(2), None, Some(1)],
+            client
+                .get_many_integers(&["baz", "foo", "bar", "qux", "foo"])
+                .await
+                .unwrap()
+        );
+        assert!(client
+            .get_many_integers(Vec::<&str>::new())
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_many_strings() {
+        let client = Client::memory();
+        client.set("foo").string("a").await.unwrap();
+        client.set("bar").string("b").await.unwrap();
+
+        assert_eq!(
+            vec![Some("b".to_owned()), Some("a".to_owned())],
+            client.get_many_strings(&["bar", "foo"]).await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_many_missing() {
+        let client = Client::memory();
+
+        assert_eq!(
+            vec![None, None],
+            client.get_many_strings(&["foo", "bar"]).await.unwrap()
+        );
+    }
 }
@@ -1,21 +1,71 @@
-use alloc::vec::Vec;
-use core::convert::TryInto;
-use hop_engine::{dashmap::DashMap, metrics::Metric};
+use alloc::{collections::VecDeque, vec::Vec};
+use core::convert::{TryFrom, TryInto};
+use hop_engine::{command::CommandId, dashmap::DashMap, metrics::Metric, state::Value};
+
+/// Protocol version and commands supported by an instance.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommandsData {
+    commands: Vec<CommandId>,
+    version: u32,
+}
+
+impl CommandsData {
+    /// Parse the response of the command listing, returning `None` if it's
+    /// malformed.
+    ///
+    /// IDs of commands that this build doesn't know about are skipped.
+    pub(crate) fn from_list(list: &VecDeque<Vec<u8>>) -> Option<Self> {
+        let mut items = list.iter();
+        let version = u32::from_be_bytes(items.next()?.as_slice().try_into().ok()?);
+        let commands = items
+            .filter_map(|item| match item.as_slice() {
+                [id] => Some(CommandId::try_from(*id).ok()),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+
+        Some(Self { commands, version })
+    }
+
+    /// Commands supported by the instance.
+    pub fn commands(&self) -> &[CommandId] {
+        &self.commands
+    }
+
+    /// Whether the instance supports a command.
+    pub fn supports(&self, command_id: CommandId) -> bool {
+        self.commands.contains(&command_id)
+    }
+
+    /// Version of the protocol used by the instance.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct StatsData {
-    inner: DashMap<Vec<u8>, Vec<u8>>,
+    inner: DashMap<Vec<u8>, Value>,
 }
 
 impl StatsData {
-    pub(crate) fn new(map: DashMap<Vec<u8>, Vec<u8>>) -> Self {
+    pub fn bytes_read(&self) -> i64 {
+        self.int(Metric::BytesRead)
+    }
+
+    pub fn bytes_written(&self) -> i64 {
+        self.int(Metric::BytesWritten)
+    }
+
+    pub(crate) fn new(map: DashMap<Vec<u8>, Value>) -> Self {
         Self { inner: map }
     }
 
     fn int(&self, metric: Metric) -> i64 {
         self.inner
             .get(metric.name().as_bytes())
-            .and_then(|bytes| bytes.as_slice().try_into().ok())
+            .and_then(|value| value.as_bytes_ref()?.try_into().ok())
             .map(i64::from_be_bytes)
             .unwrap_or_default()
     }
@@ -28,6 +78,18 @@ impl StatsData {
         self.int(Metric::CommandsSuccessful)
     }
 
+    pub fn connections_active(&self) -> i64 {
+        self.int(Metric::ConnectionsActive)
+    }
+
+    pub fn connections_opened(&self) -> i64 {
+        self.int(Metric::ConnectionsOpened)
+    }
+
+    pub fn keys_evicted(&self) -> i64 {
+        self.int(Metric::KeysEvicted)
+    }
+
     pub fn sessions_ended(&self) -> i64 {
         self.int(Metric::SessionsEnded)
     }
@@ -39,9 +101,31 @@ impl StatsData {
 
 #[cfg(test)]
 mod tests {
-    use super::StatsData;
+    use super::{CommandsData, StatsData};
+    use alloc::{collections::VecDeque, vec};
     use core::fmt::Debug;
+    use hop_engine::command::CommandId;
     use static_assertions::assert_impl_all;
 
+    assert_impl_all!(CommandsData: Clone, Debug, Eq, PartialEq);
     assert_impl_all!(StatsData: Clone, Debug);
+
+    #[test]
+    fn test_commands_data_from_list() {
+        let list = VecDeque::from(vec![
+            2u32.to_be_bytes().to_vec(),
+            vec![CommandId::Get as u8],
+            // unknown to this build
+            vec![255],
+            vec![CommandId::Set as u8],
+        ]);
+        let data = CommandsData::from_list(&list).unwrap();
+
+        assert_eq!(2, data.version());
+        assert_eq!(&[CommandId::Get, CommandId::Set], data.commands());
+        assert!(data.supports(CommandId::Get));
+        assert!(!data.supports(CommandId::Echo));
+
+        assert!(CommandsData::from_list(&VecDeque::new()).is_none());
+    }
 }
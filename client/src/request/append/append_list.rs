@@ -44,10 +44,10 @@ impl<'a, B: Backend + Send + Sync + 'static, K: AsRef<[u8]> + Send + Unpin> Futu
 
             self.fut.replace(Box::pin(async move {
                 let key = key.as_ref();
-                let value = backend.append(key, Value::List(value)).await?;
+                let value = backend.append(key, Value::from(value)).await?;
 
                 match value {
-                    Value::List(list) => Ok(list),
+                    Value::List(list) => Ok(list.into()),
                     _ => unreachable!(),
                 }
             }));
@@ -43,6 +43,23 @@ impl<'a, B: Backend, K: AsRef<[u8]> + 'a + Send + Unpin> AppendUnconfigured<B, K
     }
 
     /// Append one or more items to a list.
+    ///
+    /// The returned struct, when `await`ed, will resolve to the new list on
+    /// success.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop::Client;
+    ///
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::memory();
+    ///
+    /// client.set("foo").list([b"a".to_vec()].to_vec()).await?;
+    ///
+    /// assert_eq!(3, client.append("foo").list([b"b".to_vec(), b"c".to_vec()].to_vec()).await?.len());
+    /// # Ok(()) }
+    /// ```
     pub fn list(self, list: impl Into<Vec<Vec<u8>>>) -> AppendList<'a, B, K> {
         AppendList::new(self.backend, self.key, list.into())
     }
@@ -56,6 +73,23 @@ impl<'a, B: Backend, K: AsRef<[u8]> + 'a + Send + Unpin> AppendUnconfigured<B, K
     }
 
     /// Append to a string.
+    ///
+    /// The returned struct, when `await`ed, will resolve to the new string on
+    /// success.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop::Client;
+    ///
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::memory();
+    ///
+    /// client.set("foo").string("hello").await?;
+    ///
+    /// assert_eq!("hello world", client.append("foo").str(" world").await?);
+    /// # Ok(()) }
+    /// ```
     pub fn str(self, string: impl Into<String>) -> AppendString<'a, B, K> {
         AppendString::new(self.backend, self.key, string.into())
     }
@@ -64,9 +98,55 @@ impl<'a, B: Backend, K: AsRef<[u8]> + 'a + Send + Unpin> AppendUnconfigured<B, K
 #[cfg(test)]
 mod tests {
     use super::AppendUnconfigured;
-    use crate::backend::MemoryBackend;
-    use alloc::vec::Vec;
+    use crate::{backend::MemoryBackend, Client};
+    use alloc::{vec, vec::Vec};
     use static_assertions::assert_impl_all;
 
     assert_impl_all!(AppendUnconfigured<MemoryBackend, Vec<u8>>: Send);
+
+    #[tokio::test]
+    async fn test_bytes() {
+        let client = Client::memory();
+        client.set("foo").bytes(vec![1, 2]).await.unwrap();
+
+        assert_eq!(
+            vec![1, 2, 3],
+            client.append("foo").bytes(vec![3]).await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list() {
+        let client = Client::memory();
+        client.set("foo").list(vec![b"a".to_vec()]).await.unwrap();
+
+        assert_eq!(
+            vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()],
+            client
+                .append("foo")
+                .list(vec![b"b".to_vec(), b"c".to_vec()])
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_string() {
+        let client = Client::memory();
+        client.set("foo").string("foo").await.unwrap();
+
+        assert_eq!("foobar", client.append("foo").string("bar").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_type_mismatch() {
+        let client = Client::memory();
+        client.set("foo").string("foo").await.unwrap();
+
+        assert!(client.append("foo").bytes(vec![1]).await.is_err());
+        assert_eq!(
+            Some("foo"),
+            client.get("foo").str().await.unwrap().as_deref()
+        );
+    }
 }
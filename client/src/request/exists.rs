@@ -58,10 +58,7 @@ impl<'a, B: Backend> Exists<B> {
     ///
     /// [struct docs]: #main
     pub fn key<K: AsRef<[u8]> + 'a + Send + Unpin>(self, key: K) -> ExistsConfigured<'a, B, K> {
-        let mut keys = Vec::new();
-        keys.push(key);
-
-        ExistsConfigured::new(self.backend, keys)
+        ExistsConfigured::new(self.backend, alloc::vec![key])
     }
 
     /// Check that one or more keys exist.
@@ -6,7 +6,7 @@ use core::{
     pin::Pin,
     task::{Context, Poll},
 };
-use hop_engine::state::Value;
+use hop_engine::state::{KeyType, Value};
 
 /// A configured `get` command that will resolve to a float when `await`ed.
 ///
@@ -15,7 +15,7 @@ use hop_engine::state::Value;
 /// [`GetUnconfigured::float`]: struct.GetUnconfigured.html#method.float
 pub struct GetFloat<'a, B: Backend, K: AsRef<[u8]> + 'a + Send + Unpin> {
     backend: Option<Arc<B>>,
-    fut: MaybeInFlightFuture<'a, f64, B::Error>,
+    fut: MaybeInFlightFuture<'a, Option<f64>, B::Error>,
     key: Option<K>,
 }
 
@@ -32,7 +32,7 @@ impl<'a, B: Backend, K: AsRef<[u8]> + 'a + Send + Unpin> GetFloat<'a, B, K> {
 impl<'a, B: Backend + Send + Sync + 'static, K: AsRef<[u8]> + Send + Unpin> Future
     for GetFloat<'a, B, K>
 {
-    type Output = Result<f64, B::Error>;
+    type Output = Result<Option<f64>, B::Error>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         if self.fut.is_none() {
@@ -41,10 +41,11 @@ impl<'a, B: Backend + Send + Sync + 'static, K: AsRef<[u8]> + Send + Unpin> Futu
 
             self.fut.replace(Box::pin(async move {
                 let key = key.as_ref();
-                let value = backend.get(key).await?;
+                let value = backend.get(key, Some(KeyType::Float)).await?;
 
                 match value {
-                    Value::Float(float) => Ok(float),
+                    Some(Value::Float(float)) => Ok(Some(float)),
+                    None => Ok(None),
                     _ => unreachable!(),
                 }
             }));
@@ -6,7 +6,7 @@ use core::{
     pin::Pin,
     task::{Context, Poll},
 };
-use hop_engine::state::Value;
+use hop_engine::state::{KeyType, Value};
 
 /// A configured `get` command that will resolve to a list when `await`ed.
 ///
@@ -15,7 +15,7 @@ use hop_engine::state::Value;
 /// [`GetUnconfigured::list`]: struct.GetUnconfigured.html#method.list
 pub struct GetList<'a, B: Backend, K: AsRef<[u8]> + 'a + Send + Unpin> {
     backend: Option<Arc<B>>,
-    fut: MaybeInFlightFuture<'a, Vec<Vec<u8>>, B::Error>,
+    fut: MaybeInFlightFuture<'a, Option<Vec<Vec<u8>>>, B::Error>,
     key: Option<K>,
 }
 
@@ -32,7 +32,7 @@ impl<'a, B: Backend, K: AsRef<[u8]> + 'a + Send + Unpin> GetList<'a, B, K> {
 impl<'a, B: Backend + Send + Sync + 'static, K: AsRef<[u8]> + Send + Unpin> Future
     for GetList<'a, B, K>
 {
-    type Output = Result<Vec<Vec<u8>>, B::Error>;
+    type Output = Result<Option<Vec<Vec<u8>>>, B::Error>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         if self.fut.is_none() {
@@ -41,10 +41,11 @@ impl<'a, B: Backend + Send + Sync + 'static, K: AsRef<[u8]> + Send + Unpin> Futu
 
             self.fut.replace(Box::pin(async move {
                 let key = key.as_ref();
-                let value = backend.get(key).await?;
+                let value = backend.get(key, Some(KeyType::List)).await?;
 
                 match value {
-                    Value::List(list) => Ok(list),
+                    Some(Value::List(list)) => Ok(Some(list.into())),
+                    None => Ok(None),
                     _ => unreachable!(),
                 }
             }));
@@ -6,7 +6,12 @@ use core::{
     pin::Pin,
     task::{Context, Poll},
 };
-use hop_engine::{dashmap::DashMap, state::Value};
+use hop_engine::{
+    dashmap::DashMap,
+    state::{KeyType, Value},
+};
+
+type Map = DashMap<Vec<u8>, Value>;
 
 /// A configured `get` command that will resolve to a map when `await`ed.
 ///
@@ -15,7 +20,7 @@ use hop_engine::{dashmap::DashMap, state::Value};
 /// [`GetUnconfigured::map`]: struct.GetUnconfigured.html#method.map
 pub struct GetMap<'a, B: Backend, K: AsRef<[u8]> + 'a + Send + Unpin> {
     backend: Option<Arc<B>>,
-    fut: MaybeInFlightFuture<'a, DashMap<Vec<u8>, Vec<u8>>, B::Error>,
+    fut: MaybeInFlightFuture<'a, Option<Map>, B::Error>,
     key: Option<K>,
 }
 
@@ -32,7 +37,7 @@ impl<'a, B: Backend, K: AsRef<[u8]> + 'a + Send + Unpin> GetMap<'a, B, K> {
 impl<'a, B: Backend + Send + Sync + 'static, K: AsRef<[u8]> + Send + Unpin> Future
     for GetMap<'a, B, K>
 {
-    type Output = Result<DashMap<Vec<u8>, Vec<u8>>, B::Error>;
+    type Output = Result<Option<Map>, B::Error>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         if self.fut.is_none() {
@@ -41,10 +46,11 @@ impl<'a, B: Backend + Send + Sync + 'static, K: AsRef<[u8]> + Send + Unpin> Futu
 
             self.fut.replace(Box::pin(async move {
                 let key = key.as_ref();
-                let value = backend.get(key).await?;
+                let value = backend.get(key, Some(KeyType::Map)).await?;
 
                 match value {
-                    Value::Map(map) => Ok(map),
+                    Some(Value::Map(map)) => Ok(Some(map)),
+                    None => Ok(None),
                     _ => unreachable!(),
                 }
             }));
@@ -6,7 +6,10 @@ use core::{
     pin::Pin,
     task::{Context, Poll},
 };
-use hop_engine::{dashmap::DashSet, state::Value};
+use hop_engine::{
+    dashmap::DashSet,
+    state::{KeyType, Value},
+};
 
 /// A configured `get` command that will resolve to a set when `await`ed.
 ///
@@ -15,7 +18,7 @@ use hop_engine::{dashmap::DashSet, state::Value};
 /// [`GetUnconfigured::set`]: struct.GetUnconfigured.html#method.set
 pub struct GetSet<'a, B: Backend, K: AsRef<[u8]> + 'a + Send + Unpin> {
     backend: Option<Arc<B>>,
-    fut: MaybeInFlightFuture<'a, DashSet<Vec<u8>>, B::Error>,
+    fut: MaybeInFlightFuture<'a, Option<DashSet<Vec<u8>>>, B::Error>,
     key: Option<K>,
 }
 
@@ -32,7 +35,7 @@ impl<'a, B: Backend, K: AsRef<[u8]> + 'a + Send + Unpin> GetSet<'a, B, K> {
 impl<'a, B: Backend + Send + Sync + 'static, K: AsRef<[u8]> + Send + Unpin> Future
     for GetSet<'a, B, K>
 {
-    type Output = Result<DashSet<Vec<u8>>, B::Error>;
+    type Output = Result<Option<DashSet<Vec<u8>>>, B::Error>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         if self.fut.is_none() {
@@ -41,10 +44,11 @@ impl<'a, B: Backend + Send + Sync + 'static, K: AsRef<[u8]> + Send + Unpin> Futu
 
             self.fut.replace(Box::pin(async move {
                 let key = key.as_ref();
-                let value = backend.get(key).await?;
+                let value = backend.get(key, Some(KeyType::Set)).await?;
 
                 match value {
-                    Value::Set(set) => Ok(set),
+                    Some(Value::Set(set)) => Ok(Some(set)),
+                    None => Ok(None),
                     _ => unreachable!(),
                 }
             }));
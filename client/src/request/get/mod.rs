@@ -36,6 +36,9 @@ use hop_engine::state::Value;
 /// If you just want a raw engine value or don't know the type, `await` this
 /// struct to resolve to a value on success.
 ///
+/// Each of these resolves to `None` if the key doesn't exist. The typed
+/// requests resolve to an error if the key is of another type.
+///
 /// # Examples
 ///
 /// Get the key "foo" which is known to be a boolean:
@@ -48,8 +51,8 @@ use hop_engine::state::Value;
 ///
 /// client.set("foo").bool(true).await?;
 ///
-/// // we know that it will resolve to a boolean on success
-/// assert_eq!(true, client.get("foo").bool().await?);
+/// // we know that it will resolve to a boolean if it exists
+/// assert_eq!(Some(true), client.get("foo").bool().await?);
 /// # Ok(()) }
 /// ```
 ///
@@ -59,7 +62,7 @@ use hop_engine::state::Value;
 /// [`GetInteger`]: struct.GetInteger.html
 pub struct GetUnconfigured<'a, B: Backend, K: AsRef<[u8]> + Send + Unpin> {
     backend: Option<Arc<B>>,
-    fut: MaybeInFlightFuture<'a, Value, B::Error>,
+    fut: MaybeInFlightFuture<'a, Option<Value>, B::Error>,
     key: Option<K>,
 }
 
@@ -103,6 +106,28 @@ impl<'a, B: Backend, K: AsRef<[u8]> + 'a + Send + Unpin> GetUnconfigured<'a, B,
         GetBoolean::new(self.backend.unwrap(), self.key.unwrap())
     }
 
+    /// Get a key as whatever type it is.
+    ///
+    /// This is the same as `await`ing this struct directly, but makes it clear
+    /// at the call site that any type is accepted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop::{Client, Value};
+    ///
+    /// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::memory();
+    /// client.set("foo").float(1.5).await?;
+    ///
+    /// assert!(matches!(client.get("foo").dynamic().await?, Some(Value::Float(_))));
+    /// # Ok(()) }
+    /// ```
+    #[inline]
+    pub fn dynamic(self) -> Self {
+        self
+    }
+
     /// Get a key as some bytes.
     ///
     /// The returned struct, when `await`ed, will resolve to a `Vec<u8>` on
@@ -119,7 +144,7 @@ impl<'a, B: Backend, K: AsRef<[u8]> + 'a + Send + Unpin> GetUnconfigured<'a, B,
     /// let client = Client::memory();
     /// client.set("foo").bytes([1u8, 2, 3, 4, 5].as_ref()).await?;
     ///
-    /// assert_eq!(5, client.get("foo").bytes().await?.len());
+    /// assert_eq!(Some(5), client.get("foo").bytes().await?.map(|bytes| bytes.len()));
     /// # Ok(()) }
     /// ```
     pub fn bytes(self) -> GetBytes<'a, B, K> {
@@ -141,7 +166,7 @@ impl<'a, B: Backend, K: AsRef<[u8]> + 'a + Send + Unpin> GetUnconfigured<'a, B,
     /// let client = Client::memory();
     /// client.set("foo").float(1.23).await?;
     ///
-    /// assert!(client.get("foo").float().await? > 1.0);
+    /// assert!(client.get("foo").float().await?.unwrap() > 1.0);
     /// # Ok(()) }
     /// ```
     pub fn float(self) -> GetFloat<'a, B, K> {
@@ -171,7 +196,7 @@ impl<'a, B: Backend, K: AsRef<[u8]> + 'a + Send + Unpin> GetUnconfigured<'a, B,
     /// let client = Client::memory();
     /// client.set("foo").int(123).await?;
     ///
-    /// assert_eq!(123, client.get("foo").int().await?);
+    /// assert_eq!(Some(123), client.get("foo").int().await?);
     /// # Ok(()) }
     /// ```
     pub fn int(self) -> GetInteger<'a, B, K> {
@@ -230,7 +255,7 @@ impl<'a, B: Backend, K: AsRef<[u8]> + 'a + Send + Unpin> GetUnconfigured<'a, B,
     /// let client = Client::memory();
     /// client.set("foo").set([b"foo".to_vec(), b"bar".to_vec(), b"foo".to_vec()].to_vec()).await?;
     ///
-    /// assert_eq!(2, client.get("foo").set().await?.len());
+    /// assert_eq!(Some(2), client.get("foo").set().await?.map(|set| set.len()));
     /// # Ok(()) }
     /// ```
     pub fn set(self) -> GetSet<'a, B, K> {
@@ -261,7 +286,7 @@ impl<'a, B: Backend, K: AsRef<[u8]> + 'a + Send + Unpin> GetUnconfigured<'a, B,
     /// let client = Client::memory();
     /// client.set("foo").str("bar").await?;
     ///
-    /// assert_eq!("bar", client.get("foo").str().await?);
+    /// assert_eq!(Some("bar"), client.get("foo").str().await?.as_deref());
     /// # Ok(()) }
     /// ```
     pub fn str(self) -> GetString<'a, B, K> {
@@ -272,7 +297,7 @@ impl<'a, B: Backend, K: AsRef<[u8]> + 'a + Send + Unpin> GetUnconfigured<'a, B,
 impl<'a, B: Backend + Send + Sync + 'static, K: AsRef<[u8]> + Send + Unpin + 'a> Future
     for GetUnconfigured<'a, B, K>
 {
-    type Output = Result<Value, B::Error>;
+    type Output = Result<Option<Value>, B::Error>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         if self.fut.is_none() {
@@ -281,7 +306,7 @@ impl<'a, B: Backend + Send + Sync + 'static, K: AsRef<[u8]> + Send + Unpin + 'a>
 
             self.fut.replace(Box::pin(async move {
                 let key = key.as_ref();
-                backend.get(key).await
+                backend.get(key, None).await
             }));
         }
 
@@ -292,9 +317,55 @@ impl<'a, B: Backend + Send + Sync + 'static, K: AsRef<[u8]> + Send + Unpin + 'a>
 #[cfg(test)]
 mod tests {
     use super::GetUnconfigured;
-    use crate::backend::MemoryBackend;
+    use crate::{
+        backend::{memory::Error as MemoryError, MemoryBackend},
+        Client,
+    };
     use alloc::vec::Vec;
+    use hop_engine::{command::DispatchError, state::Value};
     use static_assertions::assert_impl_all;
 
     assert_impl_all!(GetUnconfigured<MemoryBackend, Vec<u8>>: Send);
+
+    #[tokio::test]
+    async fn test_missing_key() {
+        let client = Client::memory();
+
+        assert!(client.get("foo").await.unwrap().is_none());
+        assert!(client.get("foo").int().await.unwrap().is_none());
+        assert!(client.get("foo").str().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_type_mismatch() {
+        let client = Client::memory();
+        client.set("foo").int(1).await.unwrap();
+
+        assert!(matches!(
+            client.get("foo").str().await,
+            Err(MemoryError::RunningCommand {
+                source: DispatchError::KeyTypeDifferent
+            })
+        ));
+        assert!(client.get("foo").bool().await.is_err());
+        assert!(client.get("foo").list().await.is_err());
+        assert_eq!(Some(1), client.get("foo").int().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_dynamic() {
+        let client = Client::memory();
+        client.set("foo").int(1).await.unwrap();
+        client.set("bar").str("baz").await.unwrap();
+
+        assert!(matches!(
+            client.get("foo").dynamic().await.unwrap(),
+            Some(Value::Integer(1))
+        ));
+        assert!(matches!(
+            client.get("bar").dynamic().await.unwrap(),
+            Some(Value::String(string)) if string == "baz"
+        ));
+        assert!(client.get("qux").dynamic().await.unwrap().is_none());
+    }
 }
@@ -60,8 +60,7 @@ impl<'a, B: Backend> Is<B> {
     ///
     /// [struct docs]: #main
     pub fn key<K: AsRef<[u8]> + 'a + Send + Unpin>(self, key: K) -> IsConfigured<'a, B, K> {
-        let mut keys = Vec::new();
-        keys.push(key);
+        let keys = alloc::vec![key];
 
         IsConfigured::new(self.backend, self.key_type, keys)
     }
@@ -3,6 +3,7 @@ pub mod exists;
 pub mod get;
 pub mod is;
 pub mod set;
+pub mod touch;
 
 mod decrement;
 mod delete;
@@ -13,6 +14,7 @@ mod length;
 mod rename;
 mod stats;
 mod r#type;
+mod watch_keyspace;
 
 pub use self::{
     decrement::Decrement,
@@ -26,6 +28,8 @@ pub use self::{
     r#type::Type,
     rename::Rename,
     stats::Stats,
+    touch::{Touch, TouchConfigured},
+    watch_keyspace::WatchKeyspace,
 };
 
 use alloc::boxed::Box;
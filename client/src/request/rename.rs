@@ -1,6 +1,6 @@
 use super::MaybeInFlightFuture;
-use crate::Backend;
-use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use crate::{Backend, Value};
+use alloc::{boxed::Box, sync::Arc};
 use core::{
     future::Future,
     pin::Pin,
@@ -14,7 +14,7 @@ pub struct Rename<
     T: AsRef<[u8]> + 'a + Send + Unpin,
 > {
     backend: Option<Arc<B>>,
-    fut: MaybeInFlightFuture<'a, Vec<u8>, B::Error>,
+    fut: MaybeInFlightFuture<'a, Value, B::Error>,
     from: Option<F>,
     to: Option<T>,
 }
@@ -39,7 +39,7 @@ impl<
         T: AsRef<[u8]> + Send + Unpin,
     > Future for Rename<'a, B, F, T>
 {
-    type Output = Result<Vec<u8>, B::Error>;
+    type Output = Result<Value, B::Error>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         if self.fut.is_none() {
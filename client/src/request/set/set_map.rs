@@ -15,7 +15,7 @@ use hop_engine::{dashmap::DashMap, state::Value};
 /// [`SetUnconfigured::map`]: struct.SetUnconfigured.html#method.map
 pub struct SetMap<'a, B: Backend, K: AsRef<[u8]> + 'a + Send + Unpin> {
     backend: Option<Arc<B>>,
-    fut: MaybeInFlightFuture<'a, DashMap<Vec<u8>, Vec<u8>>, B::Error>,
+    fut: MaybeInFlightFuture<'a, DashMap<Vec<u8>, Value>, B::Error>,
     key: Option<K>,
     value: Option<DashMap<Vec<u8>, Vec<u8>>>,
 }
@@ -34,7 +34,7 @@ impl<'a, B: Backend, K: AsRef<[u8]> + 'a + Send + Unpin> SetMap<'a, B, K> {
 impl<'a, B: Backend + Send + Sync + 'static, K: AsRef<[u8]> + Send + Unpin> Future
     for SetMap<'a, B, K>
 {
-    type Output = Result<DashMap<Vec<u8>, Vec<u8>>, B::Error>;
+    type Output = Result<DashMap<Vec<u8>, Value>, B::Error>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         if self.fut.is_none() {
@@ -44,7 +44,7 @@ impl<'a, B: Backend + Send + Sync + 'static, K: AsRef<[u8]> + Send + Unpin> Futu
 
             self.fut.replace(Box::pin(async move {
                 let key = key.as_ref();
-                let value = backend.set(key, Value::Map(value)).await?;
+                let value = backend.set(key, Value::from(value)).await?;
 
                 match value {
                     Value::Map(map) => Ok(map),
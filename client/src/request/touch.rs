@@ -0,0 +1,129 @@
+use super::{CommandConfigurationError, MaybeInFlightFuture};
+use crate::Backend;
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Request to record an access of one or more keys.
+///
+/// Like [`Exists`], [`Client::touch`] returns this intermediary struct which
+/// contains two methods, [`key`] and [`keys`], to touch either one key or an
+/// iterator of keys.
+///
+/// # Examples
+///
+/// Touch one, and then two, keys:
+///
+/// ```
+/// use hop::Client;
+///
+/// # #[tokio::main] async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = Client::memory();
+/// client.increment("foo").await?;
+/// assert_eq!(1, client.touch().key("foo").await?);
+///
+/// // "bar" doesn't exist, so only one key is touched
+/// assert_eq!(1, client.touch().keys(&["foo", "bar"])?.await?);
+/// # Ok(()) }
+/// ```
+///
+/// [`Exists`]: ../exists/struct.Exists.html
+/// [`Client::touch`]: ../../struct.Client.html#method.touch
+/// [`key`]: #method.key
+/// [`keys`]: #method.keys
+pub struct Touch<B: Backend> {
+    backend: Arc<B>,
+}
+
+impl<'a, B: Backend> Touch<B> {
+    pub(crate) fn new(backend: Arc<B>) -> Self {
+        Self { backend }
+    }
+
+    /// Touch a single key.
+    ///
+    /// Refer to the [struct docs] for more information.
+    ///
+    /// [struct docs]: #main
+    pub fn key<K: AsRef<[u8]> + 'a + Send + Unpin>(self, key: K) -> TouchConfigured<'a, B, K> {
+        TouchConfigured::new(self.backend, alloc::vec![key])
+    }
+
+    /// Touch one or more keys.
+    ///
+    /// Refer to the [struct docs] for more information.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CommandConfigurationError::NoKeys`] if no keys were provided.
+    ///
+    /// Returns [`CommandConfigurationError::TooManyKeys`] if more than the
+    /// argument limit (255) worth of keys were provided.
+    ///
+    /// [`CommandConfigurationError::NoKeys`]: enum.CommandConfigurationError.html#variant.NoKeys
+    /// [`CommandConfigurationError::TooManyKeys`]: enum.CommandConfigurationError.html#variant.TooManyKeys
+    /// [struct docs]: #main
+    pub fn keys<K: AsRef<[u8]> + 'a + Send + Unpin>(
+        self,
+        keys: impl IntoIterator<Item = K>,
+    ) -> Result<TouchConfigured<'a, B, K>, CommandConfigurationError> {
+        let keys: Vec<K> = keys.into_iter().collect();
+
+        if keys.is_empty() {
+            return Err(CommandConfigurationError::NoKeys);
+        } else if keys.len() > u8::MAX as usize {
+            return Err(CommandConfigurationError::TooManyKeys);
+        }
+
+        Ok(TouchConfigured::new(self.backend, keys))
+    }
+}
+
+/// A configured request to touch one or more keys.
+pub struct TouchConfigured<'a, B: Backend, K: AsRef<[u8]> + 'a + Send + Unpin> {
+    backend: Option<Arc<B>>,
+    fut: MaybeInFlightFuture<'a, i64, B::Error>,
+    keys: Option<Vec<K>>,
+}
+
+impl<'a, B: Backend, K: AsRef<[u8]> + 'a + Send + Unpin> TouchConfigured<'a, B, K> {
+    fn new(backend: Arc<B>, keys: Vec<K>) -> Self {
+        Self {
+            backend: Some(backend),
+            fut: None,
+            keys: Some(keys),
+        }
+    }
+}
+
+impl<'a, B: Backend + Send + 'static, K: AsRef<[u8]> + 'a + Send + Unpin> Future
+    for TouchConfigured<'a, B, K>
+{
+    type Output = Result<i64, B::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.fut.is_none() {
+            let backend = { self.backend.take().expect("backend only taken once") };
+            let keys = self.keys.take().expect("keys only taken once");
+
+            self.fut
+                .replace(Box::pin(async move { backend.touch(keys).await }));
+        }
+
+        self.fut.as_mut().expect("future touch").as_mut().poll(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Touch, TouchConfigured};
+    use crate::backend::MemoryBackend;
+    use alloc::vec::Vec;
+    use static_assertions::assert_impl_all;
+
+    assert_impl_all!(Touch<MemoryBackend>: Send);
+    assert_impl_all!(TouchConfigured<MemoryBackend, Vec<u8>>: Send);
+}
@@ -0,0 +1,54 @@
+use super::MaybeInFlightFuture;
+use crate::{backend::KeyspaceEvents, Backend};
+use alloc::{boxed::Box, sync::Arc};
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+pub struct WatchKeyspace<'a, B: Backend, P: AsRef<[u8]> + 'a + Send + Unpin> {
+    backend: Option<Arc<B>>,
+    fut: MaybeInFlightFuture<'a, KeyspaceEvents<B::Error>, B::Error>,
+    pattern: Option<P>,
+}
+
+impl<'a, B: Backend, P: AsRef<[u8]> + 'a + Send + Unpin> WatchKeyspace<'a, B, P> {
+    pub(crate) fn new(backend: Arc<B>, pattern: P) -> Self {
+        Self {
+            backend: Some(backend),
+            fut: None,
+            pattern: Some(pattern),
+        }
+    }
+}
+
+impl<'a, B: Backend + Send + Sync + 'static, P: AsRef<[u8]> + Send + Unpin> Future
+    for WatchKeyspace<'a, B, P>
+{
+    type Output = Result<KeyspaceEvents<B::Error>, B::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.fut.is_none() {
+            let backend = { self.backend.take().expect("backend only taken once") };
+            let pattern = self.pattern.take().expect("pattern only taken once");
+
+            self.fut.replace(Box::pin(async move {
+                let pattern = pattern.as_ref();
+                backend.watch_keyspace(pattern).await
+            }));
+        }
+
+        self.fut.as_mut().expect("future exists").as_mut().poll(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WatchKeyspace;
+    use crate::backend::MemoryBackend;
+    use alloc::vec::Vec;
+    use static_assertions::assert_impl_all;
+
+    assert_impl_all!(WatchKeyspace<MemoryBackend, Vec<u8>>: Send);
+}
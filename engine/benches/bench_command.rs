@@ -7,6 +7,7 @@ extern crate test;
 use alloc::vec::Vec;
 use hop_engine::{
     command::{request::RequestBuilder, CommandId},
+    state::KeyType,
     Hop,
 };
 use test::Bencher;
@@ -23,3 +24,18 @@ fn bench_increment(b: &mut Bencher) {
         hop.dispatch(&req, &mut resp).unwrap();
     });
 }
+
+#[bench]
+fn bench_set_list(b: &mut Bencher) {
+    let hop = Hop::new();
+    let mut builder = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::List);
+    builder.bytes(b"foo".as_ref()).unwrap();
+    builder.args((0..200u8).map(|num| vec![num; 64])).unwrap();
+    let req = builder.into_request();
+    let mut resp = Vec::new();
+
+    b.iter(|| {
+        hop.dispatch(&req, &mut resp).unwrap();
+        resp.clear();
+    });
+}
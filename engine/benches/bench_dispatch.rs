@@ -0,0 +1,146 @@
+//! Benchmarks of the dispatch hot path: parsing requests and dispatching them
+//! against an in-process engine.
+
+#![cfg(test)]
+#![feature(test)]
+
+extern crate alloc;
+extern crate test;
+
+use alloc::vec::Vec;
+use hop_engine::{
+    command::{
+        request::{Context, RequestBuilder},
+        CommandId,
+    },
+    state::KeyType,
+    Hop,
+};
+use test::Bencher;
+
+/// The number of commands in a pipelined batch.
+const BATCH_LEN: usize = 32;
+
+fn set_request(key: &[u8], value: Vec<u8>) -> Vec<u8> {
+    let mut builder = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::Bytes);
+    builder.bytes(key).unwrap();
+    builder.bytes(value).unwrap();
+
+    builder.into_request().as_bytes().to_vec()
+}
+
+fn get_request(key: &[u8]) -> Vec<u8> {
+    let mut builder = RequestBuilder::new(CommandId::Get);
+    builder.bytes(key).unwrap();
+
+    builder.into_request().as_bytes().to_vec()
+}
+
+#[bench]
+fn bench_parse_set(b: &mut Bencher) {
+    let input = test::black_box(set_request(b"foo", vec![b'a'; 64]));
+    let mut ctx = Context::new();
+
+    b.iter(|| {
+        ctx.feed(&input).unwrap().unwrap();
+    });
+}
+
+#[bench]
+fn bench_dispatch_set(b: &mut Bencher) {
+    let hop = Hop::new();
+    let input = set_request(b"foo", vec![b'a'; 64]);
+    let mut ctx = Context::new();
+    let req = ctx.feed(&input).unwrap().unwrap();
+    let mut resp = Vec::new();
+
+    b.iter(|| {
+        hop.dispatch(&req, &mut resp).unwrap();
+        resp.clear();
+    });
+}
+
+#[bench]
+fn bench_dispatch_get(b: &mut Bencher) {
+    let hop = Hop::new();
+    let mut ctx = Context::new();
+    let set = set_request(b"foo", vec![b'a'; 64]);
+    hop.dispatch(&ctx.feed(&set).unwrap().unwrap(), &mut Vec::new())
+        .unwrap();
+
+    let input = get_request(b"foo");
+    let req = ctx.feed(&input).unwrap().unwrap();
+    let mut resp = Vec::new();
+
+    b.iter(|| {
+        hop.dispatch(&req, &mut resp).unwrap();
+        resp.clear();
+    });
+}
+
+/// Parse and dispatch every command in a buffer, like a connection does with
+/// a pipeline.
+#[bench]
+fn bench_pipelined_batch(b: &mut Bencher) {
+    let hop = Hop::new();
+    let mut input = Vec::new();
+
+    // Alternate between setting a key and getting it back.
+    for idx in 0..BATCH_LEN / 2 {
+        let key = [b'k', idx as u8];
+
+        input.extend(set_request(&key, vec![b'a'; 64]));
+        input.extend(get_request(&key));
+    }
+
+    let input = test::black_box(input);
+    let mut ctx = Context::new();
+    let mut resp = Vec::new();
+
+    b.iter(|| {
+        let mut buf = input.as_slice();
+
+        while let Some(req) = ctx.feed(buf).unwrap() {
+            hop.dispatch(&req, &mut resp).unwrap();
+            buf = &buf[ctx.consumed()..];
+        }
+
+        resp.clear();
+    });
+}
+
+/// Set a value of the given size, to catch allocation regressions that scale
+/// with the size of values.
+fn bench_set_value_len(b: &mut Bencher, len: usize) {
+    let hop = Hop::new();
+    let input = set_request(b"foo", vec![b'a'; len]);
+    let mut ctx = Context::new();
+    let mut resp = Vec::new();
+
+    b.bytes = len as u64;
+    b.iter(|| {
+        let req = ctx.feed(&input).unwrap().unwrap();
+        hop.dispatch(&req, &mut resp).unwrap();
+        resp.clear();
+    });
+}
+
+#[bench]
+fn bench_set_value_len_16(b: &mut Bencher) {
+    bench_set_value_len(b, 16);
+}
+
+#[bench]
+fn bench_set_value_len_1k(b: &mut Bencher) {
+    bench_set_value_len(b, 1024);
+}
+
+#[bench]
+fn bench_set_value_len_64k(b: &mut Bencher) {
+    bench_set_value_len(b, 64 * 1024);
+}
+
+#[bench]
+fn bench_set_value_len_1m(b: &mut Bencher) {
+    bench_set_value_len(b, 1024 * 1024);
+}
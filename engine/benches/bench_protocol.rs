@@ -3,7 +3,6 @@
 
 extern crate test;
 
-use core::iter;
 use hop_engine::command::{
     request::Context as RequestContext,
     response::{Context as ResponseContext, Instruction as ResponseInstruction, Response},
@@ -24,7 +23,7 @@ fn bench_simple_increment(b: &mut Bencher) {
 fn bench_response_list(b: &mut Bencher) {
     let mut list: Vec<Vec<u8>> = Vec::new();
     for _ in 0..15 {
-        list.push(iter::repeat(b'a').take(10).collect::<Vec<u8>>());
+        list.push(vec![b'a'; 10]);
     }
 
     let response = Response::from(list);
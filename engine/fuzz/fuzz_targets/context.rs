@@ -0,0 +1,57 @@
+//! Feed arbitrary bytes into a request context, split into chunks at arbitrary
+//! points, the way a connection feeds whatever it has read so far.
+//!
+//! The context must never panic: each feed either returns a request, needs
+//! more bytes, or returns a parse error.
+
+#![no_main]
+
+use hop_engine::command::request::Context;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // The first byte is the length of each chunk fed, so that the same
+    // commands are split at different points.
+    let (chunk_len, input) = match data.split_first() {
+        Some((len, input)) => (usize::from(*len).max(1), input),
+        None => return,
+    };
+
+    let mut ctx = Context::new();
+    let mut buf = Vec::new();
+
+    for chunk in input.chunks(chunk_len) {
+        buf.extend_from_slice(chunk);
+
+        loop {
+            match ctx.feed(&buf) {
+                Ok(Some(req)) => {
+                    // The request must lie within the bytes it was parsed from.
+                    assert!(req.as_bytes().len() <= buf.len());
+                    for arg in req.args(..).into_iter().flatten() {
+                        assert!(arg.len() <= buf.len());
+                    }
+
+                    let consumed = ctx.consumed();
+                    assert!(consumed > 0 && consumed <= buf.len());
+                    buf.drain(..consumed);
+                }
+                Ok(None) => break,
+                // A skipped command is drained like a parsed one.
+                Err(why) if why.is_recoverable() => {
+                    let consumed = ctx.consumed();
+                    assert!(consumed > 0 && consumed <= buf.len());
+                    buf.drain(..consumed);
+                }
+                // Otherwise there's no way of knowing where the invalid command
+                // ends, so drop everything that's been read.
+                Err(_) => {
+                    assert_eq!(0, ctx.consumed());
+                    buf.clear();
+
+                    break;
+                }
+            }
+        }
+    }
+});
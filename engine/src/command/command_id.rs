@@ -36,35 +36,219 @@ pub enum CommandId {
     Is = 14,
     Rename = 15,
     Type = 16,
+    Touch = 17,
+    Dump = 18,
+    Restore = 19,
     Append = 20,
     Length = 21,
     Keys = 22,
+    TypeName = 23,
+    DeleteMatching = 24,
+    ObjectInfo = 25,
+    Move = 26,
+    GetRange = 27,
+    BulkLoad = 28,
+    KeysOfType = 29,
+    Expire = 30,
+    ExpireMs = 31,
+    ExpireAt = 32,
+    ZAdd = 40,
+    ZRange = 41,
+    ZScore = 42,
+    ZRem = 43,
+    ZRangeByScore = 44,
+    PfAdd = 50,
+    PfCount = 51,
+    AppendCapped = 60,
+    BlockingPop = 61,
+    ListMove = 62,
+    MapFieldTypes = 63,
+    ListPeek = 64,
+    MapRenameField = 65,
+    MapSetNx = 66,
+    SetScan = 67,
+    MapScan = 68,
+    AppendLen = 69,
+    SetUnionStore = 70,
+    SetIntersectStore = 71,
+    SetDifferenceStore = 72,
+    ListCount = 73,
+    ListInsert = 74,
+    IncrementCapped = 75,
+    RateLimit = 76,
+    MapGetAll = 77,
+    SetMembersSorted = 78,
+    Swap = 79,
+    ListDrain = 80,
+    Scan = 81,
     Echo = 100,
     Stats = 101,
+    Watch = 102,
+    Time = 103,
+    Command = 104,
+    SlowLog = 105,
+    SlowLogReset = 106,
+    Select = 107,
+    SwapDb = 108,
+    Wait = 109,
+    Quit = 110,
+    Info = 111,
+    DefaultType = 112,
 }
 
 impl CommandId {
+    /// Retrieve the minimum number of arguments, including keys, that the
+    /// command requires.
+    pub fn arity(self) -> usize {
+        use CommandId::*;
+
+        match self {
+            Append => 1,
+            AppendCapped => 3,
+            AppendLen => 1,
+            BulkLoad => 1,
+            BlockingPop => 2,
+            Command => 0,
+            Delete => 1,
+            Decrement => 1,
+            DecrementBy => 2,
+            DefaultType => 0,
+            Dump => 1,
+            Echo => 0,
+            Exists => 1,
+            Expire => 2,
+            ExpireAt => 2,
+            ExpireMs => 2,
+            Get => 1,
+            GetRange => 3,
+            Increment => 1,
+            Info => 0,
+            IncrementBy => 2,
+            IncrementCapped => 3,
+            RateLimit => 3,
+            Is => 1,
+            Keys => 1,
+            KeysOfType => 0,
+            ObjectInfo => 1,
+            Length => 1,
+            MapFieldTypes => 2,
+            MapGetAll => 1,
+            MapRenameField => 3,
+            MapScan => 2,
+            MapSetNx => 3,
+            SetScan => 2,
+            SetMembersSorted => 1,
+            ListMove => 4,
+            ListCount => 2,
+            ListInsert => 4,
+            ListPeek => 2,
+            Move => 2,
+            PfAdd => 1,
+            PfCount => 1,
+            Rename => 2,
+            Swap => 2,
+            ListDrain => 1,
+            Scan => 1,
+            Restore => 2,
+            Select => 1,
+            SetDifferenceStore => 2,
+            SetIntersectStore => 2,
+            SetUnionStore => 2,
+            Set => 2,
+            SlowLog => 0,
+            SlowLogReset => 0,
+            Stats => 0,
+            SwapDb => 2,
+            Wait => 2,
+            Quit => 0,
+            Time => 0,
+            Touch => 1,
+            Type => 1,
+            DeleteMatching => 1,
+            TypeName => 1,
+            Watch => 1,
+            ZAdd => 1,
+            ZRange => 3,
+            ZRangeByScore => 3,
+            ZRem => 1,
+            ZScore => 2,
+        }
+    }
+
     pub fn argument_notation(self) -> ArgumentNotation {
         use ArgumentNotation::{Multiple, None, One};
         use CommandId::*;
 
         match self {
             Append => One,
+            AppendCapped => Multiple,
+            AppendLen => One,
+            BulkLoad => Multiple,
+            BlockingPop => One,
+            Command => None,
             Delete => One,
             Decrement => None,
             DecrementBy => One,
+            DefaultType => One,
+            Dump => None,
             Echo => Multiple,
             Exists => None,
+            Expire => Multiple,
+            ExpireAt => Multiple,
+            ExpireMs => Multiple,
             Get => None,
+            GetRange => Multiple,
             Increment => None,
+            Info => None,
             IncrementBy => One,
+            IncrementCapped => Multiple,
+            RateLimit => Multiple,
             Is => None,
             Keys => None,
+            KeysOfType => None,
+            ObjectInfo => None,
             Length => One,
+            MapFieldTypes => Multiple,
+            MapGetAll => None,
+            MapRenameField => Multiple,
+            MapScan => Multiple,
+            MapSetNx => Multiple,
+            SetScan => Multiple,
+            SetMembersSorted => One,
+            ListMove => Multiple,
+            ListCount => One,
+            ListInsert => One,
+            ListPeek => One,
+            Move => One,
+            PfAdd => Multiple,
+            PfCount => None,
             Rename => None,
+            Swap => None,
+            ListDrain => None,
+            Scan => Multiple,
+            Restore => Multiple,
+            Select => One,
+            SetDifferenceStore => None,
+            SetIntersectStore => None,
+            SetUnionStore => None,
             Set => One,
+            SlowLog => None,
+            SlowLogReset => None,
             Stats => None,
+            SwapDb => Multiple,
+            Wait => Multiple,
+            Quit => None,
+            Time => None,
+            Touch => None,
             Type => None,
+            DeleteMatching => One,
+            TypeName => None,
+            Watch => None,
+            ZAdd => Multiple,
+            ZRange => Multiple,
+            ZRangeByScore => Multiple,
+            ZRem => Multiple,
+            ZScore => One,
         }
     }
 
@@ -74,21 +258,74 @@ impl CommandId {
 
         match self {
             Append => One,
+            AppendCapped => One,
+            AppendLen => One,
+            BulkLoad => None,
+            BlockingPop => One,
+            Command => None,
             Delete => One,
             Decrement => One,
             DecrementBy => One,
+            DefaultType => None,
+            Dump => One,
             Echo => None,
             Exists => Multiple,
+            Expire => One,
+            ExpireAt => One,
+            ExpireMs => One,
             Get => One,
+            GetRange => One,
             Increment => One,
+            Info => None,
             IncrementBy => One,
+            IncrementCapped => One,
+            RateLimit => One,
             Is => Multiple,
             Keys => One,
+            KeysOfType => None,
+            ObjectInfo => One,
             Length => One,
+            MapFieldTypes => One,
+            MapGetAll => One,
+            MapRenameField => One,
+            MapScan => One,
+            MapSetNx => One,
+            SetScan => One,
+            SetMembersSorted => One,
+            ListMove => Two,
+            ListCount => One,
+            ListInsert => One,
+            ListPeek => One,
+            Move => One,
+            PfAdd => One,
+            PfCount => One,
             Rename => Two,
+            Swap => Two,
+            ListDrain => One,
+            Scan => None,
+            Restore => One,
+            Select => None,
+            SetDifferenceStore => Multiple,
+            SetIntersectStore => Multiple,
+            SetUnionStore => Multiple,
             Set => One,
+            SlowLog => None,
+            SlowLogReset => None,
             Stats => None,
+            SwapDb => None,
+            Wait => None,
+            Quit => None,
+            Time => None,
+            Touch => Multiple,
             Type => One,
+            DeleteMatching => None,
+            TypeName => One,
+            Watch => One,
+            ZAdd => One,
+            ZRange => One,
+            ZRangeByScore => One,
+            ZRem => One,
+            ZScore => One,
         }
     }
 
@@ -99,24 +336,113 @@ impl CommandId {
         no_args && no_keys
     }
 
+    /// Whether the command modifies the keys it's given.
+    ///
+    /// Successfully dispatching a mutating command emits a keyspace event for
+    /// each of its keys.
+    pub fn is_mutating(self) -> bool {
+        use CommandId::*;
+
+        match self {
+            Append | AppendCapped | AppendLen | BlockingPop | BulkLoad | Decrement
+            | DecrementBy | Delete | DeleteMatching | Expire | ExpireAt | ExpireMs | Increment
+            | IncrementBy | IncrementCapped | ListDrain | ListInsert | ListMove
+            | MapRenameField | MapSetNx | Move | PfAdd | RateLimit | Rename | Restore | Set
+            | SetDifferenceStore | SetIntersectStore | SetUnionStore | Swap | SwapDb | ZAdd
+            | ZRem => true,
+            Command | DefaultType | Dump | Echo | Exists | Get | GetRange | Info | Is | Keys
+            | KeysOfType | Length | ListCount | ListPeek | MapFieldTypes | MapGetAll | MapScan
+            | ObjectInfo | PfCount | Quit | Scan | Select | SetMembersSorted | SetScan
+            | SlowLog | SlowLogReset | Stats | Time | Touch | Type | TypeName | Wait | Watch
+            | ZRange | ZRangeByScore | ZScore => false,
+        }
+    }
+
+    /// Whether a connection's default key type applies to the command when a
+    /// request doesn't specify a key type.
+    ///
+    /// Only commands that create a key of the requested type use the default,
+    /// so that reads and commands working with one type of key are unaffected.
+    pub fn uses_default_key_type(self) -> bool {
+        use CommandId::*;
+
+        matches!(
+            self,
+            Append | AppendCapped | AppendLen | Decrement | Increment | Set
+        )
+    }
+
     pub fn name(&self) -> &str {
         match self {
             Self::Append => "append",
+            Self::AppendLen => "append:len",
+            Self::AppendCapped => "append:capped",
+            Self::BlockingPop => "pop:blocking",
+            Self::BulkLoad => "bulk:load",
+            Self::KeysOfType => "keys:type",
+            Self::Command => "command",
+            Self::DefaultType => "type:default",
             Self::DecrementBy => "decrement:by",
             Self::Decrement => "decrement",
             Self::Delete => "delete",
+            Self::Dump => "dump",
             Self::Echo => "echo",
             Self::Exists => "exists",
+            Self::ExpireAt => "expire:at",
+            Self::ExpireMs => "expire:ms",
+            Self::Expire => "expire",
             Self::Get => "get",
+            Self::GetRange => "get:range",
+            Self::IncrementCapped => "increment:capped",
+            Self::RateLimit => "ratelimit",
             Self::IncrementBy => "increment:by",
+            Self::Info => "info",
             Self::Increment => "increment",
             Self::Is => "is",
             Self::Keys => "keys",
+            Self::ObjectInfo => "object:info",
+            Self::ListPeek => "list:peek",
+            Self::ListInsert => "list:insert",
+            Self::ListCount => "list:count",
+            Self::ListMove => "list:move",
+            Self::SetScan => "set:scan",
+            Self::SetMembersSorted => "set:sorted",
+            Self::MapSetNx => "map:setnx",
+            Self::MapScan => "map:scan",
+            Self::MapRenameField => "map:rename",
+            Self::MapFieldTypes => "map:types",
+            Self::MapGetAll => "map:all",
             Self::Length => "length",
+            Self::Move => "move",
+            Self::PfAdd => "pfadd",
+            Self::PfCount => "pfcount",
             Self::Rename => "rename",
+            Self::Restore => "restore",
+            Self::Scan => "scan",
+            Self::Select => "select",
             Self::Set => "set",
+            Self::SetDifferenceStore => "set:differencestore",
+            Self::SetIntersectStore => "set:intersectstore",
+            Self::SetUnionStore => "set:unionstore",
+            Self::SlowLog => "slowlog",
+            Self::SlowLogReset => "slowlog:reset",
             Self::Stats => "stats",
+            Self::Quit => "quit",
+            Self::Wait => "wait",
+            Self::Swap => "swap",
+            Self::ListDrain => "list:drain",
+            Self::SwapDb => "swapdb",
+            Self::Time => "time",
+            Self::Touch => "touch",
             Self::Type => "type",
+            Self::DeleteMatching => "delete:matching",
+            Self::TypeName => "type:name",
+            Self::Watch => "watch",
+            Self::ZAdd => "zadd",
+            Self::ZRangeByScore => "zrange:score",
+            Self::ZRange => "zrange",
+            Self::ZRem => "zrem",
+            Self::ZScore => "zscore",
         }
     }
 }
@@ -133,21 +459,74 @@ impl FromStr for CommandId {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
             "append" => Self::Append,
+            "append:capped" => Self::AppendCapped,
+            "append:len" => Self::AppendLen,
+            "command" => Self::Command,
             "decrement:by" => Self::DecrementBy,
+            "type:default" => Self::DefaultType,
             "decrement" => Self::Decrement,
             "delete" => Self::Delete,
+            "dump" => Self::Dump,
             "echo" => Self::Echo,
             "exists" => Self::Exists,
+            "expire:at" => Self::ExpireAt,
+            "expire:ms" => Self::ExpireMs,
+            "expire" => Self::Expire,
             "get" => Self::Get,
+            "get:range" => Self::GetRange,
+            "bulk:load" => Self::BulkLoad,
+            "pop:blocking" => Self::BlockingPop,
+            "keys:type" => Self::KeysOfType,
             "increment:by" => Self::IncrementBy,
+            "increment:capped" => Self::IncrementCapped,
+            "ratelimit" => Self::RateLimit,
             "increment" => Self::Increment,
+            "info" => Self::Info,
             "is" => Self::Is,
             "keys" => Self::Keys,
+            "object:info" => Self::ObjectInfo,
             "length" => Self::Length,
+            "map:types" => Self::MapFieldTypes,
+            "map:all" => Self::MapGetAll,
+            "map:rename" => Self::MapRenameField,
+            "map:scan" => Self::MapScan,
+            "map:setnx" => Self::MapSetNx,
+            "set:scan" => Self::SetScan,
+            "set:sorted" => Self::SetMembersSorted,
+            "list:move" => Self::ListMove,
+            "list:count" => Self::ListCount,
+            "list:insert" => Self::ListInsert,
+            "list:peek" => Self::ListPeek,
+            "move" => Self::Move,
+            "pfadd" => Self::PfAdd,
+            "pfcount" => Self::PfCount,
             "rename" => Self::Rename,
+            "restore" => Self::Restore,
+            "scan" => Self::Scan,
+            "select" => Self::Select,
+            "set:differencestore" => Self::SetDifferenceStore,
+            "set:intersectstore" => Self::SetIntersectStore,
+            "set:unionstore" => Self::SetUnionStore,
             "set" => Self::Set,
+            "slowlog" => Self::SlowLog,
+            "slowlog:reset" => Self::SlowLogReset,
             "stats" => Self::Stats,
+            "swap" => Self::Swap,
+            "list:drain" => Self::ListDrain,
+            "swapdb" => Self::SwapDb,
+            "wait" => Self::Wait,
+            "quit" => Self::Quit,
+            "time" => Self::Time,
+            "touch" => Self::Touch,
             "type" => Self::Type,
+            "delete:matching" => Self::DeleteMatching,
+            "type:name" => Self::TypeName,
+            "watch" => Self::Watch,
+            "zadd" => Self::ZAdd,
+            "zrange:score" => Self::ZRangeByScore,
+            "zrange" => Self::ZRange,
+            "zrem" => Self::ZRem,
+            "zscore" => Self::ZScore,
             _ => return Err(InvalidCommandId),
         })
     }
@@ -169,11 +548,64 @@ impl TryFrom<u8> for CommandId {
             14 => Self::Is,
             15 => Self::Rename,
             16 => Self::Type,
+            17 => Self::Touch,
+            18 => Self::Dump,
+            19 => Self::Restore,
             20 => Self::Append,
             21 => Self::Length,
             22 => Self::Keys,
+            23 => Self::TypeName,
+            24 => Self::DeleteMatching,
+            25 => Self::ObjectInfo,
+            26 => Self::Move,
+            27 => Self::GetRange,
+            28 => Self::BulkLoad,
+            29 => Self::KeysOfType,
+            30 => Self::Expire,
+            31 => Self::ExpireMs,
+            32 => Self::ExpireAt,
+            40 => Self::ZAdd,
+            41 => Self::ZRange,
+            42 => Self::ZScore,
+            43 => Self::ZRem,
+            44 => Self::ZRangeByScore,
+            50 => Self::PfAdd,
+            51 => Self::PfCount,
+            60 => Self::AppendCapped,
+            61 => Self::BlockingPop,
+            62 => Self::ListMove,
+            63 => Self::MapFieldTypes,
+            64 => Self::ListPeek,
+            65 => Self::MapRenameField,
+            66 => Self::MapSetNx,
+            67 => Self::SetScan,
+            68 => Self::MapScan,
+            69 => Self::AppendLen,
+            70 => Self::SetUnionStore,
+            71 => Self::SetIntersectStore,
+            72 => Self::SetDifferenceStore,
+            73 => Self::ListCount,
+            74 => Self::ListInsert,
+            75 => Self::IncrementCapped,
+            76 => Self::RateLimit,
+            77 => Self::MapGetAll,
+            78 => Self::SetMembersSorted,
+            79 => Self::Swap,
+            80 => Self::ListDrain,
+            81 => Self::Scan,
             100 => Self::Echo,
             101 => Self::Stats,
+            102 => Self::Watch,
+            103 => Self::Time,
+            104 => Self::Command,
+            105 => Self::SlowLog,
+            106 => Self::SlowLogReset,
+            107 => Self::Select,
+            108 => Self::SwapDb,
+            109 => Self::Wait,
+            110 => Self::Quit,
+            111 => Self::Info,
+            112 => Self::DefaultType,
             _ => return Err(InvalidCommandId),
         })
     }
@@ -189,7 +621,7 @@ impl<'a> TryFrom<&'a str> for CommandId {
 
 #[cfg(test)]
 mod tests {
-    use super::{ArgumentNotation, CommandId, InvalidCommandId};
+    use super::{ArgumentNotation, CommandId, InvalidCommandId, KeyNotation};
     use core::{
         convert::TryFrom,
         fmt::{Debug, Display},
@@ -217,7 +649,98 @@ mod tests {
 
     #[test]
     fn test_from_str() {
+        assert_eq!(CommandId::Swap, CommandId::from_str("swap").unwrap());
+        assert_eq!(CommandId::Scan, CommandId::from_str("scan").unwrap());
+        assert_eq!(
+            CommandId::ListDrain,
+            CommandId::from_str("list:drain").unwrap()
+        );
+        assert_eq!(
+            CommandId::SetMembersSorted,
+            CommandId::from_str("set:sorted").unwrap()
+        );
+        assert_eq!(
+            CommandId::MapGetAll,
+            CommandId::from_str("map:all").unwrap()
+        );
+        assert_eq!(
+            CommandId::RateLimit,
+            CommandId::from_str("ratelimit").unwrap()
+        );
+        assert_eq!(
+            CommandId::IncrementCapped,
+            CommandId::from_str("increment:capped").unwrap()
+        );
+        assert_eq!(
+            CommandId::DefaultType,
+            CommandId::from_str("type:default").unwrap()
+        );
+        assert_eq!(
+            CommandId::ListInsert,
+            CommandId::from_str("list:insert").unwrap()
+        );
+        assert_eq!(
+            CommandId::ListCount,
+            CommandId::from_str("list:count").unwrap()
+        );
+        assert_eq!(
+            CommandId::SetDifferenceStore,
+            CommandId::from_str("set:differencestore").unwrap()
+        );
+        assert_eq!(
+            CommandId::SetIntersectStore,
+            CommandId::from_str("set:intersectstore").unwrap()
+        );
+        assert_eq!(
+            CommandId::SetUnionStore,
+            CommandId::from_str("set:unionstore").unwrap()
+        );
+        assert_eq!(
+            CommandId::AppendLen,
+            CommandId::from_str("append:len").unwrap()
+        );
+        assert_eq!(CommandId::Info, CommandId::from_str("info").unwrap());
+        assert_eq!(CommandId::MapScan, CommandId::from_str("map:scan").unwrap());
+        assert_eq!(CommandId::SetScan, CommandId::from_str("set:scan").unwrap());
+        assert_eq!(
+            CommandId::MapSetNx,
+            CommandId::from_str("map:setnx").unwrap()
+        );
+        assert_eq!(
+            CommandId::MapRenameField,
+            CommandId::from_str("map:rename").unwrap()
+        );
+        assert_eq!(CommandId::Quit, CommandId::from_str("quit").unwrap());
+        assert_eq!(CommandId::Wait, CommandId::from_str("wait").unwrap());
+        assert_eq!(
+            CommandId::ListPeek,
+            CommandId::from_str("list:peek").unwrap()
+        );
+        assert_eq!(
+            CommandId::MapFieldTypes,
+            CommandId::from_str("map:types").unwrap()
+        );
+        assert_eq!(
+            CommandId::ListMove,
+            CommandId::from_str("list:move").unwrap()
+        );
+        assert_eq!(
+            CommandId::BlockingPop,
+            CommandId::from_str("pop:blocking").unwrap()
+        );
+        assert_eq!(
+            CommandId::AppendCapped,
+            CommandId::from_str("append:capped").unwrap()
+        );
         assert_eq!(CommandId::Append, CommandId::from_str("append").unwrap());
+        assert_eq!(CommandId::Command, CommandId::from_str("command").unwrap());
+        assert_eq!(CommandId::Select, CommandId::from_str("select").unwrap());
+        assert_eq!(CommandId::SlowLog, CommandId::from_str("slowlog").unwrap());
+        assert_eq!(CommandId::SwapDb, CommandId::from_str("swapdb").unwrap());
+        assert_eq!(
+            CommandId::SlowLogReset,
+            CommandId::from_str("slowlog:reset").unwrap()
+        );
         assert_eq!(
             CommandId::DecrementBy,
             CommandId::from_str("decrement:by").unwrap()
@@ -227,8 +750,18 @@ mod tests {
             CommandId::from_str("decrement").unwrap()
         );
         assert_eq!(CommandId::Delete, CommandId::from_str("delete").unwrap());
+        assert_eq!(CommandId::Dump, CommandId::from_str("dump").unwrap());
         assert_eq!(CommandId::Echo, CommandId::from_str("echo").unwrap());
         assert_eq!(CommandId::Exists, CommandId::from_str("exists").unwrap());
+        assert_eq!(
+            CommandId::ExpireAt,
+            CommandId::from_str("expire:at").unwrap()
+        );
+        assert_eq!(
+            CommandId::ExpireMs,
+            CommandId::from_str("expire:ms").unwrap()
+        );
+        assert_eq!(CommandId::Expire, CommandId::from_str("expire").unwrap());
         assert_eq!(CommandId::Get, CommandId::from_str("get").unwrap());
         assert_eq!(
             CommandId::IncrementBy,
@@ -240,50 +773,241 @@ mod tests {
         );
         assert_eq!(CommandId::Is, CommandId::from_str("is").unwrap());
         assert_eq!(CommandId::Keys, CommandId::from_str("keys").unwrap());
+        assert_eq!(CommandId::Move, CommandId::from_str("move").unwrap());
+        assert_eq!(
+            CommandId::GetRange,
+            CommandId::from_str("get:range").unwrap()
+        );
+        assert_eq!(
+            CommandId::BulkLoad,
+            CommandId::from_str("bulk:load").unwrap()
+        );
+        assert_eq!(
+            CommandId::KeysOfType,
+            CommandId::from_str("keys:type").unwrap()
+        );
         assert_eq!(CommandId::Length, CommandId::from_str("length").unwrap());
+        assert_eq!(CommandId::PfAdd, CommandId::from_str("pfadd").unwrap());
+        assert_eq!(CommandId::PfCount, CommandId::from_str("pfcount").unwrap());
         assert_eq!(CommandId::Rename, CommandId::from_str("rename").unwrap());
+        assert_eq!(CommandId::Restore, CommandId::from_str("restore").unwrap());
         assert_eq!(CommandId::Set, CommandId::from_str("set").unwrap());
         assert_eq!(CommandId::Stats, CommandId::from_str("stats").unwrap());
+        assert_eq!(CommandId::Time, CommandId::from_str("time").unwrap());
+        assert_eq!(CommandId::Touch, CommandId::from_str("touch").unwrap());
         assert_eq!(CommandId::Type, CommandId::from_str("type").unwrap());
+        assert_eq!(
+            CommandId::DeleteMatching,
+            CommandId::from_str("delete:matching").unwrap()
+        );
+        assert_eq!(
+            CommandId::ObjectInfo,
+            CommandId::from_str("object:info").unwrap()
+        );
+        assert_eq!(
+            CommandId::TypeName,
+            CommandId::from_str("type:name").unwrap()
+        );
+        assert_eq!(CommandId::Watch, CommandId::from_str("watch").unwrap());
+        assert_eq!(CommandId::ZAdd, CommandId::from_str("zadd").unwrap());
+        assert_eq!(
+            CommandId::ZRangeByScore,
+            CommandId::from_str("zrange:score").unwrap()
+        );
+        assert_eq!(CommandId::ZRange, CommandId::from_str("zrange").unwrap());
+        assert_eq!(CommandId::ZRem, CommandId::from_str("zrem").unwrap());
+        assert_eq!(CommandId::ZScore, CommandId::from_str("zscore").unwrap());
     }
 
     #[test]
     fn test_try_from_u8() {
+        assert_eq!(CommandId::Swap, CommandId::try_from(79).unwrap());
+        assert_eq!(CommandId::ListDrain, CommandId::try_from(80).unwrap());
+        assert_eq!(CommandId::Scan, CommandId::try_from(81).unwrap());
+        assert_eq!(
+            CommandId::SetMembersSorted,
+            CommandId::try_from(78).unwrap()
+        );
+        assert_eq!(CommandId::MapGetAll, CommandId::try_from(77).unwrap());
+        assert_eq!(CommandId::RateLimit, CommandId::try_from(76).unwrap());
+        assert_eq!(CommandId::IncrementCapped, CommandId::try_from(75).unwrap());
+        assert_eq!(CommandId::DefaultType, CommandId::try_from(112).unwrap());
+        assert_eq!(CommandId::ListInsert, CommandId::try_from(74).unwrap());
+        assert_eq!(CommandId::ListCount, CommandId::try_from(73).unwrap());
+        assert_eq!(
+            CommandId::SetDifferenceStore,
+            CommandId::try_from(72).unwrap()
+        );
+        assert_eq!(
+            CommandId::SetIntersectStore,
+            CommandId::try_from(71).unwrap()
+        );
+        assert_eq!(CommandId::SetUnionStore, CommandId::try_from(70).unwrap());
+        assert_eq!(CommandId::AppendLen, CommandId::try_from(69).unwrap());
+        assert_eq!(CommandId::Info, CommandId::try_from(111).unwrap());
+        assert_eq!(CommandId::MapScan, CommandId::try_from(68).unwrap());
+        assert_eq!(CommandId::SetScan, CommandId::try_from(67).unwrap());
+        assert_eq!(CommandId::MapSetNx, CommandId::try_from(66).unwrap());
+        assert_eq!(CommandId::MapRenameField, CommandId::try_from(65).unwrap());
+        assert_eq!(CommandId::Quit, CommandId::try_from(110).unwrap());
+        assert_eq!(CommandId::Wait, CommandId::try_from(109).unwrap());
+        assert_eq!(CommandId::ListPeek, CommandId::try_from(64).unwrap());
+        assert_eq!(CommandId::MapFieldTypes, CommandId::try_from(63).unwrap());
+        assert_eq!(CommandId::ListMove, CommandId::try_from(62).unwrap());
+        assert_eq!(CommandId::BlockingPop, CommandId::try_from(61).unwrap());
+        assert_eq!(CommandId::AppendCapped, CommandId::try_from(60).unwrap());
         assert_eq!(CommandId::Append, CommandId::try_from(20).unwrap());
+        assert_eq!(CommandId::Command, CommandId::try_from(104).unwrap());
+        assert_eq!(CommandId::SlowLog, CommandId::try_from(105).unwrap());
+        assert_eq!(CommandId::SlowLogReset, CommandId::try_from(106).unwrap());
+        assert_eq!(CommandId::Select, CommandId::try_from(107).unwrap());
+        assert_eq!(CommandId::SwapDb, CommandId::try_from(108).unwrap());
         assert_eq!(CommandId::DecrementBy, CommandId::try_from(3).unwrap());
         assert_eq!(CommandId::Decrement, CommandId::try_from(1).unwrap());
         assert_eq!(CommandId::Delete, CommandId::try_from(12).unwrap());
+        assert_eq!(CommandId::Dump, CommandId::try_from(18).unwrap());
         assert_eq!(CommandId::Echo, CommandId::try_from(100).unwrap());
         assert_eq!(CommandId::Exists, CommandId::try_from(13).unwrap());
+        assert_eq!(CommandId::ExpireAt, CommandId::try_from(32).unwrap());
+        assert_eq!(CommandId::ExpireMs, CommandId::try_from(31).unwrap());
+        assert_eq!(CommandId::Expire, CommandId::try_from(30).unwrap());
         assert_eq!(CommandId::Get, CommandId::try_from(11).unwrap());
         assert_eq!(CommandId::IncrementBy, CommandId::try_from(2).unwrap());
         assert_eq!(CommandId::Increment, CommandId::try_from(0).unwrap());
         assert_eq!(CommandId::Is, CommandId::try_from(14).unwrap());
         assert_eq!(CommandId::Keys, CommandId::try_from(22).unwrap());
         assert_eq!(CommandId::Length, CommandId::try_from(21).unwrap());
+        assert_eq!(CommandId::PfAdd, CommandId::try_from(50).unwrap());
+        assert_eq!(CommandId::PfCount, CommandId::try_from(51).unwrap());
         assert_eq!(CommandId::Rename, CommandId::try_from(15).unwrap());
+        assert_eq!(CommandId::Restore, CommandId::try_from(19).unwrap());
         assert_eq!(CommandId::Set, CommandId::try_from(10).unwrap());
         assert_eq!(CommandId::Stats, CommandId::try_from(101).unwrap());
+        assert_eq!(CommandId::Time, CommandId::try_from(103).unwrap());
+        assert_eq!(CommandId::Touch, CommandId::try_from(17).unwrap());
         assert_eq!(CommandId::Type, CommandId::try_from(16).unwrap());
+        assert_eq!(CommandId::TypeName, CommandId::try_from(23).unwrap());
+        assert_eq!(CommandId::DeleteMatching, CommandId::try_from(24).unwrap());
+        assert_eq!(CommandId::ObjectInfo, CommandId::try_from(25).unwrap());
+        assert_eq!(CommandId::Move, CommandId::try_from(26).unwrap());
+        assert_eq!(CommandId::GetRange, CommandId::try_from(27).unwrap());
+        assert_eq!(CommandId::BulkLoad, CommandId::try_from(28).unwrap());
+        assert_eq!(CommandId::KeysOfType, CommandId::try_from(29).unwrap());
+        assert_eq!(CommandId::Watch, CommandId::try_from(102).unwrap());
+        assert_eq!(CommandId::ZAdd, CommandId::try_from(40).unwrap());
+        assert_eq!(CommandId::ZRangeByScore, CommandId::try_from(44).unwrap());
+        assert_eq!(CommandId::ZRange, CommandId::try_from(41).unwrap());
+        assert_eq!(CommandId::ZRem, CommandId::try_from(43).unwrap());
+        assert_eq!(CommandId::ZScore, CommandId::try_from(42).unwrap());
+    }
+
+    #[test]
+    fn test_arity() {
+        // every key of a command is a required argument
+        for id in (0..=u8::MAX).filter_map(|id| CommandId::try_from(id).ok()) {
+            let keys = match id.key_notation() {
+                KeyNotation::None => 0,
+                KeyNotation::One | KeyNotation::Multiple => 1,
+                KeyNotation::Two => 2,
+            };
+
+            assert!(id.arity() >= keys, "{:?}", id);
+        }
+
+        assert_eq!(0, CommandId::Time.arity());
+        assert_eq!(3, CommandId::GetRange.arity());
+        assert_eq!(4, CommandId::ListMove.arity());
     }
 
     #[test]
     fn test_name() {
+        assert_eq!("swap", CommandId::Swap.name());
+        assert_eq!("list:drain", CommandId::ListDrain.name());
+        assert_eq!("scan", CommandId::Scan.name());
+        assert_eq!("set:sorted", CommandId::SetMembersSorted.name());
+        assert_eq!("map:all", CommandId::MapGetAll.name());
+        assert_eq!("ratelimit", CommandId::RateLimit.name());
+        assert_eq!("increment:capped", CommandId::IncrementCapped.name());
+        assert_eq!("type:default", CommandId::DefaultType.name());
+        assert_eq!("list:insert", CommandId::ListInsert.name());
+        assert_eq!("list:count", CommandId::ListCount.name());
+        assert_eq!("set:differencestore", CommandId::SetDifferenceStore.name());
+        assert_eq!("set:intersectstore", CommandId::SetIntersectStore.name());
+        assert_eq!("set:unionstore", CommandId::SetUnionStore.name());
+        assert_eq!("append:len", CommandId::AppendLen.name());
+        assert_eq!("info", CommandId::Info.name());
+        assert_eq!("map:scan", CommandId::MapScan.name());
+        assert_eq!("set:scan", CommandId::SetScan.name());
+        assert_eq!("map:setnx", CommandId::MapSetNx.name());
+        assert_eq!("map:rename", CommandId::MapRenameField.name());
+        assert_eq!("quit", CommandId::Quit.name());
+        assert_eq!("wait", CommandId::Wait.name());
+        assert_eq!("list:peek", CommandId::ListPeek.name());
+        assert_eq!("map:types", CommandId::MapFieldTypes.name());
+        assert_eq!("list:move", CommandId::ListMove.name());
+        assert_eq!("pop:blocking", CommandId::BlockingPop.name());
+        assert_eq!("append:capped", CommandId::AppendCapped.name());
         assert_eq!("append", CommandId::Append.name());
+        assert_eq!("command", CommandId::Command.name());
+        assert_eq!("slowlog", CommandId::SlowLog.name());
+        assert_eq!("slowlog:reset", CommandId::SlowLogReset.name());
+        assert_eq!("select", CommandId::Select.name());
+        assert_eq!("swapdb", CommandId::SwapDb.name());
         assert_eq!("decrement:by", CommandId::DecrementBy.name());
         assert_eq!("decrement", CommandId::Decrement.name());
         assert_eq!("delete", CommandId::Delete.name());
+        assert_eq!("dump", CommandId::Dump.name());
         assert_eq!("echo", CommandId::Echo.name());
         assert_eq!("exists", CommandId::Exists.name());
+        assert_eq!("expire:at", CommandId::ExpireAt.name());
+        assert_eq!("expire:ms", CommandId::ExpireMs.name());
+        assert_eq!("expire", CommandId::Expire.name());
         assert_eq!("get", CommandId::Get.name());
         assert_eq!("increment:by", CommandId::IncrementBy.name());
         assert_eq!("increment", CommandId::Increment.name());
         assert_eq!("is", CommandId::Is.name());
         assert_eq!("keys", CommandId::Keys.name());
         assert_eq!("length", CommandId::Length.name());
+        assert_eq!("pfadd", CommandId::PfAdd.name());
+        assert_eq!("pfcount", CommandId::PfCount.name());
         assert_eq!("rename", CommandId::Rename.name());
+        assert_eq!("restore", CommandId::Restore.name());
         assert_eq!("set", CommandId::Set.name());
         assert_eq!("stats", CommandId::Stats.name());
+        assert_eq!("time", CommandId::Time.name());
+        assert_eq!("touch", CommandId::Touch.name());
         assert_eq!("type", CommandId::Type.name());
+        assert_eq!("type:name", CommandId::TypeName.name());
+        assert_eq!("delete:matching", CommandId::DeleteMatching.name());
+        assert_eq!("object:info", CommandId::ObjectInfo.name());
+        assert_eq!("move", CommandId::Move.name());
+        assert_eq!("get:range", CommandId::GetRange.name());
+        assert_eq!("bulk:load", CommandId::BulkLoad.name());
+        assert_eq!("keys:type", CommandId::KeysOfType.name());
+        assert_eq!("watch", CommandId::Watch.name());
+        assert_eq!("zadd", CommandId::ZAdd.name());
+        assert_eq!("zrange:score", CommandId::ZRangeByScore.name());
+        assert_eq!("zrange", CommandId::ZRange.name());
+        assert_eq!("zrem", CommandId::ZRem.name());
+        assert_eq!("zscore", CommandId::ZScore.name());
+    }
+
+    #[test]
+    fn test_is_simple() {
+        assert!(CommandId::Stats.is_simple());
+        assert!(CommandId::Time.is_simple());
+        assert!(!CommandId::Echo.is_simple());
+        assert!(!CommandId::Get.is_simple());
+    }
+
+    #[test]
+    fn test_is_mutating() {
+        assert!(CommandId::Append.is_mutating());
+        assert!(CommandId::Delete.is_mutating());
+        assert!(CommandId::Rename.is_mutating());
+        assert!(CommandId::Set.is_mutating());
+        assert!(!CommandId::Echo.is_mutating());
+        assert!(!CommandId::Get.is_mutating());
+        assert!(!CommandId::Watch.is_mutating());
     }
 }
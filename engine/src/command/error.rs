@@ -17,12 +17,21 @@ pub enum Error {
     KeyNonexistent = 5,
     KeyTypeRequired = 6,
     KeyTypeInvalid = 7,
+    OutOfMemory = 8,
+    KeyExists = 9,
+    InvalidUtf8 = 10,
+    MalformedNumber = 11,
+    ValueTooLarge = 12,
+    ResponseTooLarge = 13,
+    RequestPending = 14,
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         match self {
             Self::ArgumentRetrieval => f.write_str("couldn't retrieve required argument"),
+            Self::InvalidUtf8 => f.write_str("a string argument isn't valid UTF-8"),
+            Self::KeyExists => f.write_str("the specified key already exists"),
             Self::KeyNonexistent => f.write_str("the specified key does not exist"),
             Self::KeyTypeDifferent => f.write_str("the key has a different type than required"),
             Self::KeyTypeInvalid => {
@@ -31,7 +40,18 @@ impl Display for Error {
             Self::KeyTypeRequired => f.write_str("a key type is required to be specified"),
             Self::KeyTypeUnexpected => f.write_str("didn't expect a specified request key type"),
             Self::KeyUnspecified => f.write_str("the key wasn't specified"),
+            Self::MalformedNumber => f.write_str("a number argument isn't 8 bytes long"),
+            Self::OutOfMemory => f.write_str("the write doesn't fit within the maximum memory"),
             Self::PreconditionFailed => f.write_str("a precondition for the command failed"),
+            Self::RequestPending => {
+                f.write_str("a request with the same idempotency token is being dispatched")
+            }
+            Self::ResponseTooLarge => {
+                f.write_str("the response has more items than can be written")
+            }
+            Self::ValueTooLarge => {
+                f.write_str("the write would grow a value past its maximum size")
+            }
         }
     }
 }
@@ -49,6 +69,13 @@ impl TryFrom<u8> for Error {
             5 => Self::KeyNonexistent,
             6 => Self::KeyTypeRequired,
             7 => Self::KeyTypeInvalid,
+            8 => Self::OutOfMemory,
+            9 => Self::KeyExists,
+            10 => Self::InvalidUtf8,
+            11 => Self::MalformedNumber,
+            12 => Self::ValueTooLarge,
+            13 => Self::ResponseTooLarge,
+            14 => Self::RequestPending,
             _ => return Err(()),
         })
     }
@@ -79,13 +106,20 @@ mod tests {
     fn test_error_try_from_u8() {
         let variants = &[
             Error::ArgumentRetrieval,
+            Error::InvalidUtf8,
+            Error::KeyExists,
             Error::KeyNonexistent,
             Error::KeyTypeDifferent,
             Error::KeyTypeInvalid,
             Error::KeyTypeRequired,
             Error::KeyTypeUnexpected,
             Error::KeyUnspecified,
+            Error::MalformedNumber,
+            Error::OutOfMemory,
             Error::PreconditionFailed,
+            Error::RequestPending,
+            Error::ResponseTooLarge,
+            Error::ValueTooLarge,
         ];
 
         for variant in variants {
@@ -9,29 +9,90 @@ use alloc::borrow::ToOwned;
 use alloc::vec::Vec;
 use core::str;
 
+/// What to respond with after appending.
+#[derive(Clone, Copy)]
+pub(super) enum Reply {
+    /// The key's new value.
+    Value,
+    /// The key's new length, like a length command would respond with.
+    Length,
+}
+
 pub struct Append;
 
 impl Append {
-    fn bytes(hop: &Hop, args: Arguments<'_>, resp: &mut Vec<u8>, key: &[u8]) -> DispatchResult<()> {
+    /// Append the arguments to the key, responding with either its new value
+    /// or its new length.
+    pub(super) fn append(
+        hop: &Hop,
+        req: &Request,
+        resp: &mut Vec<u8>,
+        reply: Reply,
+    ) -> DispatchResult<()> {
+        let key = req.arg(0).ok_or(DispatchError::KeyUnspecified)?;
+        let args = req.args(1..).ok_or(DispatchError::ArgumentRetrieval)?;
+        let key_type = req
+            .key_type()
+            .or_else(|| hop.state().key_type(key))
+            .unwrap_or(KeyType::Bytes);
+
+        match key_type {
+            KeyType::Bytes => Self::bytes(hop, args, resp, key, reply),
+            KeyType::List => Self::list(hop, args, resp, key, reply),
+            KeyType::String => Self::string(hop, args, resp, key, reply),
+            _ => Err(DispatchError::KeyTypeDifferent),
+        }
+    }
+
+    fn bytes(
+        hop: &Hop,
+        args: Arguments<'_>,
+        resp: &mut Vec<u8>,
+        key: &[u8],
+        reply: Reply,
+    ) -> DispatchResult<()> {
+        let len = args.clone().map(<[u8]>::len).sum::<usize>();
+        // Check the arguments alone first so that a key isn't created for a
+        // write that can never fit.
+        hop.config().check_bytes_len(len)?;
+
         let mut key = hop.state().key_or_insert_with(key, Value::bytes);
         let bytes = key.as_bytes_mut().ok_or(DispatchError::KeyTypeDifferent)?;
+        hop.config()
+            .check_bytes_len(bytes.len().saturating_add(len))?;
 
         for arg in args {
             bytes.extend_from_slice(arg);
         }
 
-        response::write_bytes(resp, bytes.as_ref());
+        match reply {
+            Reply::Value => response::write_bytes(resp, bytes.as_ref()),
+            Reply::Length => response::write_int(resp, bytes.len() as i64),
+        }
 
         Ok(())
     }
 
-    fn list(hop: &Hop, args: Arguments<'_>, resp: &mut Vec<u8>, key: &[u8]) -> DispatchResult<()> {
+    fn list(
+        hop: &Hop,
+        args: Arguments<'_>,
+        resp: &mut Vec<u8>,
+        key: &[u8],
+        reply: Reply,
+    ) -> DispatchResult<()> {
+        hop.config().check_list_len(args.len())?;
+
         let mut key = hop.state().key_or_insert_with(key, Value::list);
         let list = key.as_list_mut().ok_or(DispatchError::KeyTypeDifferent)?;
+        hop.config()
+            .check_list_len(list.len().saturating_add(args.len()))?;
 
-        list.append(&mut args.map(ToOwned::to_owned).collect());
+        list.extend(args.map(ToOwned::to_owned));
 
-        response::write_list(resp, list.iter());
+        match reply {
+            Reply::Value => response::write_list(resp, list.iter()),
+            Reply::Length => response::write_int(resp, list.len() as i64),
+        }
 
         Ok(())
     }
@@ -41,17 +102,30 @@ impl Append {
         args: Arguments<'_>,
         resp: &mut Vec<u8>,
         key: &[u8],
+        reply: Reply,
     ) -> DispatchResult<()> {
+        // Check every argument before appending any of them.
+        let args = args
+            .map(str::from_utf8)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| DispatchError::InvalidUtf8)?;
+        let len = args.iter().map(|arg| arg.len()).sum::<usize>();
+        hop.config().check_bytes_len(len)?;
+
         let mut key = hop.state().key_or_insert_with(key, Value::string);
         let string = key.as_string_mut().ok_or(DispatchError::KeyTypeDifferent)?;
+        hop.config()
+            .check_bytes_len(string.len().saturating_add(len))?;
 
         for arg in args {
-            if let Ok(arg) = str::from_utf8(arg) {
-                string.push_str(arg);
-            }
+            string.push_str(arg);
         }
 
-        response::write_str(resp, &string);
+        match reply {
+            Reply::Value => response::write_str(resp, string),
+            // Strings are measured in characters, like the length command.
+            Reply::Length => response::write_int(resp, string.chars().count() as i64),
+        }
 
         Ok(())
     }
@@ -59,18 +133,60 @@ impl Append {
 
 impl Dispatch for Append {
     fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
-        let key = req.arg(0).ok_or(DispatchError::KeyUnspecified)?;
-        let args = req.args(1..).ok_or(DispatchError::ArgumentRetrieval)?;
-        let key_type = req
-            .key_type()
-            .or_else(|| hop.state().key_type(key))
-            .unwrap_or(KeyType::Bytes);
+        Self::append(hop, req, resp, Reply::Value)
+    }
+}
 
-        match key_type {
-            KeyType::Bytes => Self::bytes(hop, args, resp, key),
-            KeyType::List => Self::list(hop, args, resp, key),
-            KeyType::String => Self::string(hop, args, resp, key),
-            _ => Err(DispatchError::KeyTypeDifferent),
-        }
+#[cfg(test)]
+mod tests {
+    use super::Append;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{KeyType, Value},
+        Hop,
+    };
+    use alloc::{borrow::ToOwned, vec::Vec};
+
+    #[test]
+    fn test_string() {
+        let hop = Hop::new();
+        hop.state()
+            .insert(b"foo".to_vec(), Value::String("a".to_owned()));
+
+        let mut builder = RequestBuilder::new(CommandId::Append);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(b"b".as_ref()).is_ok());
+        assert!(builder.bytes(b"c".as_ref()).is_ok());
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        assert!(Append::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(resp, Response::from("abc".to_owned()).as_bytes());
+    }
+
+    #[test]
+    fn test_string_invalid_utf8() {
+        let hop = Hop::new();
+        hop.state()
+            .insert(b"foo".to_vec(), Value::String("a".to_owned()));
+
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Append, KeyType::String);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(b"b".as_ref()).is_ok());
+        assert!(builder.bytes([0xFF].as_ref()).is_ok());
+        let req = builder.into_request();
+
+        assert_eq!(
+            DispatchError::InvalidUtf8,
+            Append::dispatch(&hop, &req, &mut Vec::new()).unwrap_err()
+        );
+        // none of the arguments are appended
+        assert_eq!(
+            Some("a"),
+            hop.state()
+                .key_ref(b"foo")
+                .as_deref()
+                .and_then(Value::as_string_ref)
+        );
     }
 }
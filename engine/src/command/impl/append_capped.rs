@@ -0,0 +1,142 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{
+    state::{KeyType, Value},
+    Hop,
+};
+use alloc::{borrow::ToOwned, vec::Vec};
+
+/// Append items to a list and trim it to a maximum length in one step,
+/// keeping the most recently appended items and responding with the list's
+/// new length.
+///
+/// The first argument after the key is the maximum length as an integer, and
+/// the rest are the items to append.
+pub struct AppendCapped;
+
+impl Dispatch for AppendCapped {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+
+        match req.key_type() {
+            Some(KeyType::List) | None => {}
+            Some(_) => return Err(DispatchError::KeyTypeInvalid),
+        }
+
+        let max = req
+            .typed_arg::<i64>(1)
+            .filter(|max| *max >= 0)
+            .ok_or(DispatchError::ArgumentRetrieval)? as usize;
+        let args = req
+            .args(2..)
+            .filter(|args| args.len() > 0)
+            .ok_or(DispatchError::ArgumentRetrieval)?;
+
+        hop.config().check_list_len(args.len().min(max))?;
+
+        let mut key = hop.state().key_or_insert_with(key, Value::list);
+        let list = key.as_list_mut().ok_or(DispatchError::KeyTypeDifferent)?;
+        hop.config()
+            .check_list_len(list.len().saturating_add(args.len()).min(max))?;
+
+        list.extend(args.map(ToOwned::to_owned));
+
+        if list.len() > max {
+            list.drain(..list.len() - max);
+        }
+
+        response::write_int(resp, list.len() as i64);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AppendCapped;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{KeyType, Value},
+        Hop,
+    };
+    use alloc::{collections::VecDeque, vec, vec::Vec};
+
+    fn append(hop: &Hop, max: i64, items: &[&[u8]]) -> Result<Vec<u8>, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::AppendCapped);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(max.to_be_bytes().as_ref()).is_ok());
+
+        for item in items {
+            assert!(builder.bytes(*item).is_ok());
+        }
+
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        AppendCapped::dispatch(hop, &req, &mut resp).map(|_| resp)
+    }
+
+    #[test]
+    fn test_past_cap() {
+        let hop = Hop::new();
+
+        assert_eq!(
+            Ok(Response::from(2).as_bytes()),
+            append(&hop, 3, &[b"a", b"b"])
+        );
+        assert_eq!(
+            Ok(Response::from(3).as_bytes()),
+            append(&hop, 3, &[b"c", b"d", b"e"])
+        );
+        assert_eq!(
+            Some(&VecDeque::from(vec![
+                b"c".to_vec(),
+                b"d".to_vec(),
+                b"e".to_vec()
+            ])),
+            hop.state()
+                .key_ref(b"foo")
+                .as_deref()
+                .and_then(Value::as_list_ref)
+        );
+
+        // appending more items than the cap keeps only the newest
+        assert_eq!(
+            Ok(Response::from(2).as_bytes()),
+            append(&hop, 2, &[b"f", b"g", b"h"])
+        );
+        assert_eq!(
+            Some(&VecDeque::from(vec![b"g".to_vec(), b"h".to_vec()])),
+            hop.state()
+                .key_ref(b"foo")
+                .as_deref()
+                .and_then(Value::as_list_ref)
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+
+        assert_eq!(
+            Err(DispatchError::KeyTypeDifferent),
+            append(&hop, 3, &[b"a"])
+        );
+        assert_eq!(
+            Err(DispatchError::ArgumentRetrieval),
+            append(&hop, -1, &[b"a"])
+        );
+        assert_eq!(Err(DispatchError::ArgumentRetrieval), append(&hop, 3, &[]));
+
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::AppendCapped, KeyType::Set);
+        assert!(builder.bytes(b"bar".as_ref()).is_ok());
+        assert!(builder.bytes(3i64.to_be_bytes().as_ref()).is_ok());
+        assert!(builder.bytes(b"a".as_ref()).is_ok());
+        let req = builder.into_request();
+
+        assert_eq!(
+            DispatchError::KeyTypeInvalid,
+            AppendCapped::dispatch(&hop, &req, &mut Vec::new()).unwrap_err()
+        );
+    }
+}
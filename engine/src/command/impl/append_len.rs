@@ -0,0 +1,116 @@
+use super::{
+    super::{Dispatch, DispatchResult, Request},
+    append::{Append, Reply},
+};
+use crate::Hop;
+use alloc::vec::Vec;
+
+/// Append to a key like [`Append`], but respond with the key's new length
+/// instead of its whole value.
+///
+/// Bytes are measured in bytes, lists in items and strings in characters.
+///
+/// [`Append`]: struct.Append.html
+pub struct AppendLen;
+
+impl Dispatch for AppendLen {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        Append::append(hop, req, resp, Reply::Length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AppendLen;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{KeyType, Value},
+        Hop,
+    };
+    use alloc::{borrow::ToOwned, collections::VecDeque, vec::Vec};
+
+    fn append_len(
+        hop: &Hop,
+        key_type: Option<KeyType>,
+        args: &[&[u8]],
+    ) -> Result<Response, DispatchError> {
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::AppendLen, key_type);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+
+        for arg in args {
+            assert!(builder.bytes(*arg).is_ok());
+        }
+
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        AppendLen::dispatch(hop, &req, &mut resp).map(|_| Response::parse(&resp).unwrap())
+    }
+
+    #[test]
+    fn test_bytes() {
+        let hop = Hop::new();
+
+        assert_eq!(Ok(Response::from(3)), append_len(&hop, None, &[b"abc"]));
+        assert_eq!(Ok(Response::from(5)), append_len(&hop, None, &[b"d", b"e"]));
+        assert_eq!(
+            Some(b"abcde".as_ref()),
+            hop.state()
+                .key_ref(b"foo")
+                .as_deref()
+                .and_then(Value::as_bytes_ref)
+        );
+    }
+
+    #[test]
+    fn test_string() {
+        let hop = Hop::new();
+        hop.state()
+            .insert(b"foo".to_vec(), Value::String("a".to_owned()));
+
+        // strings are measured in characters rather than bytes
+        assert_eq!(
+            Ok(Response::from(3)),
+            append_len(&hop, Some(KeyType::String), &["🤠".as_bytes(), b"b"])
+        );
+        assert_eq!(
+            Some("a🤠b"),
+            hop.state()
+                .key_ref(b"foo")
+                .as_deref()
+                .and_then(Value::as_string_ref)
+        );
+    }
+
+    #[test]
+    fn test_list() {
+        let hop = Hop::new();
+        let mut list = VecDeque::new();
+        list.push_back(b"a".to_vec());
+        hop.state().insert(b"foo".to_vec(), Value::List(list));
+
+        assert_eq!(
+            Ok(Response::from(3)),
+            append_len(&hop, Some(KeyType::List), &[b"b", b"c"])
+        );
+        assert_eq!(
+            Some(3),
+            hop.state()
+                .key_ref(b"foo")
+                .as_deref()
+                .and_then(Value::as_list_ref)
+                .map(VecDeque::len)
+        );
+    }
+
+    #[test]
+    fn test_wrong_type() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+
+        assert_eq!(
+            Err(DispatchError::KeyTypeDifferent),
+            append_len(&hop, None, &[b"a"])
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{state::KeyType, Hop};
+use alloc::vec::Vec;
+
+/// Pop the first item of a list, responding with nil if the list is empty or
+/// doesn't exist.
+///
+/// The argument after the key is the number of milliseconds to wait for an
+/// item to be pushed if the list is empty, where 0 waits indefinitely. A
+/// single response can't wait, so the wait itself is done by the transport
+/// via [`State::wait_for_push`] once the pop has responded with nil.
+///
+/// [`State::wait_for_push`]: ../../../state/struct.State.html#method.wait_for_push
+pub struct BlockingPop;
+
+impl Dispatch for BlockingPop {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+
+        match req.key_type() {
+            Some(KeyType::List) | None => {}
+            Some(_) => return Err(DispatchError::KeyTypeInvalid),
+        }
+
+        req.typed_arg::<i64>(1)
+            .filter(|timeout| *timeout >= 0)
+            .ok_or(DispatchError::ArgumentRetrieval)?;
+
+        let mut value = match hop.state().key_mut(key) {
+            Some(value) => value,
+            None => {
+                response::write_nil(resp);
+
+                return Ok(());
+            }
+        };
+
+        let list = value.as_list_mut().ok_or(DispatchError::KeyTypeDifferent)?;
+
+        match list.pop_front() {
+            Some(item) => response::write_bytes(resp, &item),
+            None => response::write_nil(resp),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockingPop;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::Value,
+        Hop,
+    };
+    use alloc::{vec, vec::Vec};
+
+    fn pop(hop: &Hop, timeout: i64) -> Result<Vec<u8>, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::BlockingPop);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(timeout.to_be_bytes().as_ref()).is_ok());
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        BlockingPop::dispatch(hop, &req, &mut resp).map(|_| resp)
+    }
+
+    #[test]
+    fn test_pop_in_order() {
+        let hop = Hop::new();
+        hop.state().insert(
+            b"foo".to_vec(),
+            Value::from(vec![b"a".to_vec(), b"b".to_vec()]),
+        );
+
+        assert_eq!(Ok(Response::from(b"a".to_vec()).as_bytes()), pop(&hop, 0));
+        assert_eq!(Ok(Response::from(b"b".to_vec()).as_bytes()), pop(&hop, 0));
+        assert_eq!(Ok(Response::nil().as_bytes()), pop(&hop, 0));
+    }
+
+    #[test]
+    fn test_nonexistent() {
+        let hop = Hop::new();
+
+        assert_eq!(Ok(Response::nil().as_bytes()), pop(&hop, 100));
+        assert!(!hop.state().contains_key(b"foo"));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+
+        assert_eq!(Err(DispatchError::KeyTypeDifferent), pop(&hop, 0));
+        assert_eq!(Err(DispatchError::ArgumentRetrieval), pop(&hop, -1));
+    }
+}
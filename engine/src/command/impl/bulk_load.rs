@@ -0,0 +1,140 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{state::dump, Hop};
+use alloc::vec::Vec;
+
+/// Insert many keys in a single request, responding with the number of keys
+/// loaded.
+///
+/// Each argument is a batch of entries created by [`dump::encode_entry`].
+/// Every entry is validated before any are inserted, so a malformed entry
+/// leaves the keyspace untouched. Existing keys are replaced.
+///
+/// [`dump::encode_entry`]: ../../state/dump/fn.encode_entry.html
+pub struct BulkLoad;
+
+impl Dispatch for BulkLoad {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        let batches = req.args(..).ok_or(DispatchError::ArgumentRetrieval)?;
+        let mut entries = Vec::new();
+
+        for batch in batches {
+            dump::decode_entries(batch, &mut entries).ok_or(DispatchError::ArgumentRetrieval)?;
+        }
+
+        let count = entries.len();
+
+        for (key, value) in entries {
+            hop.state().insert(key, value);
+        }
+
+        response::write_int(resp, count as i64);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BulkLoad;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{dump, KeyType, Value},
+        Hop,
+    };
+    use alloc::{format, string::String, vec, vec::Vec};
+    use dashmap::DashSet;
+
+    fn value(idx: usize) -> Value {
+        match idx % 5 {
+            0 => Value::Integer(idx as i64),
+            1 => Value::Bytes(vec![idx as u8; idx % 7]),
+            2 => Value::String(format!("value {}", idx)),
+            3 => Value::from(vec![b"a".to_vec(), format!("{}", idx).into_bytes()]),
+            _ => {
+                let set = DashSet::new();
+                set.insert(format!("{}", idx).into_bytes());
+
+                Value::Set(set)
+            }
+        }
+    }
+
+    #[test]
+    fn test_load_mixed() {
+        let hop = Hop::new();
+        hop.state().insert(b"key 0".to_vec(), Value::Boolean(true));
+        let mut builder = RequestBuilder::new(CommandId::BulkLoad);
+
+        // spread the keys over a few batches
+        for batch in (0..300).collect::<Vec<_>>().chunks(100) {
+            let mut bytes = Vec::new();
+
+            for idx in batch {
+                let key = format!("key {}", idx);
+                dump::encode_entry(key.as_bytes(), &value(*idx), &mut bytes);
+            }
+
+            assert!(builder.bytes(bytes).is_ok());
+        }
+
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        assert!(BulkLoad::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(resp, Response::from(300).as_bytes());
+
+        for idx in 0..300 {
+            let key = format!("key {}", idx);
+            let loaded = hop.state().key_ref(key.as_bytes()).unwrap();
+
+            assert!(value(idx) == *loaded, "key {} differs", idx);
+        }
+
+        assert_eq!(
+            Some(KeyType::Integer),
+            hop.state().key_type(b"key 0".as_ref())
+        );
+    }
+
+    #[test]
+    fn test_malformed_tail() {
+        let hop = Hop::new();
+        let mut bytes = Vec::new();
+        dump::encode_entry(b"foo", &Value::Integer(1), &mut bytes);
+        dump::encode_entry(b"bar", &Value::String(String::from("a")), &mut bytes);
+
+        let mut cases = vec![bytes[..bytes.len() - 1].to_vec()];
+
+        // invalid key type
+        let mut kind = bytes.clone();
+        kind.extend_from_slice(&[255, 0, 0, 0, 0, 0, 0, 0, 0]);
+        cases.push(kind);
+
+        // integer of the wrong size
+        let mut int = bytes.clone();
+        int.extend_from_slice(&[KeyType::Integer as u8, 0, 0, 0, 1, b'a', 0, 0, 0, 1, 0]);
+        cases.push(int);
+
+        for case in cases {
+            let mut builder = RequestBuilder::new(CommandId::BulkLoad);
+            assert!(builder.bytes(case).is_ok());
+            let req = builder.into_request();
+
+            assert_eq!(
+                DispatchError::ArgumentRetrieval,
+                BulkLoad::dispatch(&hop, &req, &mut Vec::new()).unwrap_err()
+            );
+            assert!(!hop.state().contains_key(b"foo"));
+        }
+
+        let req = RequestBuilder::new(CommandId::BulkLoad).into_request();
+        assert_eq!(
+            DispatchError::ArgumentRetrieval,
+            BulkLoad::dispatch(&hop, &req, &mut Vec::new()).unwrap_err()
+        );
+    }
+}
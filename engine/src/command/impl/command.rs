@@ -0,0 +1,61 @@
+use super::super::{response, CommandId, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{command::PROTOCOL_VERSION, Hop};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+pub struct Command;
+
+impl Dispatch for Command {
+    fn dispatch(_: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        // The protocol version comes first, followed by the ID of each
+        // supported command.
+        let mut items = Vec::new();
+        items.push(PROTOCOL_VERSION.to_be_bytes().to_vec());
+        items.extend(
+            (0..=u8::MAX)
+                .filter(|id| CommandId::try_from(*id).is_ok())
+                .map(|id| [id].to_vec()),
+        );
+
+        response::write_list(resp, items);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Command;
+    use crate::{
+        command::{
+            request::RequestBuilder,
+            response::{Context, Instruction},
+            CommandId, Dispatch, Response, PROTOCOL_VERSION,
+        },
+        state::Value,
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_command() {
+        let req = RequestBuilder::new(CommandId::Command).into_request();
+        let mut resp = Vec::new();
+
+        assert!(Command::dispatch(&Hop::new(), &req, &mut resp).is_ok());
+
+        let list = match Context::new().feed(&resp) {
+            Ok(Instruction::Concluded(Response::Value(Value::List(list)))) => list,
+            other => panic!("unexpected response: {:?}", other),
+        };
+
+        assert_eq!(Some(&PROTOCOL_VERSION.to_be_bytes().to_vec()), list.front());
+        assert!(list.contains(&[CommandId::Get as u8].to_vec()));
+        assert!(list.contains(&[CommandId::Command as u8].to_vec()));
+        assert!(!list.contains(&[255].to_vec()));
+    }
+}
@@ -0,0 +1,87 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{state::KeyType, Hop};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// Validate a request to set the default key type of a connection.
+///
+/// The argument is the key type as a single byte, or no argument to remove
+/// the default. The default belongs to the connection rather than the engine,
+/// so the transport gives it to its parsing context via
+/// [`Context::set_default_key_type`] once the request has been validated.
+///
+/// [`Context::set_default_key_type`]: ../request/struct.Context.html#method.set_default_key_type
+pub struct DefaultType;
+
+impl DefaultType {
+    /// Retrieve the key type of a default type request.
+    ///
+    /// Returns `None` if the argument isn't a key type.
+    fn key_type(req: &Request) -> Option<Option<KeyType>> {
+        match req.arg(0) {
+            Some([byte]) => KeyType::try_from(*byte).ok().map(Some),
+            Some(_) => None,
+            None => Some(None),
+        }
+    }
+}
+
+impl Dispatch for DefaultType {
+    fn dispatch(_: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        Self::key_type(req).ok_or(DispatchError::ArgumentRetrieval)?;
+
+        response::write_bool(resp, true);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DefaultType;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::KeyType,
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_valid() {
+        let hop = Hop::new();
+        let mut resp = Vec::new();
+
+        let mut builder = RequestBuilder::new(CommandId::DefaultType);
+        assert!(builder.bytes([KeyType::String as u8].as_ref()).is_ok());
+        let req = builder.into_request();
+
+        assert_eq!(Some(Some(KeyType::String)), DefaultType::key_type(&req));
+        assert!(DefaultType::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(resp, Response::from(true).as_bytes());
+
+        // no argument removes the default
+        let req = RequestBuilder::new(CommandId::DefaultType).into_request();
+        assert_eq!(Some(None), DefaultType::key_type(&req));
+        assert!(DefaultType::dispatch(&hop, &req, &mut Vec::new()).is_ok());
+    }
+
+    #[test]
+    fn test_invalid() {
+        let hop = Hop::new();
+
+        for arg in &[[255].as_ref(), [KeyType::String as u8, 0].as_ref()] {
+            let mut builder = RequestBuilder::new(CommandId::DefaultType);
+            assert!(builder.bytes(*arg).is_ok());
+            let req = builder.into_request();
+
+            assert_eq!(
+                DispatchError::ArgumentRetrieval,
+                DefaultType::dispatch(&hop, &req, &mut Vec::new()).unwrap_err()
+            );
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use super::super::{response, CommandId, Dispatch, DispatchError, DispatchResult, Request};
+use crate::Hop;
+use alloc::vec::Vec;
+
+pub struct DeleteMatching;
+
+impl Dispatch for DeleteMatching {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        let pattern = req.arg(0).ok_or(DispatchError::ArgumentRetrieval)?;
+        let removed = hop.state().remove_matching(pattern);
+
+        // The pattern isn't a key itself, so each removed key is notified here
+        // rather than by the engine.
+        if hop.pubsub().has_keyspace_watchers() {
+            for key in &removed {
+                hop.pubsub().notify_keyspace(CommandId::DeleteMatching, key);
+            }
+        }
+
+        response::write_int(resp, removed.len() as i64);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DeleteMatching;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{KeyType, Value},
+        Hop,
+    };
+    use alloc::{format, vec::Vec};
+
+    #[test]
+    fn test_namespace() {
+        let hop = Hop::new();
+
+        for idx in 0..300 {
+            hop.state()
+                .insert(format!("session:{}", idx).into_bytes(), Value::bytes());
+        }
+
+        hop.state().insert(b"sessions".to_vec(), Value::bytes());
+        hop.state().insert(b"user:1".to_vec(), Value::Integer(1));
+
+        let mut builder = RequestBuilder::new(CommandId::DeleteMatching);
+        assert!(builder.bytes(b"session:*".as_ref()).is_ok());
+        let req = builder.into_request();
+
+        let mut resp = Vec::new();
+
+        assert!(DeleteMatching::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(resp, Response::from(300).as_bytes());
+        assert!(!hop.state().contains_key(b"session:0"));
+        assert!(!hop.state().contains_key(b"session:299"));
+        assert!(hop.state().contains_key(b"sessions"));
+        assert!(hop.state().contains_key(b"user:1"));
+
+        resp.clear();
+
+        assert!(DeleteMatching::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(resp, Response::from(0).as_bytes());
+    }
+
+    #[test]
+    fn test_no_pattern() {
+        let req = RequestBuilder::new(CommandId::DeleteMatching).into_request();
+
+        let hop = Hop::new();
+        let mut resp = Vec::new();
+
+        assert_eq!(
+            DispatchError::ArgumentRetrieval,
+            DeleteMatching::dispatch(&hop, &req, &mut resp).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_key_type_specified() {
+        let mut builder =
+            RequestBuilder::new_with_key_type(CommandId::DeleteMatching, KeyType::Bytes);
+        assert!(builder.bytes(b"*".as_ref()).is_ok());
+        let req = builder.into_request();
+
+        let hop = Hop::new();
+        let mut resp = Vec::new();
+
+        assert_eq!(
+            DispatchError::KeyTypeUnexpected,
+            DeleteMatching::dispatch(&hop, &req, &mut resp).unwrap_err()
+        );
+    }
+}
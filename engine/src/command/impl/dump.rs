@@ -0,0 +1,76 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{state::dump, Hop};
+use alloc::vec::Vec;
+
+pub struct Dump;
+
+impl Dispatch for Dump {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+
+        // The expiry has to be retrieved before taking a reference to the
+        // value, since retrieving it may remove the key.
+        let ttl = hop.state().expires_at(key).map(|expires_at| {
+            // A key that's due to expire this instant still has a TTL.
+            expires_at.saturating_sub(hop.state().now()).max(1)
+        });
+        let value = hop
+            .state()
+            .key_ref(key)
+            .ok_or(DispatchError::KeyNonexistent)?;
+
+        response::write_bytes(resp, &dump::dump(value.value(), ttl));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Dump;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{dump, ExpireFlags, Value},
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_dump() {
+        let mut builder = RequestBuilder::new(CommandId::Dump);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        let req = builder.into_request();
+
+        let mut builder = Hop::builder();
+        builder.clock(|| 1000);
+        let hop = builder.build();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        assert!(hop.state().expire_at(b"foo", 3000, ExpireFlags::NONE));
+        let mut resp = Vec::new();
+
+        assert!(Dump::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(
+            Response::from(dump::dump(&Value::Integer(1), Some(2000))).as_bytes(),
+            resp
+        );
+    }
+
+    #[test]
+    fn test_key_nonexistent() {
+        let mut builder = RequestBuilder::new(CommandId::Dump);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        let req = builder.into_request();
+
+        let hop = Hop::new();
+        let mut resp = Vec::new();
+
+        assert_eq!(
+            Err(DispatchError::KeyNonexistent),
+            Dump::dispatch(&hop, &req, &mut resp)
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{state::ExpireFlags, Hop};
+use alloc::vec::Vec;
+
+pub struct Expire;
+
+impl Expire {
+    /// Retrieve the optional flags argument shared by the expire commands.
+    ///
+    /// The flags are a single byte of [`ExpireFlags`] bits following the
+    /// expiry, and must not conflict with each other.
+    ///
+    /// [`ExpireFlags`]: ../../../state/struct.ExpireFlags.html
+    pub fn flags(req: &Request) -> DispatchResult<ExpireFlags> {
+        let bytes = match req.arg(2) {
+            Some(bytes) => bytes,
+            None => return Ok(ExpireFlags::NONE),
+        };
+
+        match bytes {
+            [bits] => ExpireFlags::from_bits(*bits)
+                .filter(|flags| flags.is_valid())
+                .ok_or(DispatchError::ArgumentRetrieval),
+            _ => Err(DispatchError::ArgumentRetrieval),
+        }
+    }
+}
+
+impl Dispatch for Expire {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+        let secs = req
+            .typed_arg::<i64>(1)
+            .ok_or(DispatchError::ArgumentRetrieval)?;
+        let flags = Self::flags(req)?;
+
+        let exists = hop.state().expire_in(key, secs.saturating_mul(1000), flags);
+
+        response::write_bool(resp, exists);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Expire;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{ExpireFlags, Value},
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    fn hop() -> Hop {
+        let mut builder = Hop::builder();
+        builder.clock(|| 1_000_000);
+
+        builder.build()
+    }
+
+    #[test]
+    fn test_expire() {
+        let mut builder = RequestBuilder::new(CommandId::Expire);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.value(Value::Integer(10)).is_ok());
+        let req = builder.into_request();
+
+        let hop = hop();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        let mut resp = Vec::new();
+
+        assert!(Expire::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(Response::from(true).as_bytes(), resp);
+        assert_eq!(Some(1_010_000), hop.state().expires_at(b"foo"));
+    }
+
+    #[test]
+    fn test_expire_missing_key() {
+        let mut builder = RequestBuilder::new(CommandId::Expire);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.value(Value::Integer(10)).is_ok());
+        let req = builder.into_request();
+
+        let hop = hop();
+        let mut resp = Vec::new();
+
+        assert!(Expire::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(Response::from(false).as_bytes(), resp);
+    }
+
+    #[test]
+    fn test_expire_non_positive() {
+        let mut builder = RequestBuilder::new(CommandId::Expire);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.value(Value::Integer(0)).is_ok());
+        let req = builder.into_request();
+
+        let hop = hop();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        let mut resp = Vec::new();
+
+        assert!(Expire::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(Response::from(true).as_bytes(), resp);
+        assert!(!hop.state().contains_key(b"foo"));
+    }
+
+    #[test]
+    fn test_argument_missing() {
+        let mut builder = RequestBuilder::new(CommandId::Expire);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        let req = builder.into_request();
+
+        let hop = hop();
+        let mut resp = Vec::new();
+
+        assert_eq!(
+            Err(DispatchError::ArgumentRetrieval),
+            Expire::dispatch(&hop, &req, &mut resp)
+        );
+    }
+
+    fn request(secs: i64, flags: ExpireFlags) -> RequestBuilder {
+        let mut builder = RequestBuilder::new(CommandId::Expire);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.value(Value::Integer(secs)).is_ok());
+        assert!(builder.bytes([flags.bits()].as_ref()).is_ok());
+
+        builder
+    }
+
+    fn dispatch(hop: &Hop, secs: i64, flags: ExpireFlags) -> bool {
+        let req = request(secs, flags).into_request();
+        let mut resp = Vec::new();
+
+        assert!(Expire::dispatch(hop, &req, &mut resp).is_ok());
+
+        resp == Response::from(true).as_bytes()
+    }
+
+    #[test]
+    fn test_flags_without_expiry() {
+        let hop = hop();
+
+        for (flags, applied) in [
+            (ExpireFlags::NX, true),
+            (ExpireFlags::XX, false),
+            (ExpireFlags::GT, false),
+            (ExpireFlags::LT, true),
+        ]
+        .iter()
+        {
+            hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+
+            assert_eq!(*applied, dispatch(&hop, 10, *flags), "{:?}", flags);
+            assert_eq!(
+                if *applied { Some(1_010_000) } else { None },
+                hop.state().expires_at(b"foo"),
+                "{:?}",
+                flags
+            );
+        }
+    }
+
+    #[test]
+    fn test_flags_with_expiry() {
+        let hop = hop();
+
+        for (flags, secs, applied) in [
+            (ExpireFlags::NX, 20, false),
+            (ExpireFlags::XX, 20, true),
+            (ExpireFlags::GT, 20, true),
+            (ExpireFlags::GT, 5, false),
+            (ExpireFlags::LT, 5, true),
+            (ExpireFlags::LT, 20, false),
+        ]
+        .iter()
+        {
+            hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+            assert!(dispatch(&hop, 10, ExpireFlags::NONE));
+
+            let expected = if *applied { *secs } else { 10 } as u64 * 1000 + 1_000_000;
+
+            assert_eq!(*applied, dispatch(&hop, *secs, *flags), "{:?}", flags);
+            assert_eq!(
+                Some(expected),
+                hop.state().expires_at(b"foo"),
+                "{:?}",
+                flags
+            );
+        }
+    }
+
+    #[test]
+    fn test_flags_conflicting() {
+        let hop = hop();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+
+        for flags in [
+            ExpireFlags::NX | ExpireFlags::XX,
+            ExpireFlags::NX | ExpireFlags::GT,
+            ExpireFlags::GT | ExpireFlags::LT,
+        ]
+        .iter()
+        {
+            let req = request(10, *flags).into_request();
+            let mut resp = Vec::new();
+
+            assert_eq!(
+                Err(DispatchError::ArgumentRetrieval),
+                Expire::dispatch(&hop, &req, &mut resp)
+            );
+        }
+
+        assert_eq!(None, hop.state().expires_at(b"foo"));
+    }
+}
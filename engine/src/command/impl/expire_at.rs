@@ -0,0 +1,87 @@
+use super::{
+    super::{response, Dispatch, DispatchError, DispatchResult, Request},
+    Expire,
+};
+use crate::Hop;
+use alloc::vec::Vec;
+
+pub struct ExpireAt;
+
+impl Dispatch for ExpireAt {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+        let unix_secs = req
+            .typed_arg::<i64>(1)
+            .ok_or(DispatchError::ArgumentRetrieval)?;
+        let flags = Expire::flags(req)?;
+
+        // Timestamps before the epoch have passed just like any other past
+        // timestamp.
+        let unix_ms = (unix_secs.max(0) as u64).saturating_mul(1000);
+        let exists = hop.state().expire_at(key, unix_ms, flags);
+
+        response::write_bool(resp, exists);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExpireAt;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, Response},
+        state::Value,
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    fn hop() -> Hop {
+        let mut builder = Hop::builder();
+        builder.clock(|| 1_000_000);
+
+        builder.build()
+    }
+
+    #[test]
+    fn test_expire_at() {
+        let mut builder = RequestBuilder::new(CommandId::ExpireAt);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.value(Value::Integer(2000)).is_ok());
+        let req = builder.into_request();
+
+        let hop = hop();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        let mut resp = Vec::new();
+
+        assert!(ExpireAt::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(Response::from(true).as_bytes(), resp);
+        assert_eq!(Some(2_000_000), hop.state().expires_at(b"foo"));
+    }
+
+    #[test]
+    fn test_expire_at_past() {
+        let mut builder = RequestBuilder::new(CommandId::ExpireAt);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.value(Value::Integer(999)).is_ok());
+        let req = builder.into_request();
+
+        let hop = hop();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        let mut resp = Vec::new();
+
+        assert!(ExpireAt::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(Response::from(true).as_bytes(), resp);
+        assert!(!hop.state().contains_key(b"foo"));
+        assert_eq!(0, hop.state().memory_used());
+
+        // the key's gone now, so expiring it again reports that it's missing
+        resp.clear();
+        assert!(ExpireAt::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(Response::from(false).as_bytes(), resp);
+    }
+}
@@ -0,0 +1,78 @@
+use super::{
+    super::{response, Dispatch, DispatchError, DispatchResult, Request},
+    Expire,
+};
+use crate::Hop;
+use alloc::vec::Vec;
+
+pub struct ExpireMs;
+
+impl Dispatch for ExpireMs {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+        let ms = req
+            .typed_arg::<i64>(1)
+            .ok_or(DispatchError::ArgumentRetrieval)?;
+        let flags = Expire::flags(req)?;
+
+        let exists = hop.state().expire_in(key, ms, flags);
+
+        response::write_bool(resp, exists);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExpireMs;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, Response},
+        state::Value,
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    fn hop() -> Hop {
+        let mut builder = Hop::builder();
+        builder.clock(|| 1_000_000);
+
+        builder.build()
+    }
+
+    #[test]
+    fn test_expire_ms() {
+        let mut builder = RequestBuilder::new(CommandId::ExpireMs);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.value(Value::Integer(1500)).is_ok());
+        let req = builder.into_request();
+
+        let hop = hop();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        let mut resp = Vec::new();
+
+        assert!(ExpireMs::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(Response::from(true).as_bytes(), resp);
+        assert_eq!(Some(1_001_500), hop.state().expires_at(b"foo"));
+    }
+
+    #[test]
+    fn test_expire_ms_negative() {
+        let mut builder = RequestBuilder::new(CommandId::ExpireMs);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.value(Value::Integer(-1)).is_ok());
+        let req = builder.into_request();
+
+        let hop = hop();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        let mut resp = Vec::new();
+
+        assert!(ExpireMs::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(Response::from(true).as_bytes(), resp);
+        assert!(!hop.state().contains_key(b"foo"));
+    }
+}
@@ -4,15 +4,20 @@ use crate::{
 };
 use alloc::vec::Vec;
 
+/// Retrieve a key's value, responding with nil if the key doesn't exist.
 pub struct Get;
 
 impl Dispatch for Get {
     fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
         let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
-        let r = hop
-            .state()
-            .key_ref(key)
-            .ok_or_else(|| DispatchError::KeyNonexistent)?;
+        let r = match hop.state().key_ref(key) {
+            Some(r) => r,
+            None => {
+                response::write_nil(resp);
+
+                return Ok(());
+            }
+        };
 
         if let Some(key_type) = req.key_type() {
             if r.value().kind() != key_type {
@@ -100,10 +105,8 @@ mod tests {
         let mut resp = Vec::new();
         let hop = Hop::new();
 
-        assert_eq!(
-            DispatchError::KeyNonexistent,
-            Get::dispatch(&hop, &req, &mut resp).unwrap_err()
-        );
+        assert!(Get::dispatch(&hop, &req, &mut resp).is_ok());
+        assert!(Response::parse(&resp).unwrap().is_nil());
     }
 
     #[test]
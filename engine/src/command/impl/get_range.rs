@@ -0,0 +1,144 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{state::KeyType, Hop};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// Retrieve a range of a bytes key.
+///
+/// Large values can be read in bounded chunks by requesting consecutive
+/// ranges, so neither side has to buffer a whole blob in a single response.
+/// Ranges starting past the end of the value are empty.
+pub struct GetRange;
+
+impl GetRange {
+    fn position(req: &Request, idx: usize) -> DispatchResult<usize> {
+        req.typed_arg::<i64>(idx)
+            .and_then(|position| usize::try_from(position).ok())
+            .ok_or(DispatchError::ArgumentRetrieval)
+    }
+}
+
+impl Dispatch for GetRange {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+
+        match req.key_type() {
+            Some(KeyType::Bytes) | None => {}
+            Some(_) => return Err(DispatchError::KeyTypeUnexpected),
+        }
+
+        let offset = Self::position(req, 1)?;
+        let len = Self::position(req, 2)?;
+
+        let r = hop
+            .state()
+            .key_ref(key)
+            .ok_or(DispatchError::KeyNonexistent)?;
+        let bytes = r.as_bytes_ref().ok_or(DispatchError::KeyTypeDifferent)?;
+
+        let start = offset.min(bytes.len());
+        let end = start.saturating_add(len).min(bytes.len());
+
+        response::write_bytes(resp, &bytes[start..end]);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GetRange;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{KeyType, Value},
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    fn request(key: &[u8], offset: i64, len: i64) -> RequestBuilder {
+        let mut builder = RequestBuilder::new(CommandId::GetRange);
+        assert!(builder.bytes(key).is_ok());
+        assert!(builder.bytes(offset.to_be_bytes().as_ref()).is_ok());
+        assert!(builder.bytes(len.to_be_bytes().as_ref()).is_ok());
+
+        builder
+    }
+
+    #[test]
+    fn test_ranges() {
+        let hop = Hop::new();
+        hop.state()
+            .insert(b"foo".to_vec(), Value::Bytes(b"abcdef".to_vec()));
+
+        let cases: &[(i64, i64, &[u8])] = &[
+            (0, 3, b"abc"),
+            (3, 3, b"def"),
+            (4, 100, b"ef"),
+            (6, 3, b""),
+            (100, 3, b""),
+            (2, 0, b""),
+            (0, i64::MAX, b"abcdef"),
+        ];
+
+        for (offset, len, expected) in cases {
+            let req = request(b"foo", *offset, *len).into_request();
+            let mut resp = Vec::new();
+
+            assert!(GetRange::dispatch(&hop, &req, &mut resp).is_ok());
+            assert_eq!(resp, Response::from(expected.to_vec()).as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_position_invalid() {
+        let hop = Hop::new();
+        hop.state()
+            .insert(b"foo".to_vec(), Value::Bytes(b"abcdef".to_vec()));
+        let mut resp = Vec::new();
+
+        for (offset, len) in &[(-1, 3), (0, -1)] {
+            let req = request(b"foo", *offset, *len).into_request();
+
+            assert_eq!(
+                DispatchError::ArgumentRetrieval,
+                GetRange::dispatch(&hop, &req, &mut resp).unwrap_err()
+            );
+        }
+
+        let mut builder = RequestBuilder::new(CommandId::GetRange);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        let req = builder.into_request();
+
+        assert_eq!(
+            DispatchError::ArgumentRetrieval,
+            GetRange::dispatch(&hop, &req, &mut resp).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_key_type_different() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        let mut resp = Vec::new();
+
+        let req = request(b"foo", 0, 1).into_request();
+        assert_eq!(
+            DispatchError::KeyTypeDifferent,
+            GetRange::dispatch(&hop, &req, &mut resp).unwrap_err()
+        );
+
+        let req = request(b"bar", 0, 1).into_request();
+        assert_eq!(
+            DispatchError::KeyNonexistent,
+            GetRange::dispatch(&hop, &req, &mut resp).unwrap_err()
+        );
+
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::GetRange, KeyType::Integer);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        let req = builder.into_request();
+        assert_eq!(
+            DispatchError::KeyTypeUnexpected,
+            GetRange::dispatch(&hop, &req, &mut resp).unwrap_err()
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request, Response};
+use crate::{state::Value, Hop};
+use alloc::vec::Vec;
+
+/// Increment an integer by an amount, clamping the result to a maximum.
+///
+/// The arguments after the key are the amount and the maximum. Responds with
+/// an array of the new value and whether it was clamped to the maximum. Below
+/// the maximum this behaves like [`IncrementBy`], so a key that doesn't exist
+/// is created as 0 before incrementing.
+///
+/// [`IncrementBy`]: struct.IncrementBy.html
+pub struct IncrementCapped;
+
+impl Dispatch for IncrementCapped {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+        let amount = req
+            .typed_arg::<i64>(1)
+            .ok_or(DispatchError::ArgumentRetrieval)?;
+        let max = req
+            .typed_arg::<i64>(2)
+            .ok_or(DispatchError::ArgumentRetrieval)?;
+
+        let mut value = hop.state().key_or_insert_with(key, Value::integer);
+        let int = value
+            .as_integer_mut()
+            .ok_or(DispatchError::KeyTypeDifferent)?;
+
+        let incremented = int.saturating_add(amount);
+        let capped = incremented > max;
+        *int = incremented.min(max);
+
+        response::write_array(resp, &[Response::from(*int), Response::from(capped)]);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IncrementCapped;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::Value,
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    fn increment(hop: &Hop, amount: i64, max: i64) -> Result<Response, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::IncrementCapped);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.value(amount).is_ok());
+        assert!(builder.value(max).is_ok());
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        IncrementCapped::dispatch(hop, &req, &mut resp).map(|_| Response::parse(&resp).unwrap())
+    }
+
+    fn value(hop: &Hop) -> Option<i64> {
+        hop.state()
+            .key_ref(b"foo")
+            .as_deref()
+            .and_then(Value::as_integer_ref)
+            .copied()
+    }
+
+    #[test]
+    fn test_below_cap() {
+        let hop = Hop::new();
+
+        for expected in 1..=3 {
+            assert_eq!(
+                Ok(Response::Array(vec![
+                    Response::from(expected * 2),
+                    Response::from(false)
+                ])),
+                increment(&hop, 2, 10)
+            );
+        }
+
+        // reaching the cap exactly isn't clamping
+        assert_eq!(
+            Ok(Response::Array(vec![
+                Response::from(10),
+                Response::from(false)
+            ])),
+            increment(&hop, 4, 10)
+        );
+        assert_eq!(Some(10), value(&hop));
+    }
+
+    #[test]
+    fn test_crossing_cap() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(8));
+
+        assert_eq!(
+            Ok(Response::Array(vec![
+                Response::from(10),
+                Response::from(true)
+            ])),
+            increment(&hop, 5, 10)
+        );
+        assert_eq!(
+            Ok(Response::Array(vec![
+                Response::from(10),
+                Response::from(true)
+            ])),
+            increment(&hop, 1, 10)
+        );
+        assert_eq!(Some(10), value(&hop));
+
+        // the increment saturates rather than overflowing
+        assert_eq!(
+            Ok(Response::Array(vec![
+                Response::from(i64::MAX),
+                Response::from(false)
+            ])),
+            increment(&hop, i64::MAX, i64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_wrong_type() {
+        let hop = Hop::new();
+        hop.state()
+            .insert(b"foo".to_vec(), Value::String("bar".into()));
+
+        assert_eq!(Err(DispatchError::KeyTypeDifferent), increment(&hop, 1, 10));
+    }
+}
@@ -0,0 +1,90 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::Hop;
+use alloc::{string::String, vec::Vec};
+use core::fmt::Write;
+
+/// Respond with a string of metadata about the engine, as `key:value` lines.
+///
+/// The lines are the `version` of the engine, its `uptime_seconds`, the
+/// number of `keys` in the selected database, the number of
+/// `connected_clients` and the `eviction_policy`.
+pub struct Info;
+
+impl Dispatch for Info {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        let mut info = String::new();
+
+        // Writing to a string can't fail.
+        let _ = write!(
+            info,
+            "version:{}\nuptime_seconds:{}\nkeys:{}\nconnected_clients:{}\neviction_policy:{}\n",
+            env!("CARGO_PKG_VERSION"),
+            hop.uptime() / 1000,
+            hop.state().len(),
+            hop.connections_active(),
+            hop.config().eviction_policy().name(),
+        );
+
+        response::write_str(resp, &info);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Info;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{KeyType, Value},
+        Hop,
+    };
+    use alloc::{format, vec::Vec};
+
+    #[test]
+    fn test_info() {
+        let mut builder = Hop::builder();
+        builder.clock(|| 1_234_567);
+        let hop = builder.build();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        hop.state().insert(b"bar".to_vec(), Value::Integer(2));
+        let _conn = hop.connect();
+
+        let req = RequestBuilder::new(CommandId::Info).into_request();
+        let mut resp = Vec::new();
+
+        assert!(Info::dispatch(&hop, &req, &mut resp).is_ok());
+
+        let info = match Response::parse(&resp) {
+            Ok(Response::Value(Value::String(info))) => info,
+            other => panic!("unexpected response: {:?}", other),
+        };
+        let lines = info.lines().collect::<Vec<_>>();
+
+        assert_eq!(
+            lines,
+            [
+                format!("version:{}", env!("CARGO_PKG_VERSION")).as_str(),
+                "uptime_seconds:0",
+                "keys:2",
+                "connected_clients:1",
+                "eviction_policy:lru",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_key_type_unexpected() {
+        let req = RequestBuilder::new_with_key_type(CommandId::Info, KeyType::Bytes).into_request();
+        let mut resp = Vec::new();
+
+        assert_eq!(
+            Err(DispatchError::KeyTypeUnexpected),
+            Info::dispatch(&Hop::new(), &req, &mut resp)
+        );
+    }
+}
@@ -6,9 +6,7 @@ pub struct Is;
 
 impl Dispatch for Is {
     fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
-        let key_type = req
-            .key_type()
-            .ok_or_else(|| DispatchError::KeyTypeRequired)?;
+        let key_type = req.key_type().ok_or(DispatchError::KeyTypeRequired)?;
         let mut args = req.args(..).ok_or(DispatchError::ArgumentRetrieval)?;
 
         let all = args.all(|key| {
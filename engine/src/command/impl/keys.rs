@@ -54,8 +54,8 @@ mod tests {
         let mut resp = Vec::new();
         let hop = Hop::new();
         let map = DashMap::new();
-        map.insert(b"key1".to_vec(), b"value2".to_vec());
-        map.insert(b"key2".to_vec(), b"value2".to_vec());
+        map.insert(b"key1".to_vec(), Value::Bytes(b"value2".to_vec()));
+        map.insert(b"key2".to_vec(), Value::Bytes(b"value2".to_vec()));
         hop.state().insert(b"foo".to_vec(), Value::Map(map));
 
         assert!(Keys::dispatch(&hop, &req, &mut resp).is_ok());
@@ -73,7 +73,7 @@ mod tests {
         let mut resp = Vec::new();
         let hop = Hop::new();
         let map = DashMap::new();
-        map.insert(b"key".to_vec(), b"value".to_vec());
+        map.insert(b"key".to_vec(), Value::Bytes(b"value".to_vec()));
         hop.state().insert(b"foo".to_vec(), Value::Map(map));
 
         assert!(Keys::dispatch(&hop, &req, &mut resp).is_ok());
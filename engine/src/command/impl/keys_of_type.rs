@@ -0,0 +1,60 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::Hop;
+use alloc::vec::Vec;
+
+/// List every key whose value is of the request's key type.
+pub struct KeysOfType;
+
+impl Dispatch for KeysOfType {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        let key_type = req.key_type().ok_or(DispatchError::KeyTypeRequired)?;
+
+        response::write_list(resp, hop.state().keys_of_type(key_type));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeysOfType;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{KeyType, Value},
+        Hop,
+    };
+    use alloc::{vec, vec::Vec};
+
+    #[test]
+    fn test_only_matching() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::list());
+        hop.state().insert(b"bar".to_vec(), Value::set());
+        hop.state().insert(b"baz".to_vec(), Value::Integer(1));
+
+        let req =
+            RequestBuilder::new_with_key_type(CommandId::KeysOfType, KeyType::Set).into_request();
+        let mut resp = Vec::new();
+
+        assert!(KeysOfType::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(resp, Response::from(vec![b"bar".to_vec()]).as_bytes());
+
+        let req =
+            RequestBuilder::new_with_key_type(CommandId::KeysOfType, KeyType::Map).into_request();
+        resp.clear();
+
+        assert!(KeysOfType::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(resp, Response::from(Vec::<Vec<u8>>::new()).as_bytes());
+    }
+
+    #[test]
+    fn test_key_type_required() {
+        let hop = Hop::new();
+        let req = RequestBuilder::new(CommandId::KeysOfType).into_request();
+
+        assert_eq!(
+            DispatchError::KeyTypeRequired,
+            KeysOfType::dispatch(&hop, &req, &mut Vec::new()).unwrap_err()
+        );
+    }
+}
@@ -46,7 +46,21 @@ impl Length {
             .state()
             .key_ref(key)
             .ok_or(DispatchError::KeyNonexistent)?;
-        let set = key.as_set_ref().ok_or(DispatchError::KeyTypeDifferent)?;
+        let len = key.set_len().ok_or(DispatchError::KeyTypeDifferent)?;
+
+        response::write_int(resp, len as i64);
+
+        Ok(())
+    }
+
+    fn sorted_set(hop: &Hop, key: &[u8], resp: &mut Vec<u8>) -> DispatchResult<()> {
+        let key = hop
+            .state()
+            .key_ref(key)
+            .ok_or(DispatchError::KeyNonexistent)?;
+        let set = key
+            .as_sorted_set_ref()
+            .ok_or(DispatchError::KeyTypeDifferent)?;
 
         response::write_int(resp, set.len() as i64);
 
@@ -79,6 +93,7 @@ impl Dispatch for Length {
             KeyType::List => Self::list(hop, key, resp),
             KeyType::Map => Self::map(hop, key, resp),
             KeyType::Set => Self::set(hop, key, resp),
+            KeyType::SortedSet => Self::sorted_set(hop, key, resp),
             KeyType::String => Self::string(hop, key, resp),
             _ => Err(DispatchError::KeyTypeInvalid),
         }
@@ -154,7 +169,6 @@ mod tests {
         let mut resp = Vec::new();
         let hop = Hop::new();
         hop.state()
-            .0
             .insert(b"foo".to_vec(), Value::Bytes([1, 2, 3].to_vec()));
 
         assert!(Length::dispatch(&hop, &req, &mut resp).is_ok());
@@ -169,9 +183,8 @@ mod tests {
 
         let mut resp = Vec::new();
         let hop = Hop::new();
-        let mut list = Vec::new();
-        list.push(b"db".to_vec());
-        hop.state().0.insert(b"hop".to_vec(), Value::List(list));
+        let list = vec![b"db".to_vec()];
+        hop.state().insert(b"hop".to_vec(), Value::from(list));
 
         assert!(Length::dispatch(&hop, &req, &mut resp).is_ok());
         assert_eq!(resp, Response::from(1).as_bytes());
@@ -186,8 +199,8 @@ mod tests {
         let mut resp = Vec::new();
         let hop = Hop::new();
         let map = DashMap::new();
-        map.insert(b"foo".to_vec(), b"bar".to_vec());
-        hop.state().0.insert(b"hop".to_vec(), Value::Map(map));
+        map.insert(b"foo".to_vec(), Value::Bytes(b"bar".to_vec()));
+        hop.state().insert(b"hop".to_vec(), Value::Map(map));
 
         assert!(Length::dispatch(&hop, &req, &mut resp).is_ok());
         assert_eq!(resp, Response::from(1).as_bytes());
@@ -203,7 +216,7 @@ mod tests {
         let hop = Hop::new();
         let set = DashSet::new();
         set.insert(b"foo".to_vec());
-        hop.state().0.insert(b"hop".to_vec(), Value::Set(set));
+        hop.state().insert(b"hop".to_vec(), Value::Set(set));
 
         assert!(Length::dispatch(&hop, &req, &mut resp).is_ok());
         assert_eq!(resp, Response::from(1).as_bytes());
@@ -217,13 +230,11 @@ mod tests {
 
         let hop = Hop::new();
         hop.state()
-            .0
             .insert(b"foo".to_vec(), Value::String("1234".to_owned()));
 
         let cowboy = "🤠";
         assert_eq!(cowboy.len(), 4);
         hop.state()
-            .0
             .insert(b"cowboy".to_vec(), Value::String(cowboy.to_owned()));
 
         let mut builder = RequestBuilder::new(CommandId::Length);
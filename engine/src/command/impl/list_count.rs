@@ -0,0 +1,90 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{state::KeyType, Hop};
+use alloc::vec::Vec;
+
+/// Respond with the number of items in a list equal to a value.
+///
+/// A list that doesn't exist has no items.
+pub struct ListCount;
+
+impl Dispatch for ListCount {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        match req.key_type() {
+            Some(KeyType::List) | None => {}
+            Some(_) => return Err(DispatchError::KeyTypeInvalid),
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+        let item = req.arg(1).ok_or(DispatchError::ArgumentRetrieval)?;
+
+        let count = match hop.state().key_ref(key).as_deref() {
+            Some(value) => value
+                .as_list_ref()
+                .ok_or(DispatchError::KeyTypeDifferent)?
+                .iter()
+                .filter(|other| other.as_slice() == item)
+                .count(),
+            None => 0,
+        };
+
+        response::write_int(resp, count as i64);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ListCount;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::Value,
+        Hop,
+    };
+    use alloc::{collections::VecDeque, vec::Vec};
+
+    fn count(hop: &Hop, item: &[u8]) -> Result<Response, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::ListCount);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(item).is_ok());
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        ListCount::dispatch(hop, &req, &mut resp).map(|_| Response::parse(&resp).unwrap())
+    }
+
+    fn hop_with_list() -> Hop {
+        let hop = Hop::new();
+        let list = [b"a", b"b", b"a", b"c", b"a"]
+            .iter()
+            .map(|item| item.to_vec())
+            .collect::<VecDeque<_>>();
+        hop.state().insert(b"foo".to_vec(), Value::List(list));
+
+        hop
+    }
+
+    #[test]
+    fn test_matches() {
+        let hop = hop_with_list();
+
+        assert_eq!(Ok(Response::from(0)), count(&hop, b"d"));
+        assert_eq!(Ok(Response::from(1)), count(&hop, b"b"));
+        assert_eq!(Ok(Response::from(3)), count(&hop, b"a"));
+        // items are compared whole
+        assert_eq!(Ok(Response::from(0)), count(&hop, b"ab"));
+    }
+
+    #[test]
+    fn test_missing_key() {
+        assert_eq!(Ok(Response::from(0)), count(&Hop::new(), b"a"));
+    }
+
+    #[test]
+    fn test_wrong_type() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+
+        assert_eq!(Err(DispatchError::KeyTypeDifferent), count(&hop, b"a"));
+    }
+}
@@ -0,0 +1,140 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{
+    state::{KeyType, Value},
+    Hop,
+};
+use alloc::vec::Vec;
+
+/// Remove a list, responding with all of its items. A list that doesn't exist
+/// has no items.
+///
+/// At most [`response::MAX_ITEMS`] items are drained at once, leaving the rest
+/// in the list to be drained again.
+///
+/// [`response::MAX_ITEMS`]: ../../response/constant.MAX_ITEMS.html
+pub struct ListDrain;
+
+impl Dispatch for ListDrain {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        match req.key_type() {
+            Some(KeyType::List) | None => {}
+            Some(_) => return Err(DispatchError::KeyTypeInvalid),
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+        let state = hop.state();
+
+        match state.key_type(key) {
+            Some(KeyType::List) | None => {}
+            Some(_) => return Err(DispatchError::KeyTypeDifferent),
+        }
+
+        let items = match state.key_mut(key) {
+            Some(mut value) => {
+                // The key was replaced by another type in the meantime.
+                let list = value.as_list_mut().ok_or(DispatchError::KeyTypeDifferent)?;
+                let count = list.len().min(response::MAX_ITEMS);
+
+                list.drain(..count).collect::<Vec<_>>()
+            }
+            None => Vec::new(),
+        };
+
+        // The list is only removed if it's still empty, so an item pushed
+        // after draining is never lost.
+        state.remove_if(
+            key,
+            |value| matches!(value, Value::List(list) if list.is_empty()),
+        );
+
+        response::write_list(resp, items);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ListDrain;
+    use crate::{
+        command::{
+            request::RequestBuilder, response, CommandId, Dispatch, DispatchError, Response,
+        },
+        state::Value,
+        Hop,
+    };
+    use alloc::{collections::VecDeque, vec, vec::Vec};
+
+    fn drain(hop: &Hop) -> Result<Response, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::ListDrain);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        ListDrain::dispatch(hop, &req, &mut resp).map(|_| Response::parse(&resp).unwrap())
+    }
+
+    #[test]
+    fn test_populated() {
+        let hop = Hop::new();
+        let list = [b"a".to_vec(), b"b".to_vec(), b"c".to_vec()];
+        hop.state().insert(
+            b"foo".to_vec(),
+            Value::List(list.iter().cloned().collect::<VecDeque<_>>()),
+        );
+
+        assert_eq!(Ok(Response::from(list.to_vec())), drain(&hop));
+        assert!(!hop.state().contains_key(b"foo"));
+
+        // draining again finds nothing
+        assert_eq!(Ok(Response::from(Vec::<Vec<u8>>::new())), drain(&hop));
+    }
+
+    #[test]
+    fn test_more_than_max_items() {
+        let hop = Hop::new();
+        let list = (0..=response::MAX_ITEMS)
+            .map(|item| (item as u32).to_be_bytes().to_vec())
+            .collect::<VecDeque<_>>();
+        hop.state()
+            .insert(b"foo".to_vec(), Value::List(list.clone()));
+
+        let first = list
+            .iter()
+            .take(response::MAX_ITEMS)
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(Ok(Response::from(first)), drain(&hop));
+
+        // the rest is left to be drained again
+        let rest = list
+            .iter()
+            .skip(response::MAX_ITEMS)
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(Ok(Response::from(rest)), drain(&hop));
+        assert!(!hop.state().contains_key(b"foo"));
+    }
+
+    #[test]
+    fn test_empty() {
+        let hop = Hop::new();
+        hop.state()
+            .insert(b"foo".to_vec(), Value::List(VecDeque::new()));
+
+        assert_eq!(Ok(Response::from(Vec::<Vec<u8>>::new())), drain(&hop));
+        assert!(!hop.state().contains_key(b"foo"));
+    }
+
+    #[test]
+    fn test_wrong_type() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+
+        assert_eq!(Err(DispatchError::KeyTypeDifferent), drain(&hop));
+        assert_eq!(
+            Some(vec![Value::Integer(1)]),
+            hop.state().key_ref(b"foo").map(|value| vec![value.clone()])
+        );
+    }
+}
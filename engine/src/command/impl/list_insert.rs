@@ -0,0 +1,149 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{state::KeyType, Hop};
+use alloc::vec::Vec;
+
+/// Insert an item next to the first item in a list equal to a pivot,
+/// responding with the new length of the list, or -1 if the pivot isn't in
+/// the list.
+///
+/// The arguments after the key are the pivot, the item to insert, and a
+/// boolean of whether to insert the item after the pivot rather than before
+/// it. A list that doesn't exist has no pivot, so it isn't created.
+pub struct ListInsert;
+
+impl Dispatch for ListInsert {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        match req.key_type() {
+            Some(KeyType::List) | None => {}
+            Some(_) => return Err(DispatchError::KeyTypeInvalid),
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+        let pivot = req.arg(1).ok_or(DispatchError::ArgumentRetrieval)?;
+        let item = req.arg(2).ok_or(DispatchError::ArgumentRetrieval)?;
+        let after = req
+            .typed_arg::<bool>(3)
+            .ok_or(DispatchError::ArgumentRetrieval)?;
+
+        let mut value = match hop.state().key_mut(key) {
+            Some(value) => value,
+            None => {
+                response::write_int(resp, -1);
+
+                return Ok(());
+            }
+        };
+
+        let list = value.as_list_mut().ok_or(DispatchError::KeyTypeDifferent)?;
+
+        match list.iter().position(|other| other.as_slice() == pivot) {
+            Some(idx) => {
+                hop.config().check_list_len(list.len() + 1)?;
+
+                let idx = if after { idx + 1 } else { idx };
+                list.insert(idx, item.to_vec());
+
+                response::write_int(resp, list.len() as i64);
+            }
+            None => response::write_int(resp, -1),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ListInsert;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::Value,
+        Hop,
+    };
+    use alloc::{collections::VecDeque, vec::Vec};
+
+    fn insert(
+        hop: &Hop,
+        pivot: &[u8],
+        item: &[u8],
+        after: bool,
+    ) -> Result<Response, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::ListInsert);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(pivot).is_ok());
+        assert!(builder.bytes(item).is_ok());
+        assert!(builder.value(after).is_ok());
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        ListInsert::dispatch(hop, &req, &mut resp).map(|_| Response::parse(&resp).unwrap())
+    }
+
+    fn hop_with_list() -> Hop {
+        let hop = Hop::new();
+        let list = [b"a", b"b", b"a"]
+            .iter()
+            .map(|item| item.to_vec())
+            .collect::<VecDeque<_>>();
+        hop.state().insert(b"foo".to_vec(), Value::List(list));
+
+        hop
+    }
+
+    fn list(hop: &Hop) -> Vec<Vec<u8>> {
+        let value = hop.state().key_ref(b"foo").unwrap();
+
+        value.as_list_ref().unwrap().iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_before() {
+        let hop = hop_with_list();
+
+        assert_eq!(Ok(Response::from(4)), insert(&hop, b"a", b"x", false));
+        assert_eq!(
+            [b"x", b"a", b"b", b"a"]
+                .iter()
+                .map(|item| item.to_vec())
+                .collect::<Vec<_>>(),
+            list(&hop)
+        );
+    }
+
+    #[test]
+    fn test_after() {
+        let hop = hop_with_list();
+
+        assert_eq!(Ok(Response::from(4)), insert(&hop, b"a", b"x", true));
+        assert_eq!(
+            [b"a", b"x", b"b", b"a"]
+                .iter()
+                .map(|item| item.to_vec())
+                .collect::<Vec<_>>(),
+            list(&hop)
+        );
+    }
+
+    #[test]
+    fn test_pivot_missing() {
+        let hop = hop_with_list();
+
+        assert_eq!(Ok(Response::from(-1)), insert(&hop, b"c", b"x", false));
+        assert_eq!(3, list(&hop).len());
+
+        let hop = Hop::new();
+        assert_eq!(Ok(Response::from(-1)), insert(&hop, b"a", b"x", false));
+        assert!(!hop.state().contains_key(b"foo"));
+    }
+
+    #[test]
+    fn test_wrong_type() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+
+        assert_eq!(
+            Err(DispatchError::KeyTypeDifferent),
+            insert(&hop, b"a", b"x", false)
+        );
+    }
+}
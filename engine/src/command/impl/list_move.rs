@@ -0,0 +1,236 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{
+    state::{KeyType, ListEnd, Value},
+    Hop,
+};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// Pop an item from an end of a source list and push it to an end of a
+/// destination list, responding with the item, or nil if the source is empty
+/// or doesn't exist.
+///
+/// The arguments after the two keys are the [`ListEnd`] to pop from and the
+/// end to push to, each as a single byte. The source and destination may be
+/// the same list to rotate it.
+///
+/// The destination is checked to be a list before popping, so an item is
+/// never popped that can't be pushed.
+///
+/// [`ListEnd`]: ../../../state/enum.ListEnd.html
+pub struct ListMove;
+
+impl ListMove {
+    fn end(req: &Request, idx: usize) -> DispatchResult<ListEnd> {
+        match req.arg(idx) {
+            Some([byte]) => ListEnd::try_from(*byte).map_err(|_| DispatchError::ArgumentRetrieval),
+            _ => Err(DispatchError::ArgumentRetrieval),
+        }
+    }
+
+    /// Pop an item from a list, if the key exists and the list isn't empty.
+    fn pop(hop: &Hop, key: &[u8], end: ListEnd) -> DispatchResult<Option<Vec<u8>>> {
+        let mut value = match hop.state().key_mut(key) {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+
+        let list = value.as_list_mut().ok_or(DispatchError::KeyTypeDifferent)?;
+
+        Ok(end.pop(list))
+    }
+}
+
+impl Dispatch for ListMove {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        match req.key_type() {
+            Some(KeyType::List) | None => {}
+            Some(_) => return Err(DispatchError::KeyTypeInvalid),
+        }
+
+        let source = req.arg(0).ok_or(DispatchError::KeyUnspecified)?;
+        let destination = req.arg(1).ok_or(DispatchError::ArgumentRetrieval)?;
+        let from = Self::end(req, 2)?;
+        let to = Self::end(req, 3)?;
+        let state = hop.state();
+
+        if source == destination {
+            let mut value = match state.key_mut(source) {
+                Some(value) => value,
+                None => {
+                    response::write_nil(resp);
+
+                    return Ok(());
+                }
+            };
+
+            let list = value.as_list_mut().ok_or(DispatchError::KeyTypeDifferent)?;
+
+            match from.pop(list) {
+                Some(item) => {
+                    response::write_bytes(resp, &item);
+                    to.push(list, item);
+                }
+                None => response::write_nil(resp),
+            }
+
+            return Ok(());
+        }
+
+        match state.key_type(destination) {
+            Some(KeyType::List) | None => {}
+            Some(_) => return Err(DispatchError::KeyTypeDifferent),
+        }
+
+        let item = match Self::pop(hop, source, from)? {
+            Some(item) => item,
+            None => {
+                response::write_nil(resp);
+
+                return Ok(());
+            }
+        };
+
+        let mut value = state.key_or_insert_with(destination, Value::list);
+
+        match value.as_list_mut() {
+            Some(list) => {
+                response::write_bytes(resp, &item);
+                to.push(list, item);
+
+                Ok(())
+            }
+            None => {
+                // The destination was replaced after it was checked, so put
+                // the item back where it came from.
+                drop(value);
+
+                if let Some(mut value) = state.key_mut(source) {
+                    if let Some(list) = value.as_list_mut() {
+                        from.push(list, item);
+                    }
+                }
+
+                Err(DispatchError::KeyTypeDifferent)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ListMove;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{ListEnd, Value},
+        Hop,
+    };
+    use alloc::{collections::VecDeque, vec, vec::Vec};
+
+    fn list(items: &[&[u8]]) -> Value {
+        Value::List(items.iter().map(|item| item.to_vec()).collect())
+    }
+
+    fn list_move(
+        hop: &Hop,
+        source: &[u8],
+        destination: &[u8],
+        from: ListEnd,
+        to: ListEnd,
+    ) -> Result<Vec<u8>, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::ListMove);
+        assert!(builder.bytes(source).is_ok());
+        assert!(builder.bytes(destination).is_ok());
+        assert!(builder.bytes([from as u8].as_ref()).is_ok());
+        assert!(builder.bytes([to as u8].as_ref()).is_ok());
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        ListMove::dispatch(hop, &req, &mut resp).map(|_| resp)
+    }
+
+    fn items(hop: &Hop, key: &[u8]) -> Vec<Vec<u8>> {
+        hop.state()
+            .key_ref(key)
+            .as_deref()
+            .and_then(Value::as_list_ref)
+            .map(|list| list.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_directions() {
+        let cases = [
+            (ListEnd::Front, ListEnd::Front, b"a", [b"a", b"x", b"y"]),
+            (ListEnd::Front, ListEnd::Back, b"a", [b"x", b"y", b"a"]),
+            (ListEnd::Back, ListEnd::Front, b"c", [b"c", b"x", b"y"]),
+            (ListEnd::Back, ListEnd::Back, b"c", [b"x", b"y", b"c"]),
+        ];
+
+        for (from, to, moved, destination) in cases.iter() {
+            let hop = Hop::new();
+            hop.state()
+                .insert(b"foo".to_vec(), list(&[b"a", b"b", b"c"]));
+            hop.state().insert(b"bar".to_vec(), list(&[b"x", b"y"]));
+
+            assert_eq!(
+                Ok(Response::from(moved.to_vec()).as_bytes()),
+                list_move(&hop, b"foo", b"bar", *from, *to)
+            );
+            assert_eq!(2, items(&hop, b"foo").len());
+            assert_eq!(
+                destination
+                    .iter()
+                    .map(|item| item.to_vec())
+                    .collect::<Vec<_>>(),
+                items(&hop, b"bar")
+            );
+        }
+    }
+
+    #[test]
+    fn test_rotate() {
+        let hop = Hop::new();
+        hop.state()
+            .insert(b"foo".to_vec(), list(&[b"a", b"b", b"c"]));
+
+        assert_eq!(
+            Ok(Response::from(b"a".to_vec()).as_bytes()),
+            list_move(&hop, b"foo", b"foo", ListEnd::Front, ListEnd::Back)
+        );
+        assert_eq!(
+            vec![b"b".to_vec(), b"c".to_vec(), b"a".to_vec()],
+            items(&hop, b"foo")
+        );
+    }
+
+    #[test]
+    fn test_empty_source() {
+        let hop = Hop::new();
+        hop.state()
+            .insert(b"foo".to_vec(), Value::List(VecDeque::new()));
+
+        for source in [b"foo".as_ref(), b"missing".as_ref()].iter() {
+            assert_eq!(
+                Ok(Response::nil().as_bytes()),
+                list_move(&hop, source, b"bar", ListEnd::Front, ListEnd::Back)
+            );
+        }
+
+        // the destination isn't created
+        assert!(!hop.state().contains_key(b"bar"));
+    }
+
+    #[test]
+    fn test_destination_wrong_type() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), list(&[b"a"]));
+        hop.state().insert(b"bar".to_vec(), Value::Integer(1));
+
+        assert_eq!(
+            Err(DispatchError::KeyTypeDifferent),
+            list_move(&hop, b"foo", b"bar", ListEnd::Front, ListEnd::Back)
+        );
+        assert_eq!(vec![b"a".to_vec()], items(&hop, b"foo"));
+    }
+}
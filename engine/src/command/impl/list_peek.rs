@@ -0,0 +1,113 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{
+    state::{KeyType, ListEnd},
+    Hop,
+};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// Respond with the item at an end of a list without removing it, or nil if
+/// the list is empty or doesn't exist.
+///
+/// The argument after the key is the [`ListEnd`] to peek at as a single byte.
+///
+/// [`ListEnd`]: ../../../state/enum.ListEnd.html
+pub struct ListPeek;
+
+impl Dispatch for ListPeek {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        match req.key_type() {
+            Some(KeyType::List) | None => {}
+            Some(_) => return Err(DispatchError::KeyTypeInvalid),
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+        let end = match req.arg(1) {
+            Some([byte]) => {
+                ListEnd::try_from(*byte).map_err(|_| DispatchError::ArgumentRetrieval)?
+            }
+            _ => return Err(DispatchError::ArgumentRetrieval),
+        };
+
+        let value = hop.state().key_ref(key);
+        let list = match value.as_deref() {
+            Some(value) => value.as_list_ref().ok_or(DispatchError::KeyTypeDifferent)?,
+            None => {
+                response::write_nil(resp);
+
+                return Ok(());
+            }
+        };
+
+        match end.peek(list) {
+            Some(item) => response::write_bytes(resp, item),
+            None => response::write_nil(resp),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ListPeek;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{ListEnd, Value},
+        Hop,
+    };
+    use alloc::{collections::VecDeque, vec::Vec};
+
+    fn peek(hop: &Hop, end: ListEnd) -> Result<Vec<u8>, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::ListPeek);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes([end as u8].as_ref()).is_ok());
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        ListPeek::dispatch(hop, &req, &mut resp).map(|_| resp)
+    }
+
+    #[test]
+    fn test_ends() {
+        let hop = Hop::new();
+        let list = [b"a", b"b", b"c"].iter().map(|item| item.to_vec());
+        hop.state()
+            .insert(b"foo".to_vec(), Value::List(list.collect()));
+
+        assert_eq!(
+            Ok(Response::from(b"a".to_vec()).as_bytes()),
+            peek(&hop, ListEnd::Front)
+        );
+        assert_eq!(
+            Ok(Response::from(b"c".to_vec()).as_bytes()),
+            peek(&hop, ListEnd::Back)
+        );
+
+        // nothing was removed
+        let value = hop.state().key_ref(b"foo").unwrap();
+        assert_eq!(3, value.as_list_ref().unwrap().len());
+    }
+
+    #[test]
+    fn test_empty() {
+        let hop = Hop::new();
+        assert_eq!(Ok(Response::nil().as_bytes()), peek(&hop, ListEnd::Front));
+
+        hop.state()
+            .insert(b"foo".to_vec(), Value::List(VecDeque::new()));
+        assert_eq!(Ok(Response::nil().as_bytes()), peek(&hop, ListEnd::Front));
+        assert_eq!(Ok(Response::nil().as_bytes()), peek(&hop, ListEnd::Back));
+    }
+
+    #[test]
+    fn test_wrong_type() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+
+        assert_eq!(
+            Err(DispatchError::KeyTypeDifferent),
+            peek(&hop, ListEnd::Front)
+        );
+    }
+}
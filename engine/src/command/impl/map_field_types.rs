@@ -0,0 +1,114 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request, Response};
+use crate::{state::KeyType, Hop};
+use alloc::{borrow::ToOwned, vec::Vec};
+
+/// Respond with an array of the type of each of the fields' values in a map,
+/// or nil for fields that aren't in the map. A map that doesn't exist has no
+/// fields.
+pub struct MapFieldTypes;
+
+impl Dispatch for MapFieldTypes {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        match req.key_type() {
+            Some(KeyType::Map) | None => {}
+            Some(_) => return Err(DispatchError::KeyTypeInvalid),
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+
+        if req.arg_count() < 2 {
+            return Err(DispatchError::ArgumentRetrieval);
+        }
+
+        let fields = req.args(1..).ok_or(DispatchError::ArgumentRetrieval)?;
+        let value = hop.state().key_ref(key);
+
+        let map = match value.as_deref() {
+            Some(value) => Some(value.as_map_ref().ok_or(DispatchError::KeyTypeDifferent)?),
+            None => None,
+        };
+
+        let types = fields.map(|field| {
+            match map.and_then(|map| map.get(field).map(|value| value.kind())) {
+                Some(kind) => Response::from(kind.name().to_owned()),
+                None => Response::nil(),
+            }
+        });
+
+        response::write_array(resp, types);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MapFieldTypes;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::Value,
+        Hop,
+    };
+    use alloc::{borrow::ToOwned, vec, vec::Vec};
+    use dashmap::DashMap;
+
+    fn field_types(hop: &Hop, fields: &[&[u8]]) -> Result<Response, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::MapFieldTypes);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+
+        for field in fields {
+            assert!(builder.bytes(*field).is_ok());
+        }
+
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        MapFieldTypes::dispatch(hop, &req, &mut resp).map(|_| Response::parse(&resp).unwrap())
+    }
+
+    #[test]
+    fn test_present_and_absent() {
+        let hop = Hop::new();
+        let map = DashMap::new();
+        map.insert(b"name".to_vec(), Value::String("hop".to_owned()));
+        map.insert(b"age".to_vec(), Value::Integer(3));
+        map.insert(b"blob".to_vec(), Value::Bytes(vec![0xff, 0x00]));
+        hop.state().insert(b"foo".to_vec(), Value::Map(map));
+
+        assert_eq!(
+            Ok(Response::from(vec![
+                Response::from("string".to_owned()),
+                Response::nil(),
+                Response::from("integer".to_owned()),
+                Response::from("bytes".to_owned()),
+            ])),
+            field_types(&hop, &[b"name", b"missing", b"age", b"blob"])
+        );
+    }
+
+    #[test]
+    fn test_nonexistent_map() {
+        let hop = Hop::new();
+
+        assert_eq!(
+            Ok(Response::from(vec![Response::nil(), Response::nil()])),
+            field_types(&hop, &[b"a", b"b"])
+        );
+        assert!(!hop.state().contains_key(b"foo"));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+
+        assert_eq!(
+            Err(DispatchError::KeyTypeDifferent),
+            field_types(&hop, &[b"a"])
+        );
+        assert_eq!(
+            Err(DispatchError::ArgumentRetrieval),
+            field_types(&Hop::new(), &[])
+        );
+    }
+}
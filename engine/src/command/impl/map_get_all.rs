@@ -0,0 +1,85 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{state::KeyType, Hop};
+use alloc::vec::Vec;
+use dashmap::DashMap;
+
+/// Respond with every field of a map and its value. A map that doesn't exist
+/// has no fields.
+pub struct MapGetAll;
+
+impl Dispatch for MapGetAll {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        match req.key_type() {
+            Some(KeyType::Map) | None => {}
+            Some(_) => return Err(DispatchError::KeyTypeInvalid),
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+
+        match hop.state().key_ref(key).as_deref() {
+            Some(value) => {
+                let map = value.as_map_ref().ok_or(DispatchError::KeyTypeDifferent)?;
+
+                response::write_map(resp, map);
+            }
+            None => response::write_map(resp, &DashMap::new()),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MapGetAll;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{KeyType, Value},
+        Hop,
+    };
+    use alloc::{borrow::ToOwned, vec::Vec};
+    use dashmap::DashMap;
+
+    fn get_all(hop: &Hop, key_type: Option<KeyType>) -> Result<Response, DispatchError> {
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::MapGetAll, key_type);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        MapGetAll::dispatch(hop, &req, &mut resp).map(|_| Response::parse(&resp).unwrap())
+    }
+
+    #[test]
+    fn test_populated() {
+        let hop = Hop::new();
+        let map = DashMap::new();
+        map.insert(b"name".to_vec(), Value::String("hop".to_owned()));
+        map.insert(b"age".to_vec(), Value::Integer(3));
+        hop.state().insert(b"foo".to_vec(), Value::Map(map.clone()));
+
+        assert_eq!(Ok(Response::from(map)), get_all(&hop, None));
+    }
+
+    #[test]
+    fn test_nonexistent_map() {
+        let hop = Hop::new();
+
+        assert_eq!(
+            Ok(Response::from(DashMap::<Vec<u8>, Value>::new())),
+            get_all(&hop, Some(KeyType::Map))
+        );
+        assert!(!hop.state().contains_key(b"foo"));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+
+        assert_eq!(Err(DispatchError::KeyTypeDifferent), get_all(&hop, None));
+        assert_eq!(
+            Err(DispatchError::KeyTypeInvalid),
+            get_all(&hop, Some(KeyType::Integer))
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{state::KeyType, Hop};
+use alloc::vec::Vec;
+
+/// Rename a field in a map, responding with whether the field was renamed.
+///
+/// The arguments after the key are the field's current name, its new name and
+/// optionally a boolean of whether to overwrite a field that already has the
+/// new name. Without overwriting, renaming to an existing field fails with
+/// [`DispatchError::PreconditionFailed`]. A map or field that doesn't exist
+/// isn't renamed.
+///
+/// [`DispatchError::PreconditionFailed`]: ../../enum.DispatchError.html#variant.PreconditionFailed
+pub struct MapRenameField;
+
+impl Dispatch for MapRenameField {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        match req.key_type() {
+            Some(KeyType::Map) | None => {}
+            Some(_) => return Err(DispatchError::KeyTypeInvalid),
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+        let from = req.arg(1).ok_or(DispatchError::ArgumentRetrieval)?;
+        let to = req.arg(2).ok_or(DispatchError::ArgumentRetrieval)?;
+
+        let overwrite = match req.arg(3) {
+            Some(_) => req
+                .typed_arg::<bool>(3)
+                .ok_or(DispatchError::ArgumentRetrieval)?,
+            None => false,
+        };
+
+        let mut value = match hop.state().key_mut(key) {
+            Some(value) => value,
+            None => {
+                response::write_bool(resp, false);
+
+                return Ok(());
+            }
+        };
+
+        let map = value.as_map_mut().ok_or(DispatchError::KeyTypeDifferent)?;
+
+        if !map.contains_key(from) {
+            response::write_bool(resp, false);
+
+            return Ok(());
+        }
+
+        if from != to {
+            if !overwrite && map.contains_key(to) {
+                return Err(DispatchError::PreconditionFailed);
+            }
+
+            if let Some((_, field)) = map.remove(from) {
+                map.insert(to.to_vec(), field);
+            }
+        }
+
+        response::write_bool(resp, true);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MapRenameField;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::Value,
+        Hop,
+    };
+    use alloc::vec::Vec;
+    use dashmap::DashMap;
+
+    fn rename(
+        hop: &Hop,
+        from: &[u8],
+        to: &[u8],
+        overwrite: Option<bool>,
+    ) -> Result<Response, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::MapRenameField);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(from).is_ok());
+        assert!(builder.bytes(to).is_ok());
+
+        if let Some(overwrite) = overwrite {
+            assert!(builder.value(overwrite).is_ok());
+        }
+
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        MapRenameField::dispatch(hop, &req, &mut resp).map(|_| Response::parse(&resp).unwrap())
+    }
+
+    fn hop_with_map() -> Hop {
+        let hop = Hop::new();
+        let map = DashMap::new();
+        map.insert(b"a".to_vec(), Value::Integer(1));
+        map.insert(b"b".to_vec(), Value::Integer(2));
+        hop.state().insert(b"foo".to_vec(), Value::Map(map));
+
+        hop
+    }
+
+    fn field(hop: &Hop, name: &[u8]) -> Option<i64> {
+        let value = hop.state().key_ref(b"foo").unwrap();
+        let map = value.as_map_ref().unwrap();
+        let field = map.get(name)?;
+
+        field.as_integer_ref().copied()
+    }
+
+    #[test]
+    fn test_rename() {
+        let hop = hop_with_map();
+
+        assert_eq!(Ok(Response::from(true)), rename(&hop, b"a", b"c", None));
+        assert_eq!(None, field(&hop, b"a"));
+        assert_eq!(Some(1), field(&hop, b"c"));
+
+        assert_eq!(Ok(Response::from(true)), rename(&hop, b"c", b"c", None));
+        assert_eq!(Some(1), field(&hop, b"c"));
+    }
+
+    #[test]
+    fn test_collision() {
+        let hop = hop_with_map();
+
+        assert_eq!(
+            Err(DispatchError::PreconditionFailed),
+            rename(&hop, b"a", b"b", None)
+        );
+        assert_eq!(
+            Err(DispatchError::PreconditionFailed),
+            rename(&hop, b"a", b"b", Some(false))
+        );
+        assert_eq!(Some(1), field(&hop, b"a"));
+        assert_eq!(Some(2), field(&hop, b"b"));
+
+        assert_eq!(
+            Ok(Response::from(true)),
+            rename(&hop, b"a", b"b", Some(true))
+        );
+        assert_eq!(None, field(&hop, b"a"));
+        assert_eq!(Some(1), field(&hop, b"b"));
+    }
+
+    #[test]
+    fn test_missing_source() {
+        let hop = hop_with_map();
+
+        assert_eq!(Ok(Response::from(false)), rename(&hop, b"c", b"d", None));
+        assert_eq!(None, field(&hop, b"d"));
+
+        assert_eq!(
+            Ok(Response::from(false)),
+            rename(&Hop::new(), b"a", b"b", None)
+        );
+    }
+
+    #[test]
+    fn test_wrong_type() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+
+        assert_eq!(
+            Err(DispatchError::KeyTypeDifferent),
+            rename(&hop, b"a", b"b", None)
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request, Response};
+use crate::{state::KeyType, Hop};
+use alloc::vec::Vec;
+use dashmap::DashMap;
+
+/// The number of fields in a page if the request doesn't give a count.
+const DEFAULT_COUNT: usize = 10;
+
+/// Respond with a page of a map's fields and the cursor of the next page.
+///
+/// The argument after the key is the cursor, which is 0 to start, optionally
+/// followed by the number of fields to respond with. The response is an array
+/// of the next cursor, which is 0 once every field has been returned, and a
+/// map of the page's fields to their values.
+///
+/// Fields are paged in byte order like [`SetScan`], so paging through a map
+/// that isn't modified returns every field exactly once. A map that doesn't
+/// exist has no fields.
+///
+/// [`SetScan`]: struct.SetScan.html
+pub struct MapScan;
+
+impl Dispatch for MapScan {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        match req.key_type() {
+            Some(KeyType::Map) | None => {}
+            Some(_) => return Err(DispatchError::KeyTypeInvalid),
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+        let cursor = req
+            .typed_arg::<i64>(1)
+            .filter(|cursor| *cursor >= 0)
+            .ok_or(DispatchError::ArgumentRetrieval)? as usize;
+
+        let count = match req.arg(2) {
+            Some(_) => req
+                .typed_arg::<i64>(2)
+                .filter(|count| *count > 0)
+                .ok_or(DispatchError::ArgumentRetrieval)? as usize,
+            None => DEFAULT_COUNT,
+        };
+
+        let mut fields = match hop.state().key_ref(key).as_deref() {
+            Some(value) => value
+                .as_map_ref()
+                .ok_or(DispatchError::KeyTypeDifferent)?
+                .iter()
+                .map(|field| (field.key().clone(), field.value().clone()))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        fields.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+        let start = cursor.min(fields.len());
+        let end = start.saturating_add(count).min(fields.len());
+        let next = if end < fields.len() { end } else { 0 };
+        let page = fields.drain(start..end).collect::<DashMap<_, _>>();
+
+        response::write_array(resp, &[Response::from(next as i64), Response::from(page)]);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MapScan;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::Value,
+        Hop,
+    };
+    use alloc::{format, vec, vec::Vec};
+    use dashmap::DashMap;
+
+    fn scan(hop: &Hop, cursor: i64, count: Option<i64>) -> Result<Response, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::MapScan);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.value(cursor).is_ok());
+
+        if let Some(count) = count {
+            assert!(builder.value(count).is_ok());
+        }
+
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        MapScan::dispatch(hop, &req, &mut resp).map(|_| Response::parse(&resp).unwrap())
+    }
+
+    /// Parse a page into its next cursor and its fields in byte order.
+    fn page(resp: &Response) -> (i64, Vec<(Vec<u8>, i64)>) {
+        match resp.as_array().unwrap() {
+            [Response::Value(Value::Integer(next)), Response::Value(Value::Map(map))] => {
+                let mut fields = map
+                    .iter()
+                    .map(|field| {
+                        let value = field.value().as_integer_ref().copied().unwrap();
+
+                        (field.key().clone(), value)
+                    })
+                    .collect::<Vec<_>>();
+                fields.sort();
+
+                (*next, fields)
+            }
+            other => panic!("unexpected page: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_pages() {
+        let hop = Hop::new();
+        let map = DashMap::new();
+        map.insert(b"c".to_vec(), Value::Integer(3));
+        map.insert(b"a".to_vec(), Value::Integer(1));
+        map.insert(b"b".to_vec(), Value::Integer(2));
+        hop.state().insert(b"foo".to_vec(), Value::Map(map));
+
+        assert_eq!(
+            (2, vec![(b"a".to_vec(), 1), (b"b".to_vec(), 2)]),
+            page(&scan(&hop, 0, Some(2)).unwrap())
+        );
+        assert_eq!(
+            (0, vec![(b"c".to_vec(), 3)]),
+            page(&scan(&hop, 2, Some(2)).unwrap())
+        );
+        assert_eq!((0, vec![]), page(&scan(&hop, 3, None).unwrap()));
+    }
+
+    #[test]
+    fn test_large_map() {
+        let hop = Hop::new();
+        let map = DashMap::new();
+
+        for idx in 0..3000 {
+            map.insert(format!("field{}", idx).into_bytes(), Value::Integer(idx));
+        }
+
+        hop.state().insert(b"foo".to_vec(), Value::Map(map));
+
+        let mut seen = Vec::new();
+        let mut cursor = 0;
+
+        loop {
+            let (next, fields) = page(&scan(&hop, cursor, Some(128)).unwrap());
+            seen.extend(fields);
+            cursor = next;
+
+            if cursor == 0 {
+                break;
+            }
+        }
+
+        assert_eq!(3000, seen.len());
+        seen.sort();
+        seen.dedup();
+        assert_eq!(3000, seen.len());
+        assert!(seen
+            .iter()
+            .all(|(field, value)| *field == format!("field{}", value).into_bytes()));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let hop = Hop::new();
+
+        assert_eq!((0, vec![]), page(&scan(&hop, 0, None).unwrap()));
+        assert_eq!(Err(DispatchError::ArgumentRetrieval), scan(&hop, -1, None));
+        assert_eq!(
+            Err(DispatchError::ArgumentRetrieval),
+            scan(&hop, 0, Some(0))
+        );
+
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        assert_eq!(Err(DispatchError::KeyTypeDifferent), scan(&hop, 0, None));
+    }
+}
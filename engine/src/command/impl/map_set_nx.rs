@@ -0,0 +1,115 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{
+    state::{KeyType, Value},
+    Hop,
+};
+use alloc::vec::Vec;
+use dashmap::mapref::entry::Entry;
+
+/// Set a field in a map only if the field doesn't exist, responding with
+/// whether the field was set.
+///
+/// The arguments after the key are the field and its value. The map is created
+/// if the key doesn't exist, and an existing field is left untouched.
+pub struct MapSetNx;
+
+impl Dispatch for MapSetNx {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        match req.key_type() {
+            Some(KeyType::Map) | None => {}
+            Some(_) => return Err(DispatchError::KeyTypeInvalid),
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+        let field = req.arg(1).ok_or(DispatchError::ArgumentRetrieval)?;
+        let value = req.arg(2).ok_or(DispatchError::ArgumentRetrieval)?;
+
+        let mut key = hop.state().key_or_insert_with(key, Value::map);
+        let map = key.as_map_mut().ok_or(DispatchError::KeyTypeDifferent)?;
+
+        let set = match map.entry(field.to_vec()) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(Value::Bytes(value.to_vec()));
+
+                true
+            }
+        };
+
+        response::write_bool(resp, set);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MapSetNx;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::Value,
+        Hop,
+    };
+    use alloc::vec::Vec;
+    use dashmap::DashMap;
+
+    fn set_nx(hop: &Hop, field: &[u8], value: &[u8]) -> Result<Response, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::MapSetNx);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(field).is_ok());
+        assert!(builder.bytes(value).is_ok());
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        MapSetNx::dispatch(hop, &req, &mut resp).map(|_| Response::parse(&resp).unwrap())
+    }
+
+    fn field(hop: &Hop, name: &[u8]) -> Option<Vec<u8>> {
+        let value = hop.state().key_ref(b"foo")?;
+        let map = value.as_map_ref().unwrap();
+        let field = map.get(name)?;
+
+        field.as_bytes_ref().map(<[u8]>::to_vec)
+    }
+
+    #[test]
+    fn test_fresh_field() {
+        let hop = Hop::new();
+        let map = DashMap::new();
+        map.insert(b"a".to_vec(), Value::Bytes(b"1".to_vec()));
+        hop.state().insert(b"foo".to_vec(), Value::Map(map));
+
+        assert_eq!(Ok(Response::from(true)), set_nx(&hop, b"b", b"2"));
+        assert_eq!(Some(b"1".to_vec()), field(&hop, b"a"));
+        assert_eq!(Some(b"2".to_vec()), field(&hop, b"b"));
+    }
+
+    #[test]
+    fn test_existing_field() {
+        let hop = Hop::new();
+
+        assert_eq!(Ok(Response::from(true)), set_nx(&hop, b"a", b"1"));
+        assert_eq!(Ok(Response::from(false)), set_nx(&hop, b"a", b"2"));
+        assert_eq!(Some(b"1".to_vec()), field(&hop, b"a"));
+    }
+
+    #[test]
+    fn test_creates_map() {
+        let hop = Hop::new();
+
+        assert_eq!(None, field(&hop, b"a"));
+        assert_eq!(Ok(Response::from(true)), set_nx(&hop, b"a", b"1"));
+        assert_eq!(Some(b"1".to_vec()), field(&hop, b"a"));
+    }
+
+    #[test]
+    fn test_wrong_type() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+
+        assert_eq!(
+            Err(DispatchError::KeyTypeDifferent),
+            set_nx(&hop, b"a", b"1")
+        );
+    }
+}
@@ -1,22 +1,90 @@
 mod append;
+mod append_capped;
+mod append_len;
+mod blocking_pop;
+mod bulk_load;
+mod command;
 mod decrement;
 mod decrement_by;
+mod default_type;
 mod delete;
+mod delete_matching;
+mod dump;
 mod echo;
 mod exists;
+mod expire;
+mod expire_at;
+mod expire_ms;
 mod get;
+mod get_range;
 mod increment;
 mod increment_by;
+mod increment_capped;
+mod info;
 mod is;
 mod keys;
+mod keys_of_type;
 mod length;
+mod list_count;
+mod list_drain;
+mod list_insert;
+mod list_move;
+mod list_peek;
+mod map_field_types;
+mod map_get_all;
+mod map_rename_field;
+mod map_scan;
+mod map_set_nx;
+mod r#move;
+mod object_info;
+mod pf_add;
+mod pf_count;
+mod quit;
+mod rate_limit;
 mod rename;
+mod restore;
+mod scan;
+mod select;
 mod set;
+mod set_difference_store;
+mod set_intersect_store;
+mod set_members_sorted;
+mod set_scan;
+mod set_store;
+mod set_union_store;
+mod slow_log;
+mod slow_log_reset;
 mod stats;
+mod swap;
+mod swap_db;
+mod time;
+mod touch;
 mod r#type;
+mod type_name;
+mod wait;
+mod watch;
+mod z_add;
+mod z_range;
+mod z_range_by_score;
+mod z_rem;
+mod z_score;
 
 pub use self::{
-    append::Append, decrement::Decrement, decrement_by::DecrementBy, delete::Delete, echo::Echo,
-    exists::Exists, get::Get, increment::Increment, increment_by::IncrementBy, is::Is, keys::Keys,
-    length::Length, r#type::Type, rename::Rename, set::Set, stats::Stats,
+    append::Append, append_capped::AppendCapped, append_len::AppendLen, blocking_pop::BlockingPop,
+    bulk_load::BulkLoad, command::Command, decrement::Decrement, decrement_by::DecrementBy,
+    default_type::DefaultType, delete::Delete, delete_matching::DeleteMatching, dump::Dump,
+    echo::Echo, exists::Exists, expire::Expire, expire_at::ExpireAt, expire_ms::ExpireMs, get::Get,
+    get_range::GetRange, increment::Increment, increment_by::IncrementBy,
+    increment_capped::IncrementCapped, info::Info, is::Is, keys::Keys, keys_of_type::KeysOfType,
+    length::Length, list_count::ListCount, list_drain::ListDrain, list_insert::ListInsert,
+    list_move::ListMove, list_peek::ListPeek, map_field_types::MapFieldTypes,
+    map_get_all::MapGetAll, map_rename_field::MapRenameField, map_scan::MapScan,
+    map_set_nx::MapSetNx, object_info::ObjectInfo, pf_add::PfAdd, pf_count::PfCount, quit::Quit,
+    r#move::Move, r#type::Type, rate_limit::RateLimit, rename::Rename, restore::Restore,
+    scan::Scan, select::Select, set::Set, set_difference_store::SetDifferenceStore,
+    set_intersect_store::SetIntersectStore, set_members_sorted::SetMembersSorted,
+    set_scan::SetScan, set_union_store::SetUnionStore, slow_log::SlowLog,
+    slow_log_reset::SlowLogReset, stats::Stats, swap::Swap, swap_db::SwapDb, time::Time,
+    touch::Touch, type_name::TypeName, wait::Wait, watch::Watch, z_add::ZAdd, z_range::ZRange,
+    z_range_by_score::ZRangeByScore, z_rem::ZRem, z_score::ZScore,
 };
@@ -0,0 +1,167 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{state::ExpireFlags, Hop};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+pub struct Move;
+
+impl Dispatch for Move {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        let target = req
+            .typed_arg::<i64>(1)
+            .and_then(|database| usize::try_from(database).ok())
+            .and_then(|database| hop.state_of(database))
+            .ok_or(DispatchError::ArgumentRetrieval)?;
+
+        // A key already in the target database is never replaced, which also
+        // covers moving a key to the database it's already in.
+        if target.contains_key(key) {
+            response::write_bool(resp, false);
+
+            return Ok(());
+        }
+
+        let expires_at = hop.state().expires_at(key);
+
+        let moved = match hop.state().remove(key) {
+            Some((key, value)) => {
+                target.insert(key.clone(), value);
+
+                if let Some(unix_ms) = expires_at {
+                    target.expire_at(&key, unix_ms, ExpireFlags::NONE);
+                }
+
+                true
+            }
+            None => false,
+        };
+
+        response::write_bool(resp, moved);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Move;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{ExpireFlags, KeyType, Value},
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    fn request(key: &[u8], database: i64) -> RequestBuilder {
+        let mut builder = RequestBuilder::new(CommandId::Move);
+        assert!(builder.bytes(key).is_ok());
+        assert!(builder.bytes(database.to_be_bytes().as_ref()).is_ok());
+
+        builder
+    }
+
+    #[test]
+    fn test_move() {
+        let mut builder = Hop::builder();
+        builder.clock(|| 1000);
+        let hop = builder.build();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        assert!(hop.state().expire_in(b"foo", 500, ExpireFlags::NONE));
+
+        let req = request(b"foo", 1).into_request();
+        let mut resp = Vec::new();
+
+        assert!(Move::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(resp, Response::from(true).as_bytes());
+
+        let other = hop.select(1).unwrap();
+        assert!(!hop.state().contains_key(b"foo"));
+        assert!(matches!(
+            other.state().key_ref(b"foo").as_deref(),
+            Some(Value::Integer(1))
+        ));
+        assert_eq!(Some(1500), other.state().expires_at(b"foo"));
+
+        // moving it again finds nothing to move
+        resp.clear();
+        assert!(Move::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(resp, Response::from(false).as_bytes());
+    }
+
+    #[test]
+    fn test_target_has_key() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        hop.state_of(2)
+            .unwrap()
+            .insert(b"foo".to_vec(), Value::Integer(2));
+
+        let req = request(b"foo", 2).into_request();
+        let mut resp = Vec::new();
+
+        assert!(Move::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(resp, Response::from(false).as_bytes());
+        assert!(matches!(
+            hop.state().key_ref(b"foo").as_deref(),
+            Some(Value::Integer(1))
+        ));
+        assert!(matches!(
+            hop.state_of(2).unwrap().key_ref(b"foo").as_deref(),
+            Some(Value::Integer(2))
+        ));
+    }
+
+    #[test]
+    fn test_databases_isolated() {
+        let hop = Hop::new();
+        let other = hop.select(1).unwrap();
+
+        let mut set = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::Integer);
+        assert!(set.bytes(b"foo".as_ref()).is_ok());
+        assert!(set.bytes(1i64.to_be_bytes().as_ref()).is_ok());
+        assert!(other.dispatch(&set.into_request(), &mut Vec::new()).is_ok());
+
+        assert!(other.state().contains_key(b"foo"));
+        assert!(!hop.state().contains_key(b"foo"));
+
+        // moving it back from database 1 to 0
+        let mut resp = Vec::new();
+        let req = request(b"foo", 0).into_request();
+        assert!(other.dispatch(&req, &mut resp).is_ok());
+        assert_eq!(resp, Response::from(true).as_bytes());
+        assert!(hop.state().contains_key(b"foo"));
+        assert!(!other.state().contains_key(b"foo"));
+    }
+
+    #[test]
+    fn test_database_invalid() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+
+        let mut resp = Vec::new();
+
+        for database in &[-1, hop.config().databases() as i64] {
+            let req = request(b"foo", *database).into_request();
+
+            assert_eq!(
+                DispatchError::ArgumentRetrieval,
+                Move::dispatch(&hop, &req, &mut resp).unwrap_err()
+            );
+        }
+
+        let mut builder = RequestBuilder::new(CommandId::Move);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        let req = builder.into_request();
+
+        assert_eq!(
+            DispatchError::ArgumentRetrieval,
+            Move::dispatch(&hop, &req, &mut resp).unwrap_err()
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{
+    state::{EvictionPolicy, Value},
+    Hop,
+};
+use alloc::vec::Vec;
+use dashmap::DashMap;
+
+pub struct ObjectInfo;
+
+impl Dispatch for ObjectInfo {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        // None of these count as an access of the key, so inspecting a key
+        // doesn't make it look hot.
+        let state = hop.state();
+        let key_type = state.key_type(key).ok_or(DispatchError::KeyNonexistent)?;
+        let idle = state.idle_time(key).unwrap_or_default() as i64;
+        let size = state.key_size(key).unwrap_or_default() as i64;
+        let encoding = state
+            .key_encoding(key)
+            .ok_or(DispatchError::KeyNonexistent)?;
+
+        let map = DashMap::with_capacity(5);
+        map.insert(
+            b"type".to_vec(),
+            Value::Bytes(key_type.name().as_bytes().to_vec()),
+        );
+        map.insert(
+            b"encoding".to_vec(),
+            Value::Bytes(encoding.as_bytes().to_vec()),
+        );
+        map.insert(b"size".to_vec(), Value::Bytes(size.to_be_bytes().to_vec()));
+        map.insert(
+            b"idle_ms".to_vec(),
+            Value::Bytes(idle.to_be_bytes().to_vec()),
+        );
+
+        // Frequencies are only meaningful when they decide what's evicted.
+        if hop.config().eviction_policy() == EvictionPolicy::LeastFrequentlyUsed {
+            let frequency = i64::from(state.frequency(key).unwrap_or_default());
+
+            map.insert(
+                b"frequency".to_vec(),
+                Value::Bytes(frequency.to_be_bytes().to_vec()),
+            );
+        }
+
+        response::write_map(resp, &map);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ObjectInfo;
+    use crate::{
+        command::{
+            request::RequestBuilder,
+            response::{Context, Instruction},
+            CommandId, Dispatch, DispatchError, Response,
+        },
+        state::{EvictionPolicy, KeyType, Value},
+        Hop,
+    };
+    use alloc::vec::Vec;
+    use core::{
+        convert::TryInto,
+        sync::atomic::{AtomicU64, Ordering},
+    };
+    use dashmap::{DashMap, DashSet};
+
+    fn info(hop: &Hop, key: &[u8]) -> DashMap<Vec<u8>, Value> {
+        let mut builder = RequestBuilder::new(CommandId::ObjectInfo);
+        assert!(builder.bytes(key).is_ok());
+        let req = builder.into_request();
+
+        let mut resp = Vec::new();
+        assert!(ObjectInfo::dispatch(hop, &req, &mut resp).is_ok());
+
+        match Context::new().feed(&resp) {
+            Ok(Instruction::Concluded(Response::Value(Value::Map(map)))) => map,
+            other => panic!("unexpected response: {:?}", other),
+        }
+    }
+
+    fn int(map: &DashMap<Vec<u8>, Value>, name: &[u8]) -> i64 {
+        let value = map.get(name).unwrap();
+
+        i64::from_be_bytes(value.as_bytes_ref().unwrap().try_into().unwrap())
+    }
+
+    #[test]
+    fn test_idle_time_grows() {
+        static NOW: AtomicU64 = AtomicU64::new(1000);
+
+        let mut builder = Hop::builder();
+        builder.clock(|| NOW.load(Ordering::SeqCst));
+        let hop = builder.build();
+        hop.state()
+            .insert(b"foo".to_vec(), Value::Bytes(b"bar".to_vec()));
+
+        let map = info(&hop, b"foo");
+        assert_eq!(
+            Some(Value::Bytes(b"bytes".to_vec())),
+            map.get(b"type".as_ref()).map(|v| v.clone())
+        );
+        assert_eq!(6, int(&map, b"size"));
+        assert_eq!(0, int(&map, b"idle_ms"));
+        assert!(map.get(b"frequency".as_ref()).is_none());
+
+        NOW.store(1500, Ordering::SeqCst);
+        assert_eq!(500, int(&info(&hop, b"foo"), b"idle_ms"));
+
+        // inspecting the key didn't count as an access
+        NOW.store(2000, Ordering::SeqCst);
+        assert_eq!(1000, int(&info(&hop, b"foo"), b"idle_ms"));
+    }
+
+    #[test]
+    fn test_encoding() {
+        let hop = Hop::new();
+        let set = DashSet::new();
+        set.insert(b"1".to_vec());
+        set.insert(b"2".to_vec());
+        hop.state().insert(b"foo".to_vec(), Value::set_from(set));
+
+        let map = info(&hop, b"foo");
+        assert_eq!(
+            Some(Value::Bytes(b"set".to_vec())),
+            map.get(b"type".as_ref()).map(|v| v.clone())
+        );
+        assert_eq!(
+            Some(Value::Bytes(b"packed".to_vec())),
+            map.get(b"encoding".as_ref()).map(|v| v.clone())
+        );
+
+        hop.state()
+            .key_mut(b"foo")
+            .unwrap()
+            .insert_set_member(b"bar".to_vec());
+        assert_eq!(
+            Some(Value::Bytes(b"general".to_vec())),
+            info(&hop, b"foo")
+                .get(b"encoding".as_ref())
+                .map(|v| v.clone())
+        );
+    }
+
+    #[test]
+    fn test_frequency_with_lfu() {
+        let mut builder = Hop::builder();
+        builder.eviction_policy(EvictionPolicy::LeastFrequentlyUsed);
+        let hop = builder.build();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        hop.state().key_ref(b"foo");
+
+        assert_eq!(2, int(&info(&hop, b"foo"), b"frequency"));
+    }
+
+    #[test]
+    fn test_key_nonexistent() {
+        let mut builder = RequestBuilder::new(CommandId::ObjectInfo);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        let req = builder.into_request();
+
+        let hop = Hop::new();
+        let mut resp = Vec::new();
+
+        assert_eq!(
+            DispatchError::KeyNonexistent,
+            ObjectInfo::dispatch(&hop, &req, &mut resp).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_key_type_specified() {
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::ObjectInfo, KeyType::Bytes);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        let req = builder.into_request();
+
+        let hop = Hop::new();
+        let mut resp = Vec::new();
+
+        assert_eq!(
+            DispatchError::KeyTypeUnexpected,
+            ObjectInfo::dispatch(&hop, &req, &mut resp).unwrap_err()
+        );
+    }
+}
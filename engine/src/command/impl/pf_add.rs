@@ -0,0 +1,92 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{
+    state::{hyper_log_log, KeyType, Value},
+    Hop,
+};
+use alloc::vec::Vec;
+
+pub struct PfAdd;
+
+impl Dispatch for PfAdd {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if !matches!(req.key_type(), None | Some(KeyType::Bytes)) {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+        let args = req.args(1..).ok_or(DispatchError::ArgumentRetrieval)?;
+
+        let mut key = hop
+            .state()
+            .key_or_insert_with(key, || Value::Bytes(hyper_log_log::new()));
+        let registers = key
+            .as_bytes_mut()
+            .filter(|bytes| hyper_log_log::is_valid(bytes))
+            .ok_or(DispatchError::KeyTypeDifferent)?;
+
+        // Every item has to be added, so don't short circuit.
+        let changed = args.fold(false, |changed, item| {
+            hyper_log_log::add(registers, item) | changed
+        });
+
+        response::write_bool(resp, changed);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PfAdd;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{hyper_log_log, Value},
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    fn pfadd(hop: &Hop, items: &[&[u8]]) -> Result<Vec<u8>, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::PfAdd);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+
+        for item in items {
+            assert!(builder.bytes(*item).is_ok());
+        }
+
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        PfAdd::dispatch(hop, &req, &mut resp).map(|_| resp)
+    }
+
+    #[test]
+    fn test_add() {
+        let hop = Hop::new();
+
+        assert_eq!(
+            Response::from(true).as_bytes(),
+            pfadd(&hop, &[b"a", b"b"]).unwrap()
+        );
+        assert_eq!(
+            Response::from(false).as_bytes(),
+            pfadd(&hop, &[b"b", b"a"]).unwrap()
+        );
+
+        let key = hop.state().key_ref(b"foo").unwrap();
+        let registers = key.as_bytes_ref().unwrap();
+        assert_eq!(hyper_log_log::SIZE, registers.len());
+        assert_eq!(2, hyper_log_log::count(registers));
+    }
+
+    #[test]
+    fn test_key_type_different() {
+        let hop = Hop::new();
+        hop.state()
+            .insert(b"foo".to_vec(), Value::Bytes(b"bar".to_vec()));
+
+        assert_eq!(
+            DispatchError::KeyTypeDifferent,
+            pfadd(&hop, &[b"a"]).unwrap_err()
+        );
+    }
+}
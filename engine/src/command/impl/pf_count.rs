@@ -0,0 +1,84 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{
+    state::{hyper_log_log, KeyType},
+    Hop,
+};
+use alloc::vec::Vec;
+
+pub struct PfCount;
+
+impl Dispatch for PfCount {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if !matches!(req.key_type(), None | Some(KeyType::Bytes)) {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+
+        // A nonexistent key hasn't had any items added.
+        let count = match hop.state().key_ref(key) {
+            Some(key) => {
+                let registers = key
+                    .as_bytes_ref()
+                    .filter(|bytes| hyper_log_log::is_valid(bytes))
+                    .ok_or(DispatchError::KeyTypeDifferent)?;
+
+                hyper_log_log::count(registers)
+            }
+            None => 0,
+        };
+
+        response::write_int(resp, count as i64);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PfCount;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{hyper_log_log, Value},
+        Hop,
+    };
+    use alloc::{format, vec::Vec};
+
+    fn pfcount(hop: &Hop) -> Result<Vec<u8>, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::PfCount);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        PfCount::dispatch(hop, &req, &mut resp).map(|_| resp)
+    }
+
+    #[test]
+    fn test_count() {
+        let hop = Hop::new();
+        let mut registers = hyper_log_log::new();
+
+        for idx in 0..10_000 {
+            hyper_log_log::add(&mut registers, format!("{}", idx).as_bytes());
+        }
+
+        let expected = hyper_log_log::count(&registers) as i64;
+        assert!((expected - 10_000).abs() < 300);
+        hop.state().insert(b"foo".to_vec(), Value::Bytes(registers));
+
+        assert_eq!(Response::from(expected).as_bytes(), pfcount(&hop).unwrap());
+    }
+
+    #[test]
+    fn test_key_nonexistent() {
+        assert_eq!(Response::from(0).as_bytes(), pfcount(&Hop::new()).unwrap());
+    }
+
+    #[test]
+    fn test_key_type_different() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+
+        assert_eq!(DispatchError::KeyTypeDifferent, pfcount(&hop).unwrap_err());
+    }
+}
@@ -0,0 +1,51 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::Hop;
+use alloc::vec::Vec;
+
+/// Acknowledge a request to close the connection.
+///
+/// The connection belongs to the transport rather than the engine, so the
+/// transport closes it once the response has been written.
+pub struct Quit;
+
+impl Dispatch for Quit {
+    fn dispatch(_: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        response::write_bool(resp, true);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Quit;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::KeyType,
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_quit() {
+        let req = RequestBuilder::new(CommandId::Quit).into_request();
+        let mut resp = Vec::new();
+
+        assert!(Quit::dispatch(&Hop::new(), &req, &mut resp).is_ok());
+        assert_eq!(resp, Response::from(true).as_bytes());
+    }
+
+    #[test]
+    fn test_key_type_unexpected() {
+        let req = RequestBuilder::new_with_key_type(CommandId::Quit, KeyType::Bytes).into_request();
+
+        assert_eq!(
+            Err(DispatchError::KeyTypeUnexpected),
+            Quit::dispatch(&Hop::new(), &req, &mut Vec::new())
+        );
+    }
+}
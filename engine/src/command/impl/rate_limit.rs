@@ -0,0 +1,150 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request, Response};
+use crate::{
+    state::{ExpireFlags, Value},
+    Hop,
+};
+use alloc::vec::Vec;
+
+/// Count a call against a fixed-window rate limit, responding with an array
+/// of whether the call is allowed and how many calls remain in the window.
+///
+/// The arguments after the key are the limit and the window length in
+/// milliseconds. The key holds the window's call count as an integer and
+/// expires when the window ends, so the next call after that opens a new
+/// window. Calls over the limit aren't counted.
+pub struct RateLimit;
+
+impl Dispatch for RateLimit {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+        let limit = req
+            .typed_arg::<i64>(1)
+            .filter(|limit| *limit >= 0)
+            .ok_or(DispatchError::ArgumentRetrieval)?;
+        let window = req
+            .typed_arg::<i64>(2)
+            .filter(|window| *window > 0)
+            .ok_or(DispatchError::ArgumentRetrieval)?;
+
+        let mut value = hop.state().key_or_insert_with(key, Value::integer);
+        let count = value
+            .as_integer_mut()
+            .ok_or(DispatchError::KeyTypeDifferent)?;
+
+        let allowed = *count < limit;
+
+        if allowed {
+            *count += 1;
+        }
+
+        let remaining = limit.saturating_sub(*count).max(0);
+
+        // The reference has to be released before the expiry can be set. Only
+        // the call opening the window sets it, so later calls don't extend it.
+        drop(value);
+        hop.state().expire_in(key, window, ExpireFlags::NX);
+
+        response::write_array(resp, &[Response::from(allowed), Response::from(remaining)]);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RateLimit;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::Value,
+        Hop,
+    };
+    use alloc::vec::Vec;
+    use core::sync::atomic::{AtomicU64, Ordering};
+
+    fn hop() -> Hop {
+        let mut builder = Hop::builder();
+        builder.clock(|| 1_000_000);
+
+        builder.build()
+    }
+
+    fn limit(hop: &Hop, key: &[u8], limit: i64, window: i64) -> Result<Response, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::RateLimit);
+        assert!(builder.bytes(key).is_ok());
+        assert!(builder.value(limit).is_ok());
+        assert!(builder.value(window).is_ok());
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        RateLimit::dispatch(hop, &req, &mut resp).map(|_| Response::parse(&resp).unwrap())
+    }
+
+    fn outcome(allowed: bool, remaining: i64) -> Result<Response, DispatchError> {
+        Ok(Response::Array(vec![
+            Response::from(allowed),
+            Response::from(remaining),
+        ]))
+    }
+
+    #[test]
+    fn test_exhaust_and_refill() {
+        static NOW: AtomicU64 = AtomicU64::new(1_000_000);
+
+        let mut builder = Hop::builder();
+        builder.clock(|| NOW.load(Ordering::SeqCst));
+        let hop = builder.build();
+        let start = NOW.load(Ordering::SeqCst);
+
+        assert_eq!(outcome(true, 2), limit(&hop, b"exhaust", 3, 1000));
+        assert_eq!(Some(start + 1000), hop.state().expires_at(b"exhaust"));
+
+        NOW.fetch_add(500, Ordering::SeqCst);
+        assert_eq!(outcome(true, 1), limit(&hop, b"exhaust", 3, 1000));
+        assert_eq!(outcome(true, 0), limit(&hop, b"exhaust", 3, 1000));
+        assert_eq!(outcome(false, 0), limit(&hop, b"exhaust", 3, 1000));
+        assert_eq!(outcome(false, 0), limit(&hop, b"exhaust", 3, 1000));
+
+        // later calls don't extend the window
+        assert_eq!(Some(start + 1000), hop.state().expires_at(b"exhaust"));
+
+        NOW.fetch_add(500, Ordering::SeqCst);
+        assert_eq!(outcome(true, 2), limit(&hop, b"exhaust", 3, 1000));
+        assert_eq!(Some(start + 2000), hop.state().expires_at(b"exhaust"));
+    }
+
+    #[test]
+    fn test_zero_limit() {
+        let hop = hop();
+
+        assert_eq!(outcome(false, 0), limit(&hop, b"zero", 0, 1000));
+        assert!(hop.state().expires_at(b"zero").is_some());
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        let hop = hop();
+
+        assert_eq!(
+            Err(DispatchError::ArgumentRetrieval),
+            limit(&hop, b"invalid", -1, 1000)
+        );
+        assert_eq!(
+            Err(DispatchError::ArgumentRetrieval),
+            limit(&hop, b"invalid", 1, 0)
+        );
+        assert!(!hop.state().contains_key(b"invalid"));
+    }
+
+    #[test]
+    fn test_wrong_type() {
+        let hop = hop();
+        hop.state()
+            .insert(b"wrong".to_vec(), Value::String("bar".into()));
+
+        assert_eq!(
+            Err(DispatchError::KeyTypeDifferent),
+            limit(&hop, b"wrong", 1, 1000)
+        );
+        assert!(hop.state().expires_at(b"wrong").is_none());
+    }
+}
@@ -2,6 +2,11 @@ use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
 use crate::Hop;
 use alloc::vec::Vec;
 
+/// Move a key's value and expiry to another key, responding with the moved
+/// value.
+///
+/// If the destination key already exists then its value and expiry are
+/// overwritten.
 pub struct Rename;
 
 impl Dispatch for Rename {
@@ -18,14 +23,13 @@ impl Dispatch for Rename {
             return Err(DispatchError::KeyNonexistent);
         }
 
-        if state.contains_key(arg) {
-            return Err(DispatchError::PreconditionFailed);
+        if !state.rename(key, arg.to_vec()) {
+            return Err(DispatchError::KeyNonexistent);
         }
 
-        let (_, v) = state.remove(key).ok_or(DispatchError::KeyNonexistent)?;
-        state.insert(arg.to_vec(), v);
+        let value = state.key_ref(arg).ok_or(DispatchError::KeyNonexistent)?;
 
-        response::write_bytes(resp, arg);
+        response::write_value(resp, value.value());
 
         Ok(())
     }
@@ -36,7 +40,7 @@ mod tests {
     use super::Rename;
     use crate::{
         command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
-        state::Value,
+        state::{ExpireFlags, Value},
         Hop,
     };
     use alloc::vec::Vec;
@@ -54,7 +58,60 @@ mod tests {
             .insert(b"foo".to_vec(), Value::Bytes([1, 2, 3].to_vec()));
 
         assert!(Rename::dispatch(&hop, &req, &mut resp).is_ok());
-        assert_eq!(resp, Response::from(b"bar".to_vec()).as_bytes());
+        assert_eq!(resp, Response::from([1, 2, 3].to_vec()).as_bytes());
+        assert!(!hop.state().contains_key(b"foo"));
+    }
+
+    #[test]
+    fn test_rename_keeps_expiry() {
+        let mut builder = RequestBuilder::new(CommandId::Rename);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(b"bar".as_ref()).is_ok());
+        let req = builder.into_request();
+
+        let mut builder = Hop::builder();
+        builder.clock(|| 1000);
+        let hop = builder.build();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        hop.state().insert(b"baz".to_vec(), Value::Integer(2));
+        assert!(hop.state().expire_in(b"foo", 500, ExpireFlags::NONE));
+
+        assert!(Rename::dispatch(&hop, &req, &mut Vec::new()).is_ok());
+        assert_eq!(Some(1500), hop.state().expires_at(b"bar"));
+        assert_eq!(None, hop.state().expires_at(b"foo"));
+
+        // a key without an expiry stays without one
+        let mut builder = RequestBuilder::new(CommandId::Rename);
+        assert!(builder.bytes(b"baz".as_ref()).is_ok());
+        assert!(builder.bytes(b"qux".as_ref()).is_ok());
+        let req = builder.into_request();
+
+        assert!(Rename::dispatch(&hop, &req, &mut Vec::new()).is_ok());
+        assert_eq!(None, hop.state().expires_at(b"qux"));
+        assert!(hop.state().contains_key(b"qux"));
+    }
+
+    #[test]
+    fn test_rename_events() {
+        let mut builder = RequestBuilder::new(CommandId::Rename);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(b"bar".as_ref()).is_ok());
+        let req = builder.into_request();
+
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        let rx = hop.pubsub().watch_keyspace(b"*".to_vec());
+
+        assert!(hop.dispatch(&req, &mut Vec::new()).is_ok());
+
+        // both the source and the destination were modified
+        for key in &[b"foo", b"bar"] {
+            let event = rx.try_receive().unwrap();
+            assert_eq!(CommandId::Rename, event.command_id());
+            assert_eq!(key.as_ref(), event.key());
+        }
+
+        assert!(rx.try_receive().is_err());
     }
 
     #[test]
@@ -74,21 +131,53 @@ mod tests {
     }
 
     #[test]
-    fn test_rename_destination_already_exists() {
+    fn test_rename_destination_overwritten() {
         let mut builder = RequestBuilder::new(CommandId::Rename);
         assert!(builder.bytes(b"foo".as_ref()).is_ok());
         assert!(builder.bytes(b"bar".as_ref()).is_ok());
         let req = builder.into_request();
 
         let mut resp = Vec::new();
+        let mut builder = Hop::builder();
+        builder.clock(|| 1000);
+        let hop = builder.build();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        hop.state()
+            .insert(b"bar".to_vec(), Value::String("old".into()));
+        assert!(hop.state().expire_in(b"bar", 500, ExpireFlags::NONE));
+
+        assert!(Rename::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(resp, Response::from(1).as_bytes());
+        assert!(!hop.state().contains_key(b"foo"));
+        assert_eq!(
+            Some(&1),
+            hop.state().key_ref(b"bar").unwrap().as_integer_ref()
+        );
+        // the destination's expiry is replaced by the source's lack of one
+        assert_eq!(None, hop.state().expires_at(b"bar"));
+    }
+
+    #[test]
+    fn test_rename_destination_overwritten_events() {
+        let mut builder = RequestBuilder::new(CommandId::Rename);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(b"bar".as_ref()).is_ok());
+        let req = builder.into_request();
+
         let hop = Hop::new();
-        hop.state().insert(b"foo".to_vec(), Value::bytes());
-        hop.state().insert(b"bar".to_vec(), Value::bytes());
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        hop.state().insert(b"bar".to_vec(), Value::Integer(2));
+        let rx = hop.pubsub().watch_keyspace(b"*".to_vec());
 
-        assert!(matches!(
-            Rename::dispatch(&hop, &req, &mut resp),
-            Err(DispatchError::PreconditionFailed)
-        ));
+        let mut resp = Vec::new();
+        assert!(hop.dispatch(&req, &mut resp).is_ok());
+        assert_eq!(resp, Response::from(1).as_bytes());
+        assert_eq!(1, hop.state().len());
+
+        for key in &[b"foo", b"bar"] {
+            let event = rx.try_receive().unwrap();
+            assert_eq!(key.as_ref(), event.key());
+        }
     }
 
     #[test]
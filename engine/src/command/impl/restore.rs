@@ -0,0 +1,223 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{
+    state::{dump, ExpireFlags},
+    Hop,
+};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+pub struct Restore;
+
+impl Dispatch for Restore {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+        let blob = req.arg(1).ok_or(DispatchError::ArgumentRetrieval)?;
+        let (value, mut ttl) = dump::restore(blob).ok_or(DispatchError::ArgumentRetrieval)?;
+
+        let replace = match req.arg(2) {
+            Some(_) => req
+                .typed_arg::<bool>(2)
+                .ok_or(DispatchError::ArgumentRetrieval)?,
+            None => false,
+        };
+
+        // A given TTL overrides the dumped one, with 0 meaning that the key
+        // doesn't expire.
+        if req.arg(3).is_some() {
+            ttl = match req.typed_arg::<i64>(3) {
+                Some(0) => None,
+                Some(ms) if ms > 0 => Some(ms as u64),
+                _ => return Err(DispatchError::ArgumentRetrieval),
+            };
+        }
+
+        if !replace && hop.state().contains_key(key) {
+            return Err(DispatchError::KeyExists);
+        }
+
+        hop.state().insert(key.to_vec(), value);
+
+        if let Some(ttl) = ttl {
+            let ms = i64::try_from(ttl).unwrap_or(i64::MAX);
+
+            hop.state().expire_in(key, ms, ExpireFlags::NONE);
+        }
+
+        response::write_bool(resp, true);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::Dump, Restore};
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{ExpireFlags, KeyType, Value},
+        Hop,
+    };
+    use alloc::{string::String, vec, vec::Vec};
+    use dashmap::{DashMap, DashSet};
+
+    fn hop() -> Hop {
+        let mut builder = Hop::builder();
+        builder.clock(|| 1000);
+
+        builder.build()
+    }
+
+    fn dump(hop: &Hop, key: &[u8]) -> Vec<u8> {
+        let mut builder = RequestBuilder::new(CommandId::Dump);
+        assert!(builder.bytes(key).is_ok());
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        assert!(Dump::dispatch(hop, &req, &mut resp).is_ok());
+
+        // strip the response length, type and bytes length to get the blob
+        resp[9..].to_vec()
+    }
+
+    fn restore(
+        hop: &Hop,
+        key: &[u8],
+        blob: &[u8],
+        replace: Option<bool>,
+        ttl: Option<i64>,
+    ) -> Result<(), DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::Restore);
+        assert!(builder.bytes(key).is_ok());
+        assert!(builder.bytes(blob).is_ok());
+
+        if replace.is_some() || ttl.is_some() {
+            assert!(builder
+                .value(Value::Boolean(replace.unwrap_or(false)))
+                .is_ok());
+        }
+
+        if let Some(ttl) = ttl {
+            assert!(builder.value(Value::Integer(ttl)).is_ok());
+        }
+
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        Restore::dispatch(hop, &req, &mut resp)?;
+        assert_eq!(Response::from(true).as_bytes(), resp);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roundtrip_all_key_types() {
+        let map = DashMap::new();
+        map.insert(b"a".to_vec(), Value::Bytes(b"b".to_vec()));
+        let set = DashSet::new();
+        set.insert(b"a".to_vec());
+
+        let values = vec![
+            Value::Boolean(true),
+            Value::Bytes(vec![1, 2, 3]),
+            Value::Float(1.5),
+            Value::Integer(7),
+            Value::from(vec![b"a".to_vec(), b"b".to_vec()]),
+            Value::Map(map),
+            Value::Set(set),
+            Value::String(String::from("foo")),
+        ];
+
+        let source = hop();
+        let target = hop();
+
+        for value in values {
+            let kind = value.kind();
+            source.state().insert(b"foo".to_vec(), value);
+
+            let blob = dump(&source, b"foo");
+            assert!(restore(&target, b"foo", &blob, Some(true), None).is_ok());
+
+            // dumping the restored key gives the same blob back
+            assert_eq!(Some(kind), target.state().key_type(b"foo"));
+            assert_eq!(blob, dump(&target, b"foo"), "{:?}", kind);
+        }
+
+        assert_eq!(Some(KeyType::String), target.state().key_type(b"foo"));
+    }
+
+    #[test]
+    fn test_ttl() {
+        let hop = hop();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        assert!(hop.state().expire_at(b"foo", 3000, ExpireFlags::NONE));
+        let blob = dump(&hop, b"foo");
+
+        // the dumped TTL is kept
+        assert!(restore(&hop, b"bar", &blob, None, None).is_ok());
+        assert_eq!(Some(3000), hop.state().expires_at(b"bar"));
+
+        // a given TTL overrides it
+        assert!(restore(&hop, b"baz", &blob, None, Some(500)).is_ok());
+        assert_eq!(Some(1500), hop.state().expires_at(b"baz"));
+
+        // as does a TTL of 0, meaning it doesn't expire
+        assert!(restore(&hop, b"qux", &blob, None, Some(0)).is_ok());
+        assert_eq!(None, hop.state().expires_at(b"qux"));
+        assert!(hop.state().contains_key(b"qux"));
+
+        assert_eq!(
+            Err(DispatchError::ArgumentRetrieval),
+            restore(&hop, b"quux", &blob, None, Some(-1))
+        );
+    }
+
+    #[test]
+    fn test_key_exists() {
+        let hop = hop();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        hop.state().insert(b"bar".to_vec(), Value::Integer(2));
+        let blob = dump(&hop, b"foo");
+
+        assert_eq!(
+            Err(DispatchError::KeyExists),
+            restore(&hop, b"bar", &blob, None, None)
+        );
+        assert_eq!(
+            Err(DispatchError::KeyExists),
+            restore(&hop, b"bar", &blob, Some(false), None)
+        );
+        assert_eq!(
+            Some(&2),
+            hop.state()
+                .key_ref(b"bar")
+                .unwrap()
+                .value()
+                .as_integer_ref()
+        );
+
+        assert!(restore(&hop, b"bar", &blob, Some(true), None).is_ok());
+        assert_eq!(
+            Some(&1),
+            hop.state()
+                .key_ref(b"bar")
+                .unwrap()
+                .value()
+                .as_integer_ref()
+        );
+    }
+
+    #[test]
+    fn test_malformed() {
+        let hop = hop();
+
+        assert_eq!(
+            Err(DispatchError::ArgumentRetrieval),
+            restore(&hop, b"foo", &[0, 1, 2], None, None)
+        );
+        assert!(!hop.state().contains_key(b"foo"));
+    }
+}
@@ -0,0 +1,182 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request, Response};
+use crate::Hop;
+use alloc::vec::Vec;
+
+/// The number of keys in a page if the request doesn't give a count.
+const DEFAULT_COUNT: usize = 10;
+
+/// Respond with a page of the keyspace's keys and the cursor of the next page.
+///
+/// The first argument is the cursor, which is 0 to start, optionally followed
+/// by a glob-style pattern that keys must match and then the number of keys to
+/// respond with. The response is an array of the next cursor, which is 0 once
+/// every key has been returned, and a list of the page's keys.
+///
+/// Keys are paged in byte order like [`SetScan`], so paging through a keyspace
+/// that isn't modified returns every matching key exactly once.
+///
+/// [`SetScan`]: struct.SetScan.html
+pub struct Scan;
+
+impl Dispatch for Scan {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        let cursor = req
+            .typed_arg::<i64>(0)
+            .filter(|cursor| *cursor >= 0)
+            .ok_or(DispatchError::ArgumentRetrieval)? as usize;
+        let pattern = req.arg(1).unwrap_or(b"*");
+
+        let count = match req.arg(2) {
+            Some(_) => req
+                .typed_arg::<i64>(2)
+                .filter(|count| *count > 0)
+                .ok_or(DispatchError::ArgumentRetrieval)? as usize,
+            None => DEFAULT_COUNT,
+        };
+
+        let mut keys = hop.state().keys_matching(pattern).collect::<Vec<_>>();
+        keys.sort_unstable();
+
+        let start = cursor.min(keys.len());
+        let end = start.saturating_add(count).min(keys.len());
+        let next = if end < keys.len() { end } else { 0 };
+        let page = keys.drain(start..end).collect::<Vec<_>>();
+
+        response::write_array(resp, &[Response::from(next as i64), Response::from(page)]);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Scan;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{KeyType, Value},
+        Hop,
+    };
+    use alloc::{format, vec, vec::Vec};
+
+    fn scan(
+        hop: &Hop,
+        cursor: i64,
+        pattern: Option<&[u8]>,
+        count: Option<i64>,
+    ) -> Result<Response, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::Scan);
+        assert!(builder.value(cursor).is_ok());
+
+        if let Some(pattern) = pattern {
+            assert!(builder.bytes(pattern).is_ok());
+        }
+
+        if let Some(count) = count {
+            assert!(builder.value(count).is_ok());
+        }
+
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        Scan::dispatch(hop, &req, &mut resp).map(|_| Response::parse(&resp).unwrap())
+    }
+
+    fn page(next: i64, keys: &[&[u8]]) -> Response {
+        Response::from(vec![
+            Response::from(next),
+            Response::from(keys.iter().map(|key| key.to_vec()).collect::<Vec<_>>()),
+        ])
+    }
+
+    #[test]
+    fn test_pages() {
+        let hop = Hop::new();
+
+        for key in [b"c", b"a", b"b"].iter() {
+            hop.state().insert(key.to_vec(), Value::Integer(1));
+        }
+
+        assert_eq!(
+            Ok(page(2, &[b"a", b"b"])),
+            scan(&hop, 0, Some(b"*"), Some(2))
+        );
+        assert_eq!(Ok(page(0, &[b"c"])), scan(&hop, 2, Some(b"*"), Some(2)));
+        assert_eq!(Ok(page(0, &[b"a", b"b", b"c"])), scan(&hop, 0, None, None));
+        assert_eq!(Ok(page(0, &[])), scan(&hop, 5, None, None));
+    }
+
+    #[test]
+    fn test_pattern() {
+        let hop = Hop::new();
+        hop.state().insert(b"session:1".to_vec(), Value::Integer(1));
+        hop.state().insert(b"session:2".to_vec(), Value::Integer(2));
+        hop.state().insert(b"user:1".to_vec(), Value::Integer(3));
+
+        assert_eq!(
+            Ok(page(0, &[b"session:1", b"session:2"])),
+            scan(&hop, 0, Some(b"session:*"), None)
+        );
+        assert_eq!(Ok(page(0, &[])), scan(&hop, 0, Some(b"nothing*"), None));
+    }
+
+    #[test]
+    fn test_large_keyspace() {
+        let hop = Hop::new();
+
+        for idx in 0..1000 {
+            hop.state()
+                .insert(format!("key{}", idx).into_bytes(), Value::Integer(idx));
+        }
+
+        let mut seen = Vec::new();
+        let mut cursor = 0;
+
+        loop {
+            let resp = scan(&hop, cursor, Some(b"*"), Some(64)).unwrap();
+
+            match resp.as_array().unwrap() {
+                [Response::Value(Value::Integer(next)), Response::Value(Value::List(keys))] => {
+                    cursor = *next;
+                    seen.extend(keys.iter().cloned());
+                }
+                other => panic!("unexpected page: {:?}", other),
+            }
+
+            if cursor == 0 {
+                break;
+            }
+        }
+
+        let len = seen.len();
+        seen.dedup();
+        assert_eq!(1000, len);
+        assert_eq!(1000, seen.len());
+    }
+
+    #[test]
+    fn test_invalid() {
+        let hop = Hop::new();
+
+        assert_eq!(
+            Err(DispatchError::ArgumentRetrieval),
+            scan(&hop, -1, None, None)
+        );
+        assert_eq!(
+            Err(DispatchError::ArgumentRetrieval),
+            scan(&hop, 0, Some(b"*"), Some(0))
+        );
+
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Scan, KeyType::Bytes);
+        assert!(builder.value(0).is_ok());
+        let req = builder.into_request();
+
+        assert_eq!(
+            Err(DispatchError::KeyTypeUnexpected),
+            Scan::dispatch(&hop, &req, &mut Vec::new())
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::Hop;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// Validate a request to select the database of a connection.
+///
+/// The selected database belongs to the connection rather than the engine, so
+/// the transport switches to it via [`Hop::select`] once the request has been
+/// validated.
+///
+/// [`Hop::select`]: ../../../hop/struct.Hop.html#method.select
+pub struct Select;
+
+impl Select {
+    /// Retrieve the database index of a select request.
+    ///
+    /// Returns `None` if the index is missing or negative.
+    fn database(req: &Request) -> Option<usize> {
+        req.typed_arg::<i64>(0)
+            .and_then(|database| usize::try_from(database).ok())
+    }
+}
+
+impl Dispatch for Select {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        Self::database(req)
+            .and_then(|database| hop.state_of(database))
+            .ok_or(DispatchError::ArgumentRetrieval)?;
+
+        response::write_bool(resp, true);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Select;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    fn request(database: i64) -> RequestBuilder {
+        let mut builder = RequestBuilder::new(CommandId::Select);
+        assert!(builder.bytes(database.to_be_bytes().as_ref()).is_ok());
+
+        builder
+    }
+
+    #[test]
+    fn test_valid() {
+        let hop = Hop::new();
+        let req = request(1).into_request();
+        let mut resp = Vec::new();
+
+        assert_eq!(Some(1), Select::database(&req));
+        assert!(Select::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(resp, Response::from(true).as_bytes());
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        let hop = Hop::new();
+        let mut resp = Vec::new();
+
+        for database in &[-1, hop.config().databases() as i64] {
+            let req = request(*database).into_request();
+
+            assert_eq!(
+                DispatchError::ArgumentRetrieval,
+                Select::dispatch(&hop, &req, &mut resp).unwrap_err()
+            );
+        }
+
+        let req = RequestBuilder::new(CommandId::Select).into_request();
+
+        assert_eq!(
+            DispatchError::ArgumentRetrieval,
+            Select::dispatch(&hop, &req, &mut resp).unwrap_err()
+        );
+    }
+}
@@ -4,6 +4,7 @@ use crate::{
     Hop,
 };
 use alloc::{borrow::ToOwned, vec::Vec};
+use core::{convert::TryInto, str};
 
 pub struct Set;
 
@@ -27,6 +28,7 @@ impl Set {
         let arg = req
             .typed_arg::<&[u8]>(1)
             .ok_or(DispatchError::ArgumentRetrieval)?;
+        hop.config().check_bytes_len(arg.len())?;
         hop.state().remove(key);
         let mut key = hop.state().key_or_insert_with(key, Value::bytes);
         let bytes = key.as_bytes_mut().ok_or(DispatchError::KeyTypeDifferent)?;
@@ -39,7 +41,7 @@ impl Set {
     }
 
     fn float(hop: &Hop, req: &Request, resp: &mut Vec<u8>, key: &[u8]) -> DispatchResult<()> {
-        let arg = req.typed_arg(1).ok_or(DispatchError::ArgumentRetrieval)?;
+        let arg = f64::from_be_bytes(Self::number(req)?);
         hop.state().remove(key);
         let mut key = hop.state().key_or_insert_with(key, Value::float);
         let float = key.as_float_mut().ok_or(DispatchError::KeyTypeDifferent)?;
@@ -52,7 +54,7 @@ impl Set {
     }
 
     fn integer(hop: &Hop, req: &Request, resp: &mut Vec<u8>, key: &[u8]) -> DispatchResult<()> {
-        let arg = req.typed_arg(1).ok_or(DispatchError::ArgumentRetrieval)?;
+        let arg = i64::from_be_bytes(Self::number(req)?);
         hop.state().remove(key);
         let mut key = hop.state().key_or_insert_with(key, Value::integer);
         let int = key
@@ -66,16 +68,24 @@ impl Set {
         Ok(())
     }
 
+    /// Retrieve the big-endian bytes of a float or integer argument, which
+    /// must be exactly 8 bytes long.
+    fn number(req: &Request) -> DispatchResult<[u8; 8]> {
+        let arg = req.arg(1).ok_or(DispatchError::ArgumentRetrieval)?;
+
+        arg.try_into().map_err(|_| DispatchError::MalformedNumber)
+    }
+
     fn list(hop: &Hop, req: &Request, resp: &mut Vec<u8>, key: &[u8]) -> DispatchResult<()> {
         let args = req.args(1..).ok_or(DispatchError::ArgumentRetrieval)?;
+        hop.config().check_list_len(args.len())?;
         hop.state().remove(key);
         let mut key = hop.state().key_or_insert_with(key, Value::list);
         let list = key.as_list_mut().ok_or(DispatchError::KeyTypeDifferent)?;
 
         *list = args.map(ToOwned::to_owned).collect();
-        let args = req.args(1..).ok_or(DispatchError::ArgumentRetrieval)?;
 
-        response::write_list(resp, args);
+        response::write_list(resp, list.iter());
 
         Ok(())
     }
@@ -97,19 +107,38 @@ impl Set {
         let args = req.typed_args().ok_or(DispatchError::ArgumentRetrieval)?;
         hop.state().remove(key);
         let mut key = hop.state().key_or_insert_with(key, Value::set);
-        let set = key.as_set_mut().ok_or(DispatchError::KeyTypeDifferent)?;
+
+        if !key.is_set() {
+            return Err(DispatchError::KeyTypeDifferent);
+        }
 
         response::write_set(resp, &args);
 
+        // Sets of integers are packed.
+        *key = Value::set_from(args);
+
+        Ok(())
+    }
+
+    fn sorted_set(hop: &Hop, req: &Request, resp: &mut Vec<u8>, key: &[u8]) -> DispatchResult<()> {
+        let args = req.typed_args().ok_or(DispatchError::ArgumentRetrieval)?;
+        hop.state().remove(key);
+        let mut key = hop.state().key_or_insert_with(key, Value::sorted_set);
+        let set = key
+            .as_sorted_set_mut()
+            .ok_or(DispatchError::KeyTypeDifferent)?;
+
         *set = args;
 
+        response::write_list(resp, set.iter().map(|(member, _)| member));
+
         Ok(())
     }
 
     fn string(hop: &Hop, req: &Request, resp: &mut Vec<u8>, key: &[u8]) -> DispatchResult<()> {
-        let arg = req
-            .typed_arg::<&str>(1)
-            .ok_or(DispatchError::ArgumentRetrieval)?;
+        let arg = req.arg(1).ok_or(DispatchError::ArgumentRetrieval)?;
+        let arg = str::from_utf8(arg).map_err(|_| DispatchError::InvalidUtf8)?;
+        hop.config().check_bytes_len(arg.len())?;
         hop.state().remove(key);
         let mut key = hop.state().key_or_insert_with(key, Value::string);
         let string = key.as_string_mut().ok_or(DispatchError::KeyTypeDifferent)?;
@@ -144,6 +173,7 @@ impl Dispatch for Set {
             KeyType::List => Self::list(hop, req, resp, key),
             KeyType::Map => Self::map(hop, req, resp, key),
             KeyType::Set => Self::set(hop, req, resp, key),
+            KeyType::SortedSet => Self::sorted_set(hop, req, resp, key),
             KeyType::String => Self::string(hop, req, resp, key),
         }
     }
@@ -158,6 +188,7 @@ mod tests {
         Hop,
     };
     use alloc::vec::Vec;
+    use dashmap::DashSet;
 
     #[test]
     fn test_types_no_arg() {
@@ -172,6 +203,7 @@ mod tests {
             KeyType::List,
             KeyType::Map,
             KeyType::Set,
+            KeyType::SortedSet,
             KeyType::String,
         ];
 
@@ -349,6 +381,68 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_set_integers_packed() {
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::Set);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(b"1".to_vec()).is_ok());
+        assert!(builder.bytes(b"-2".to_vec()).is_ok());
+        let req = builder.into_request();
+
+        let hop = Hop::new();
+        let mut resp = Vec::new();
+        assert!(Set::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(Some("packed"), hop.state().key_encoding(b"foo"));
+
+        // the response is the same as for any other set
+        let set = DashSet::new();
+        set.insert(b"1".to_vec());
+        set.insert(b"-2".to_vec());
+        assert_eq!(Ok(Response::from(set)), Response::parse(&resp));
+
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Get, KeyType::Set);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        let mut resp = Vec::new();
+        assert!(hop.dispatch(&builder.into_request(), &mut resp).is_ok());
+        assert_eq!(
+            Some(2),
+            Response::parse(&resp)
+                .unwrap()
+                .value()
+                .and_then(Value::as_set_ref)
+                .map(|set| set.len())
+        );
+    }
+
+    #[test]
+    fn test_sorted_set_two_entries() {
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::SortedSet);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(2f64.to_be_bytes().to_vec()).is_ok());
+        assert!(builder.bytes(b"value1".to_vec()).is_ok());
+        assert!(builder.bytes(1f64.to_be_bytes().to_vec()).is_ok());
+        assert!(builder.bytes(b"value2".to_vec()).is_ok());
+        let req = builder.into_request();
+
+        let hop = Hop::new();
+
+        let mut resp = Vec::new();
+
+        assert!(Set::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(
+            resp,
+            Response::from([b"value2".to_vec(), b"value1".to_vec()].to_vec()).as_bytes()
+        );
+        assert_eq!(
+            Some(2),
+            hop.state()
+                .key_ref(b"foo")
+                .as_deref()
+                .and_then(Value::as_sorted_set_ref)
+                .map(|set| set.len()),
+        );
+    }
+
     #[test]
     fn test_str() {
         let mut builder = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::String);
@@ -370,4 +464,57 @@ mod tests {
                 .and_then(Value::as_string_ref)
         );
     }
+
+    #[test]
+    fn test_str_invalid_utf8() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::String);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes([0xF0, 0x28, 0x8C, 0x28].as_ref()).is_ok());
+        let req = builder.into_request();
+
+        assert_eq!(
+            DispatchError::InvalidUtf8,
+            Set::dispatch(&hop, &req, &mut Vec::new()).unwrap_err()
+        );
+        // the existing value is left alone
+        assert_eq!(
+            Some(&1),
+            hop.state()
+                .key_ref(b"foo")
+                .as_deref()
+                .and_then(Value::as_integer_ref)
+        );
+
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::String);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        let req = builder.into_request();
+
+        assert_eq!(
+            DispatchError::ArgumentRetrieval,
+            Set::dispatch(&hop, &req, &mut Vec::new()).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_number_wrong_width() {
+        let hop = Hop::new();
+
+        for key_type in &[KeyType::Float, KeyType::Integer] {
+            for arg in &[[0; 4].as_ref(), [0; 9].as_ref()] {
+                let mut builder = RequestBuilder::new_with_key_type(CommandId::Set, *key_type);
+                assert!(builder.bytes(b"foo".as_ref()).is_ok());
+                assert!(builder.bytes(*arg).is_ok());
+                let req = builder.into_request();
+
+                assert_eq!(
+                    DispatchError::MalformedNumber,
+                    Set::dispatch(&hop, &req, &mut Vec::new()).unwrap_err()
+                );
+                assert!(!hop.state().contains_key(b"foo"));
+            }
+        }
+    }
 }
@@ -0,0 +1,100 @@
+use super::{
+    super::{Dispatch, DispatchResult, Request},
+    set_store::{self, Operation},
+};
+use crate::Hop;
+use alloc::vec::Vec;
+
+/// Store the members of the first set that aren't in any of the other sets in
+/// a destination key, responding with the number of members stored.
+///
+/// The first key is the destination and the rest are the sets. The
+/// destination's existing value is replaced whatever its type, and is removed
+/// if the result is empty. Sets that don't exist have no members.
+pub struct SetDifferenceStore;
+
+impl Dispatch for SetDifferenceStore {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        set_store::store(hop, req, resp, Operation::Difference)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SetDifferenceStore;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::Value,
+        Hop,
+    };
+    use alloc::{vec, vec::Vec};
+    use dashmap::DashSet;
+
+    fn insert_set(hop: &Hop, key: &[u8], members: &[&[u8]]) {
+        let set = members
+            .iter()
+            .map(|member| member.to_vec())
+            .collect::<DashSet<_>>();
+
+        hop.state().insert(key.to_vec(), Value::set_from(set));
+    }
+
+    fn store(hop: &Hop, keys: &[&[u8]]) -> Result<Response, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::SetDifferenceStore);
+
+        for key in keys {
+            assert!(builder.bytes(*key).is_ok());
+        }
+
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        SetDifferenceStore::dispatch(hop, &req, &mut resp).map(|_| Response::parse(&resp).unwrap())
+    }
+
+    fn members(hop: &Hop, key: &[u8]) -> Option<Vec<Vec<u8>>> {
+        let value = hop.state().key_ref(key)?;
+        let mut members = match (value.as_int_set_ref(), value.as_set_ref()) {
+            (Some(set), _) => set.members().collect::<Vec<_>>(),
+            (_, Some(set)) => set.iter().map(|member| member.key().clone()).collect(),
+            _ => panic!("not a set"),
+        };
+        members.sort();
+
+        Some(members)
+    }
+
+    #[test]
+    fn test_difference() {
+        let hop = Hop::new();
+        insert_set(&hop, b"a", &[b"1", b"2", b"x", b"y"]);
+        insert_set(&hop, b"b", &[b"2"]);
+        insert_set(&hop, b"c", &[b"y", b"z"]);
+
+        assert_eq!(
+            Ok(Response::from(2)),
+            store(&hop, &[b"dest", b"a", b"b", b"c"])
+        );
+        assert_eq!(
+            Some(vec![b"1".to_vec(), b"x".to_vec()]),
+            members(&hop, b"dest")
+        );
+
+        // only the first set's members can be in the result
+        assert_eq!(Ok(Response::from(1)), store(&hop, &[b"dest", b"c", b"a"]));
+        assert_eq!(Some(vec![b"z".to_vec()]), members(&hop, b"dest"));
+    }
+
+    #[test]
+    fn test_empty_result() {
+        let hop = Hop::new();
+        insert_set(&hop, b"a", &[b"1"]);
+        hop.state().insert(b"dest".to_vec(), Value::Integer(1));
+
+        assert_eq!(
+            Ok(Response::from(0)),
+            store(&hop, &[b"dest", b"missing", b"a"])
+        );
+        assert!(!hop.state().contains_key(b"dest"));
+    }
+}
@@ -0,0 +1,159 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{
+    state::{IntSet, KeyType},
+    Hop,
+};
+use alloc::{string::ToString, vec::Vec};
+
+/// Respond with a list of a set's members in byte order.
+///
+/// The optional argument after the key is a boolean of whether to sort the
+/// members numerically instead. This only applies if every member is an
+/// integer, and otherwise the members are still sorted in byte order. A set
+/// that doesn't exist has no members.
+pub struct SetMembersSorted;
+
+impl Dispatch for SetMembersSorted {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        match req.key_type() {
+            Some(KeyType::Set) | None => {}
+            Some(_) => return Err(DispatchError::KeyTypeInvalid),
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+
+        let numeric = match req.arg(1) {
+            Some(_) => req
+                .typed_arg::<bool>(1)
+                .ok_or(DispatchError::ArgumentRetrieval)?,
+            None => false,
+        };
+
+        let mut members = match hop.state().key_ref(key).as_deref() {
+            Some(value) => match (value.as_int_set_ref(), value.as_set_ref()) {
+                (Some(set), _) => set.members().collect(),
+                (_, Some(set)) => set.iter().map(|member| member.key().clone()).collect(),
+                _ => return Err(DispatchError::KeyTypeDifferent),
+            },
+            None => Vec::new(),
+        };
+
+        let ints = if numeric {
+            members
+                .iter()
+                .map(|member| IntSet::parse(member))
+                .collect::<Option<Vec<_>>>()
+        } else {
+            None
+        };
+
+        match ints {
+            Some(mut ints) => {
+                ints.sort_unstable();
+
+                response::write_list(resp, ints.iter().map(|int| int.to_string()));
+            }
+            None => {
+                members.sort_unstable();
+
+                response::write_list(resp, members);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SetMembersSorted;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::Value,
+        Hop,
+    };
+    use alloc::vec::Vec;
+    use dashmap::DashSet;
+
+    fn sorted(hop: &Hop, numeric: Option<bool>) -> Result<Response, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::SetMembersSorted);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+
+        if let Some(numeric) = numeric {
+            assert!(builder.value(numeric).is_ok());
+        }
+
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        SetMembersSorted::dispatch(hop, &req, &mut resp).map(|_| Response::parse(&resp).unwrap())
+    }
+
+    fn list(members: &[&[u8]]) -> Response {
+        Response::from(
+            members
+                .iter()
+                .map(|member| member.to_vec())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn set(members: &[&[u8]]) -> DashSet<Vec<u8>> {
+        members.iter().map(|member| member.to_vec()).collect()
+    }
+
+    #[test]
+    fn test_lexicographic() {
+        let hop = Hop::new();
+        let members = set(&[b"pear", b"apple", b"fig", b"banana"]);
+        hop.state()
+            .insert(b"foo".to_vec(), Value::set_from(members));
+
+        assert_eq!(
+            Ok(list(&[b"apple", b"banana", b"fig", b"pear"])),
+            sorted(&hop, None)
+        );
+
+        // members that aren't all integers fall back to byte order
+        assert_eq!(
+            Ok(list(&[b"apple", b"banana", b"fig", b"pear"])),
+            sorted(&hop, Some(true))
+        );
+    }
+
+    #[test]
+    fn test_numeric() {
+        let members = set(&[b"10", b"-3", b"2", b"100"]);
+
+        // both packed and unpacked sets of integers
+        for value in [Value::set_from(members.clone()), Value::Set(members)] {
+            let hop = Hop::new();
+            hop.state().insert(b"foo".to_vec(), value);
+
+            assert_eq!(
+                Ok(list(&[b"-3", b"10", b"100", b"2"])),
+                sorted(&hop, Some(false))
+            );
+            assert_eq!(
+                Ok(list(&[b"-3", b"2", b"10", b"100"])),
+                sorted(&hop, Some(true))
+            );
+        }
+    }
+
+    #[test]
+    fn test_nonexistent_set() {
+        let hop = Hop::new();
+
+        assert_eq!(Ok(list(&[])), sorted(&hop, Some(true)));
+        assert!(!hop.state().contains_key(b"foo"));
+    }
+
+    #[test]
+    fn test_wrong_type() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+
+        assert_eq!(Err(DispatchError::KeyTypeDifferent), sorted(&hop, None));
+    }
+}
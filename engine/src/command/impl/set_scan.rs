@@ -0,0 +1,177 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request, Response};
+use crate::{state::KeyType, Hop};
+use alloc::vec::Vec;
+
+/// The number of members in a page if the request doesn't give a count.
+const DEFAULT_COUNT: usize = 10;
+
+/// Respond with a page of a set's members and the cursor of the next page.
+///
+/// The argument after the key is the cursor, which is 0 to start, optionally
+/// followed by the number of members to respond with. The response is an
+/// array of the next cursor, which is 0 once every member has been returned,
+/// and a list of the page's members.
+///
+/// Members are paged in byte order, so paging through a set that isn't
+/// modified returns every member exactly once. A set that doesn't exist has no
+/// members.
+pub struct SetScan;
+
+impl Dispatch for SetScan {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        match req.key_type() {
+            Some(KeyType::Set) | None => {}
+            Some(_) => return Err(DispatchError::KeyTypeInvalid),
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+        let cursor = req
+            .typed_arg::<i64>(1)
+            .filter(|cursor| *cursor >= 0)
+            .ok_or(DispatchError::ArgumentRetrieval)? as usize;
+
+        let count = match req.arg(2) {
+            Some(_) => req
+                .typed_arg::<i64>(2)
+                .filter(|count| *count > 0)
+                .ok_or(DispatchError::ArgumentRetrieval)? as usize,
+            None => DEFAULT_COUNT,
+        };
+
+        let mut members = match hop.state().key_ref(key).as_deref() {
+            Some(value) => match (value.as_int_set_ref(), value.as_set_ref()) {
+                (Some(set), _) => set.members().collect(),
+                (_, Some(set)) => set.iter().map(|member| member.key().clone()).collect(),
+                _ => return Err(DispatchError::KeyTypeDifferent),
+            },
+            None => Vec::new(),
+        };
+
+        members.sort_unstable();
+
+        let start = cursor.min(members.len());
+        let end = start.saturating_add(count).min(members.len());
+        let next = if end < members.len() { end } else { 0 };
+        let page = members.drain(start..end).collect::<Vec<_>>();
+
+        response::write_array(resp, &[Response::from(next as i64), Response::from(page)]);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SetScan;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::Value,
+        Hop,
+    };
+    use alloc::{format, vec, vec::Vec};
+    use dashmap::DashSet;
+
+    fn scan(hop: &Hop, cursor: i64, count: Option<i64>) -> Result<Response, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::SetScan);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.value(cursor).is_ok());
+
+        if let Some(count) = count {
+            assert!(builder.value(count).is_ok());
+        }
+
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        SetScan::dispatch(hop, &req, &mut resp).map(|_| Response::parse(&resp).unwrap())
+    }
+
+    fn page(next: i64, members: &[&[u8]]) -> Response {
+        Response::from(vec![
+            Response::from(next),
+            Response::from(
+                members
+                    .iter()
+                    .map(|member| member.to_vec())
+                    .collect::<Vec<_>>(),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_pages() {
+        let hop = Hop::new();
+        let set = DashSet::new();
+        set.insert(b"c".to_vec());
+        set.insert(b"a".to_vec());
+        set.insert(b"b".to_vec());
+        hop.state().insert(b"foo".to_vec(), Value::Set(set));
+
+        assert_eq!(Ok(page(2, &[b"a", b"b"])), scan(&hop, 0, Some(2)));
+        assert_eq!(Ok(page(0, &[b"c"])), scan(&hop, 2, Some(2)));
+        assert_eq!(Ok(page(0, &[b"a", b"b", b"c"])), scan(&hop, 0, None));
+        assert_eq!(Ok(page(0, &[])), scan(&hop, 5, None));
+    }
+
+    #[test]
+    fn test_large_set() {
+        let hop = Hop::new();
+        let set = DashSet::new();
+
+        for idx in 0..1000 {
+            set.insert(format!("member{}", idx).into_bytes());
+        }
+
+        hop.state().insert(b"foo".to_vec(), Value::Set(set));
+
+        let mut seen = Vec::new();
+        let mut cursor = 0;
+
+        loop {
+            let resp = scan(&hop, cursor, Some(64)).unwrap();
+
+            match resp.as_array().unwrap() {
+                [Response::Value(Value::Integer(next)), Response::Value(Value::List(members))] => {
+                    cursor = *next;
+                    seen.extend(members.iter().cloned());
+                }
+                other => panic!("unexpected page: {:?}", other),
+            }
+
+            if cursor == 0 {
+                break;
+            }
+        }
+
+        let len = seen.len();
+        seen.dedup();
+        assert_eq!(1000, len);
+        assert_eq!(1000, seen.len());
+    }
+
+    #[test]
+    fn test_int_set() {
+        let hop = Hop::new();
+        let set = DashSet::new();
+        set.insert(b"2".to_vec());
+        set.insert(b"1".to_vec());
+        hop.state().insert(b"foo".to_vec(), Value::set_from(set));
+
+        assert_eq!(Ok(page(0, &[b"1", b"2"])), scan(&hop, 0, None));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let hop = Hop::new();
+
+        assert_eq!(Ok(page(0, &[])), scan(&hop, 0, None));
+        assert_eq!(Err(DispatchError::ArgumentRetrieval), scan(&hop, -1, None));
+        assert_eq!(
+            Err(DispatchError::ArgumentRetrieval),
+            scan(&hop, 0, Some(0))
+        );
+
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        assert_eq!(Err(DispatchError::KeyTypeDifferent), scan(&hop, 0, None));
+    }
+}
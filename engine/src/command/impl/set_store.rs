@@ -0,0 +1,89 @@
+//! Shared implementation of the commands storing the result of a set
+//! operation.
+
+use super::super::{response, DispatchError, DispatchResult, Request};
+use crate::{
+    state::{KeyType, Value},
+    Hop,
+};
+use alloc::{collections::BTreeSet, vec::Vec};
+use dashmap::DashSet;
+
+/// An operation combining the members of one or more sets.
+#[derive(Clone, Copy)]
+pub(super) enum Operation {
+    /// Members in any of the sets.
+    Union,
+    /// Members in all of the sets.
+    Intersect,
+    /// Members in the first set but none of the others.
+    Difference,
+}
+
+/// Combine the sets named after the destination key and store the result in
+/// the destination, responding with the number of members stored.
+///
+/// The destination's existing value is replaced whatever its type, and is
+/// removed if the result is empty. Sets that don't exist have no members.
+pub(super) fn store(
+    hop: &Hop,
+    req: &Request,
+    resp: &mut Vec<u8>,
+    operation: Operation,
+) -> DispatchResult<()> {
+    match req.key_type() {
+        Some(KeyType::Set) | None => {}
+        Some(_) => return Err(DispatchError::KeyTypeInvalid),
+    }
+
+    let destination = req.key().ok_or(DispatchError::KeyUnspecified)?;
+    let mut sources = req
+        .args(1..)
+        .filter(|sources| sources.len() > 0)
+        .ok_or(DispatchError::ArgumentRetrieval)?;
+
+    // Every source is read before the destination is touched, since the
+    // destination may also be a source.
+    let first = sources.next().ok_or(DispatchError::ArgumentRetrieval)?;
+    let mut result = members(hop, first)?;
+
+    for source in sources {
+        let other = members(hop, source)?;
+
+        match operation {
+            Operation::Union => result.extend(other),
+            Operation::Intersect => result = result.intersection(&other).cloned().collect(),
+            Operation::Difference => result = result.difference(&other).cloned().collect(),
+        }
+    }
+
+    let len = result.len();
+
+    if result.is_empty() {
+        hop.state().remove(destination);
+    } else {
+        let set = result.into_iter().collect::<DashSet<_>>();
+
+        hop.state()
+            .insert(destination.to_vec(), Value::set_from(set));
+    }
+
+    response::write_int(resp, len as i64);
+
+    Ok(())
+}
+
+/// Retrieve the members of the set at a key, which has none if it doesn't
+/// exist.
+fn members(hop: &Hop, key: &[u8]) -> DispatchResult<BTreeSet<Vec<u8>>> {
+    let value = match hop.state().key_ref(key) {
+        Some(value) => value,
+        None => return Ok(BTreeSet::new()),
+    };
+
+    match (value.as_int_set_ref(), value.as_set_ref()) {
+        (Some(set), _) => Ok(set.members().collect()),
+        (_, Some(set)) => Ok(set.iter().map(|member| member.key().clone()).collect()),
+        _ => Err(DispatchError::KeyTypeDifferent),
+    }
+}
@@ -0,0 +1,124 @@
+use super::{
+    super::{Dispatch, DispatchResult, Request},
+    set_store::{self, Operation},
+};
+use crate::Hop;
+use alloc::vec::Vec;
+
+/// Store the union of sets in a destination key, responding with the number
+/// of members stored.
+///
+/// The first key is the destination and the rest are the sets. The
+/// destination's existing value is replaced whatever its type, and is removed
+/// if the result is empty. Sets that don't exist have no members.
+pub struct SetUnionStore;
+
+impl Dispatch for SetUnionStore {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        set_store::store(hop, req, resp, Operation::Union)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SetUnionStore;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::Value,
+        Hop,
+    };
+    use alloc::{vec, vec::Vec};
+    use dashmap::DashSet;
+
+    fn insert_set(hop: &Hop, key: &[u8], members: &[&[u8]]) {
+        let set = members
+            .iter()
+            .map(|member| member.to_vec())
+            .collect::<DashSet<_>>();
+
+        hop.state().insert(key.to_vec(), Value::set_from(set));
+    }
+
+    fn store(hop: &Hop, keys: &[&[u8]]) -> Result<Response, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::SetUnionStore);
+
+        for key in keys {
+            assert!(builder.bytes(*key).is_ok());
+        }
+
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        SetUnionStore::dispatch(hop, &req, &mut resp).map(|_| Response::parse(&resp).unwrap())
+    }
+
+    fn members(hop: &Hop, key: &[u8]) -> Option<Vec<Vec<u8>>> {
+        let value = hop.state().key_ref(key)?;
+        let mut members = match (value.as_int_set_ref(), value.as_set_ref()) {
+            (Some(set), _) => set.members().collect::<Vec<_>>(),
+            (_, Some(set)) => set.iter().map(|member| member.key().clone()).collect(),
+            _ => panic!("not a set"),
+        };
+        members.sort();
+
+        Some(members)
+    }
+
+    #[test]
+    fn test_union() {
+        let hop = Hop::new();
+        insert_set(&hop, b"a", &[b"1", b"2"]);
+        insert_set(&hop, b"b", &[b"2", b"x"]);
+
+        assert_eq!(
+            Ok(Response::from(3)),
+            store(&hop, &[b"dest", b"a", b"b", b"missing"])
+        );
+        assert_eq!(
+            Some(vec![b"1".to_vec(), b"2".to_vec(), b"x".to_vec()]),
+            members(&hop, b"dest")
+        );
+        // the sources are untouched
+        assert_eq!(
+            Some(vec![b"1".to_vec(), b"2".to_vec()]),
+            members(&hop, b"a")
+        );
+    }
+
+    #[test]
+    fn test_overwrites_destination() {
+        let hop = Hop::new();
+        insert_set(&hop, b"a", &[b"1"]);
+        hop.state().insert(b"dest".to_vec(), Value::Integer(1));
+
+        assert_eq!(Ok(Response::from(1)), store(&hop, &[b"dest", b"a"]));
+        assert_eq!(Some(vec![b"1".to_vec()]), members(&hop, b"dest"));
+
+        // the destination may also be a source
+        insert_set(&hop, b"b", &[b"2"]);
+        assert_eq!(
+            Ok(Response::from(2)),
+            store(&hop, &[b"dest", b"dest", b"b"])
+        );
+        assert_eq!(
+            Some(vec![b"1".to_vec(), b"2".to_vec()]),
+            members(&hop, b"dest")
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let hop = Hop::new();
+        hop.state().insert(b"a".to_vec(), Value::Integer(1));
+
+        assert_eq!(
+            Err(DispatchError::KeyTypeDifferent),
+            store(&hop, &[b"dest", b"a"])
+        );
+        assert_eq!(
+            Err(DispatchError::ArgumentRetrieval),
+            store(&hop, &[b"dest"])
+        );
+        assert!(!hop.state().contains_key(b"dest"));
+    }
+}
@@ -0,0 +1,122 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::Hop;
+use alloc::vec::Vec;
+
+pub struct SlowLog;
+
+impl Dispatch for SlowLog {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        // Each entry, newest first, is the command ID followed by the 8-byte
+        // Unix timestamp in milliseconds and the 8-byte duration in
+        // microseconds.
+        let entries = hop.slowlog().entries().into_iter().map(|entry| {
+            let mut item = Vec::with_capacity(17);
+            item.push(entry.command_id() as u8);
+            item.extend_from_slice(&entry.timestamp().to_be_bytes());
+            item.extend_from_slice(&entry.duration().to_be_bytes());
+
+            item
+        });
+
+        response::write_list(resp, entries);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SlowLog;
+    use crate::{
+        command::{
+            request::RequestBuilder,
+            response::{Context, Instruction},
+            CommandId, Dispatch, DispatchError, Response,
+        },
+        state::{KeyType, Value},
+        Hop,
+    };
+    use alloc::{format, vec::Vec};
+    use core::convert::TryInto;
+
+    fn entries(hop: &Hop) -> Vec<Vec<u8>> {
+        let req = RequestBuilder::new(CommandId::SlowLog).into_request();
+        let mut resp = Vec::new();
+        assert!(SlowLog::dispatch(hop, &req, &mut resp).is_ok());
+
+        if resp == Response::from(Vec::<Vec<u8>>::new()).as_bytes() {
+            return Vec::new();
+        }
+
+        match Context::new().feed(&resp) {
+            Ok(Instruction::Concluded(Response::Value(Value::List(list)))) => {
+                list.into_iter().collect()
+            }
+            other => panic!("unexpected response: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_slow_command_recorded() {
+        let mut builder = Hop::builder();
+        builder.clock(|| 1000).slowlog_threshold(1000);
+        let hop = builder.build();
+
+        let mut resp = Vec::new();
+
+        // an echo is quick, but removing a couple hundred thousand keys isn't
+        let mut echo = RequestBuilder::new(CommandId::Echo);
+        assert!(echo.bytes(b"foo".as_ref()).is_ok());
+        assert!(hop.dispatch(&echo.into_request(), &mut resp).is_ok());
+        assert!(entries(&hop).is_empty());
+
+        for idx in 0..200_000 {
+            hop.state()
+                .insert(format!("foo:{}", idx).into_bytes(), Value::bytes());
+        }
+
+        let mut delete = RequestBuilder::new(CommandId::DeleteMatching);
+        assert!(delete.bytes(b"foo:*".as_ref()).is_ok());
+        assert!(hop.dispatch(&delete.into_request(), &mut resp).is_ok());
+
+        let entries = entries(&hop);
+        assert_eq!(1, entries.len());
+
+        let entry = &entries[0];
+        assert_eq!(17, entry.len());
+        assert_eq!(CommandId::DeleteMatching as u8, entry[0]);
+        assert_eq!(1000, u64::from_be_bytes(entry[1..9].try_into().unwrap()));
+        assert!(u64::from_be_bytes(entry[9..].try_into().unwrap()) >= 1000);
+    }
+
+    #[test]
+    fn test_disabled() {
+        let mut builder = Hop::builder();
+        builder.slowlog_threshold(None);
+        let hop = builder.build();
+
+        let mut echo = RequestBuilder::new(CommandId::Echo);
+        assert!(echo.bytes(b"foo".as_ref()).is_ok());
+        assert!(hop.dispatch(&echo.into_request(), &mut Vec::new()).is_ok());
+
+        assert!(entries(&hop).is_empty());
+    }
+
+    #[test]
+    fn test_key_type_specified() {
+        let req =
+            RequestBuilder::new_with_key_type(CommandId::SlowLog, KeyType::Bytes).into_request();
+
+        let hop = Hop::new();
+        let mut resp = Vec::new();
+
+        assert_eq!(
+            DispatchError::KeyTypeUnexpected,
+            SlowLog::dispatch(&hop, &req, &mut resp).unwrap_err()
+        );
+    }
+}
@@ -0,0 +1,49 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::Hop;
+use alloc::vec::Vec;
+
+pub struct SlowLogReset;
+
+impl Dispatch for SlowLogReset {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        let removed = hop.slowlog().reset();
+
+        response::write_int(resp, removed as i64);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SlowLogReset;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, Response},
+        slowlog::SlowLogEntry,
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_reset() {
+        let hop = Hop::new();
+        hop.slowlog()
+            .record(SlowLogEntry::new(CommandId::Get, 1000, 20_000));
+
+        let req = RequestBuilder::new(CommandId::SlowLogReset).into_request();
+        let mut resp = Vec::new();
+
+        assert!(SlowLogReset::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(resp, Response::from(1).as_bytes());
+        assert!(hop.slowlog().entries().is_empty());
+
+        resp.clear();
+
+        assert!(SlowLogReset::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(resp, Response::from(0).as_bytes());
+    }
+}
@@ -1,6 +1,6 @@
 use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
 use crate::metrics::Metric;
-use crate::Hop;
+use crate::{state::Value, Hop};
 use alloc::vec::Vec;
 use core::convert::TryFrom;
 use dashmap::DashMap;
@@ -9,8 +9,12 @@ pub struct Stats;
 
 impl Stats {
     const COUNTERS: &'static [Metric] = &[
+        Metric::BytesRead,
+        Metric::BytesWritten,
         Metric::CommandsErrored,
         Metric::CommandsSuccessful,
+        Metric::ConnectionsOpened,
+        Metric::KeysEvicted,
         Metric::SessionsStarted,
     ];
 }
@@ -21,7 +25,7 @@ impl Dispatch for Stats {
             return Err(DispatchError::KeyTypeUnexpected);
         }
 
-        let map = DashMap::with_capacity(4);
+        let map = DashMap::with_capacity(Self::COUNTERS.len() + 1);
         let metrics = hop.metrics();
 
         for counter in Self::COUNTERS {
@@ -30,11 +34,22 @@ impl Dispatch for Stats {
                 None => continue,
             };
             let key = counter.name().as_bytes().to_vec();
-            let value = count.to_be_bytes().to_vec();
+            let value = Value::Bytes(count.to_be_bytes().to_vec());
 
             map.insert(key, value);
         }
 
+        // Like counters that were never incremented, the gauge is omitted
+        // while it's 0.
+        match i64::try_from(hop.connections_active()) {
+            Ok(0) | Err(_) => {}
+            Ok(active) => {
+                let key = Metric::ConnectionsActive.name().as_bytes().to_vec();
+
+                map.insert(key, Value::Bytes(active.to_be_bytes().to_vec()));
+            }
+        }
+
         response::write_map(res, &map);
 
         Ok(())
@@ -45,9 +60,13 @@ impl Dispatch for Stats {
 mod tests {
     use super::Stats;
     use crate::{
-        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        command::{
+            request::RequestBuilder,
+            response::{Context, Instruction},
+            CommandId, Dispatch, DispatchError, Response,
+        },
         metrics::Metric,
-        state::KeyType,
+        state::{KeyType, Value},
         Hop,
     };
     use dashmap::DashMap;
@@ -60,7 +79,10 @@ mod tests {
         let mut resp = Vec::new();
 
         assert!(Stats::dispatch(&hop, &req, &mut resp).is_ok());
-        assert_eq!(resp, Response::from(DashMap::new()).as_bytes());
+        assert_eq!(
+            resp,
+            Response::from(DashMap::<Vec<u8>, Value>::new()).as_bytes()
+        );
     }
 
     #[test]
@@ -78,6 +100,38 @@ mod tests {
         assert_eq!(resp, Response::from(expected).as_bytes());
     }
 
+    #[test]
+    fn test_stats_connections() {
+        let req = RequestBuilder::new(CommandId::Stats).into_request();
+
+        let hop = Hop::new();
+        let mut conn = hop.connect();
+        conn.record_read(3);
+
+        let mut resp = Vec::new();
+        assert!(Stats::dispatch(&hop, &req, &mut resp).is_ok());
+
+        let map = match Context::new().feed(&resp) {
+            Ok(Instruction::Concluded(Response::Value(Value::Map(map)))) => map,
+            other => panic!("unexpected response: {:?}", other),
+        };
+        let get = |name: &[u8]| map.get(name).map(|value| value.clone());
+
+        assert_eq!(3, map.len());
+        assert_eq!(
+            Some(Value::Bytes(3i64.to_be_bytes().to_vec())),
+            get(b"bytes_read")
+        );
+        assert_eq!(
+            Some(Value::Bytes(1i64.to_be_bytes().to_vec())),
+            get(b"connections_active")
+        );
+        assert_eq!(
+            Some(Value::Bytes(1i64.to_be_bytes().to_vec())),
+            get(b"connections_opened")
+        );
+    }
+
     #[test]
     fn test_stats_errors_with_key_type() {
         let builder = RequestBuilder::new_with_key_type(CommandId::Stats, KeyType::Map);
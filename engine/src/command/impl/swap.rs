@@ -0,0 +1,129 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::Hop;
+use alloc::vec::Vec;
+
+/// Exchange the values and expiries of two keys, responding with `true`.
+///
+/// Both keys must exist, since a one-sided swap would just be a rename. If
+/// either doesn't then neither key is changed.
+pub struct Swap;
+
+impl Dispatch for Swap {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        let a = req.arg(0).ok_or(DispatchError::KeyUnspecified)?;
+        let b = req.arg(1).ok_or(DispatchError::ArgumentRetrieval)?;
+
+        if !hop.state().swap(a, b) {
+            return Err(DispatchError::KeyNonexistent);
+        }
+
+        response::write_bool(resp, true);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Swap;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{ExpireFlags, Value},
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    fn swap(hop: &Hop, a: &[u8], b: &[u8]) -> Result<Vec<u8>, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::Swap);
+        assert!(builder.bytes(a).is_ok());
+        assert!(builder.bytes(b).is_ok());
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        Swap::dispatch(hop, &req, &mut resp).map(|_| resp)
+    }
+
+    fn hop() -> Hop {
+        let mut builder = Hop::builder();
+        builder.clock(|| 1_000_000);
+
+        builder.build()
+    }
+
+    #[test]
+    fn test_same_type() {
+        let hop = hop();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        hop.state().insert(b"bar".to_vec(), Value::Integer(2));
+
+        assert_eq!(
+            Ok(Response::from(true).as_bytes()),
+            swap(&hop, b"foo", b"bar")
+        );
+        assert_eq!(
+            Some(Value::Integer(2)),
+            hop.state().key_ref(b"foo").map(|v| v.clone())
+        );
+        assert_eq!(
+            Some(Value::Integer(1)),
+            hop.state().key_ref(b"bar").map(|v| v.clone())
+        );
+    }
+
+    #[test]
+    fn test_different_types_with_expiries() {
+        let hop = hop();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        hop.state()
+            .insert(b"bar".to_vec(), Value::String("baz".into()));
+        hop.state().expire_in(b"foo", 10_000, ExpireFlags::NONE);
+
+        assert!(swap(&hop, b"foo", b"bar").is_ok());
+        assert_eq!(
+            Some(Value::String("baz".into())),
+            hop.state().key_ref(b"foo").map(|v| v.clone())
+        );
+        assert_eq!(
+            Some(Value::Integer(1)),
+            hop.state().key_ref(b"bar").map(|v| v.clone())
+        );
+        assert_eq!(None, hop.state().expires_at(b"foo"));
+        assert_eq!(Some(1_010_000), hop.state().expires_at(b"bar"));
+    }
+
+    #[test]
+    fn test_nonexistent() {
+        let hop = hop();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+
+        assert_eq!(
+            Err(DispatchError::KeyNonexistent),
+            swap(&hop, b"foo", b"bar")
+        );
+        assert_eq!(
+            Err(DispatchError::KeyNonexistent),
+            swap(&hop, b"bar", b"foo")
+        );
+        assert_eq!(
+            Some(Value::Integer(1)),
+            hop.state().key_ref(b"foo").map(|v| v.clone())
+        );
+        assert!(!hop.state().contains_key(b"bar"));
+    }
+
+    #[test]
+    fn test_same_key() {
+        let hop = hop();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+
+        assert!(swap(&hop, b"foo", b"foo").is_ok());
+        assert_eq!(
+            Some(Value::Integer(1)),
+            hop.state().key_ref(b"foo").map(|v| v.clone())
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::Hop;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+pub struct SwapDb;
+
+impl SwapDb {
+    fn database(req: &Request, idx: usize) -> DispatchResult<usize> {
+        req.typed_arg::<i64>(idx)
+            .and_then(|database| usize::try_from(database).ok())
+            .ok_or(DispatchError::ArgumentRetrieval)
+    }
+}
+
+impl Dispatch for SwapDb {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        let a = Self::database(req, 0)?;
+        let b = Self::database(req, 1)?;
+
+        if !hop.swap_databases(a, b) {
+            return Err(DispatchError::ArgumentRetrieval);
+        }
+
+        response::write_bool(resp, true);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SwapDb;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::Value,
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    fn request(a: i64, b: i64) -> RequestBuilder {
+        let mut builder = RequestBuilder::new(CommandId::SwapDb);
+        assert!(builder.bytes(a.to_be_bytes().as_ref()).is_ok());
+        assert!(builder.bytes(b.to_be_bytes().as_ref()).is_ok());
+
+        builder
+    }
+
+    #[test]
+    fn test_swap() {
+        let hop = Hop::new();
+        let spare = hop.select(3).unwrap();
+        hop.state().insert(b"old".to_vec(), Value::Integer(1));
+        spare.state().insert(b"new".to_vec(), Value::Integer(2));
+
+        let req = request(0, 3).into_request();
+        let mut resp = Vec::new();
+
+        assert!(SwapDb::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(resp, Response::from(true).as_bytes());
+
+        // handles to either database see the other's keys
+        assert!(hop.state().contains_key(b"new"));
+        assert!(!hop.state().contains_key(b"old"));
+        assert!(spare.state().contains_key(b"old"));
+        assert!(!spare.state().contains_key(b"new"));
+
+        // and swapping back restores them
+        resp.clear();
+        assert!(SwapDb::dispatch(&hop, &req, &mut resp).is_ok());
+        assert!(hop.state().contains_key(b"old"));
+        assert!(spare.state().contains_key(b"new"));
+    }
+
+    #[test]
+    fn test_database_invalid() {
+        let hop = Hop::new();
+        let databases = hop.config().databases() as i64;
+        let mut resp = Vec::new();
+
+        for (a, b) in &[(0, databases), (-1, 0)] {
+            let req = request(*a, *b).into_request();
+
+            assert_eq!(
+                DispatchError::ArgumentRetrieval,
+                SwapDb::dispatch(&hop, &req, &mut resp).unwrap_err()
+            );
+        }
+
+        let mut builder = RequestBuilder::new(CommandId::SwapDb);
+        assert!(builder.bytes(0i64.to_be_bytes().as_ref()).is_ok());
+        let req = builder.into_request();
+
+        assert_eq!(
+            DispatchError::ArgumentRetrieval,
+            SwapDb::dispatch(&hop, &req, &mut resp).unwrap_err()
+        );
+    }
+}
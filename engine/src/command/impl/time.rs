@@ -0,0 +1,68 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::Hop;
+use alloc::vec::Vec;
+
+pub struct Time;
+
+impl Dispatch for Time {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        // Use the same clock as expiries so that clients can coordinate TTLs
+        // with it. The clock has millisecond precision.
+        let now = hop.state().now();
+        let secs = now / 1000;
+        let micros = (now % 1000) * 1000;
+
+        response::write_list(resp, [secs.to_be_bytes(), micros.to_be_bytes()]);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Time;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::KeyType,
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_time() {
+        let mut builder = Hop::builder();
+        builder.clock(|| 1_234_567);
+        let hop = builder.build();
+
+        let req = RequestBuilder::new(CommandId::Time).into_request();
+        let mut resp = Vec::new();
+
+        assert!(Time::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(
+            Response::from(
+                [
+                    1234u64.to_be_bytes().to_vec(),
+                    567_000u64.to_be_bytes().to_vec()
+                ]
+                .to_vec()
+            )
+            .as_bytes(),
+            resp
+        );
+    }
+
+    #[test]
+    fn test_key_type_unexpected() {
+        let req =
+            RequestBuilder::new_with_key_type(CommandId::Time, KeyType::Integer).into_request();
+
+        assert_eq!(
+            DispatchError::KeyTypeUnexpected,
+            Time::dispatch(&Hop::new(), &req, &mut Vec::new()).unwrap_err()
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::Hop;
+use alloc::vec::Vec;
+
+pub struct Touch;
+
+impl Dispatch for Touch {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        let args = req.args(..).ok_or(DispatchError::ArgumentRetrieval)?;
+
+        let touched = args.filter(|key| hop.state().touch(key)).count();
+
+        response::write_int(resp, touched as i64);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Touch;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{EvictionPolicy, KeyType, Value},
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_touch() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        hop.state().insert(b"bar".to_vec(), Value::Integer(2));
+        let frequency = hop.state().frequency(b"foo").unwrap();
+
+        let mut builder = RequestBuilder::new(CommandId::Touch);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(b"baz".as_ref()).is_ok());
+        let req = builder.into_request();
+
+        let mut resp = Vec::new();
+        assert!(Touch::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(resp, Response::from(1).as_bytes());
+
+        // "foo" was touched after "bar" was inserted, so it's now the most
+        // recently used key
+        assert!(hop.state().frequency(b"foo").unwrap() > frequency);
+        assert_eq!(
+            Some(b"bar".to_vec()),
+            hop.state().evict(EvictionPolicy::LeastRecentlyUsed, &[])
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let hop = Hop::new();
+        let mut resp = Vec::new();
+
+        let req = RequestBuilder::new(CommandId::Touch).into_request();
+        assert_eq!(
+            DispatchError::ArgumentRetrieval,
+            Touch::dispatch(&hop, &req, &mut resp).unwrap_err()
+        );
+
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Touch, KeyType::Integer);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        let req = builder.into_request();
+        assert_eq!(
+            DispatchError::KeyTypeUnexpected,
+            Touch::dispatch(&hop, &req, &mut resp).unwrap_err()
+        );
+    }
+}
@@ -17,7 +17,7 @@ impl Dispatch for Type {
         let key_type = hop
             .state()
             .key_type(key)
-            .ok_or_else(|| DispatchError::KeyNonexistent)?;
+            .ok_or(DispatchError::KeyNonexistent)?;
 
         response::write_int(resp, key_type as i64);
 
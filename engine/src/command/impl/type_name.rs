@@ -0,0 +1,92 @@
+use crate::{
+    command::{response, Dispatch, DispatchError, DispatchResult, Request},
+    Hop,
+};
+use alloc::{string::ToString, vec::Vec};
+
+pub struct TypeName;
+
+impl Dispatch for TypeName {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        let key_type = hop
+            .state()
+            .key_type(key)
+            .ok_or(DispatchError::KeyNonexistent)?;
+
+        response::write_str(resp, &key_type.to_string());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TypeName;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{KeyType, Value},
+        Hop,
+    };
+    use alloc::{borrow::ToOwned, vec::Vec};
+
+    #[test]
+    fn test_types() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Boolean(true));
+        hop.state()
+            .insert(b"bar".to_vec(), Value::from(vec![b"baz".to_vec()]));
+        hop.state()
+            .insert(b"baz".to_vec(), Value::String("qux".to_owned()));
+
+        for (key, name) in &[
+            (b"foo".as_ref(), "boolean"),
+            (b"bar".as_ref(), "list"),
+            (b"baz".as_ref(), "string"),
+        ] {
+            let mut builder = RequestBuilder::new(CommandId::TypeName);
+            assert!(builder.bytes(*key).is_ok());
+            let req = builder.into_request();
+
+            let mut resp = Vec::new();
+
+            assert!(TypeName::dispatch(&hop, &req, &mut resp).is_ok());
+            assert_eq!(resp, Response::from((*name).to_owned()).as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_key_nonexistent() {
+        let mut builder = RequestBuilder::new(CommandId::TypeName);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        let req = builder.into_request();
+
+        let hop = Hop::new();
+        let mut resp = Vec::new();
+
+        assert_eq!(
+            DispatchError::KeyNonexistent,
+            TypeName::dispatch(&hop, &req, &mut resp).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_key_type_specified() {
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::TypeName, KeyType::List);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        let req = builder.into_request();
+
+        let hop = Hop::new();
+        let mut resp = Vec::new();
+
+        assert_eq!(
+            DispatchError::KeyTypeUnexpected,
+            TypeName::dispatch(&hop, &req, &mut resp).unwrap_err()
+        );
+    }
+}
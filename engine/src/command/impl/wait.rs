@@ -0,0 +1,67 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::Hop;
+use alloc::vec::Vec;
+
+/// Wait until writes have been acknowledged by a number of replicas or a
+/// timeout in milliseconds passes, responding with the number of replicas
+/// that acknowledged them.
+///
+/// The arguments are the number of replicas and the timeout, each as an
+/// integer that isn't negative. There's no replication, so there are never
+/// any replicas to wait for and this responds with 0 immediately.
+pub struct Wait;
+
+impl Dispatch for Wait {
+    fn dispatch(_: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        for idx in 0..2 {
+            match req.typed_arg::<i64>(idx) {
+                Some(arg) if arg >= 0 => {}
+                _ => return Err(DispatchError::ArgumentRetrieval),
+            }
+        }
+
+        response::write_int(resp, 0);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Wait;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    fn wait(args: &[i64]) -> Result<Vec<u8>, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::Wait);
+
+        for arg in args {
+            assert!(builder.bytes(arg.to_be_bytes().as_ref()).is_ok());
+        }
+
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        Wait::dispatch(&Hop::new(), &req, &mut resp).map(|_| resp)
+    }
+
+    #[test]
+    fn test_standalone() {
+        assert_eq!(Ok(Response::from(0).as_bytes()), wait(&[1, 100]));
+        assert_eq!(Ok(Response::from(0).as_bytes()), wait(&[0, 0]));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(Err(DispatchError::ArgumentRetrieval), wait(&[1]));
+        assert_eq!(Err(DispatchError::ArgumentRetrieval), wait(&[-1, 100]));
+        assert_eq!(Err(DispatchError::ArgumentRetrieval), wait(&[1, -1]));
+    }
+}
@@ -0,0 +1,70 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::Hop;
+use alloc::vec::Vec;
+
+/// Validate a request to watch the keyspace.
+///
+/// The engine can't push events over a single response, so the watch itself
+/// is started by the transport via [`PubSubManager::watch_keyspace`] once the
+/// request has been validated.
+///
+/// [`PubSubManager::watch_keyspace`]: ../../../pubsub/struct.PubSubManager.html#method.watch_keyspace
+pub struct Watch;
+
+impl Dispatch for Watch {
+    fn dispatch(_: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if req.key_type().is_some() {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        req.key().ok_or(DispatchError::KeyUnspecified)?;
+
+        response::write_bool(resp, true);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Watch;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::KeyType,
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_pattern() {
+        let mut builder = RequestBuilder::new(CommandId::Watch);
+        assert!(builder.bytes(b"foo*".as_ref()).is_ok());
+        let req = builder.into_request();
+
+        let hop = Hop::new();
+        let mut resp = Vec::new();
+
+        assert!(Watch::dispatch(&hop, &req, &mut resp).is_ok());
+        assert_eq!(resp, Response::from(true).as_bytes());
+    }
+
+    #[test]
+    fn test_errors() {
+        let hop = Hop::new();
+        let mut resp = Vec::new();
+
+        let req = RequestBuilder::new(CommandId::Watch).into_request();
+        assert_eq!(
+            DispatchError::KeyUnspecified,
+            Watch::dispatch(&hop, &req, &mut resp).unwrap_err()
+        );
+
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Watch, KeyType::Bytes);
+        assert!(builder.bytes(b"foo*".as_ref()).is_ok());
+        let req = builder.into_request();
+        assert_eq!(
+            DispatchError::KeyTypeUnexpected,
+            Watch::dispatch(&hop, &req, &mut resp).unwrap_err()
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use super::super::{request::Argument, response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{
+    state::{KeyType, Value},
+    Hop,
+};
+use alloc::vec::Vec;
+
+pub struct ZAdd;
+
+impl Dispatch for ZAdd {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if !matches!(req.key_type(), None | Some(KeyType::SortedSet)) {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+        let mut args = req.args(1..).ok_or(DispatchError::ArgumentRetrieval)?;
+
+        if args.len() % 2 != 0 {
+            return Err(DispatchError::ArgumentRetrieval);
+        }
+
+        // Validate every score up front so that a bad pair doesn't leave the
+        // set partially updated.
+        let mut members = Vec::with_capacity(args.len() / 2);
+
+        while let (Some(score), Some(member)) = (args.next(), args.next()) {
+            let score = f64::convert(score)
+                .filter(|score| !score.is_nan())
+                .ok_or(DispatchError::ArgumentRetrieval)?;
+
+            members.push((member, score));
+        }
+
+        let mut key = hop.state().key_or_insert_with(key, Value::sorted_set);
+        let set = key
+            .as_sorted_set_mut()
+            .ok_or(DispatchError::KeyTypeDifferent)?;
+
+        let added = members
+            .into_iter()
+            .filter(|(member, score)| set.insert(member.to_vec(), *score))
+            .count();
+
+        response::write_int(resp, added as i64);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ZAdd;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::Value,
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    fn zadd(hop: &Hop, pairs: &[(f64, &[u8])]) -> Result<Vec<u8>, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::ZAdd);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+
+        for (score, member) in pairs {
+            assert!(builder.bytes(score.to_be_bytes().to_vec()).is_ok());
+            assert!(builder.bytes(*member).is_ok());
+        }
+
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        ZAdd::dispatch(hop, &req, &mut resp).map(|_| resp)
+    }
+
+    fn members(hop: &Hop) -> Vec<(Vec<u8>, f64)> {
+        hop.state()
+            .key_ref(b"foo")
+            .unwrap()
+            .as_sorted_set_ref()
+            .unwrap()
+            .iter()
+            .map(|(member, score)| (member.to_vec(), score))
+            .collect()
+    }
+
+    #[test]
+    fn test_insertion_order() {
+        let hop = Hop::new();
+
+        let resp = zadd(&hop, &[(3.0, b"c"), (1.0, b"a"), (2.0, b"b")]).unwrap();
+        assert_eq!(resp, Response::from(3).as_bytes());
+        assert_eq!(
+            [
+                (b"a".to_vec(), 1.0),
+                (b"b".to_vec(), 2.0),
+                (b"c".to_vec(), 3.0)
+            ]
+            .to_vec(),
+            members(&hop)
+        );
+    }
+
+    #[test]
+    fn test_score_update() {
+        let hop = Hop::new();
+        assert!(zadd(&hop, &[(1.0, b"a"), (2.0, b"b")]).is_ok());
+
+        // only "c" is new
+        let resp = zadd(&hop, &[(3.0, b"a"), (0.5, b"c")]).unwrap();
+        assert_eq!(resp, Response::from(1).as_bytes());
+        assert_eq!(
+            [
+                (b"c".to_vec(), 0.5),
+                (b"b".to_vec(), 2.0),
+                (b"a".to_vec(), 3.0)
+            ]
+            .to_vec(),
+            members(&hop)
+        );
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        let hop = Hop::new();
+
+        assert_eq!(
+            DispatchError::ArgumentRetrieval,
+            zadd(&hop, &[(f64::NAN, b"a")]).unwrap_err()
+        );
+
+        let mut builder = RequestBuilder::new(CommandId::ZAdd);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(1f64.to_be_bytes().to_vec()).is_ok());
+        let req = builder.into_request();
+        assert_eq!(
+            DispatchError::ArgumentRetrieval,
+            ZAdd::dispatch(&hop, &req, &mut Vec::new()).unwrap_err()
+        );
+
+        assert!(!hop.state().contains_key(b"foo"));
+    }
+
+    #[test]
+    fn test_key_type_different() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+
+        assert_eq!(
+            DispatchError::KeyTypeDifferent,
+            zadd(&hop, &[(1.0, b"a")]).unwrap_err()
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{state::KeyType, Hop};
+use alloc::vec::Vec;
+
+pub struct ZRange;
+
+impl Dispatch for ZRange {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if !matches!(req.key_type(), None | Some(KeyType::SortedSet)) {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+        let start = req
+            .typed_arg::<i64>(1)
+            .ok_or(DispatchError::ArgumentRetrieval)?;
+        let stop = req
+            .typed_arg::<i64>(2)
+            .ok_or(DispatchError::ArgumentRetrieval)?;
+
+        // A nonexistent key is treated as an empty set.
+        let key = match hop.state().key_ref(key) {
+            Some(key) => key,
+            None => {
+                response::write_list(resp, Vec::<Vec<u8>>::new());
+
+                return Ok(());
+            }
+        };
+        let set = key
+            .as_sorted_set_ref()
+            .ok_or(DispatchError::KeyTypeDifferent)?;
+
+        response::write_list(
+            resp,
+            set.range_by_rank(start, stop).map(|(member, _)| member),
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ZRange;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{SortedSet, Value},
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    fn zrange(hop: &Hop, start: i64, stop: i64) -> Result<Vec<u8>, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::ZRange);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(start.to_be_bytes().to_vec()).is_ok());
+        assert!(builder.bytes(stop.to_be_bytes().to_vec()).is_ok());
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        ZRange::dispatch(hop, &req, &mut resp).map(|_| resp)
+    }
+
+    fn list(items: &[&[u8]]) -> Vec<u8> {
+        Response::from(items.iter().map(|item| item.to_vec()).collect::<Vec<_>>()).as_bytes()
+    }
+
+    #[test]
+    fn test_range_by_rank() {
+        let hop = Hop::new();
+        let mut set = SortedSet::new();
+        set.insert(b"c".to_vec(), 3.0);
+        set.insert(b"a".to_vec(), 1.0);
+        set.insert(b"b".to_vec(), 2.0);
+        hop.state().insert(b"foo".to_vec(), Value::SortedSet(set));
+
+        assert_eq!(list(&[b"a", b"b", b"c"]), zrange(&hop, 0, -1).unwrap());
+        assert_eq!(list(&[b"b"]), zrange(&hop, 1, 1).unwrap());
+        assert_eq!(list(&[b"b", b"c"]), zrange(&hop, -2, 10).unwrap());
+        assert_eq!(list(&[]), zrange(&hop, 2, 0).unwrap());
+    }
+
+    #[test]
+    fn test_key_nonexistent() {
+        assert_eq!(list(&[]), zrange(&Hop::new(), 0, -1).unwrap());
+    }
+
+    #[test]
+    fn test_key_type_different() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+
+        assert_eq!(
+            DispatchError::KeyTypeDifferent,
+            zrange(&hop, 0, -1).unwrap_err()
+        );
+    }
+}
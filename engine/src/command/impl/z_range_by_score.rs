@@ -0,0 +1,217 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{
+    state::{KeyType, ScoreRangeFlags},
+    Hop,
+};
+use alloc::vec::Vec;
+
+pub struct ZRangeByScore;
+
+impl ZRangeByScore {
+    /// Retrieve the optional flags argument, a single byte of
+    /// [`ScoreRangeFlags`] bits following the bounds.
+    ///
+    /// [`ScoreRangeFlags`]: ../../../state/struct.ScoreRangeFlags.html
+    fn flags(req: &Request) -> DispatchResult<ScoreRangeFlags> {
+        match req.arg(3) {
+            Some([bits]) => {
+                ScoreRangeFlags::from_bits(*bits).ok_or(DispatchError::ArgumentRetrieval)
+            }
+            Some(_) => Err(DispatchError::ArgumentRetrieval),
+            None => Ok(ScoreRangeFlags::NONE),
+        }
+    }
+
+    fn score(req: &Request, idx: usize) -> DispatchResult<f64> {
+        req.typed_arg::<f64>(idx)
+            .filter(|score| !score.is_nan())
+            .ok_or(DispatchError::ArgumentRetrieval)
+    }
+}
+
+impl Dispatch for ZRangeByScore {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if !matches!(req.key_type(), None | Some(KeyType::SortedSet)) {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+        let (min, max) = Self::flags(req)?.bounds(Self::score(req, 1)?, Self::score(req, 2)?);
+
+        let offset = match req.arg(4) {
+            Some(_) => req
+                .typed_arg::<i64>(4)
+                .filter(|offset| *offset >= 0)
+                .ok_or(DispatchError::ArgumentRetrieval)? as usize,
+            None => 0,
+        };
+
+        // A negative limit returns all of the remaining members.
+        let limit = match req.arg(5) {
+            Some(_) => {
+                let limit = req
+                    .typed_arg::<i64>(5)
+                    .ok_or(DispatchError::ArgumentRetrieval)?;
+
+                if limit < 0 {
+                    usize::MAX
+                } else {
+                    limit as usize
+                }
+            }
+            None => usize::MAX,
+        };
+
+        // A nonexistent key is treated as an empty set.
+        let key = match hop.state().key_ref(key) {
+            Some(key) => key,
+            None => {
+                response::write_list(resp, Vec::<Vec<u8>>::new());
+
+                return Ok(());
+            }
+        };
+        let set = key
+            .as_sorted_set_ref()
+            .ok_or(DispatchError::KeyTypeDifferent)?;
+
+        let members = set
+            .range_by_score(min, max)
+            .skip(offset)
+            .take(limit)
+            .map(|(member, _)| member);
+
+        response::write_list(resp, members);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ZRangeByScore;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{ScoreRangeFlags, SortedSet, Value},
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    fn hop() -> Hop {
+        let hop = Hop::new();
+        let mut set = SortedSet::new();
+
+        for (idx, member) in [b"a", b"b", b"c", b"d"].iter().enumerate() {
+            set.insert(member.to_vec(), idx as f64);
+        }
+
+        hop.state().insert(b"foo".to_vec(), Value::SortedSet(set));
+
+        hop
+    }
+
+    fn request(min: f64, max: f64, flags: ScoreRangeFlags) -> RequestBuilder {
+        let mut builder = RequestBuilder::new(CommandId::ZRangeByScore);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(min.to_be_bytes().to_vec()).is_ok());
+        assert!(builder.bytes(max.to_be_bytes().to_vec()).is_ok());
+        assert!(builder.bytes([flags.bits()].as_ref()).is_ok());
+
+        builder
+    }
+
+    fn dispatch(hop: &Hop, builder: RequestBuilder) -> Result<Vec<u8>, DispatchError> {
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        ZRangeByScore::dispatch(hop, &req, &mut resp).map(|_| resp)
+    }
+
+    fn list(items: &[&[u8]]) -> Vec<u8> {
+        Response::from(items.iter().map(|item| item.to_vec()).collect::<Vec<_>>()).as_bytes()
+    }
+
+    #[test]
+    fn test_inclusive() {
+        let resp = dispatch(&hop(), request(1.0, 2.0, ScoreRangeFlags::NONE)).unwrap();
+        assert_eq!(list(&[b"b", b"c"]), resp);
+    }
+
+    #[test]
+    fn test_exclusive() {
+        let hop = hop();
+
+        let resp = dispatch(&hop, request(1.0, 3.0, ScoreRangeFlags::MIN_EXCLUSIVE)).unwrap();
+        assert_eq!(list(&[b"c", b"d"]), resp);
+
+        let resp = dispatch(&hop, request(1.0, 3.0, ScoreRangeFlags::MAX_EXCLUSIVE)).unwrap();
+        assert_eq!(list(&[b"b", b"c"]), resp);
+
+        let flags = ScoreRangeFlags::MIN_EXCLUSIVE | ScoreRangeFlags::MAX_EXCLUSIVE;
+        let resp = dispatch(&hop, request(1.0, 3.0, flags)).unwrap();
+        assert_eq!(list(&[b"c"]), resp);
+    }
+
+    #[test]
+    fn test_infinite_bounds() {
+        let builder = request(f64::NEG_INFINITY, f64::INFINITY, ScoreRangeFlags::NONE);
+        let resp = dispatch(&hop(), builder).unwrap();
+        assert_eq!(list(&[b"a", b"b", b"c", b"d"]), resp);
+    }
+
+    #[test]
+    fn test_limit_offset() {
+        let hop = hop();
+
+        let mut builder = request(f64::NEG_INFINITY, f64::INFINITY, ScoreRangeFlags::NONE);
+        assert!(builder.bytes(1i64.to_be_bytes().to_vec()).is_ok());
+        assert!(builder.bytes(2i64.to_be_bytes().to_vec()).is_ok());
+        assert_eq!(list(&[b"b", b"c"]), dispatch(&hop, builder).unwrap());
+
+        let mut builder = request(f64::NEG_INFINITY, f64::INFINITY, ScoreRangeFlags::NONE);
+        assert!(builder.bytes(2i64.to_be_bytes().to_vec()).is_ok());
+        assert!(builder.bytes((-1i64).to_be_bytes().to_vec()).is_ok());
+        assert_eq!(list(&[b"c", b"d"]), dispatch(&hop, builder).unwrap());
+
+        let mut builder = request(f64::NEG_INFINITY, f64::INFINITY, ScoreRangeFlags::NONE);
+        assert!(builder.bytes((-1i64).to_be_bytes().to_vec()).is_ok());
+        assert_eq!(
+            DispatchError::ArgumentRetrieval,
+            dispatch(&hop, builder).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_empty_ranges() {
+        let hop = hop();
+
+        let resp = dispatch(&hop, request(2.0, 1.0, ScoreRangeFlags::NONE)).unwrap();
+        assert_eq!(list(&[]), resp);
+
+        let flags = ScoreRangeFlags::MIN_EXCLUSIVE | ScoreRangeFlags::MAX_EXCLUSIVE;
+        let resp = dispatch(&hop, request(1.0, 2.0, flags)).unwrap();
+        assert_eq!(list(&[]), resp);
+
+        let resp = dispatch(&hop, request(10.0, f64::INFINITY, ScoreRangeFlags::NONE)).unwrap();
+        assert_eq!(list(&[]), resp);
+
+        let resp = dispatch(&Hop::new(), request(0.0, 1.0, ScoreRangeFlags::NONE)).unwrap();
+        assert_eq!(list(&[]), resp);
+    }
+
+    #[test]
+    fn test_invalid_bounds() {
+        let resp = dispatch(&hop(), request(f64::NAN, 1.0, ScoreRangeFlags::NONE));
+        assert_eq!(DispatchError::ArgumentRetrieval, resp.unwrap_err());
+
+        let mut builder = RequestBuilder::new(CommandId::ZRangeByScore);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(1f64.to_be_bytes().to_vec()).is_ok());
+        assert!(builder.bytes(2f64.to_be_bytes().to_vec()).is_ok());
+        assert!(builder.bytes([1 << 2].as_ref()).is_ok());
+        assert_eq!(
+            DispatchError::ArgumentRetrieval,
+            dispatch(&hop(), builder).unwrap_err()
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{state::KeyType, Hop};
+use alloc::vec::Vec;
+
+pub struct ZRem;
+
+impl Dispatch for ZRem {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if !matches!(req.key_type(), None | Some(KeyType::SortedSet)) {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+        let args = req.args(1..).ok_or(DispatchError::ArgumentRetrieval)?;
+
+        let (removed, is_empty) = match hop.state().key_mut(key) {
+            Some(mut value) => {
+                let set = value
+                    .as_sorted_set_mut()
+                    .ok_or(DispatchError::KeyTypeDifferent)?;
+                let removed = args.filter(|member| set.remove(member)).count();
+
+                (removed, set.is_empty())
+            }
+            None => (0, false),
+        };
+
+        // Like other collections, a sorted set without members doesn't exist.
+        if is_empty {
+            hop.state().remove(key);
+        }
+
+        response::write_int(resp, removed as i64);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ZRem;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{SortedSet, Value},
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    fn zrem(hop: &Hop, members: &[&[u8]]) -> Result<Vec<u8>, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::ZRem);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+
+        for member in members {
+            assert!(builder.bytes(*member).is_ok());
+        }
+
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        ZRem::dispatch(hop, &req, &mut resp).map(|_| resp)
+    }
+
+    #[test]
+    fn test_remove() {
+        let hop = Hop::new();
+        let mut set = SortedSet::new();
+        set.insert(b"a".to_vec(), 1.0);
+        set.insert(b"b".to_vec(), 2.0);
+        set.insert(b"c".to_vec(), 3.0);
+        hop.state().insert(b"foo".to_vec(), Value::SortedSet(set));
+
+        assert_eq!(
+            Response::from(2).as_bytes(),
+            zrem(&hop, &[b"a", b"c", b"d"]).unwrap()
+        );
+        assert_eq!(
+            Some(1),
+            hop.state()
+                .key_ref(b"foo")
+                .as_deref()
+                .and_then(Value::as_sorted_set_ref)
+                .map(SortedSet::len)
+        );
+
+        // removing the last member removes the key
+        assert_eq!(Response::from(1).as_bytes(), zrem(&hop, &[b"b"]).unwrap());
+        assert!(!hop.state().contains_key(b"foo"));
+    }
+
+    #[test]
+    fn test_key_nonexistent() {
+        assert_eq!(
+            Response::from(0).as_bytes(),
+            zrem(&Hop::new(), &[b"a"]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_key_type_different() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+
+        assert_eq!(
+            DispatchError::KeyTypeDifferent,
+            zrem(&hop, &[b"a"]).unwrap_err()
+        );
+    }
+}
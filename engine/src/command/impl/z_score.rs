@@ -0,0 +1,71 @@
+use super::super::{response, Dispatch, DispatchError, DispatchResult, Request};
+use crate::{state::KeyType, Hop};
+use alloc::vec::Vec;
+
+pub struct ZScore;
+
+impl Dispatch for ZScore {
+    fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()> {
+        if !matches!(req.key_type(), None | Some(KeyType::SortedSet)) {
+            return Err(DispatchError::KeyTypeUnexpected);
+        }
+
+        let key = req.key().ok_or(DispatchError::KeyUnspecified)?;
+        let member = req.arg(1).ok_or(DispatchError::ArgumentRetrieval)?;
+        let key = hop
+            .state()
+            .key_ref(key)
+            .ok_or(DispatchError::KeyNonexistent)?;
+        let set = key
+            .as_sorted_set_ref()
+            .ok_or(DispatchError::KeyTypeDifferent)?;
+        let score = set.score(member).ok_or(DispatchError::KeyNonexistent)?;
+
+        response::write_float(resp, score);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ZScore;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, Dispatch, DispatchError, Response},
+        state::{SortedSet, Value},
+        Hop,
+    };
+    use alloc::vec::Vec;
+
+    fn zscore(hop: &Hop, member: &[u8]) -> Result<Vec<u8>, DispatchError> {
+        let mut builder = RequestBuilder::new(CommandId::ZScore);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(member).is_ok());
+        let req = builder.into_request();
+        let mut resp = Vec::new();
+
+        ZScore::dispatch(hop, &req, &mut resp).map(|_| resp)
+    }
+
+    #[test]
+    fn test_score() {
+        let hop = Hop::new();
+        let mut set = SortedSet::new();
+        set.insert(b"a".to_vec(), 1.5);
+        hop.state().insert(b"foo".to_vec(), Value::SortedSet(set));
+
+        assert_eq!(Response::from(1.5).as_bytes(), zscore(&hop, b"a").unwrap());
+        assert_eq!(
+            DispatchError::KeyNonexistent,
+            zscore(&hop, b"b").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_key_nonexistent() {
+        assert_eq!(
+            DispatchError::KeyNonexistent,
+            zscore(&Hop::new(), b"a").unwrap_err()
+        );
+    }
+}
@@ -15,6 +15,15 @@ pub use self::{
 use crate::Hop;
 use alloc::vec::Vec;
 
+/// Version of the protocol, incremented whenever requests or responses change
+/// in a way that isn't backwards compatible.
+///
+/// Adding commands doesn't change the version; clients can instead check
+/// which commands are supported with [`CommandId::Command`].
+///
+/// [`CommandId::Command`]: command_id/enum.CommandId.html#variant.Command
+pub const PROTOCOL_VERSION: u32 = 1;
+
 pub trait Dispatch {
     fn dispatch(hop: &Hop, req: &Request, resp: &mut Vec<u8>) -> DispatchResult<()>;
 }
@@ -1,15 +1,26 @@
-use super::Request;
+use super::{Request, IDEMPOTENCY_TOKEN_MARKER};
 use crate::{
     command::CommandId,
     state::{KeyType, Value},
 };
-use alloc::{borrow::Cow, vec::Vec};
+use alloc::{
+    borrow::{Cow, ToOwned},
+    vec::Vec,
+};
 use arrayvec::ArrayVec;
-use core::fmt::{Display, Formatter, Result as FmtResult};
+use core::{
+    convert::TryFrom,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum RequestBuilderError {
     ArgumentEmpty,
+    ArgumentTooLarge,
+    /// A map field's value isn't bytes, which is the only type requests can
+    /// set fields to.
+    MapValueUnsupported,
+    TokenInvalid,
     TooManyArguments,
     ValueEmpty,
 }
@@ -18,6 +29,15 @@ impl Display for RequestBuilderError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         match self {
             Self::ArgumentEmpty => f.write_str("the provided argument is empty"),
+            Self::ArgumentTooLarge => {
+                f.write_str("the provided argument is longer than the protocol allows")
+            }
+            Self::MapValueUnsupported => {
+                f.write_str("the provided map has a value that isn't bytes")
+            }
+            Self::TokenInvalid => {
+                f.write_str("the provided idempotency token is empty or over 255 bytes")
+            }
             Self::TooManyArguments => {
                 f.write_str("too many arguments have been given to the builder")
             }
@@ -37,6 +57,7 @@ pub struct RequestBuilder {
     command_id: CommandId,
     key_type: Option<KeyType>,
     positions: ArrayVec<[usize; 256]>,
+    token: Option<Vec<u8>>,
 }
 
 impl RequestBuilder {
@@ -56,6 +77,7 @@ impl RequestBuilder {
             command_id,
             key_type: None,
             positions: ArrayVec::new(),
+            token: None,
         }
     }
 
@@ -85,16 +107,35 @@ impl RequestBuilder {
             command_id,
             key_type,
             positions: ArrayVec::new(),
+            token: None,
         }
     }
 
     /// Consume the builder and return the built request.
     pub fn into_request(self) -> Request<'static> {
+        let (buf, positions, start) = match self.token {
+            Some(token) => {
+                let start = 2 + token.len();
+
+                let mut buf = Vec::with_capacity(start + self.buf.len());
+                buf.push(IDEMPOTENCY_TOKEN_MARKER);
+                buf.push(token.len() as u8);
+                buf.extend_from_slice(&token);
+                buf.extend_from_slice(&self.buf);
+
+                let positions = self.positions.iter().map(|pos| pos + start).collect();
+
+                (buf, positions, start)
+            }
+            None => (self.buf, self.positions, 0),
+        };
+
         Request {
-            buf: Cow::Owned(self.buf),
+            buf: Cow::Owned(buf),
             command_id: self.command_id,
             key_type: self.key_type,
-            positions: Cow::Owned(self.positions),
+            positions: Cow::Owned(positions),
+            start,
         }
     }
 
@@ -108,6 +149,31 @@ impl RequestBuilder {
         self.key_type.as_ref()
     }
 
+    /// Set the idempotency token of the request.
+    ///
+    /// When a request with a token is dispatched successfully, its response
+    /// is remembered for a while. Dispatching another request with the same
+    /// token returns the remembered response instead of dispatching it again,
+    /// so mutations can be retried safely.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RequestBuilderError::TokenInvalid`] if the token is empty or
+    /// longer than 255 bytes.
+    ///
+    /// [`RequestBuilderError::TokenInvalid`]: enum.RequestBuilderError.html#variant.TokenInvalid
+    pub fn token(&mut self, token: impl Into<Vec<u8>>) -> Result<&mut Self, RequestBuilderError> {
+        let token = token.into();
+
+        if token.is_empty() || token.len() > u8::MAX as usize {
+            return Err(RequestBuilderError::TokenInvalid);
+        }
+
+        self.token = Some(token);
+
+        Ok(self)
+    }
+
     /// Add an argument containing the given bytes.
     ///
     /// # Errors
@@ -115,10 +181,14 @@ impl RequestBuilder {
     /// Returns [`RequestBuilderError::ArgumentEmpty`] if the given value is
     /// empty.
     ///
+    /// Returns [`RequestBuilderError::ArgumentTooLarge`] if the given value is
+    /// longer than `u32::MAX` bytes.
+    ///
     /// Returns [`RequestBuilderError::TooManyArguments`] if the argument would
     /// not fit in the arguments list.
     ///
     /// [`RequestBuilderError::ArgumentEmpty`]: enum.RequestBuilderError.html#variant.ArgumentEmpty
+    /// [`RequestBuilderError::ArgumentTooLarge`]: enum.RequestBuilderError.html#variant.ArgumentTooLarge
     /// [`RequestBuilderError::TooManyArguments`]: enum.RequestBuilderError.html#variant.TooManyArguments
     pub fn bytes(&mut self, bytes: impl Into<Vec<u8>>) -> Result<&mut Self, RequestBuilderError> {
         self._bytes(bytes.into())
@@ -134,6 +204,52 @@ impl RequestBuilder {
         Ok(self)
     }
 
+    /// Add multiple arguments, each containing the given bytes.
+    ///
+    /// All of the arguments are validated before any are added, so the
+    /// builder is left untouched if an error is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RequestBuilderError::ArgumentEmpty`] if any of the given
+    /// items is empty.
+    ///
+    /// Returns [`RequestBuilderError::ArgumentTooLarge`] if any of the given
+    /// items is longer than `u32::MAX` bytes.
+    ///
+    /// Returns [`RequestBuilderError::TooManyArguments`] if the arguments
+    /// would not fit in the arguments list.
+    ///
+    /// [`RequestBuilderError::ArgumentEmpty`]: enum.RequestBuilderError.html#variant.ArgumentEmpty
+    /// [`RequestBuilderError::ArgumentTooLarge`]: enum.RequestBuilderError.html#variant.ArgumentTooLarge
+    /// [`RequestBuilderError::TooManyArguments`]: enum.RequestBuilderError.html#variant.TooManyArguments
+    pub fn args<I: IntoIterator<Item = T>, T: Into<Vec<u8>>>(
+        &mut self,
+        items: I,
+    ) -> Result<&mut Self, RequestBuilderError> {
+        let items = items.into_iter().map(Into::into).collect::<Vec<_>>();
+
+        let remaining = u8::MAX - self.argument_count;
+
+        if items.len() > remaining as usize {
+            return Err(RequestBuilderError::TooManyArguments);
+        }
+
+        if items.iter().any(Vec::is_empty) {
+            return Err(RequestBuilderError::ArgumentEmpty);
+        }
+
+        for item in &items {
+            Self::argument_len(item.len())?;
+        }
+
+        for item in items {
+            self.push_argument(item)?;
+        }
+
+        Ok(self)
+    }
+
     /// Add a value's serialised representation to the arguments.
     ///
     /// # Errors
@@ -141,13 +257,17 @@ impl RequestBuilder {
     /// Returns [`RequestBuilderError::ArgumentEmpty`] if the given value is
     /// empty.
     ///
+    /// Returns [`RequestBuilderError::ArgumentTooLarge`] if an item of the
+    /// given value is longer than `u32::MAX` bytes.
+    ///
     /// Returns [`RequestBuilderError::TooManyArguments`] if the argument would
     /// not fit in the arguments list.
     ///
     /// Returns [`RequestBuilderError::ValueEmpty`] if the given value's
-    /// bytes, list, map, set, or string variant is empty.
+    /// bytes, list, map, set, sorted set, or string variant is empty.
     ///
     /// [`RequestBuilderError::ArgumentEmpty`]: enum.RequestBuilderError.html#variant.ArgumentEmpty
+    /// [`RequestBuilderError::ArgumentTooLarge`]: enum.RequestBuilderError.html#variant.ArgumentTooLarge
     /// [`RequestBuilderError::TooManyArguments`]: enum.RequestBuilderError.html#variant.TooManyArguments
     /// [`RequestBuilderError::ValueEmpty`]: enum.RequestBuilderError.html#variant.ValueEmpty
     pub fn value(&mut self, value: impl Into<Value>) -> Result<&mut Self, RequestBuilderError> {
@@ -170,6 +290,19 @@ impl RequestBuilder {
                 self.push_argument(Cow::Borrowed(float.to_be_bytes().as_ref()))?
             }
             Value::Integer(int) => self.push_argument(Cow::Borrowed(int.to_be_bytes().as_ref()))?,
+            Value::IntSet(set) => {
+                if self.arguments_would_overfill(set.len() as u8) {
+                    return Err(RequestBuilderError::TooManyArguments);
+                }
+
+                if set.is_empty() {
+                    return Err(RequestBuilderError::ValueEmpty);
+                }
+
+                for member in set.members() {
+                    self.push_argument(member)?;
+                }
+            }
             Value::List(list) => {
                 if self.arguments_would_overfill(list.len() as u8) {
                     return Err(RequestBuilderError::TooManyArguments);
@@ -193,6 +326,11 @@ impl RequestBuilder {
                 }
 
                 for (k, v) in map.into_iter() {
+                    let v = match v {
+                        Value::Bytes(v) => v,
+                        _ => return Err(RequestBuilderError::MapValueUnsupported),
+                    };
+
                     self.push_argument(k)?;
                     self.push_argument(v)?;
                 }
@@ -210,6 +348,20 @@ impl RequestBuilder {
                     self.push_argument(item)?;
                 }
             }
+            Value::SortedSet(set) => {
+                if self.arguments_would_overfill((set.len() * 2) as u8) {
+                    return Err(RequestBuilderError::TooManyArguments);
+                }
+
+                if set.is_empty() {
+                    return Err(RequestBuilderError::ValueEmpty);
+                }
+
+                for (member, score) in set.iter() {
+                    self.push_argument(Cow::Borrowed(score.to_be_bytes().as_ref()))?;
+                    self.push_argument(member.to_vec())?;
+                }
+            }
             Value::String(string) => {
                 if string.is_empty() {
                     return Err(RequestBuilderError::ValueEmpty);
@@ -226,6 +378,12 @@ impl RequestBuilder {
         self.argument_count.checked_add(amount).is_none()
     }
 
+    /// Convert the length of an argument to its framed length, which is a
+    /// 4 byte integer.
+    fn argument_len(len: usize) -> Result<u32, RequestBuilderError> {
+        u32::try_from(len).map_err(|_| RequestBuilderError::ArgumentTooLarge)
+    }
+
     fn update_count(&mut self) {
         self.buf[1 + self.key_type.is_some() as usize] = self.argument_count;
     }
@@ -234,9 +392,13 @@ impl RequestBuilder {
     ///
     /// # Errors
     ///
+    /// Returns [`RequestBuilderError::ArgumentTooLarge`] if the argument is
+    /// longer than an argument length can describe.
+    ///
     /// Returns [`RequestBuilderError::TooManyArguments`] if the list of
     /// arguments is already full.
     ///
+    /// [`RequestBuilderError::ArgumentTooLarge`]: enum.RequestBuilderError.html#variant.ArgumentTooLarge
     /// [`RequestBuilderError::TooManyArguments`]: enum.RequestBuilderError.html#variant.TooManyArguments
     fn push_argument<'a>(
         &mut self,
@@ -245,7 +407,7 @@ impl RequestBuilder {
         let argument = argument.into();
         let argument_len = argument.len();
 
-        let argument_len_bytes = (argument_len as u32).to_be_bytes();
+        let argument_len_bytes = Self::argument_len(argument_len)?.to_be_bytes();
         self.buf.extend_from_slice(&argument_len_bytes);
 
         match argument {
@@ -277,8 +439,10 @@ impl RequestBuilder {
 impl From<Request<'_>> for RequestBuilder {
     fn from(request: Request) -> Self {
         let mut builder = Self::new_with_key_type(request.command_id, request.key_type);
-        builder.buf = request.buf.into_owned();
-        builder.positions = request.positions.into_owned();
+        let start = request.start;
+        builder.token = request.token().map(ToOwned::to_owned);
+        builder.buf = request.buf[start..].to_vec();
+        builder.positions = request.positions.iter().map(|pos| pos - start).collect();
 
         builder
     }
@@ -286,7 +450,7 @@ impl From<Request<'_>> for RequestBuilder {
 
 #[cfg(test)]
 mod tests {
-    use super::RequestBuilder;
+    use super::{RequestBuilder, RequestBuilderError, IDEMPOTENCY_TOKEN_MARKER};
     use crate::{
         command::{CommandId, Request},
         state::{KeyType, Value},
@@ -294,6 +458,51 @@ mod tests {
     use alloc::borrow::Cow;
     use arrayvec::ArrayVec;
 
+    #[test]
+    fn test_argument_too_large() {
+        assert_eq!(
+            Ok(u32::MAX),
+            RequestBuilder::argument_len(u32::MAX as usize)
+        );
+
+        // an argument this large can only exist with 64 bit pointers, and
+        // allocating one would take 4 GiB, so only its length is checked
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(
+            Err(RequestBuilderError::ArgumentTooLarge),
+            RequestBuilder::argument_len(u32::MAX as usize + 1)
+        );
+    }
+
+    #[test]
+    fn test_token() {
+        let mut builder = RequestBuilder::new(CommandId::Stats);
+        assert_eq!(
+            Err(RequestBuilderError::TokenInvalid),
+            builder.token(b"".as_ref()).map(|_| ())
+        );
+        assert_eq!(
+            Err(RequestBuilderError::TokenInvalid),
+            builder.token([0; 256].as_ref()).map(|_| ())
+        );
+
+        assert!(builder.token(b"ab".as_ref()).is_ok());
+        let req = builder.into_request();
+        assert_eq!(
+            req.as_bytes(),
+            &[
+                IDEMPOTENCY_TOKEN_MARKER,
+                2,
+                b'a',
+                b'b',
+                CommandId::Stats as u8
+            ]
+        );
+
+        // converting back into a builder keeps the token
+        assert_eq!(req, RequestBuilder::from(req.clone()).into_request());
+    }
+
     #[test]
     fn test_cmd_id() {
         let builder = RequestBuilder::new(CommandId::Stats);
@@ -305,6 +514,7 @@ mod tests {
                 command_id: CommandId::Stats,
                 key_type: None,
                 positions: Cow::Owned(ArrayVec::new()),
+                start: 0,
             }
         );
     }
@@ -326,6 +536,7 @@ mod tests {
                 command_id: CommandId::Decrement,
                 key_type: Some(KeyType::Integer),
                 positions: Cow::Owned(ArrayVec::new()),
+                start: 0,
             }
         );
     }
@@ -335,9 +546,6 @@ mod tests {
         let mut builder = RequestBuilder::new_with_key_type(CommandId::Append, KeyType::List);
         assert!(builder.bytes(b"foo".as_ref()).is_ok());
 
-        let mut expected_args = Vec::new();
-        expected_args.push(b"foo".as_ref());
-
         let mut positions = ArrayVec::new();
         positions.push(9);
 
@@ -361,6 +569,7 @@ mod tests {
                 command_id: CommandId::Append,
                 key_type: Some(KeyType::List),
                 positions: Cow::Owned(positions),
+                start: 0,
             }
         );
     }
@@ -371,10 +580,6 @@ mod tests {
         assert!(builder.bytes(b"foo".as_ref()).is_ok());
         assert!(builder.value(Value::Integer(123)).is_ok());
 
-        let mut expected_args = Vec::new();
-        expected_args.push(b"foo".as_ref());
-        expected_args.push(123i64.to_be_bytes().as_ref());
-
         let mut positions = ArrayVec::new();
         positions.push(9);
         positions.push(21);
@@ -411,10 +616,45 @@ mod tests {
                 command_id: CommandId::Set,
                 key_type: Some(KeyType::String),
                 positions: Cow::Owned(positions),
+                start: 0,
             }
         );
     }
 
+    #[test]
+    fn test_args() {
+        let items = vec!["foo", "bar", "baz"];
+
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::List);
+        assert!(builder.bytes(b"key".as_ref()).is_ok());
+        assert!(builder.args(items).is_ok());
+
+        let req = builder.into_request();
+        assert_eq!(Some(KeyType::List), req.key_type());
+        assert_eq!(Some(b"key".as_ref()), req.key());
+        assert_eq!(
+            Some(vec![b"foo".as_ref(), b"bar".as_ref(), b"baz".as_ref()]),
+            req.args(1..).map(|args| args.collect::<Vec<_>>())
+        );
+    }
+
+    #[test]
+    fn test_args_errors() {
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::List);
+        assert_eq!(
+            Err(RequestBuilderError::ArgumentEmpty),
+            builder.args(vec!["foo", ""]).map(|_| ())
+        );
+        assert!(builder.positions.is_empty());
+
+        let too_many = (0..=u8::MAX).map(|num| vec![num]);
+        assert_eq!(
+            Err(RequestBuilderError::TooManyArguments),
+            builder.args(too_many).map(|_| ())
+        );
+        assert!(builder.positions.is_empty());
+    }
+
     #[test]
     fn test_positions() {
         let mut builder = RequestBuilder::new(CommandId::Decrement);
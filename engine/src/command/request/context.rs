@@ -1,4 +1,4 @@
-use super::{super::ContextConclusion, Request};
+use super::{super::ContextConclusion, Request, IDEMPOTENCY_TOKEN_MARKER};
 use crate::{command::CommandId, state::KeyType};
 use alloc::borrow::Cow;
 use arrayvec::ArrayVec;
@@ -9,8 +9,27 @@ type Conclusion<'a> = ContextConclusion<(CommandId, Option<KeyType>)>;
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 #[repr(u8)]
 pub enum ParseError {
+    /// The command ID isn't known.
+    ///
+    /// This is only returned once the rest of the command has been skipped, so
+    /// the bytes after it can still be parsed. Unknown commands are assumed to
+    /// declare an argument count, like every command that isn't simple.
     CommandIdInvalid = 0,
     KeyTypeInvalid = 1,
+    TokenInvalid = 2,
+    /// The command declares more arguments than the context allows.
+    TooManyArguments = 3,
+}
+
+impl ParseError {
+    /// Whether the erroring command was skipped, so that parsing can carry on
+    /// with the bytes after it.
+    ///
+    /// Otherwise where the erroring command ends isn't known, so nothing after
+    /// it can be parsed.
+    pub fn is_recoverable(self) -> bool {
+        matches!(self, Self::CommandIdInvalid)
+    }
 }
 
 impl TryFrom<u8> for ParseError {
@@ -20,6 +39,8 @@ impl TryFrom<u8> for ParseError {
         Ok(match value {
             0 => Self::CommandIdInvalid,
             1 => Self::KeyTypeInvalid,
+            2 => Self::TokenInvalid,
+            3 => Self::TooManyArguments,
             _ => return Err(()),
         })
     }
@@ -37,6 +58,9 @@ enum Stage {
         command_id: CommandId,
         key_type: Option<KeyType>,
     },
+    Skipping {
+        argument_count: Option<u8>,
+    },
 }
 
 impl Default for Stage {
@@ -47,18 +71,126 @@ impl Default for Stage {
 
 #[derive(Debug)]
 pub struct Context {
+    /// Number of bytes of the last fed buffer that were parsed.
+    consumed: usize,
+    /// Key type of parsed requests that don't specify one.
+    default_key_type: Option<KeyType>,
     idx: usize,
+    /// Maximum number of arguments a command may declare.
+    max_arguments: u8,
     positions: ArrayVec<[usize; 256]>,
     stage: Stage,
+    /// Index of the command's first byte, after any idempotency token.
+    start: usize,
 }
 
 impl Context {
     const ARG_LEN_BYTES: usize = 4;
 
+    /// The default maximum number of arguments a command may declare.
+    ///
+    /// The argument count is a single byte, so this is every count a command
+    /// can declare and nothing is rejected unless a lower maximum is given.
+    pub const MAX_ARGUMENTS_DEFAULT: u8 = u8::MAX;
+
     pub fn new() -> Self {
         Default::default()
     }
 
+    /// Create a context rejecting commands that declare more than the given
+    /// number of arguments with [`ParseError::TooManyArguments`].
+    ///
+    /// Commands are rejected as soon as their argument count is read, before
+    /// any of their arguments are buffered. The default maximum is
+    /// [`MAX_ARGUMENTS_DEFAULT`]. A maximum of 0 is raised to 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::command::{
+    ///     request::{Context, ParseError, RequestBuilder},
+    ///     CommandId,
+    /// };
+    ///
+    /// let mut builder = RequestBuilder::new(CommandId::Exists);
+    /// builder.args(vec!["foo", "bar"]).unwrap();
+    /// let req = builder.into_request();
+    ///
+    /// let mut ctx = Context::with_max_arguments(1);
+    /// assert_eq!(Err(ParseError::TooManyArguments), ctx.feed(req.as_bytes()));
+    /// ```
+    ///
+    /// [`ParseError::TooManyArguments`]: enum.ParseError.html#variant.TooManyArguments
+    /// [`MAX_ARGUMENTS_DEFAULT`]: #associatedconstant.MAX_ARGUMENTS_DEFAULT
+    pub fn with_max_arguments(max_arguments: u8) -> Self {
+        Self {
+            max_arguments: max_arguments.max(1),
+            ..Default::default()
+        }
+    }
+
+    /// Retrieve the maximum number of arguments a command may declare.
+    pub fn max_arguments(&self) -> u8 {
+        self.max_arguments
+    }
+
+    /// Retrieve the key type given to parsed requests that don't specify one.
+    pub fn default_key_type(&self) -> Option<KeyType> {
+        self.default_key_type
+    }
+
+    /// Set the key type given to parsed requests that don't specify one, or
+    /// `None` to parse them without a key type.
+    ///
+    /// The default only applies to commands that [use a default key type].
+    /// A request specifying a key type keeps it, and the bytes of a request
+    /// are left as they were sent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::{
+    ///     command::{
+    ///         request::{Context, RequestBuilder},
+    ///         CommandId,
+    ///     },
+    ///     state::KeyType,
+    /// };
+    ///
+    /// let mut builder = RequestBuilder::new(CommandId::Set);
+    /// builder.args(vec!["foo", "bar"]).unwrap();
+    /// let req = builder.into_request();
+    ///
+    /// let mut ctx = Context::new();
+    /// ctx.set_default_key_type(Some(KeyType::String));
+    ///
+    /// let parsed = ctx.feed(req.as_bytes()).unwrap().unwrap();
+    /// assert_eq!(Some(KeyType::String), parsed.key_type());
+    /// assert_eq!(Some(b"bar".as_ref()), parsed.arg(1));
+    /// ```
+    ///
+    /// [use a default key type]: ../enum.CommandId.html#method.uses_default_key_type
+    pub fn set_default_key_type(&mut self, key_type: Option<KeyType>) {
+        self.default_key_type = key_type;
+    }
+
+    /// Feed a buffer of bytes into the context, returning a request if one
+    /// has been fully parsed.
+    ///
+    /// The buffer must start at the beginning of a command and must be given
+    /// again, with any newly read bytes appended, each time `Ok(None)` is
+    /// returned. Any bytes after the end of a parsed request are left
+    /// untouched; [`consumed`] is then the number of bytes of the buffer that
+    /// the request used.
+    ///
+    /// If an error is returned the context is reset. If the error [is
+    /// recoverable] then [`consumed`] is the length of the skipped command,
+    /// which is the prefix of the buffer to drain before feeding the bytes
+    /// after it. Otherwise where the command ends isn't known, so nothing
+    /// after it can be parsed.
+    ///
+    /// [is recoverable]: enum.ParseError.html#method.is_recoverable
+    /// [`consumed`]: #method.consumed
     pub fn feed<'a>(&'a mut self, buf: &'a [u8]) -> Result<Option<Request<'a>>, ParseError> {
         loop {
             let conclusion = {
@@ -68,72 +200,165 @@ impl Context {
                 let idx = self.idx;
 
                 if buf.get(idx..).is_none() {
+                    self.consumed = idx;
+
                     return Ok(None);
                 }
 
-                match self.stage {
-                    Stage::Init => self.stage_init(buf)?,
+                let conclusion = match self.stage {
+                    Stage::Init => self.stage_init(buf),
                     Stage::Kind {
                         command_id,
                         key_type,
-                    } => self.stage_kind(buf, key_type, command_id)?,
+                    } => self.stage_kind(buf, key_type, command_id),
                     Stage::ArgumentParsing {
                         argument_count,
                         command_id,
                         key_type,
-                    } => self.stage_argument_parsing(buf, command_id, key_type, argument_count)?,
+                    } => self.stage_argument_parsing(buf, command_id, key_type, argument_count),
+                    Stage::Skipping { argument_count } => self.stage_skipping(buf, argument_count),
+                };
+
+                match conclusion {
+                    Ok(conclusion) => conclusion,
+                    Err(why) => {
+                        let consumed = if why.is_recoverable() { self.idx } else { 0 };
+                        self.reset();
+                        self.consumed = consumed;
+
+                        return Err(why);
+                    }
                 }
             };
 
             match conclusion {
                 Conclusion::Finished((command_id, key_type)) => {
-                    self.reset();
+                    let len = self.idx;
+                    self.consumed = len;
+
+                    // The positions are only cleared when the next command
+                    // starts being parsed, since the request borrows them.
+                    let start = self.start;
+                    let key_type = match key_type {
+                        None if command_id.uses_default_key_type() => self.default_key_type,
+                        key_type => key_type,
+                    };
+                    self.idx = 0;
+                    self.stage = Stage::default();
+                    self.start = 0;
 
                     return Ok(Some(Request {
-                        buf: Cow::Borrowed(buf),
+                        buf: Cow::Borrowed(&buf[..len]),
                         command_id,
                         key_type,
                         positions: Cow::Borrowed(&self.positions),
+                        start,
                     }));
                 }
-                Conclusion::Incomplete => return Ok(None),
+                Conclusion::Incomplete => {
+                    self.consumed = self.idx;
+
+                    return Ok(None);
+                }
                 Conclusion::Next => continue,
             }
         }
     }
 
+    /// Retrieve the number of bytes of the last fed buffer that have been
+    /// parsed.
+    ///
+    /// After a request is returned this is the length of the request, which
+    /// is the prefix of the buffer to drain before feeding the bytes after
+    /// it. While a request is incomplete this is how much of it has been
+    /// parsed so far, but those bytes must still be given again on the next
+    /// feed. After a recoverable error this is the length of the skipped
+    /// command, and after any other error this is 0.
+    pub fn consumed(&self) -> usize {
+        self.consumed
+    }
+
     fn stage_init<'a>(&'a mut self, buf: &'a [u8]) -> Result<Conclusion<'a>, ParseError> {
-        let byte = match buf.first() {
+        let start = self.start;
+
+        let byte = match buf.get(start) {
             Some(byte) => *byte,
             None => return Ok(Conclusion::Incomplete),
         };
 
-        // If the first bit is flipped, then this byte is denoting the type of
-        // key to work with. This means that byte idx 2 is the argument length.
-        //
-        // If the first bit is 0, then this byte is the argument length, and the
-        // type of key to work with is not a requirement.
-        let key_type = if byte >> 7 == 1 {
-            let key_type_id = byte >> 1;
+        // A request may be prefixed by an idempotency token: the marker byte,
+        // the length of the token, and then the token itself.
+        if byte == IDEMPOTENCY_TOKEN_MARKER {
+            // Only one token may precede a command.
+            if start != 0 {
+                return Err(ParseError::TokenInvalid);
+            }
 
-            Some(KeyType::try_from(key_type_id).map_err(|_| ParseError::KeyTypeInvalid)?)
-        } else {
-            None
-        };
+            return self.stage_token(buf);
+        }
 
-        let command_id = CommandId::try_from(byte).map_err(|_| ParseError::CommandIdInvalid)?;
+        self.positions.clear();
+
+        // If the first bit is flipped, then the next byte is denoting the type
+        // of key to work with. This means that byte idx 2 is the argument
+        // length.
+        //
+        // If the first bit is 0, then the next byte is the argument length,
+        // and the type of key to work with is not a requirement.
+        let command_id = match CommandId::try_from(byte & 0b0111_1111) {
+            Ok(command_id) => command_id,
+            Err(_) => {
+                // Skip the key type, if there is one, without checking it.
+                self.stage = Stage::Skipping {
+                    argument_count: None,
+                };
+                self.idx = start + 1 + (byte >> 7) as usize;
+
+                return Ok(Conclusion::Next);
+            }
+        };
 
         // If the command type is simple and has no arguments or keys, then
         // we can just return a successful command here.
         if command_id.is_simple() {
+            self.idx = start + 1;
+
             return Ok(Conclusion::Finished((command_id, None)));
         }
 
+        let key_type = if byte >> 7 == 1 {
+            let key_type_id = match buf.get(start + 1) {
+                Some(key_type_id) => *key_type_id,
+                None => return Ok(Conclusion::Incomplete),
+            };
+
+            Some(KeyType::try_from(key_type_id).map_err(|_| ParseError::KeyTypeInvalid)?)
+        } else {
+            None
+        };
+
         self.stage = Stage::Kind {
             command_id,
             key_type,
         };
-        self.idx = self.idx.wrapping_add(1);
+        self.idx = start + 1 + key_type.is_some() as usize;
+
+        Ok(Conclusion::Next)
+    }
+
+    fn stage_token(&mut self, buf: &[u8]) -> Result<Conclusion<'_>, ParseError> {
+        let token_len = match buf.get(1) {
+            Some(0) => return Err(ParseError::TokenInvalid),
+            Some(token_len) => *token_len as usize,
+            None => return Ok(Conclusion::Incomplete),
+        };
+
+        if buf.len() < 2 + token_len {
+            return Ok(Conclusion::Incomplete);
+        }
+
+        self.start = 2 + token_len;
+        self.idx = self.start;
 
         Ok(Conclusion::Next)
     }
@@ -143,18 +368,27 @@ impl Context {
         buf: &[u8],
         key_type: Option<KeyType>,
         command_id: CommandId,
-    ) -> Result<Conclusion, ParseError> {
+    ) -> Result<Conclusion<'_>, ParseError> {
         let argument_count = match buf.get(self.idx) {
             Some(argument_count) => *argument_count,
             None => return Ok(Conclusion::Incomplete),
         };
 
+        if argument_count > self.max_arguments {
+            return Err(ParseError::TooManyArguments);
+        }
+
+        self.idx = self.idx.saturating_add(1);
+
+        if argument_count == 0 {
+            return Ok(Conclusion::Finished((command_id, key_type)));
+        }
+
         self.stage = Stage::ArgumentParsing {
             argument_count,
             command_id,
             key_type,
         };
-        self.idx = self.idx.saturating_add(1);
 
         Ok(Conclusion::Next)
     }
@@ -165,42 +399,110 @@ impl Context {
         command_id: CommandId,
         key_type: Option<KeyType>,
         argument_count: u8,
-    ) -> Result<Conclusion, ParseError> {
-        let len_bytes = match buf.get(self.idx..self.idx + Self::ARG_LEN_BYTES) {
-            Some(bytes) => bytes.try_into().unwrap(),
-            None => return Ok(Conclusion::Incomplete),
-        };
-
-        let arg_len = u32::from_be_bytes(len_bytes) as usize;
+    ) -> Result<Conclusion<'a>, ParseError> {
+        if !self.parse_argument(buf) {
+            return Ok(Conclusion::Incomplete);
+        }
 
-        if buf.get(self.idx..self.idx + arg_len).is_some() {
-            self.positions.push(self.idx + arg_len);
+        if self.positions.len() == argument_count as usize {
+            Ok(Conclusion::Finished((command_id, key_type)))
         } else {
-            return Ok(Conclusion::Incomplete);
+            Ok(Conclusion::Next)
         }
+    }
 
-        self.idx += 4 + arg_len;
+    /// Skip the arguments of a command whose ID is invalid, erroring once the
+    /// whole command has been skipped.
+    fn stage_skipping(
+        &mut self,
+        buf: &[u8],
+        argument_count: Option<u8>,
+    ) -> Result<Conclusion<'_>, ParseError> {
+        let argument_count = match argument_count {
+            Some(argument_count) => argument_count,
+            None => {
+                let argument_count = match buf.get(self.idx) {
+                    Some(argument_count) => *argument_count,
+                    None => return Ok(Conclusion::Incomplete),
+                };
+
+                if argument_count > self.max_arguments {
+                    return Err(ParseError::TooManyArguments);
+                }
+
+                self.idx = self.idx.saturating_add(1);
+                self.stage = Stage::Skipping {
+                    argument_count: Some(argument_count),
+                };
+
+                argument_count
+            }
+        };
+
+        if self.positions.len() < argument_count as usize && !self.parse_argument(buf) {
+            return Ok(Conclusion::Incomplete);
+        }
 
         if self.positions.len() == argument_count as usize {
-            Ok(Conclusion::Finished((command_id, key_type)))
+            Err(ParseError::CommandIdInvalid)
         } else {
             Ok(Conclusion::Next)
         }
     }
 
+    /// Parse the argument at the index, returning whether all of it has been
+    /// read.
+    fn parse_argument(&mut self, buf: &[u8]) -> bool {
+        // The input is untrusted, so positions are computed with checked
+        // arithmetic. A position past the end of the buffer means that more
+        // bytes are needed; one that overflows can never be satisfied, but no
+        // buffer is ever long enough for it, so it's treated the same way.
+        let len_end = match self.idx.checked_add(Self::ARG_LEN_BYTES) {
+            Some(len_end) => len_end,
+            None => return false,
+        };
+
+        let len_bytes: [u8; 4] = match buf
+            .get(self.idx..len_end)
+            .and_then(|bytes| bytes.try_into().ok())
+        {
+            Some(bytes) => bytes,
+            None => return false,
+        };
+
+        let arg_len = u32::from_be_bytes(len_bytes) as usize;
+
+        let arg_end = match len_end.checked_add(arg_len) {
+            Some(arg_end) if arg_end <= buf.len() => arg_end,
+            _ => return false,
+        };
+
+        // Positions are the index of the last byte of each argument.
+        self.positions.push(arg_end - 1);
+        self.idx = arg_end;
+
+        true
+    }
+
     fn reset(&mut self) {
+        self.consumed = 0;
         self.idx = 0;
         self.positions.clear();
         self.stage = Stage::default();
+        self.start = 0;
     }
 }
 
 impl Default for Context {
     fn default() -> Self {
         Self {
+            consumed: 0,
+            default_key_type: None,
             idx: 0,
+            max_arguments: Self::MAX_ARGUMENTS_DEFAULT,
             positions: ArrayVec::new(),
             stage: Stage::default(),
+            start: 0,
         }
     }
 }
@@ -208,9 +510,10 @@ impl Default for Context {
 #[cfg(test)]
 mod tests {
     use super::{
-        super::{super::error::Result, CommandId},
-        Context, ParseError, Stage,
+        super::{super::error::Result, CommandId, RequestBuilder, IDEMPOTENCY_TOKEN_MARKER},
+        Conclusion, Context, ParseError, Stage,
     };
+    use crate::state::KeyType;
     use core::{convert::TryFrom, fmt::Debug, hash::Hash};
     use static_assertions::assert_impl_all;
 
@@ -261,6 +564,164 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_builder_roundtrip() {
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::List);
+        assert!(builder.args(vec!["foo", "bar", "baz"]).is_ok());
+        let expected = builder.into_request();
+
+        let mut ctx = Context::new();
+        let req = ctx.feed(expected.as_bytes()).unwrap().unwrap();
+
+        assert_eq!(expected, req.clone());
+        assert_eq!(Some(b"foo".as_ref()), req.key());
+        assert_eq!(Some(b"baz".as_ref()), req.arg(2));
+    }
+
+    #[test]
+    fn test_argument_containing_newlines() {
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::Bytes);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(b"\nbar\n\nbaz\n".as_ref()).is_ok());
+        let expected = builder.into_request();
+        let bytes = expected.as_bytes();
+
+        let mut ctx = Context::new();
+
+        // feed the request in byte by byte, as if each byte were its own read
+        for len in 1..bytes.len() {
+            assert!(ctx.feed(&bytes[..len]).unwrap().is_none());
+        }
+
+        let req = ctx.feed(bytes).unwrap().unwrap();
+        assert_eq!(Some(b"\nbar\n\nbaz\n".as_ref()), req.arg(1));
+    }
+
+    #[test]
+    fn test_stops_at_command_boundary() {
+        let mut builder = RequestBuilder::new(CommandId::Increment);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        let first = builder.into_request();
+        let mut builder = RequestBuilder::new(CommandId::Echo);
+        assert!(builder.bytes(b"bar".as_ref()).is_ok());
+        let second = builder.into_request();
+
+        let mut buf = first.as_bytes().to_vec();
+        buf.extend_from_slice(second.as_bytes());
+        buf.push(CommandId::Stats as u8);
+
+        let mut ctx = Context::new();
+
+        let req = ctx.feed(&buf).unwrap().unwrap();
+        assert_eq!(first, req);
+        buf.drain(..ctx.consumed());
+
+        let req = ctx.feed(&buf).unwrap().unwrap();
+        assert_eq!(second, req);
+        buf.drain(..ctx.consumed());
+
+        let req = ctx.feed(&buf).unwrap().unwrap();
+        assert_eq!(CommandId::Stats, req.command_id());
+        assert_eq!(1, req.as_bytes().len());
+    }
+
+    #[test]
+    fn test_consumed() {
+        let mut builder = RequestBuilder::new(CommandId::Echo);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        let req = builder.into_request();
+        let mut buf = req.as_bytes().to_vec();
+        buf.push(CommandId::Stats as u8);
+
+        let mut ctx = Context::new();
+        assert_eq!(0, ctx.consumed());
+
+        // only the command ID
+        assert!(ctx.feed(&buf[..1]).unwrap().is_none());
+        assert_eq!(1, ctx.consumed());
+
+        // the header has been parsed but not the argument
+        assert!(ctx.feed(&buf[..5]).unwrap().is_none());
+        assert_eq!(2, ctx.consumed());
+
+        // the whole request, without the command after it
+        assert_eq!(req, ctx.feed(&buf).unwrap().unwrap());
+        assert_eq!(req.as_bytes().len(), ctx.consumed());
+
+        buf.drain(..ctx.consumed());
+        assert!(ctx.feed(&buf).unwrap().is_some());
+        assert_eq!(1, ctx.consumed());
+
+        // an invalid command is skipped
+        assert_eq!(Err(ParseError::CommandIdInvalid), ctx.feed(&[126, 0, 1]));
+        assert_eq!(2, ctx.consumed());
+
+        // where an invalid key type's command ends isn't known
+        assert!(ctx.feed(&[0b1000_0000, 255]).is_err());
+        assert_eq!(0, ctx.consumed());
+    }
+
+    #[test]
+    fn test_error_resets() {
+        let mut ctx = Context::new();
+        assert_eq!(
+            ParseError::CommandIdInvalid,
+            ctx.feed(&[126, 0]).unwrap_err()
+        );
+        assert_eq!(
+            ParseError::KeyTypeInvalid,
+            ctx.feed(&[0b1000_0000, 255]).unwrap_err()
+        );
+
+        let req = ctx.feed(&[CommandId::Stats as u8]).unwrap().unwrap();
+        assert_eq!(CommandId::Stats, req.command_id());
+    }
+
+    #[test]
+    fn test_command_id_invalid_skipped() {
+        let mut get = RequestBuilder::new(CommandId::Get);
+        assert!(get.bytes(b"foo".as_ref()).is_ok());
+        let get = get.into_request();
+
+        // an unknown command with a key type and two arguments, followed by a
+        // known command
+        let mut buf = vec![
+            0b1111_1110,
+            255,
+            2,
+            0,
+            0,
+            0,
+            1,
+            b'a',
+            0,
+            0,
+            0,
+            2,
+            b'b',
+            b'c',
+        ];
+        let len = buf.len();
+        buf.extend_from_slice(get.as_bytes());
+
+        let mut ctx = Context::new();
+
+        for end in 1..len {
+            assert!(ctx.feed(&buf[..end]).unwrap().is_none());
+        }
+
+        assert_eq!(Err(ParseError::CommandIdInvalid), ctx.feed(&buf));
+        assert_eq!(len, ctx.consumed());
+
+        buf.drain(..ctx.consumed());
+        assert_eq!(get, ctx.feed(&buf).unwrap().unwrap());
+
+        // skipped commands may declare too many arguments too
+        let mut ctx = Context::with_max_arguments(1);
+        assert_eq!(Err(ParseError::TooManyArguments), ctx.feed(&[126, 2]));
+        assert_eq!(0, ctx.consumed());
+    }
+
     #[test]
     fn test_parse_error_try_from_u8() {
         assert_eq!(
@@ -268,5 +729,225 @@ mod tests {
             ParseError::CommandIdInvalid
         );
         assert_eq!(ParseError::try_from(1).unwrap(), ParseError::KeyTypeInvalid);
+        assert_eq!(ParseError::try_from(2).unwrap(), ParseError::TokenInvalid);
+        assert_eq!(
+            ParseError::try_from(3).unwrap(),
+            ParseError::TooManyArguments
+        );
+    }
+
+    #[test]
+    fn test_default_key_type() {
+        let mut ctx = Context::new();
+        assert!(ctx.default_key_type().is_none());
+        ctx.set_default_key_type(Some(KeyType::String));
+        assert_eq!(Some(KeyType::String), ctx.default_key_type());
+
+        let mut builder = RequestBuilder::new(CommandId::Set);
+        assert!(builder.args(vec!["foo", "bar"]).is_ok());
+        assert!(builder.token(b"token".as_ref()).is_ok());
+        let req = builder.into_request();
+
+        // the arguments are where they were sent despite the key type
+        let parsed = ctx.feed(req.as_bytes()).unwrap().unwrap();
+        assert_eq!(Some(KeyType::String), parsed.key_type());
+        assert_eq!(Some(b"foo".as_ref()), parsed.key());
+        assert_eq!(Some(b"bar".as_ref()), parsed.arg(1));
+        assert_eq!(req.as_bytes(), parsed.as_bytes());
+
+        // a key type sent with the request overrides the default
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::Bytes);
+        assert!(builder.args(vec!["foo", "bar"]).is_ok());
+        let req = builder.into_request();
+        let parsed = ctx.feed(req.as_bytes()).unwrap().unwrap();
+        assert_eq!(Some(KeyType::Bytes), parsed.key_type());
+        assert_eq!(Some(b"foo".as_ref()), parsed.key());
+
+        // commands that don't create keys don't use the default
+        let mut builder = RequestBuilder::new(CommandId::Get);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        let req = builder.into_request();
+        assert_eq!(None, ctx.feed(req.as_bytes()).unwrap().unwrap().key_type());
+
+        ctx.set_default_key_type(None);
+        let mut builder = RequestBuilder::new(CommandId::Set);
+        assert!(builder.args(vec!["foo", "bar"]).is_ok());
+        let req = builder.into_request();
+        assert_eq!(None, ctx.feed(req.as_bytes()).unwrap().unwrap().key_type());
+    }
+
+    #[test]
+    fn test_too_many_arguments() {
+        let mut builder = RequestBuilder::new(CommandId::Exists);
+        assert!(builder.args(vec!["a", "b", "c"]).is_ok());
+        let req = builder.into_request();
+        let bytes = req.as_bytes();
+
+        let mut ctx = Context::with_max_arguments(2);
+        assert_eq!(2, ctx.max_arguments());
+
+        // the command is rejected once its argument count is read, before any
+        // of its arguments arrive
+        assert_eq!(
+            ParseError::TooManyArguments,
+            ctx.feed(&bytes[..2]).unwrap_err()
+        );
+        assert_eq!(ParseError::TooManyArguments, ctx.feed(bytes).unwrap_err());
+
+        // commands within the limit are still parsed
+        let mut builder = RequestBuilder::new(CommandId::Exists);
+        assert!(builder.args(vec!["a", "b"]).is_ok());
+        let req = builder.into_request();
+        assert_eq!(req, ctx.feed(req.as_bytes()).unwrap().unwrap());
+
+        assert_eq!(1, Context::with_max_arguments(0).max_arguments());
+    }
+
+    #[test]
+    fn test_max_arguments_default() {
+        let mut ctx = Context::default();
+        assert_eq!(u8::MAX, ctx.max_arguments());
+
+        // the most arguments a command can declare are still parsed
+        let mut builder = RequestBuilder::new(CommandId::Exists);
+        assert!(builder.args((0..u8::MAX).map(|idx| vec![idx])).is_ok());
+        let req = builder.into_request();
+        assert_eq!(255, req.arg_count());
+        assert_eq!(req, ctx.feed(req.as_bytes()).unwrap().unwrap());
+    }
+
+    /// Feed arbitrary bytes in chunks like the fuzz target does, asserting that
+    /// the context never panics or parses outside of the buffer.
+    #[test]
+    fn test_arbitrary_input() {
+        // A xorshift generator, so that failures are reproducible.
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+
+            state
+        };
+
+        for _ in 0..2000 {
+            let len = (next() % 64) as usize;
+            let input = (0..len).map(|_| next() as u8).collect::<Vec<_>>();
+            let chunk_len = (next() % 8 + 1) as usize;
+
+            let mut ctx = Context::new();
+            let mut buf = Vec::new();
+
+            for chunk in input.chunks(chunk_len) {
+                buf.extend_from_slice(chunk);
+
+                loop {
+                    match ctx.feed(&buf) {
+                        Ok(Some(req)) => {
+                            assert!(req.as_bytes().len() <= buf.len());
+                            buf.drain(..ctx.consumed());
+                        }
+                        Ok(None) => break,
+                        Err(why) if why.is_recoverable() => {
+                            assert!(ctx.consumed() > 0 && ctx.consumed() <= buf.len());
+                            buf.drain(..ctx.consumed());
+                        }
+                        Err(_) => {
+                            assert_eq!(0, ctx.consumed());
+                            buf.clear();
+
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_truncated_length_prefix() {
+        let mut builder = RequestBuilder::new(CommandId::Get);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        let req = builder.into_request();
+        let bytes = req.as_bytes();
+
+        let mut ctx = Context::new();
+
+        // the command ID, the argument count, and 0 to 3 bytes of the length
+        for len in 2..6 {
+            assert!(ctx.feed(&bytes[..len]).unwrap().is_none());
+        }
+
+        assert_eq!(req, ctx.feed(bytes).unwrap().unwrap());
+
+        // a length longer than the buffer needs more bytes
+        let mut ctx = Context::new();
+        let buf = [CommandId::Get as u8, 1, 0xff, 0xff, 0xff, 0xff, b'a'];
+        assert!(ctx.feed(&buf).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_argument_position_overflow() {
+        let buf = [0xff; 8];
+
+        // the end of the length overflows, or is right at the limit
+        for idx in &[usize::MAX - 1, usize::MAX - 3, usize::MAX - 4] {
+            let mut ctx = Context::new();
+            ctx.idx = *idx;
+            assert!(matches!(
+                ctx.stage_argument_parsing(&buf, CommandId::Get, None, 1),
+                Ok(Conclusion::Incomplete)
+            ));
+        }
+
+        // the maximum argument length is past the end of the buffer
+        let mut ctx = Context::new();
+        assert!(matches!(
+            ctx.stage_argument_parsing(&buf, CommandId::Get, None, 1),
+            Ok(Conclusion::Incomplete)
+        ));
+        assert!(ctx.positions.is_empty());
+    }
+
+    #[test]
+    fn test_token() {
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::Bytes);
+        assert!(builder.token(b"retry-1".as_ref()).is_ok());
+        assert!(builder.args(vec!["foo", "bar"]).is_ok());
+        let expected = builder.into_request();
+        let bytes = expected.as_bytes();
+
+        let mut ctx = Context::new();
+
+        for len in 1..bytes.len() {
+            assert!(ctx.feed(&bytes[..len]).unwrap().is_none());
+        }
+
+        let req = ctx.feed(bytes).unwrap().unwrap();
+        assert_eq!(expected, req.clone());
+        assert_eq!(Some(b"retry-1".as_ref()), req.token());
+        assert_eq!(Some(b"foo".as_ref()), req.key());
+        assert_eq!(Some(b"bar".as_ref()), req.arg(1));
+        assert_eq!(bytes.len(), req.as_bytes().len());
+
+        // the context starts afresh for the next request
+        let req = ctx.feed(&[CommandId::Stats as u8]).unwrap().unwrap();
+        assert!(req.token().is_none());
+    }
+
+    #[test]
+    fn test_token_invalid() {
+        let mut ctx = Context::new();
+        assert_eq!(
+            ParseError::TokenInvalid,
+            ctx.feed(&[IDEMPOTENCY_TOKEN_MARKER, 0]).unwrap_err()
+        );
+
+        // only one token may precede a command
+        assert_eq!(
+            ParseError::TokenInvalid,
+            ctx.feed(&[IDEMPOTENCY_TOKEN_MARKER, 1, b'a', IDEMPOTENCY_TOKEN_MARKER])
+                .unwrap_err()
+        );
     }
 }
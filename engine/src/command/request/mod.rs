@@ -7,7 +7,7 @@ pub use self::{
 };
 
 use super::command_id::{CommandId, KeyNotation};
-use crate::state::KeyType;
+use crate::state::{KeyType, SortedSet, Value};
 use alloc::{
     borrow::{Cow, ToOwned},
     vec::Vec,
@@ -20,6 +20,13 @@ use core::{
 };
 use dashmap::{DashMap, DashSet};
 
+/// Byte introducing an idempotency token at the start of a request.
+///
+/// It's followed by the length of the token as a byte and then the token
+/// itself, after which the request continues as normal. This byte is never
+/// used as a command ID.
+pub const IDEMPOTENCY_TOKEN_MARKER: u8 = 0x7F;
+
 pub trait Argument<'a> {
     fn convert(bytes: &'a [u8]) -> Option<Self>
     where
@@ -62,12 +69,13 @@ impl Argument<'_> for i64 {
     }
 }
 
-impl MultiArgument<'_> for DashMap<Vec<u8>, Vec<u8>> {
+/// Maps are given as pairs of a field and its value, which is stored as bytes.
+impl MultiArgument<'_> for DashMap<Vec<u8>, Value> {
     fn convert(mut args: Arguments<'_>) -> Option<Self> {
-        let map = DashMap::new();
+        let map = DashMap::with_capacity(args.len() / 2);
 
         while let (Some(k), Some(v)) = (args.next(), args.next()) {
-            map.insert(k.to_owned(), v.to_owned());
+            map.insert(k.to_owned(), Value::Bytes(v.to_owned()));
         }
 
         Some(map)
@@ -76,7 +84,7 @@ impl MultiArgument<'_> for DashMap<Vec<u8>, Vec<u8>> {
 
 impl MultiArgument<'_> for DashSet<Vec<u8>> {
     fn convert(args: Arguments<'_>) -> Option<Self> {
-        let set = DashSet::new();
+        let set = DashSet::with_capacity(args.len());
 
         for arg in args {
             set.insert(arg.to_owned());
@@ -86,13 +94,34 @@ impl MultiArgument<'_> for DashSet<Vec<u8>> {
     }
 }
 
+/// Sorted sets are given as pairs of a score and a member.
+///
+/// Returns `None` if a member is missing its score or a score is NaN.
+impl MultiArgument<'_> for SortedSet {
+    fn convert(mut args: Arguments<'_>) -> Option<Self> {
+        if args.len() % 2 != 0 {
+            return None;
+        }
+
+        let mut set = SortedSet::new();
+
+        while let (Some(score), Some(member)) = (args.next(), args.next()) {
+            let score = f64::convert(score).filter(|score| !score.is_nan())?;
+
+            set.insert(member.to_owned(), score);
+        }
+
+        Some(set)
+    }
+}
+
 impl<'a> Argument<'a> for &'a str {
     fn convert(bytes: &'a [u8]) -> Option<Self> {
         str::from_utf8(bytes).ok()
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Arguments<'a> {
     idx: usize,
     to: usize,
@@ -101,7 +130,7 @@ pub struct Arguments<'a> {
 
 impl ExactSizeIterator for Arguments<'_> {
     fn len(&self) -> usize {
-        self.to
+        self.to - self.idx
     }
 }
 
@@ -118,6 +147,12 @@ impl<'a> Iterator for Arguments<'a> {
 
         self.request.arg(idx)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+
+        (len, Some(len))
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -126,6 +161,8 @@ pub struct Request<'a> {
     command_id: CommandId,
     key_type: Option<KeyType>,
     positions: Cow<'a, ArrayVec<[usize; 256]>>,
+    /// Index of the command's first byte, after any idempotency token.
+    start: usize,
 }
 
 impl<'a> Request<'a> {
@@ -147,10 +184,11 @@ impl<'a> Request<'a> {
             Bound::Excluded(amt) => *amt,
             Bound::Included(amt) => *amt + 1,
             Bound::Unbounded => self.arg_count(),
-        };
+        }
+        .min(self.arg_count());
 
         Some(Arguments {
-            idx: start,
+            idx: start.min(end),
             request: self,
             to: end,
         })
@@ -166,9 +204,14 @@ impl<'a> Request<'a> {
         let position = self.positions.get(idx).copied()?;
 
         if idx == 0 {
-            let base = if self.key_type.is_some() { 1 } else { 0 };
-
-            return self.buf.get(6 + base..=position);
+            // A key type given by a context's default isn't in the bytes, so
+            // check the flag of the command byte for a key type byte.
+            let base = self
+                .buf
+                .get(self.start)
+                .map_or(0, |byte| (byte >> 7) as usize);
+
+            return self.buf.get(self.start + 6 + base..=position);
         }
 
         let previous = self.positions.get(idx - 1)?;
@@ -206,6 +249,45 @@ impl<'a> Request<'a> {
         self.key_type
     }
 
+    /// Returns the number of bytes the request's command and arguments take
+    /// up, including the length prefix of each argument.
+    ///
+    /// Any idempotency token isn't included. This is useful for rejecting or
+    /// throttling large requests after they've been parsed but before they're
+    /// dispatched.
+    ///
+    /// This is an `O(1)` time complexity operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::command::{request::RequestBuilder, CommandId};
+    ///
+    /// let mut builder = RequestBuilder::new(CommandId::Echo);
+    /// builder.args(vec!["foo", "barbaz"])?;
+    /// let req = builder.into_request();
+    ///
+    /// // command ID, argument count, and two arguments with their lengths
+    /// assert_eq!(1 + 1 + (4 + 3) + (4 + 6), req.estimated_size());
+    /// # Ok::<_, hop_engine::command::request::RequestBuilderError>(())
+    /// ```
+    pub fn estimated_size(&self) -> usize {
+        self.buf.len() - self.start
+    }
+
+    /// Returns the idempotency token of the request, if any.
+    ///
+    /// Refer to [`RequestBuilder::token`] for how tokens are used.
+    ///
+    /// [`RequestBuilder::token`]: struct.RequestBuilder.html#method.token
+    pub fn token(&self) -> Option<&[u8]> {
+        if self.start == 0 {
+            return None;
+        }
+
+        self.buf.get(2..self.start)
+    }
+
     // pub fn into_args(mut self) -> Option<Vec<Vec<u8>>> {
     //     self.args.take()
     // }
@@ -228,11 +310,11 @@ impl From<RequestBuilder> for Request<'_> {
 #[cfg(test)]
 mod tests {
     use super::{super::CommandId, Request, RequestBuilder};
-    use crate::state::KeyType;
+    use crate::state::{KeyType, SortedSet};
     use core::fmt::Debug;
     use static_assertions::assert_impl_all;
 
-    assert_impl_all!(Request: Debug);
+    assert_impl_all!(Request: Clone, Debug, Eq, PartialEq);
 
     #[test]
     fn test_request_into_bytes_simple() {
@@ -259,6 +341,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_clone_eq() {
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Append, KeyType::List);
+        assert!(builder.args(vec!["foo", "bar"]).is_ok());
+        let req = builder.into_request();
+        let cloned = req.clone();
+
+        assert_eq!(req, cloned);
+        assert_eq!(Some(KeyType::List), cloned.key_type());
+        assert_eq!(
+            req.args(..).unwrap().collect::<Vec<_>>(),
+            cloned.args(..).unwrap().collect::<Vec<_>>()
+        );
+        assert_ne!(req, RequestBuilder::new(CommandId::Append).into_request());
+    }
+
+    #[test]
+    fn test_estimated_size() {
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::Bytes);
+        assert!(builder.args(vec!["foo", "barbaz"]).is_ok());
+        let req = builder.into_request();
+
+        let args_len = req.args(..).unwrap().map(<[u8]>::len).sum::<usize>();
+        // command ID, key type, argument count, and the length of each argument
+        let overhead = 1 + 1 + 1 + 4 * req.arg_count();
+        assert_eq!(args_len + overhead, req.estimated_size());
+
+        // a token isn't counted
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::Bytes);
+        assert!(builder.token(b"retry".as_ref()).is_ok());
+        assert!(builder.args(vec!["foo", "barbaz"]).is_ok());
+        assert_eq!(args_len + overhead, builder.into_request().estimated_size());
+
+        let req = RequestBuilder::new(CommandId::Stats).into_request();
+        assert_eq!(1, req.estimated_size());
+    }
+
     #[test]
     fn test_args() {
         let mut builder = RequestBuilder::new(CommandId::Decrement);
@@ -284,6 +403,44 @@ mod tests {
         assert_eq!(Some(b"baz".as_ref()), args.next());
     }
 
+    #[test]
+    fn test_args_len() {
+        let mut builder = RequestBuilder::new(CommandId::Echo);
+        assert!(builder.args(vec!["foo", "bar", "baz"]).is_ok());
+        let req = builder.into_request();
+
+        let mut args = req.args(1..).unwrap();
+        assert_eq!(2, args.len());
+        assert!(args.next().is_some());
+        assert_eq!((1, Some(1)), args.size_hint());
+
+        // ranges past the end are clamped to the number of arguments
+        assert_eq!(3, req.args(..10).unwrap().len());
+        assert_eq!(0, req.args(5..).unwrap().len());
+    }
+
+    #[test]
+    fn test_typed_args_sorted_set() {
+        let mut builder = RequestBuilder::new(CommandId::Set);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(2f64.to_be_bytes().to_vec()).is_ok());
+        assert!(builder.bytes(b"a".as_ref()).is_ok());
+        assert!(builder.bytes(1f64.to_be_bytes().to_vec()).is_ok());
+        assert!(builder.bytes(b"b".as_ref()).is_ok());
+        let req = builder.into_request();
+
+        let set = req.typed_args::<SortedSet>().unwrap();
+        assert_eq!(Some(2.0), set.score(b"a"));
+        assert_eq!(Some(1.0), set.score(b"b"));
+
+        // a score without a member
+        let mut builder = RequestBuilder::new(CommandId::Set);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(1f64.to_be_bytes().to_vec()).is_ok());
+        let req = builder.into_request();
+        assert!(req.typed_args::<SortedSet>().is_none());
+    }
+
     #[test]
     fn test_request_into_bytes_echo() {
         let mut builder = RequestBuilder::new(CommandId::Echo);
@@ -43,6 +43,7 @@ enum Stage {
     /// The type is known, and now the length of the argument(s) is being read
     /// for the following types:
     ///
+    /// - array
     /// - bytes
     /// - list
     /// - map
@@ -52,6 +53,10 @@ enum Stage {
         kind: ResponseType,
         read_len: usize,
     },
+    Array {
+        items: Vec<Response>,
+        len: u16,
+    },
     Boolean,
     Bytes {
         len: u32,
@@ -63,7 +68,7 @@ enum Stage {
         len: u16,
     },
     Map {
-        map: DashMap<Vec<u8>, Vec<u8>>,
+        map: DashMap<Vec<u8>, Value>,
         len: u16,
     },
     DispatchError,
@@ -99,6 +104,7 @@ impl Context {
             let instruction = match self.stage {
                 Stage::Init => self.stage_init(buf)?,
                 Stage::DetermineType => self.stage_determine_type(buf)?,
+                Stage::Array { .. } => self.stage_array(buf)?,
                 Stage::Boolean => self.stage_boolean(buf)?,
                 Stage::Bytes { len } => self.stage_bytes(buf, len)?,
                 Stage::Float => self.stage_float(buf)?,
@@ -162,16 +168,22 @@ impl Context {
 
         let kind = ResponseType::try_from(byte).map_err(|_| ParseError::ResponseTypeInvalid)?;
 
+        // A nil is only its type.
+        if kind == ResponseType::Nil {
+            return Ok(Some(Instruction::Concluded(Response::Nil)));
+        }
+
         self.stage = match kind {
             ResponseType::Boolean => Stage::Boolean,
             ResponseType::Float => Stage::Float,
             ResponseType::Integer => Stage::Integer,
-            ResponseType::List | ResponseType::Map | ResponseType::Set => {
+            ResponseType::Array | ResponseType::List | ResponseType::Map | ResponseType::Set => {
                 Stage::TypeInit { kind, read_len: 2 }
             }
             ResponseType::Bytes | ResponseType::String => Stage::TypeInit { kind, read_len: 4 },
             ResponseType::DispatchError => Stage::DispatchError,
             ResponseType::ParseError => Stage::ParseError,
+            ResponseType::Nil => unreachable!(),
         };
 
         self.idx += 1;
@@ -179,6 +191,41 @@ impl Context {
         Ok(None)
     }
 
+    fn stage_array(&mut self, buf: &[u8]) -> Result<Option<Instruction>, ParseError> {
+        debug_assert!(self.idx > 6);
+        debug_assert!(buf.len() > 6);
+
+        // Each element is a whole response, so it's decoded by its own
+        // context.
+        let item = match Context::new().feed(&buf[self.idx..])? {
+            Instruction::Concluded(item) => item,
+            Instruction::ReadBytes(amount) => return Ok(Some(Instruction::ReadBytes(amount))),
+        };
+
+        // The element's context already checked that its length prefix is
+        // there and that it's complete.
+        let item_len = u32::from_be_bytes(buf[self.idx..self.idx + 4].try_into().unwrap());
+        let item_end = self.idx + 4 + item_len as usize;
+
+        match self.stage {
+            Stage::Array { ref mut items, len } => {
+                items.push(item);
+
+                if items.len() < len as usize {
+                    self.idx = item_end;
+
+                    return Ok(None);
+                }
+            }
+            _ => unreachable!(),
+        };
+
+        match mem::take(&mut self.stage) {
+            Stage::Array { items, .. } => Ok(Some(Instruction::Concluded(Response::Array(items)))),
+            _ => unreachable!(),
+        }
+    }
+
     fn stage_boolean(&mut self, buf: &[u8]) -> Result<Option<Instruction>, ParseError> {
         debug_assert_eq!(self.idx, 5);
 
@@ -317,31 +364,21 @@ impl Context {
             }
         };
 
-        let value_size_end = key_value_end + 4;
-
-        let value_len = match buf.get(key_value_end..value_size_end) {
-            Some(value_len) => u32::from_be_bytes(value_len.try_into().unwrap()),
-            None => {
-                let remaining = remaining_bytes(self.idx, buf.len(), value_size_end);
-
-                return Ok(Some(Instruction::ReadBytes(remaining)));
-            }
+        // Like array elements, each value is a whole response decoded by its
+        // own context.
+        let value = match Context::new().feed(&buf[key_value_end..])? {
+            Instruction::Concluded(Response::Value(value)) => value,
+            Instruction::Concluded(_) => return Err(ParseError::ResponseTypeInvalid),
+            Instruction::ReadBytes(amount) => return Ok(Some(Instruction::ReadBytes(amount))),
         };
 
-        let value_end = value_size_end + value_len as usize;
-
-        let value = match buf.get(value_size_end..value_end) {
-            Some(value) => value,
-            None => {
-                let remaining = remaining_bytes(value_size_end, buf.len(), value_end);
-
-                return Ok(Some(Instruction::ReadBytes(remaining)));
-            }
-        };
+        let value_len =
+            u32::from_be_bytes(buf[key_value_end..key_value_end + 4].try_into().unwrap());
+        let value_end = key_value_end + 4 + value_len as usize;
 
         match self.stage {
             Stage::Map { ref map, len } => {
-                map.insert(key.to_vec(), value.to_vec());
+                map.insert(key.to_vec(), value);
 
                 if map.len() < len as usize {
                     self.idx = value_end;
@@ -387,14 +424,36 @@ impl Context {
         debug_assert_eq!(bytes.len(), read_len);
 
         self.stage = match kind {
+            ResponseType::Array => {
+                let len = u16::from_be_bytes(bytes.try_into().unwrap());
+
+                if len == 0 {
+                    return Ok(Some(Instruction::Concluded(Response::Array(Vec::new()))));
+                }
+
+                Stage::Array {
+                    items: Vec::new(),
+                    len,
+                }
+            }
             ResponseType::Bytes => {
                 let len = u32::from_be_bytes(bytes.try_into().unwrap());
 
+                if len == 0 {
+                    return Ok(Some(Instruction::Concluded(Response::Value(Value::Bytes(
+                        Vec::new(),
+                    )))));
+                }
+
                 Stage::Bytes { len }
             }
             ResponseType::List => {
                 let len = u16::from_be_bytes(bytes.try_into().unwrap());
 
+                if len == 0 {
+                    return Ok(Some(Instruction::Concluded(Response::Value(Value::list()))));
+                }
+
                 Stage::List {
                     args: Vec::new(),
                     len,
@@ -404,7 +463,7 @@ impl Context {
                 let len = u16::from_be_bytes(bytes.try_into().unwrap());
 
                 if len == 0 {
-                    return Ok(Some(Instruction::Concluded(Response::from(DashMap::new()))));
+                    return Ok(Some(Instruction::Concluded(Response::Value(Value::map()))));
                 }
 
                 Stage::Map {
@@ -415,6 +474,10 @@ impl Context {
             ResponseType::Set => {
                 let len = u16::from_be_bytes(bytes.try_into().unwrap());
 
+                if len == 0 {
+                    return Ok(Some(Instruction::Concluded(Response::Value(Value::set()))));
+                }
+
                 Stage::Set {
                     args: DashSet::new(),
                     len,
@@ -436,6 +499,7 @@ impl Context {
             | ResponseType::DispatchError
             | ResponseType::Float
             | ResponseType::Integer
+            | ResponseType::Nil
             | ResponseType::ParseError => {
                 unreachable!();
             }
@@ -540,6 +604,7 @@ mod tests {
         Context, Instruction, ParseError, Stage,
     };
     use crate::{command::DispatchError, state::Value};
+    use alloc::{borrow::ToOwned, vec};
     use core::{fmt::Debug, hash::Hash};
     use static_assertions::assert_impl_all;
 
@@ -588,6 +653,15 @@ mod tests {
             Ok(Instruction::Concluded(Response::Value(Value::Bytes(x)))) if x == [2, 3, 0]),);
     }
 
+    #[test]
+    fn test_bytes_empty() {
+        let mut ctx = Context::new();
+        let buf = [0, 0, 0, 5, ResponseType::Bytes as u8, 0, 0, 0, 0];
+        assert!(
+            matches!(ctx.feed(&buf), Ok(Instruction::Concluded(Response::Value(Value::Bytes(x)))) if x.is_empty())
+        );
+    }
+
     #[test]
     fn test_req_dispatch_error_unfinished() {
         let mut ctx = Context::new();
@@ -694,6 +768,43 @@ mod tests {
             Ok(Instruction::Concluded(Response::Value(Value::List(list)))) if list == [b"foo", b"bar"]));
     }
 
+    #[test]
+    fn test_array() {
+        let mut ctx = Context::new();
+        let mut buf = Response::from(vec![
+            Response::from("foo".to_owned()),
+            Response::nil(),
+            Response::from(vec![Response::from(2)]),
+        ])
+        .as_bytes();
+        let last = buf.pop().unwrap();
+        assert!(matches!(ctx.feed(&buf), Ok(Instruction::ReadBytes(1))));
+
+        buf.push(last);
+
+        match ctx.feed(&buf) {
+            Ok(Instruction::Concluded(Response::Array(items))) => {
+                assert_eq!(3, items.len());
+                assert_eq!(Some("foo"), items[0].as_str());
+                assert!(items[1].is_nil());
+                assert_eq!(
+                    Some(2),
+                    items[2].as_array().and_then(|inner| inner[0].as_int())
+                );
+            }
+            other => panic!("unexpected instruction: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_array_empty() {
+        let mut ctx = Context::new();
+        let buf = [0, 0, 0, 3, ResponseType::Array as u8, 0, 0];
+        assert!(
+            matches!(ctx.feed(&buf), Ok(Instruction::Concluded(Response::Array(items))) if items.is_empty())
+        );
+    }
+
     #[test]
     fn test_map() {
         let mut ctx = Context::new();
@@ -701,7 +812,7 @@ mod tests {
             0,
             0,
             0,
-            15,
+            20,
             ResponseType::Map as u8,
             // item count
             0,
@@ -712,23 +823,54 @@ mod tests {
             b'f',
             b'o',
             b'o',
-            // item 1 value len
+            // item 1 value, a whole response
+            0,
+            0,
+            0,
+            9,
+            ResponseType::Integer as u8,
+            0,
+            0,
+            0,
+            0,
             0,
             0,
             0,
             4,
-            // item 1 value
-            b'b',
-            b'a',
-            b'r',
-            b'!',
         ]
         .to_vec();
         assert!(
-            matches!(ctx.feed(&buf), Ok(Instruction::Concluded(Response::Value(Value::Map(map)))) if map.len() == 1)
+            matches!(ctx.feed(&buf), Ok(Instruction::Concluded(Response::Value(Value::Map(map)))) if map.len() == 1 && map.get(b"foo".as_ref()).unwrap().as_integer_ref() == Some(&4))
         );
     }
 
+    #[test]
+    fn test_map_value_error() {
+        let mut ctx = Context::new();
+        let buf = [
+            0,
+            0,
+            0,
+            11,
+            ResponseType::Map as u8,
+            // item count
+            0,
+            1,
+            // item 1 key len
+            1,
+            // item 1 key
+            b'f',
+            // item 1 value, an error rather than a value
+            0,
+            0,
+            0,
+            2,
+            ResponseType::DispatchError as u8,
+            0,
+        ];
+        assert_eq!(ParseError::ResponseTypeInvalid, ctx.feed(&buf).unwrap_err());
+    }
+
     #[test]
     fn test_map_no_items() {
         let mut ctx = Context::new();
@@ -3,11 +3,47 @@ mod context;
 pub use context::{Context, Instruction, ParseError};
 
 use super::{request::ParseError as RequestParseError, DispatchError};
-use crate::state::Value;
-use alloc::{string::String, vec::Vec};
-use core::convert::TryFrom;
+use crate::state::{IntSet, Value};
+use alloc::{collections::VecDeque, string::String, vec::Vec};
+use core::{
+    borrow::Borrow,
+    convert::TryFrom,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
 use dashmap::{DashMap, DashSet};
 
+/// The maximum number of items in an array, list, map, or set response, since
+/// the item count is written as 2 bytes.
+///
+/// Writing a response with more items writes a
+/// [`DispatchError::ResponseTooLarge`] instead.
+///
+/// [`DispatchError::ResponseTooLarge`]: ../enum.DispatchError.html#variant.ResponseTooLarge
+pub const MAX_ITEMS: usize = u16::MAX as usize;
+
+/// An error decoding a response via [`Response::parse`].
+///
+/// [`Response::parse`]: enum.Response.html#method.parse
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DecodeError {
+    /// The bytes end before the response does.
+    Incomplete,
+    /// The bytes aren't a valid response.
+    Invalid(ParseError),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Incomplete => f.write_str("the response is incomplete"),
+            Self::Invalid(why) => write!(f, "the response is invalid: {:?}", why),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
+
 /// The type of response value.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 #[repr(u8)]
@@ -22,6 +58,8 @@ pub enum ResponseType {
     String = 7,
     ParseError = 8,
     DispatchError = 9,
+    Nil = 10,
+    Array = 11,
 }
 
 impl TryFrom<u8> for ResponseType {
@@ -39,19 +77,119 @@ impl TryFrom<u8> for ResponseType {
             7 => Self::String,
             8 => Self::ParseError,
             9 => Self::DispatchError,
+            10 => Self::Nil,
+            11 => Self::Array,
             _ => return Err(()),
         })
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Response {
+    /// An array of responses, each of which may be of a different type, nil, or
+    /// another array.
+    Array(Vec<Response>),
     DispatchError(DispatchError),
+    /// There's no value, such as for a key that doesn't exist.
+    Nil,
     ParseError(RequestParseError),
     Value(Value),
 }
 
 impl Response {
+    /// Create a response signalling that there's no value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::command::Response;
+    ///
+    /// let bytes = Response::nil().as_bytes();
+    ///
+    /// assert!(Response::parse(&bytes)?.is_nil());
+    /// # Ok::<_, hop_engine::command::response::DecodeError>(())
+    /// ```
+    pub fn nil() -> Self {
+        Self::Nil
+    }
+
+    /// Whether the response signals that there's no value.
+    pub fn is_nil(&self) -> bool {
+        matches!(self, Self::Nil)
+    }
+
+    /// Decode the response at the start of a buffer, which must contain the
+    /// whole response including its length prefix.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecodeError::Incomplete`] if the buffer ends before the
+    /// response does.
+    ///
+    /// Returns [`DecodeError::Invalid`] if the buffer isn't a valid response.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::command::Response;
+    ///
+    /// let bytes = Response::from(123).as_bytes();
+    ///
+    /// assert_eq!(Some(123), Response::parse(&bytes)?.as_int());
+    /// # Ok::<_, hop_engine::command::response::DecodeError>(())
+    /// ```
+    ///
+    /// [`DecodeError::Incomplete`]: enum.DecodeError.html#variant.Incomplete
+    /// [`DecodeError::Invalid`]: enum.DecodeError.html#variant.Invalid
+    pub fn parse(bytes: &[u8]) -> Result<Self, DecodeError> {
+        match Context::new().feed(bytes) {
+            Ok(Instruction::Concluded(response)) => Ok(response),
+            Ok(Instruction::ReadBytes(_)) => Err(DecodeError::Incomplete),
+            Err(why) => Err(DecodeError::Invalid(why)),
+        }
+    }
+
+    /// Retrieve the elements if the response is an array.
+    pub fn as_array(&self) -> Option<&[Response]> {
+        match self {
+            Self::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Retrieve the value if the response is a boolean.
+    pub fn as_bool(&self) -> Option<bool> {
+        self.value()?.as_boolean_ref().copied()
+    }
+
+    /// Retrieve the value if the response is an integer.
+    pub fn as_int(&self) -> Option<i64> {
+        self.value()?.as_integer_ref().copied()
+    }
+
+    /// Retrieve the value if the response is a list.
+    pub fn as_list(&self) -> Option<&VecDeque<Vec<u8>>> {
+        self.value()?.as_list_ref()
+    }
+
+    /// Retrieve the value if the response is a map.
+    pub fn as_map(&self) -> Option<&DashMap<Vec<u8>, Value>> {
+        self.value()?.as_map_ref()
+    }
+
+    /// Retrieve the value if the response is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        self.value()?.as_string_ref()
+    }
+
+    /// Retrieve the value if the response isn't an error or nil.
+    pub fn value(&self) -> Option<&Value> {
+        match self {
+            Self::Value(value) => Some(value),
+            Self::Array(_) | Self::DispatchError(_) | Self::Nil | Self::ParseError(_) => None,
+        }
+    }
+
     pub fn as_bytes(&self) -> Vec<u8> {
         let mut buf = Vec::new();
         self.copy_to(&mut buf);
@@ -61,7 +199,9 @@ impl Response {
 
     pub fn copy_to(&self, buf: &mut Vec<u8>) {
         match self {
+            Self::Array(items) => write_array(buf, items),
             Self::DispatchError(err) => write_dispatch_error(buf, *err),
+            Self::Nil => write_nil(buf),
             Self::ParseError(err) => write_parse_error(buf, *err),
             Self::Value(value) => write_value(buf, value),
         }
@@ -94,13 +234,20 @@ impl From<i64> for Response {
 
 impl From<Vec<Vec<u8>>> for Response {
     fn from(value: Vec<Vec<u8>>) -> Self {
-        Self::Value(Value::List(value))
+        Self::Value(Value::List(value.into()))
+    }
+}
+
+impl From<DashMap<Vec<u8>, Value>> for Response {
+    fn from(value: DashMap<Vec<u8>, Value>) -> Self {
+        Self::Value(Value::Map(value))
     }
 }
 
+/// Create a map whose values are bytes.
 impl From<DashMap<Vec<u8>, Vec<u8>>> for Response {
     fn from(value: DashMap<Vec<u8>, Vec<u8>>) -> Self {
-        Self::Value(Value::Map(value))
+        Self::Value(Value::from(value))
     }
 }
 
@@ -116,6 +263,18 @@ impl From<String> for Response {
     }
 }
 
+impl From<Vec<Response>> for Response {
+    fn from(value: Vec<Response>) -> Self {
+        Self::Array(value)
+    }
+}
+
+impl From<Vec<Value>> for Response {
+    fn from(value: Vec<Value>) -> Self {
+        Self::Array(value.into_iter().map(Self::Value).collect())
+    }
+}
+
 impl From<DispatchError> for Response {
     fn from(value: DispatchError) -> Self {
         Self::DispatchError(value)
@@ -128,6 +287,40 @@ impl From<RequestParseError> for Response {
     }
 }
 
+/// Write an array of responses.
+///
+/// Each element is written as a whole response, including its length prefix
+/// and type, so elements may be of different types, nil, or arrays
+/// themselves.
+pub fn write_array<T: IntoIterator<Item = U>, U: Borrow<Response>>(to: &mut Vec<u8>, value: T) {
+    // Like lists, the response and element count are pre-written as 0 and
+    // written over once the elements have been written.
+    let start = to.len();
+    to.extend_from_slice(&[0, 0, 0, 0]);
+    to.push(ResponseType::Array as u8);
+    to.extend_from_slice(&[0, 0]);
+
+    let mut item_count = 0usize;
+
+    for item in value {
+        item_count += 1;
+
+        if item_count > MAX_ITEMS {
+            to.truncate(start);
+            write_dispatch_error(to, DispatchError::ResponseTooLarge);
+
+            return;
+        }
+
+        item.borrow().copy_to(to);
+    }
+
+    let msg_len = (to.len() - start - 4) as u32;
+
+    to[start..start + 4].clone_from_slice(&msg_len.to_be_bytes());
+    to[start + 5..start + 7].clone_from_slice(&(item_count as u16).to_be_bytes());
+}
+
 pub fn write_bool(to: &mut Vec<u8>, value: bool) {
     // kind + 1 byte bool
     to.extend_from_slice(&2u32.to_be_bytes());
@@ -153,6 +346,12 @@ pub fn write_dispatch_error(to: &mut Vec<u8>, value: DispatchError) {
     to.push(value as u8);
 }
 
+pub fn write_nil(to: &mut Vec<u8>) {
+    // kind
+    to.extend_from_slice(&1u32.to_be_bytes());
+    to.push(ResponseType::Nil as u8);
+}
+
 pub fn write_parse_error(to: &mut Vec<u8>, value: RequestParseError) {
     // kind + 1 byte error
     to.extend_from_slice(&2u32.to_be_bytes());
@@ -197,12 +396,21 @@ pub fn write_list<T: IntoIterator<Item = U>, U: AsRef<[u8]>>(to: &mut Vec<u8>, v
     to.push(0);
     to.push(0);
 
-    let mut item_count = 0u16;
+    let mut item_count = 0usize;
 
     // Now for each list item, push its length and then the item itself.
     for item in value {
         item_count += 1;
 
+        // The items written so far are thrown away, since the count can't be
+        // known without iterating.
+        if item_count > MAX_ITEMS {
+            to.truncate(start);
+            write_dispatch_error(to, DispatchError::ResponseTooLarge);
+
+            return;
+        }
+
         let item = item.as_ref();
 
         let item_len = item.len() as u32;
@@ -216,24 +424,22 @@ pub fn write_list<T: IntoIterator<Item = U>, U: AsRef<[u8]>>(to: &mut Vec<u8>, v
     let msg_len_bytes = msg_len.to_be_bytes();
 
     to[start..start + 4].clone_from_slice(&msg_len_bytes[..4]);
-    to[start + 5..start + 7].clone_from_slice(&item_count.to_be_bytes());
+    to[start + 5..start + 7].clone_from_slice(&(item_count as u16).to_be_bytes());
 }
 
-pub fn write_map(to: &mut Vec<u8>, value: &DashMap<Vec<u8>, Vec<u8>>) {
-    {
-        // kind + 2 byte map size
-        let mut response_len: u32 = 1 + 2;
-
-        for item in value.iter() {
-            let (key, value) = item.pair();
-
-            // key len + key bytes len + value len + value bytes len
-            response_len += 1 + key.len() as u32 + 4 + value.len() as u32;
-        }
+/// Write a map, with each field's value written as a whole response, including
+/// its length prefix and type, so that fields may be of different types.
+pub fn write_map(to: &mut Vec<u8>, value: &DashMap<Vec<u8>, Value>) {
+    if value.len() > MAX_ITEMS {
+        write_dispatch_error(to, DispatchError::ResponseTooLarge);
 
-        to.extend_from_slice(&response_len.to_be_bytes());
+        return;
     }
 
+    // Like lists, the response length is pre-written as 0 and written over
+    // once the fields have been written.
+    let start = to.len();
+    to.extend_from_slice(&[0, 0, 0, 0]);
     to.push(ResponseType::Map as u8);
 
     // Maps can only contain up to u16 items.
@@ -242,17 +448,23 @@ pub fn write_map(to: &mut Vec<u8>, value: &DashMap<Vec<u8>, Vec<u8>>) {
     for item in value.iter() {
         let (key, value) = item.pair();
 
-        let key_len = key.len() as u8;
-        let value_len = value.len() as u32;
-
-        to.push(key_len);
+        to.push(key.len() as u8);
         to.extend_from_slice(key);
-        to.extend_from_slice(&value_len.to_be_bytes());
-        to.extend_from_slice(value);
+        write_value(to, value);
     }
+
+    let msg_len = (to.len() - start - 4) as u32;
+
+    to[start..start + 4].clone_from_slice(&msg_len.to_be_bytes());
 }
 
 pub fn write_set(to: &mut Vec<u8>, value: &DashSet<Vec<u8>>) {
+    if value.len() > MAX_ITEMS {
+        write_dispatch_error(to, DispatchError::ResponseTooLarge);
+
+        return;
+    }
+
     {
         // kind + 2 byte set size
         let mut response_len: u32 = 1 + 2;
@@ -278,6 +490,32 @@ pub fn write_set(to: &mut Vec<u8>, value: &DashSet<Vec<u8>>) {
     }
 }
 
+/// Write a packed set of integers, which is written the same as a set of its
+/// members.
+pub fn write_int_set(to: &mut Vec<u8>, value: &IntSet) {
+    let members = value.members().collect::<Vec<_>>();
+
+    if members.len() > MAX_ITEMS {
+        write_dispatch_error(to, DispatchError::ResponseTooLarge);
+
+        return;
+    }
+
+    // kind + 2 byte set size + item len and item bytes for each member
+    let response_len = 1 + 2 + members.iter().map(|member| 2 + member.len()).sum::<usize>();
+    to.extend_from_slice(&(response_len as u32).to_be_bytes());
+
+    to.push(ResponseType::Set as u8);
+
+    // Sets can only contain up to u16 items.
+    to.extend_from_slice(&(members.len() as u16).to_be_bytes());
+
+    for member in members {
+        to.extend_from_slice(&(member.len() as u16).to_be_bytes());
+        to.extend_from_slice(&member);
+    }
+}
+
 pub fn write_str(to: &mut Vec<u8>, value: &str) {
     let len = value.len() as u32;
 
@@ -296,25 +534,32 @@ pub fn write_value(to: &mut Vec<u8>, value: &Value) {
         Value::Bytes(bytes) => write_bytes(to, bytes),
         Value::Float(float) => write_float(to, *float),
         Value::Integer(int) => write_int(to, *int),
+        Value::IntSet(set) => write_int_set(to, set),
         Value::List(list) => write_list(to, list),
         Value::Map(map) => write_map(to, map),
         Value::Set(set) => write_set(to, set),
+        Value::SortedSet(set) => write_list(to, set.iter().map(|(member, _)| member)),
         Value::String(string) => write_str(to, string),
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Response, ResponseType};
-    use crate::state::Value;
-    use alloc::{borrow::ToOwned, string::String, vec::Vec};
+    use super::{
+        Context, DecodeError, Instruction, ParseError, RequestParseError, Response, ResponseType,
+        MAX_ITEMS,
+    };
+    use crate::{command::DispatchError, state::Value};
+    use alloc::{borrow::ToOwned, string::String, vec, vec::Vec};
     use core::{fmt::Debug, hash::Hash};
     use dashmap::{DashMap, DashSet};
     use static_assertions::assert_impl_all;
 
     assert_impl_all!(ResponseType: Clone, Copy, Debug, Eq, Hash, PartialEq);
     assert_impl_all!(
-        Response: Debug,
+        Response: Clone,
+        Debug,
+        PartialEq,
         From<bool>,
         From<Vec<u8>>,
         From<f64>,
@@ -323,8 +568,179 @@ mod tests {
         From<DashMap<Vec<u8>, Vec<u8>>>,
         From<DashSet<Vec<u8>>>,
         From<String>,
+        From<Vec<Response>>,
+        From<Vec<Value>>,
     );
 
+    fn round_trip(resp: Response) {
+        assert_eq!(Ok(resp.clone()), Response::parse(&resp.as_bytes()));
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        let map = DashMap::new();
+        map.insert(b"foo".to_vec(), b"bar".to_vec());
+        let set = DashSet::new();
+        set.insert(b"foo".to_vec());
+
+        round_trip(Response::from(true));
+        round_trip(Response::from(b"foo".to_vec()));
+        round_trip(Response::from(Vec::<u8>::new()));
+        round_trip(Response::from(1.5));
+        round_trip(Response::from(-7));
+        round_trip(Response::from(vec![b"foo".to_vec(), b"bar".to_vec()]));
+        round_trip(Response::from(Vec::<Vec<u8>>::new()));
+        round_trip(Response::from(map));
+        round_trip(Response::from(DashMap::<Vec<u8>, Value>::new()));
+        round_trip(Response::from(set));
+        round_trip(Response::from(DashSet::new()));
+        round_trip(Response::from("foo".to_owned()));
+        round_trip(Response::from(String::new()));
+        round_trip(Response::from(DispatchError::KeyNonexistent));
+        round_trip(Response::from(RequestParseError::CommandIdInvalid));
+        round_trip(Response::nil());
+    }
+
+    #[test]
+    fn test_map_mixed_round_trip() {
+        let nested = DashMap::new();
+        nested.insert(b"a".to_vec(), Value::Boolean(true));
+
+        let map = DashMap::new();
+        map.insert(b"bytes".to_vec(), Value::Bytes(b"bar".to_vec()));
+        map.insert(b"empty".to_vec(), Value::Bytes(Vec::new()));
+        map.insert(b"int".to_vec(), Value::Integer(-7));
+        map.insert(b"list".to_vec(), Value::from(vec![b"x".to_vec()]));
+        map.insert(b"map".to_vec(), Value::Map(nested));
+        map.insert(b"string".to_vec(), Value::String("foo".to_owned()));
+
+        round_trip(Response::from(map));
+    }
+
+    #[test]
+    fn test_array_round_trip() {
+        round_trip(Response::from(vec![
+            Response::from(1),
+            Response::from("foo".to_owned()),
+            Response::nil(),
+            Response::from(-2),
+            Response::from(String::new()),
+            Response::nil(),
+        ]));
+        round_trip(Response::from(vec![
+            Value::Integer(3),
+            Value::String("bar".to_owned()),
+        ]));
+        round_trip(Response::from(Vec::<Response>::new()));
+
+        // arrays can be nested
+        round_trip(Response::from(vec![
+            Response::from(vec![Response::nil(), Response::from(4)]),
+            Response::from(Vec::<Response>::new()),
+            Response::from(DispatchError::KeyNonexistent),
+        ]));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let bytes = Response::from(b"foo".to_vec()).as_bytes();
+        assert_eq!(
+            Err(DecodeError::Incomplete),
+            Response::parse(&bytes[..bytes.len() - 1])
+        );
+        assert_eq!(
+            Err(DecodeError::Invalid(ParseError::ResponseTypeInvalid)),
+            Response::parse(&[0, 0, 0, 1, 255])
+        );
+    }
+
+    #[test]
+    fn test_accessors() {
+        assert_eq!(Some(true), Response::from(true).as_bool());
+        assert_eq!(Some(3), Response::from(3).as_int());
+        assert_eq!(Some("foo"), Response::from("foo".to_owned()).as_str());
+        assert_eq!(
+            Some(1),
+            Response::from(vec![b"foo".to_vec()])
+                .as_list()
+                .map(|l| l.len())
+        );
+        assert!(Response::from(DashMap::<Vec<u8>, Value>::new())
+            .as_map()
+            .is_some());
+
+        // accessors of other types and errors are none
+        assert!(Response::from(3).as_bool().is_none());
+        assert!(Response::from(true).as_int().is_none());
+        assert!(Response::from(DispatchError::KeyNonexistent)
+            .value()
+            .is_none());
+        assert!(Response::nil().value().is_none());
+        assert!(Response::nil().is_nil());
+        assert!(!Response::from(false).is_nil());
+    }
+
+    #[test]
+    fn test_nil() {
+        assert_eq!(
+            [0, 0, 0, 1, 10].as_ref(),
+            Response::nil().as_bytes().as_slice()
+        );
+    }
+
+    #[test]
+    fn test_clone_eq() {
+        let resp = Response::from(vec![b"foo".to_vec(), b"bar".to_vec()]);
+        let decoded = match Context::new().feed(&resp.as_bytes()) {
+            Ok(Instruction::Concluded(decoded)) => decoded,
+            other => panic!("unexpected instruction: {:?}", other),
+        };
+
+        assert_eq!(resp.clone(), decoded);
+        assert_ne!(resp, Response::from(DispatchError::KeyNonexistent));
+    }
+
+    #[test]
+    fn test_array() {
+        let resp = Response::from(vec![Response::from(7), Response::nil()]);
+
+        assert_eq!(
+            resp.as_bytes(),
+            [
+                0,
+                0,
+                0,
+                21,
+                ResponseType::Array as u8,
+                // number of elements
+                0,
+                2,
+                // first element, a whole integer response
+                0,
+                0,
+                0,
+                9,
+                ResponseType::Integer as u8,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                7,
+                // second element, a whole nil response
+                0,
+                0,
+                0,
+                1,
+                ResponseType::Nil as u8,
+            ],
+        );
+        assert_eq!(Some(2), resp.as_array().map(<[_]>::len));
+        assert!(resp.value().is_none());
+    }
+
     #[test]
     fn test_bool() {
         assert_eq!(
@@ -462,9 +878,7 @@ mod tests {
 
     #[test]
     fn test_list() {
-        let mut list = Vec::new();
-        list.push(b"hop".to_vec());
-        list.push(b"db".to_vec());
+        let list = vec![b"hop".to_vec(), b"db".to_vec()];
 
         assert_eq!(
             Response::from(list).as_bytes(),
@@ -498,6 +912,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_list_too_large() {
+        let mut buf = vec![1, 2];
+        super::write_list(&mut buf, (0..=MAX_ITEMS).map(|_| b"a"));
+
+        // the items already written are replaced by the error
+        assert_eq!(&[1, 2], &buf[..2]);
+        assert_eq!(
+            Ok(Response::DispatchError(DispatchError::ResponseTooLarge)),
+            Response::parse(&buf[2..]),
+        );
+
+        let mut buf = Vec::new();
+        super::write_list(&mut buf, (0..MAX_ITEMS).map(|_| b"a"));
+        assert_eq!(
+            Some(MAX_ITEMS),
+            Response::parse(&buf)
+                .ok()
+                .and_then(|resp| resp.as_list().map(|list| list.len())),
+        );
+    }
+
+    #[test]
+    fn test_array_too_large() {
+        let items = (0..=MAX_ITEMS).map(|_| Response::Nil).collect::<Vec<_>>();
+
+        assert_eq!(
+            Ok(Response::DispatchError(DispatchError::ResponseTooLarge)),
+            Response::parse(&Response::from(items).as_bytes()),
+        );
+    }
+
+    #[test]
+    fn test_set_too_large() {
+        let set = (0..=MAX_ITEMS as u32)
+            .map(|item| item.to_be_bytes().to_vec())
+            .collect::<DashSet<_>>();
+
+        assert_eq!(
+            Ok(Response::DispatchError(DispatchError::ResponseTooLarge)),
+            Response::parse(&Response::from(set).as_bytes()),
+        );
+    }
+
     #[test]
     fn test_list_empty() {
         let v: Vec<Vec<_>> = Vec::new();
@@ -520,8 +978,8 @@ mod tests {
     #[test]
     fn test_map() {
         let map = DashMap::new();
-        map.insert(b"f".to_vec(), b"foo".to_vec());
-        map.insert(b"123".to_vec(), Vec::new());
+        map.insert(b"f".to_vec(), Value::Bytes(b"foo".to_vec()));
+        map.insert(b"123".to_vec(), Value::Integer(7));
 
         // Ordering can be random, so we need to check if it's one of either of
         // these.
@@ -530,7 +988,7 @@ mod tests {
                 0,
                 0,
                 0,
-                20,
+                34,
                 ResponseType::Map as u8,
                 // length of map (there can be up to u16 items)
                 0,
@@ -541,22 +999,34 @@ mod tests {
                 b'1',
                 b'2',
                 b'3',
-                // length of first value, u32 (nothing)
+                // first value, a whole integer response (7)
+                0,
+                0,
+                0,
+                9,
+                ResponseType::Integer as u8,
+                0,
                 0,
                 0,
                 0,
                 0,
-                // first value (nothing)
+                0,
+                0,
+                7,
                 // length of second key, u8 ("f")
                 1,
                 // second key ("f")
                 b'f',
-                // length of second value, u32 ("foo")
+                // second value, a whole bytes response ("foo")
+                0,
+                0,
+                0,
+                8,
+                ResponseType::Bytes as u8,
                 0,
                 0,
                 0,
                 3,
-                // second value ("foo")
                 b'f',
                 b'o',
                 b'o',
@@ -565,7 +1035,7 @@ mod tests {
                 0,
                 0,
                 0,
-                20,
+                34,
                 ResponseType::Map as u8,
                 // length of map (there can be up to u16 items)
                 0,
@@ -574,12 +1044,16 @@ mod tests {
                 1,
                 // first key ("f")
                 b'f',
-                // length of first value, u32 ("foo")
+                // first value, a whole bytes response ("foo")
+                0,
+                0,
+                0,
+                8,
+                ResponseType::Bytes as u8,
                 0,
                 0,
                 0,
                 3,
-                // first value ("foo")
                 b'f',
                 b'o',
                 b'o',
@@ -589,12 +1063,20 @@ mod tests {
                 b'1',
                 b'2',
                 b'3',
-                // length of second value, u32 (nothing)
+                // second value, a whole integer response (7)
+                0,
+                0,
+                0,
+                9,
+                ResponseType::Integer as u8,
+                0,
+                0,
+                0,
                 0,
                 0,
                 0,
                 0,
-                // second value (nothing)
+                7,
             ],
         ];
 
@@ -606,7 +1088,7 @@ mod tests {
     #[test]
     fn test_map_empty() {
         assert_eq!(
-            Response::from(DashMap::new()).as_bytes(),
+            Response::from(DashMap::<Vec<u8>, Value>::new()).as_bytes(),
             [0, 0, 0, 3, ResponseType::Map as u8, 0, 0]
         );
     }
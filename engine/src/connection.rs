@@ -0,0 +1,118 @@
+//! Tracking of connections to the engine and their traffic.
+//!
+//! Transports create a [`Connection`] via [`Hop::connect`] for each peer and
+//! record the bytes and commands passing through it, which are aggregated
+//! into the engine's metrics.
+//!
+//! [`Connection`]: struct.Connection.html
+//! [`Hop::connect`]: ../hop/struct.Hop.html#method.connect
+
+use crate::{metrics::Metric, Hop};
+use core::sync::atomic::Ordering;
+
+/// An open connection to the engine.
+///
+/// The engine counts the connection as active until this is dropped.
+#[derive(Debug)]
+pub struct Connection {
+    bytes_read: u64,
+    bytes_written: u64,
+    commands: u64,
+    hop: Hop,
+}
+
+impl Connection {
+    pub(crate) fn new(hop: Hop) -> Self {
+        hop.0.connections_active.fetch_add(1, Ordering::SeqCst);
+        hop.0.metrics_writer.increment(Metric::ConnectionsOpened);
+
+        Self {
+            bytes_read: 0,
+            bytes_written: 0,
+            commands: 0,
+            hop,
+        }
+    }
+
+    /// Retrieve the number of bytes read from the peer.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// Retrieve the number of bytes written to the peer.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Retrieve the number of commands processed for the peer.
+    pub fn commands(&self) -> u64 {
+        self.commands
+    }
+
+    /// Record that a command was processed.
+    pub fn record_command(&mut self) {
+        self.commands += 1;
+    }
+
+    /// Record that bytes were read from the peer.
+    pub fn record_read(&mut self, bytes: usize) {
+        self.bytes_read += bytes as u64;
+        self.hop
+            .0
+            .metrics_writer
+            .add(Metric::BytesRead, bytes as u64);
+    }
+
+    /// Record that bytes were written to the peer.
+    pub fn record_written(&mut self, bytes: usize) {
+        self.bytes_written += bytes as u64;
+        self.hop
+            .0
+            .metrics_writer
+            .add(Metric::BytesWritten, bytes as u64);
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.hop.0.connections_active.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Connection;
+    use crate::{metrics::Metric, Hop};
+    use core::fmt::Debug;
+    use static_assertions::assert_impl_all;
+
+    assert_impl_all!(Connection: Debug, Send, Sync);
+
+    #[test]
+    fn test_traffic() {
+        let hop = Hop::new();
+        let mut first = hop.connect();
+        let mut second = hop.connect();
+        assert_eq!(2, hop.connections_active());
+
+        first.record_read(10);
+        first.record_command();
+        first.record_written(4);
+        second.record_read(5);
+
+        assert_eq!(10, first.bytes_read());
+        assert_eq!(4, first.bytes_written());
+        assert_eq!(1, first.commands());
+        assert_eq!(0, second.commands());
+
+        let metrics = hop.metrics();
+        assert_eq!(Some(2), metrics.counter(&Metric::ConnectionsOpened));
+        assert_eq!(Some(15), metrics.counter(&Metric::BytesRead));
+        assert_eq!(Some(4), metrics.counter(&Metric::BytesWritten));
+
+        drop(first);
+        assert_eq!(1, hop.connections_active());
+        drop(second);
+        assert_eq!(0, hop.connections_active());
+    }
+}
@@ -10,28 +10,113 @@
 //! [`Hop`]: struct.Hop.html
 
 use crate::{
-    command::{r#impl::*, CommandId, Dispatch, DispatchResult, Request},
+    command::{
+        command_id::KeyNotation, r#impl::*, CommandId, Dispatch, DispatchError, DispatchResult,
+        Request,
+    },
+    connection::Connection,
+    idempotency::{Claim, IdempotencyCache},
     metrics::{Metric, Metrics, Reader, Writer},
     pubsub::PubSubManager,
     session::SessionManager,
-    state::State,
+    slowlog::{self, SlowLogEntry},
+    state::{self, Clock, EvictionPolicy, State},
 };
 use alloc::{sync::Arc, vec::Vec};
+#[cfg(feature = "log")]
+use core::{
+    ascii,
+    fmt::{Display, Formatter, Result as FmtResult, Write},
+};
+use core::{
+    ptr,
+    sync::atomic::{self, AtomicU64, AtomicUsize, Ordering},
+};
 
 /// Configuration defining how a Hop engine will operate.
 ///
-/// This includes things like enabling or disabling pubsub support.
+/// This includes things like enabling or disabling pubsub support and limiting
+/// memory usage.
 ///
 /// See [`Builder`] for constructing a configured Hop engine.
 ///
 /// [`Builder`]: struct.Builder.html
 #[derive(Clone, Debug)]
 pub struct Config {
+    bytes_len_max: Option<usize>,
+    capacity: usize,
+    clock: Clock,
+    databases: usize,
+    eviction_policy: EvictionPolicy,
+    idempotency_len_max: usize,
+    idempotency_ttl: u64,
+    list_len_max: Option<usize>,
+    log_commands: bool,
+    memory_max: Option<usize>,
     pubsub_enabled: bool,
     sessions_active_max: usize,
+    slowlog_len_max: usize,
+    slowlog_threshold: Option<u64>,
 }
 
 impl Config {
+    /// Retrieve the maximum number of bytes that a bytes or string value may
+    /// hold, if there is a maximum.
+    pub fn bytes_len_max(&self) -> Option<usize> {
+        self.bytes_len_max
+    }
+
+    /// Retrieve the number of keys that database 0 has room for before it
+    /// has to grow.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Retrieve the clock used to determine when keys expire.
+    pub fn clock(&self) -> Clock {
+        self.clock
+    }
+
+    /// Retrieve the number of logical databases, each with its own keyspace.
+    pub fn databases(&self) -> usize {
+        self.databases
+    }
+
+    /// Retrieve the policy for evicting keys when the maximum memory would be
+    /// exceeded.
+    pub fn eviction_policy(&self) -> EvictionPolicy {
+        self.eviction_policy
+    }
+
+    /// Retrieve the maximum number of responses remembered for requests with
+    /// an idempotency token.
+    pub fn idempotency_len_max(&self) -> usize {
+        self.idempotency_len_max
+    }
+
+    /// Retrieve the number of milliseconds that the response to a request
+    /// with an idempotency token is remembered for.
+    pub fn idempotency_ttl(&self) -> u64 {
+        self.idempotency_ttl
+    }
+
+    /// Retrieve the maximum number of items that a list may hold, if there is
+    /// a maximum.
+    pub fn list_len_max(&self) -> Option<usize> {
+        self.list_len_max
+    }
+
+    /// Retrieve whether each dispatched command is logged.
+    pub fn log_commands(&self) -> bool {
+        self.log_commands
+    }
+
+    /// Retrieve the approximate maximum number of bytes that keys and their
+    /// values may use, if there is a maximum.
+    pub fn memory_max(&self) -> Option<usize> {
+        self.memory_max
+    }
+
     /// Retrieve whether pubsub is enabled.
     pub fn pubsub_enabled(&self) -> bool {
         self.pubsub_enabled
@@ -42,13 +127,53 @@ impl Config {
     pub fn sessions_active_max(&self) -> usize {
         self.sessions_active_max
     }
+
+    /// Retrieve the maximum number of entries kept in the slowlog.
+    pub fn slowlog_len_max(&self) -> usize {
+        self.slowlog_len_max
+    }
+
+    /// Retrieve the number of microseconds a command's dispatch must take for
+    /// it to be recorded in the slowlog, if the slowlog is enabled.
+    pub fn slowlog_threshold(&self) -> Option<u64> {
+        self.slowlog_threshold
+    }
+
+    /// Check that a write leaves a bytes or string value within the maximum
+    /// number of bytes.
+    pub(crate) fn check_bytes_len(&self, len: usize) -> DispatchResult<()> {
+        match self.bytes_len_max {
+            Some(max) if len > max => Err(DispatchError::ValueTooLarge),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that a write leaves a list within the maximum number of items.
+    pub(crate) fn check_list_len(&self, len: usize) -> DispatchResult<()> {
+        match self.list_len_max {
+            Some(max) if len > max => Err(DispatchError::ValueTooLarge),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            bytes_len_max: None,
+            capacity: 0,
+            clock: state::system_clock,
+            databases: 16,
+            eviction_policy: EvictionPolicy::default(),
+            idempotency_len_max: 1024,
+            idempotency_ttl: 60_000,
+            list_len_max: None,
+            log_commands: false,
+            memory_max: None,
             pubsub_enabled: true,
             sessions_active_max: usize::MAX,
+            slowlog_len_max: 128,
+            slowlog_threshold: Some(10_000),
         }
     }
 }
@@ -90,6 +215,136 @@ impl Builder {
         self.into()
     }
 
+    /// Set the maximum number of bytes that a bytes or string value may hold.
+    ///
+    /// Writes that would grow a value past the maximum fail with
+    /// [`DispatchError::ValueTooLarge`] and leave the value unchanged.
+    ///
+    /// By default there is no maximum.
+    ///
+    /// [`DispatchError::ValueTooLarge`]: ../command/enum.DispatchError.html#variant.ValueTooLarge
+    pub fn bytes_len_max(&mut self, bytes_len_max: impl Into<Option<usize>>) -> &mut Self {
+        self.0.bytes_len_max = bytes_len_max.into();
+
+        self
+    }
+
+    /// Set the number of keys that database 0 has room for before it has to
+    /// grow.
+    ///
+    /// Pre-sizing the keyspace avoids growing it repeatedly while loading a
+    /// known number of keys. The keyspace still grows past the capacity.
+    ///
+    /// By default this is 0.
+    pub fn capacity(&mut self, capacity: usize) -> &mut Self {
+        self.0.capacity = capacity;
+
+        self
+    }
+
+    /// Set the clock used to determine when keys expire, returning the current
+    /// Unix time in milliseconds.
+    ///
+    /// By default this is [`state::system_clock`].
+    ///
+    /// [`state::system_clock`]: ../state/fn.system_clock.html
+    pub fn clock(&mut self, clock: Clock) -> &mut Self {
+        self.0.clock = clock;
+
+        self
+    }
+
+    /// Set the number of logical databases, each with its own keyspace.
+    ///
+    /// Commands operate on database 0 unless another is selected via
+    /// [`Hop::select`]. There is always at least one database.
+    ///
+    /// By default this is 16.
+    ///
+    /// [`Hop::select`]: struct.Hop.html#method.select
+    pub fn databases(&mut self, databases: usize) -> &mut Self {
+        self.0.databases = databases.max(1);
+
+        self
+    }
+
+    /// Set the policy for evicting keys when the maximum memory would be
+    /// exceeded.
+    ///
+    /// By default this is [`EvictionPolicy::LeastRecentlyUsed`].
+    ///
+    /// [`EvictionPolicy::LeastRecentlyUsed`]: ../state/enum.EvictionPolicy.html#variant.LeastRecentlyUsed
+    pub fn eviction_policy(&mut self, eviction_policy: EvictionPolicy) -> &mut Self {
+        self.0.eviction_policy = eviction_policy;
+
+        self
+    }
+
+    /// Set the maximum number of responses remembered for requests with an
+    /// idempotency token, after which the responses closest to expiring are
+    /// forgotten.
+    ///
+    /// By default this is 1024.
+    pub fn idempotency_len_max(&mut self, idempotency_len_max: usize) -> &mut Self {
+        self.0.idempotency_len_max = idempotency_len_max;
+
+        self
+    }
+
+    /// Set the number of milliseconds that the response to a request with an
+    /// idempotency token is remembered for.
+    ///
+    /// By default this is 60 seconds.
+    pub fn idempotency_ttl(&mut self, idempotency_ttl: u64) -> &mut Self {
+        self.0.idempotency_ttl = idempotency_ttl;
+
+        self
+    }
+
+    /// Set the maximum number of items that a list may hold.
+    ///
+    /// Writes that would grow a list past the maximum fail with
+    /// [`DispatchError::ValueTooLarge`] and leave the list unchanged.
+    ///
+    /// By default there is no maximum.
+    ///
+    /// [`DispatchError::ValueTooLarge`]: ../command/enum.DispatchError.html#variant.ValueTooLarge
+    pub fn list_len_max(&mut self, list_len_max: impl Into<Option<usize>>) -> &mut Self {
+        self.0.list_len_max = list_len_max.into();
+
+        self
+    }
+
+    /// Set whether to log each dispatched command at the debug level, with its
+    /// command ID, first key, argument count and result.
+    ///
+    /// Commands can only be logged with the `log` feature, so without it
+    /// nothing is logged.
+    ///
+    /// By default this is `false`.
+    pub fn log_commands(&mut self, log_commands: bool) -> &mut Self {
+        self.0.log_commands = log_commands;
+
+        self
+    }
+
+    /// Set the approximate maximum number of bytes that keys and their values
+    /// may use.
+    ///
+    /// When a write would exceed the maximum, keys are evicted according to
+    /// the [eviction policy] until it fits. If it still doesn't fit then the
+    /// write fails with [`DispatchError::OutOfMemory`].
+    ///
+    /// By default there is no maximum.
+    ///
+    /// [`DispatchError::OutOfMemory`]: ../command/enum.DispatchError.html#variant.OutOfMemory
+    /// [eviction policy]: #method.eviction_policy
+    pub fn memory_max(&mut self, memory_max: impl Into<Option<usize>>) -> &mut Self {
+        self.0.memory_max = memory_max.into();
+
+        self
+    }
+
     /// Set whether to enable pubsub.
     ///
     /// By default this is `true`.
@@ -107,46 +362,185 @@ impl Builder {
 
         self
     }
+
+    /// Set the maximum number of entries kept in the slowlog, after which the
+    /// oldest entries are dropped.
+    ///
+    /// By default this is 128.
+    pub fn slowlog_len_max(&mut self, slowlog_len_max: usize) -> &mut Self {
+        self.0.slowlog_len_max = slowlog_len_max;
+
+        self
+    }
+
+    /// Set the number of microseconds a command's dispatch must take for it
+    /// to be recorded in the slowlog, or `None` to disable the slowlog.
+    ///
+    /// Dispatches can only be timed with the `std` feature, so without it
+    /// nothing is recorded.
+    ///
+    /// By default this is 10 milliseconds.
+    pub fn slowlog_threshold(&mut self, slowlog_threshold: impl Into<Option<u64>>) -> &mut Self {
+        self.0.slowlog_threshold = slowlog_threshold.into();
+
+        self
+    }
 }
 
 impl From<Builder> for Hop {
     fn from(builder: Builder) -> Self {
-        Self(Arc::new(HopRef {
-            config: builder.0,
-            ..Default::default()
-        }))
+        let states = (0..builder.0.databases)
+            .map(|database| {
+                let capacity = if database == 0 { builder.0.capacity } else { 0 };
+
+                State::with_clock_and_capacity(builder.0.clock, capacity)
+            })
+            .collect();
+        let database_states = DatabaseStates::new(builder.0.databases);
+
+        Self(
+            Arc::new(HopRef {
+                database_states,
+                idempotency: IdempotencyCache::new(
+                    builder.0.idempotency_len_max,
+                    builder.0.idempotency_ttl,
+                ),
+                slowlog: slowlog::SlowLog::new(builder.0.slowlog_len_max),
+                started: (builder.0.clock)(),
+                states,
+                config: builder.0,
+                ..Default::default()
+            }),
+            0,
+        )
+    }
+}
+
+/// Index into `states` of each database's state, so that databases can be
+/// swapped without moving their keys.
+///
+/// The indexes are read whenever a database's state is retrieved, so they're
+/// read without locking. The version is odd while databases are being swapped
+/// and reads retry until they see an even version that didn't change, so that
+/// no read sees two databases with the same state.
+#[derive(Debug)]
+struct DatabaseStates {
+    indexes: Vec<AtomicUsize>,
+    version: AtomicUsize,
+}
+
+impl DatabaseStates {
+    fn new(databases: usize) -> Self {
+        Self {
+            indexes: (0..databases).map(AtomicUsize::new).collect(),
+            version: AtomicUsize::new(0),
+        }
+    }
+
+    fn get(&self, database: usize) -> Option<usize> {
+        let index = self.indexes.get(database)?;
+
+        loop {
+            let version = self.version.load(Ordering::Acquire);
+            let state = index.load(Ordering::Relaxed);
+            atomic::fence(Ordering::Acquire);
+
+            if version % 2 == 0 && version == self.version.load(Ordering::Relaxed) {
+                return Some(state);
+            }
+        }
+    }
+
+    fn swap(&self, a: usize, b: usize) -> bool {
+        if a >= self.indexes.len() || b >= self.indexes.len() {
+            return false;
+        }
+
+        // Making the version odd also keeps other swaps out until it's even
+        // again.
+        let version = loop {
+            let version = self.version.load(Ordering::Relaxed);
+
+            if version % 2 == 0
+                && self
+                    .version
+                    .compare_exchange_weak(
+                        version,
+                        version + 1,
+                        Ordering::Acquire,
+                        Ordering::Relaxed,
+                    )
+                    .is_ok()
+            {
+                break version;
+            }
+        };
+        atomic::fence(Ordering::Release);
+
+        let a_state = self.indexes[a].load(Ordering::Relaxed);
+        let b_state = self.indexes[b].swap(a_state, Ordering::Relaxed);
+        self.indexes[a].store(b_state, Ordering::Relaxed);
+
+        self.version
+            .store(version.wrapping_add(2), Ordering::Release);
+
+        true
     }
 }
 
 #[derive(Debug)]
 pub(crate) struct HopRef {
     config: Config,
+    pub(crate) connections_active: AtomicU64,
+    database_states: DatabaseStates,
+    idempotency: IdempotencyCache,
     metrics: Metrics,
     pub(crate) metrics_writer: Writer,
     pubsub: PubSubManager,
     sessions: SessionManager,
-    state: State,
+    slowlog: slowlog::SlowLog,
+    /// Unix time in milliseconds that the engine was created at, according to
+    /// its clock.
+    started: u64,
+    states: Vec<State>,
 }
 
 impl Default for HopRef {
     fn default() -> Self {
+        let config = Config::default();
         let metrics = Metrics::default();
         let writer = metrics.writer();
 
         Self {
-            config: Config::default(),
+            connections_active: AtomicU64::new(0),
+            database_states: DatabaseStates::new(config.databases),
+            idempotency: IdempotencyCache::new(config.idempotency_len_max, config.idempotency_ttl),
             metrics,
             metrics_writer: writer.clone(),
             pubsub: PubSubManager::default(),
             sessions: SessionManager::new(writer),
-            state: State::default(),
+            slowlog: slowlog::SlowLog::new(config.slowlog_len_max),
+            started: (config.clock)(),
+            states: (0..config.databases).map(|_| State::default()).collect(),
+            config,
         }
     }
 }
 
 /// The hop engine.
+///
+/// Each handle operates on one of the engine's logical databases, which is
+/// database 0 unless another is selected via [`select`].
+///
+/// A handle is cheap to clone, and every clone shares the same engine: its
+/// keys, configuration, connections, metrics, and subscriptions. A write
+/// through one clone is visible through all of the others, so a server can
+/// clone a handle for each connection. A clone operates on the same database
+/// as the handle it was cloned from.
+///
+/// [`select`]: #method.select
 #[derive(Clone, Debug, Default)]
-pub struct Hop(pub(crate) Arc<HopRef>);
+pub struct Hop(pub(crate) Arc<HopRef>, usize);
 
 impl Hop {
     /// Create a new instance of the engine using the default configuration.
@@ -158,6 +552,20 @@ impl Hop {
         Default::default()
     }
 
+    /// Create a new instance of the engine using the default configuration,
+    /// with room for at least the given number of keys in database 0 before
+    /// it has to grow.
+    ///
+    /// Refer to [`Builder::capacity`] for more information.
+    ///
+    /// [`Builder::capacity`]: struct.Builder.html#method.capacity
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut builder = Self::builder();
+        builder.capacity(capacity);
+
+        builder.build()
+    }
+
     /// Create a new builder for constructing a configured engine.
     ///
     /// Refer to the `Builder` documentation for more information.
@@ -165,27 +573,211 @@ impl Hop {
         Builder::default()
     }
 
+    /// Check that a request could be dispatched, without dispatching it or
+    /// mutating any state.
+    ///
+    /// The request must have at least the command's [arity] of arguments,
+    /// including its keys. If the command is mutating and the request names a
+    /// key type, then its key must be of that type if it exists, unless the
+    /// command replaces the key's value.
+    ///
+    /// Passing validation doesn't guarantee that dispatching will succeed,
+    /// since arguments aren't parsed and the state may change in between.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::{
+    ///     command::{request::RequestBuilder, CommandId, DispatchError},
+    ///     Hop,
+    /// };
+    ///
+    /// let hop = Hop::new();
+    ///
+    /// let mut builder = RequestBuilder::new(CommandId::Rename);
+    /// builder.bytes(b"foo".as_ref()).unwrap();
+    ///
+    /// assert_eq!(Err(DispatchError::KeyUnspecified), hop.validate(&builder.into_request()));
+    /// ```
+    ///
+    /// [arity]: ../command/enum.CommandId.html#method.arity
+    pub fn validate(&self, req: &Request) -> DispatchResult<()> {
+        let command_id = req.command_id();
+
+        if req.arg_count() < command_id.arity() {
+            // Keys come before any other arguments, so a missing key means
+            // there are fewer arguments than keys.
+            let keys = match command_id.key_notation() {
+                KeyNotation::None => 0,
+                KeyNotation::One | KeyNotation::Multiple => 1,
+                KeyNotation::Two => 2,
+            };
+
+            return Err(if req.arg_count() < keys {
+                DispatchError::KeyUnspecified
+            } else {
+                DispatchError::ArgumentRetrieval
+            });
+        }
+
+        let key_type = match req.key_type() {
+            Some(key_type) if command_id.is_mutating() => key_type,
+            _ => return Ok(()),
+        };
+
+        // These replace the key's value rather than modifying it.
+        if matches!(
+            command_id,
+            CommandId::Restore
+                | CommandId::Set
+                | CommandId::SetDifferenceStore
+                | CommandId::SetIntersectStore
+                | CommandId::SetUnionStore
+        ) {
+            return Ok(());
+        }
+
+        match req.key().and_then(|key| self.state().key_type(key)) {
+            Some(existing) if existing != key_type => Err(DispatchError::KeyTypeDifferent),
+            _ => Ok(()),
+        }
+    }
+
     /// Dispatch a request to the engine, providing a response to write the
     /// response to on success.
+    ///
+    /// The response is appended to the buffer rather than replacing its
+    /// contents, so one buffer can be reused across a pipeline of requests
+    /// and written out in one go.
+    ///
+    /// If the request has an idempotency token that was dispatched
+    /// successfully recently, the response of that dispatch is written
+    /// instead of dispatching the request again. If a request with the token
+    /// is still being dispatched then [`DispatchError::RequestPending`] is
+    /// returned, and the request can be retried once it's done.
+    ///
+    /// [`DispatchError::RequestPending`]: ../command/enum.DispatchError.html#variant.RequestPending
     pub fn dispatch(&self, req: &Request, res: &mut Vec<u8>) -> DispatchResult<()> {
-        let res = match req.command_id() {
+        let token = match req.token() {
+            Some(token) => token,
+            None => return self.dispatch_inner(req, res),
+        };
+
+        let now = self.state().now();
+
+        match self.0.idempotency.claim(token, now) {
+            Claim::Claimed => {}
+            Claim::Pending => return Err(DispatchError::RequestPending),
+            Claim::Response(cached) => {
+                res.extend_from_slice(&cached);
+
+                return Ok(());
+            }
+        }
+
+        let start = res.len();
+
+        if let Err(why) = self.dispatch_inner(req, res) {
+            self.0.idempotency.release(token);
+
+            return Err(why);
+        }
+
+        self.0
+            .idempotency
+            .insert(token.to_vec(), res[start..].to_vec(), now);
+
+        Ok(())
+    }
+
+    fn dispatch_inner(&self, req: &Request, res: &mut Vec<u8>) -> DispatchResult<()> {
+        #[cfg(feature = "std")]
+        let started = std::time::Instant::now();
+
+        // Keys accessed by the command are recorded as accessed at the time
+        // of the last read of the clock.
+        self.state().now();
+
+        let res = self.make_room(req).and_then(|_| match req.command_id() {
             CommandId::Append => Append::dispatch(self, req, res),
+            CommandId::AppendCapped => AppendCapped::dispatch(self, req, res),
+            CommandId::AppendLen => AppendLen::dispatch(self, req, res),
+            CommandId::Command => Command::dispatch(self, req, res),
             CommandId::DecrementBy => DecrementBy::dispatch(self, req, res),
+            CommandId::DefaultType => DefaultType::dispatch(self, req, res),
             CommandId::Decrement => Decrement::dispatch(self, req, res),
             CommandId::Delete => Delete::dispatch(self, req, res),
+            CommandId::Dump => Dump::dispatch(self, req, res),
             CommandId::Echo => Echo::dispatch(self, req, res),
             CommandId::Exists => Exists::dispatch(self, req, res),
+            CommandId::Expire => Expire::dispatch(self, req, res),
+            CommandId::ExpireAt => ExpireAt::dispatch(self, req, res),
+            CommandId::ExpireMs => ExpireMs::dispatch(self, req, res),
             CommandId::Get => Get::dispatch(self, req, res),
+            CommandId::GetRange => GetRange::dispatch(self, req, res),
+            CommandId::BulkLoad => BulkLoad::dispatch(self, req, res),
+            CommandId::BlockingPop => BlockingPop::dispatch(self, req, res),
+            CommandId::KeysOfType => KeysOfType::dispatch(self, req, res),
             CommandId::Increment => Increment::dispatch(self, req, res),
+            CommandId::Info => Info::dispatch(self, req, res),
             CommandId::IncrementBy => IncrementBy::dispatch(self, req, res),
+            CommandId::IncrementCapped => IncrementCapped::dispatch(self, req, res),
+            CommandId::RateLimit => RateLimit::dispatch(self, req, res),
             CommandId::Is => Is::dispatch(self, req, res),
             CommandId::Keys => Keys::dispatch(self, req, res),
+            CommandId::Move => Move::dispatch(self, req, res),
+            CommandId::ObjectInfo => ObjectInfo::dispatch(self, req, res),
+            CommandId::PfAdd => PfAdd::dispatch(self, req, res),
+            CommandId::PfCount => PfCount::dispatch(self, req, res),
             CommandId::Rename => Rename::dispatch(self, req, res),
+            CommandId::Swap => Swap::dispatch(self, req, res),
+            CommandId::ListDrain => ListDrain::dispatch(self, req, res),
+            CommandId::Restore => Restore::dispatch(self, req, res),
+            CommandId::Select => Select::dispatch(self, req, res),
+            CommandId::SetDifferenceStore => SetDifferenceStore::dispatch(self, req, res),
+            CommandId::SetIntersectStore => SetIntersectStore::dispatch(self, req, res),
+            CommandId::SetUnionStore => SetUnionStore::dispatch(self, req, res),
             CommandId::Set => Set::dispatch(self, req, res),
+            CommandId::SlowLog => SlowLog::dispatch(self, req, res),
+            CommandId::SlowLogReset => SlowLogReset::dispatch(self, req, res),
             CommandId::Stats => Stats::dispatch(self, req, res),
+            CommandId::SwapDb => SwapDb::dispatch(self, req, res),
+            CommandId::Wait => Wait::dispatch(self, req, res),
+            CommandId::Quit => Quit::dispatch(self, req, res),
+            CommandId::Time => Time::dispatch(self, req, res),
+            CommandId::Touch => Touch::dispatch(self, req, res),
             CommandId::Type => Type::dispatch(self, req, res),
+            CommandId::DeleteMatching => DeleteMatching::dispatch(self, req, res),
+            CommandId::TypeName => TypeName::dispatch(self, req, res),
             CommandId::Length => Length::dispatch(self, req, res),
-        };
+            CommandId::MapFieldTypes => MapFieldTypes::dispatch(self, req, res),
+            CommandId::MapGetAll => MapGetAll::dispatch(self, req, res),
+            CommandId::MapRenameField => MapRenameField::dispatch(self, req, res),
+            CommandId::MapScan => MapScan::dispatch(self, req, res),
+            CommandId::MapSetNx => MapSetNx::dispatch(self, req, res),
+            CommandId::SetScan => SetScan::dispatch(self, req, res),
+            CommandId::Scan => Scan::dispatch(self, req, res),
+            CommandId::SetMembersSorted => SetMembersSorted::dispatch(self, req, res),
+            CommandId::ListMove => ListMove::dispatch(self, req, res),
+            CommandId::ListCount => ListCount::dispatch(self, req, res),
+            CommandId::ListInsert => ListInsert::dispatch(self, req, res),
+            CommandId::ListPeek => ListPeek::dispatch(self, req, res),
+            CommandId::Watch => Watch::dispatch(self, req, res),
+            CommandId::ZAdd => ZAdd::dispatch(self, req, res),
+            CommandId::ZRange => ZRange::dispatch(self, req, res),
+            CommandId::ZRangeByScore => ZRangeByScore::dispatch(self, req, res),
+            CommandId::ZRem => ZRem::dispatch(self, req, res),
+            CommandId::ZScore => ZScore::dispatch(self, req, res),
+        });
+
+        if res.is_ok() && req.command_id().is_mutating() {
+            for key in Self::keys(req) {
+                self.state().refresh_size(key);
+            }
+
+            self.notify_keyspace(req);
+            self.notify_pushed(req);
+        }
 
         self.0.metrics_writer.increment(if res.is_ok() {
             Metric::CommandsSuccessful
@@ -193,14 +785,202 @@ impl Hop {
             Metric::CommandsErrored
         });
 
+        #[cfg(feature = "std")]
+        self.record_if_slow(req.command_id(), started.elapsed().as_micros() as u64);
+
+        #[cfg(feature = "log")]
+        self.log_dispatch(req, &res);
+
         res
     }
 
+    /// Log a dispatched command, if command logging is enabled.
+    ///
+    /// The key is written with non-printable bytes escaped, so nothing is
+    /// allocated for it.
+    #[cfg(feature = "log")]
+    fn log_dispatch(&self, req: &Request, res: &DispatchResult<()>) {
+        if !self.0.config.log_commands {
+            return;
+        }
+
+        let command_id = req.command_id();
+        let command = command_id.name();
+        let key = EscapedKey(Self::keys(req).next());
+        let args = req.arg_count();
+
+        match res {
+            Ok(()) => log::debug!(
+                target: "hop_engine::dispatch",
+                "command={} key={} args={} result=ok",
+                command,
+                key,
+                args,
+            ),
+            Err(why) => log::debug!(
+                target: "hop_engine::dispatch",
+                "command={} key={} args={} result={:?}",
+                command,
+                key,
+                args,
+                why,
+            ),
+        }
+    }
+
+    /// Record a command in the slowlog if its dispatch took at least the
+    /// configured threshold.
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    fn record_if_slow(&self, command_id: CommandId, duration: u64) {
+        match self.0.config.slowlog_threshold {
+            Some(threshold) if duration >= threshold => {}
+            _ => return,
+        }
+
+        let entry = SlowLogEntry::new(command_id, self.state().now(), duration);
+        self.0.slowlog.record(entry);
+    }
+
+    /// Return an iterator over the keys of a request.
+    fn keys<'a>(req: &'a Request<'_>) -> impl Iterator<Item = &'a [u8]> {
+        let key_count = match req.command_id().key_notation() {
+            KeyNotation::One => 1,
+            KeyNotation::Two => 2,
+            KeyNotation::Multiple => req.arg_count(),
+            KeyNotation::None => 0,
+        };
+
+        (0..key_count).filter_map(move |idx| req.arg(idx))
+    }
+
+    /// Evict keys until a write fits within the maximum memory, if there is
+    /// one.
+    ///
+    /// The size of the write is approximated by the size of the request. Keys
+    /// of the request itself are never evicted.
+    fn make_room(&self, req: &Request) -> DispatchResult<()> {
+        let memory_max = match self.0.config.memory_max {
+            Some(memory_max) => memory_max,
+            None => return Ok(()),
+        };
+
+        // Only commands that can grow the state need room. Mutating commands
+        // are assumed to unless they only ever remove or rearrange data.
+        match req.command_id() {
+            command_id if !command_id.is_mutating() => return Ok(()),
+            CommandId::BlockingPop
+            | CommandId::Delete
+            | CommandId::DeleteMatching
+            | CommandId::Expire
+            | CommandId::ExpireAt
+            | CommandId::ExpireMs
+            | CommandId::ListDrain
+            | CommandId::Swap
+            | CommandId::SwapDb
+            | CommandId::ZRem => return Ok(()),
+            _ => {}
+        }
+
+        let size = req.as_bytes().len();
+        let keys = Self::keys(req).collect::<Vec<_>>();
+
+        // The maximum applies to all databases together. Keys are evicted
+        // from the selected database first.
+        let policy = self.0.config.eviction_policy;
+        let selected = self.state();
+        let others = self
+            .0
+            .states
+            .iter()
+            .filter(|state| !ptr::eq(*state, selected));
+
+        while self.memory_used().saturating_add(size) > memory_max {
+            let evicted = selected.evict(policy, &keys).is_some()
+                || others
+                    .clone()
+                    .any(|state| state.evict(policy, &[]).is_some());
+
+            if !evicted {
+                return Err(DispatchError::OutOfMemory);
+            }
+
+            self.0.metrics_writer.increment(Metric::KeysEvicted);
+        }
+
+        Ok(())
+    }
+
+    /// Wake connections waiting for items to be pushed to the lists modified
+    /// by a request.
+    fn notify_pushed(&self, req: &Request) {
+        // Only commands that can add items to a list wake the waiters, so that
+        // a blocking pop doesn't wake itself by popping from an empty list.
+        match req.command_id() {
+            CommandId::Append
+            | CommandId::AppendCapped
+            | CommandId::AppendLen
+            | CommandId::ListInsert
+            | CommandId::ListMove
+            | CommandId::Rename
+            | CommandId::Restore
+            | CommandId::Set
+            | CommandId::Swap => {}
+            _ => return,
+        }
+
+        for key in Self::keys(req) {
+            self.state().notify_pushed(key);
+        }
+    }
+
+    /// Notify keyspace watchers of the keys modified by a request.
+    fn notify_keyspace(&self, req: &Request) {
+        if !self.0.pubsub.has_keyspace_watchers() {
+            return;
+        }
+
+        for key in Self::keys(req) {
+            self.0.pubsub.notify_keyspace(req.command_id(), key);
+        }
+    }
+
     /// Return an immutable reference to the configuration.
     pub fn config(&self) -> &Config {
         &self.0.config
     }
 
+    /// Record that a connection was opened, returning a handle to record its
+    /// traffic with.
+    ///
+    /// The connection counts as active until the handle is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::Hop;
+    ///
+    /// let hop = Hop::new();
+    /// let conn = hop.connect();
+    /// assert_eq!(1, hop.connections_active());
+    ///
+    /// drop(conn);
+    /// assert_eq!(0, hop.connections_active());
+    /// ```
+    pub fn connect(&self) -> Connection {
+        Connection::new(self.clone())
+    }
+
+    /// Retrieve the number of connections that are currently open.
+    pub fn connections_active(&self) -> u64 {
+        self.0.connections_active.load(Ordering::SeqCst)
+    }
+
+    /// Retrieve the number of milliseconds since the engine was created,
+    /// according to its clock.
+    pub fn uptime(&self) -> u64 {
+        self.state().now().saturating_sub(self.0.started)
+    }
+
     /// Return a new reader to read metrics from.
     pub fn metrics(&self) -> Reader {
         self.0.metrics.reader()
@@ -211,24 +991,717 @@ impl Hop {
         &self.0.pubsub
     }
 
+    /// Return an immutable reference to the slowlog.
+    pub fn slowlog(&self) -> &slowlog::SlowLog {
+        &self.0.slowlog
+    }
+
     /// Return an immutable reference to the session manager.
     pub fn sessions(&self) -> &SessionManager {
         &self.0.sessions
     }
 
-    /// Return an immutable reference to the state.
+    /// Retrieve the index of the selected database.
+    pub fn database(&self) -> usize {
+        self.1
+    }
+
+    /// Retrieve the approximate number of bytes used by keys and their values
+    /// across all databases.
+    fn memory_used(&self) -> usize {
+        self.0.states.iter().map(State::memory_used).sum()
+    }
+
+    /// Create a handle to the same engine operating on another database.
+    ///
+    /// Returns `None` if the database doesn't exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::{state::Value, Hop};
+    ///
+    /// let hop = Hop::new();
+    /// let other = hop.select(1).unwrap();
+    /// other.state().insert(b"foo".to_vec(), Value::Integer(1));
+    ///
+    /// assert!(other.state().contains_key(b"foo"));
+    /// assert!(!hop.state().contains_key(b"foo"));
+    /// assert!(hop.select(hop.config().databases()).is_none());
+    /// ```
+    pub fn select(&self, database: usize) -> Option<Self> {
+        if database < self.0.states.len() {
+            Some(Self(Arc::clone(&self.0), database))
+        } else {
+            None
+        }
+    }
+
+    /// Return an immutable reference to the state of the selected database.
     #[inline]
     pub fn state(&self) -> &State {
-        &self.0.state
+        let idx = self.0.database_states.get(self.1).unwrap_or(self.1);
+
+        &self.0.states[idx]
+    }
+
+    /// Return an immutable reference to the state of a database, if it
+    /// exists.
+    pub fn state_of(&self, database: usize) -> Option<&State> {
+        let idx = self.0.database_states.get(database)?;
+
+        self.0.states.get(idx)
+    }
+
+    /// Swap the keyspaces of two databases, returning whether both exist.
+    ///
+    /// Only the databases' handles to their keyspaces are exchanged, so this
+    /// is constant time regardless of the number of keys.
+    ///
+    /// A command looks up its database's keyspace each time it accesses keys,
+    /// so a command dispatched while the databases are swapped may access
+    /// keys of both.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::{state::Value, Hop};
+    ///
+    /// let hop = Hop::new();
+    /// hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+    ///
+    /// assert!(hop.swap_databases(0, 1));
+    /// assert!(!hop.state().contains_key(b"foo"));
+    /// assert!(hop.state_of(1).unwrap().contains_key(b"foo"));
+    /// ```
+    pub fn swap_databases(&self, a: usize, b: usize) -> bool {
+        self.0.database_states.swap(a, b)
+    }
+}
+
+/// A key written with non-printable bytes escaped, or `-` if there's no key.
+#[cfg(feature = "log")]
+struct EscapedKey<'a>(Option<&'a [u8]>);
+
+#[cfg(feature = "log")]
+impl Display for EscapedKey<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let key = match self.0 {
+            Some(key) => key,
+            None => return f.write_char('-'),
+        };
+
+        for byte in key {
+            for escaped in ascii::escape_default(*byte) {
+                f.write_char(char::from(escaped))?;
+            }
+        }
+
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{Hop, HopRef};
-    use core::fmt::Debug;
+    use crate::{
+        command::{request::RequestBuilder, CommandId, DispatchError, Request, Response},
+        metrics::Metric,
+        state::{EvictionPolicy, KeyType, Value},
+    };
+    use alloc::vec::Vec;
+    use core::{
+        fmt::Debug,
+        sync::atomic::{AtomicU64, Ordering},
+    };
     use static_assertions::assert_impl_all;
 
     assert_impl_all!(Hop: Clone, Debug, Default);
     assert_impl_all!(HopRef: Debug);
+
+    #[test]
+    fn test_clone_shares_state() {
+        let hop = Hop::new();
+        let clone = hop.clone();
+
+        let mut builder = RequestBuilder::new(CommandId::Increment);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(clone
+            .dispatch(&builder.into_request(), &mut Vec::new())
+            .is_ok());
+
+        // the write through the clone is visible through the original, and
+        // the other way around
+        assert_eq!(
+            Some(&1),
+            hop.state().key_ref(b"foo").unwrap().as_integer_ref()
+        );
+        hop.state().insert(b"bar".to_vec(), Value::Boolean(true));
+        assert!(clone.state().contains_key(b"bar"));
+
+        // everything else about the engine is shared too
+        assert_eq!(Some(1), hop.metrics().counter(&Metric::CommandsSuccessful));
+        let _conn = clone.connect();
+        assert_eq!(1, hop.connections_active());
+
+        // a clone of a selected database stays on that database
+        let selected = hop.select(1).unwrap().clone();
+        selected
+            .state()
+            .insert(b"baz".to_vec(), Value::Boolean(true));
+        assert!(hop.select(1).unwrap().state().contains_key(b"baz"));
+        assert!(!hop.state().contains_key(b"baz"));
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn test_log_commands() {
+        use log::{LevelFilter, Log, Metadata, Record};
+        use std::{
+            string::{String, ToString},
+            sync::Mutex,
+        };
+
+        struct CapturingLogger(Mutex<Vec<String>>);
+
+        impl Log for CapturingLogger {
+            fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+                metadata.target() == "hop_engine::dispatch"
+            }
+
+            fn log(&self, record: &Record<'_>) {
+                if self.enabled(record.metadata()) {
+                    self.0.lock().unwrap().push(record.args().to_string());
+                }
+            }
+
+            fn flush(&self) {}
+        }
+
+        let logger = Box::leak(Box::new(CapturingLogger(Mutex::new(Vec::new()))));
+
+        // other tests may dispatch at the same time, so only records of this
+        // test's keys are looked at
+        assert!(log::set_logger(logger).is_ok());
+        log::set_max_level(LevelFilter::Debug);
+
+        let mut builder = Hop::builder();
+        builder.log_commands(true);
+        let hop = builder.build();
+        hop.state()
+            .insert(b"logged_str".to_vec(), Value::String("foo".into()));
+
+        for key in [b"logged\x01".as_ref(), b"logged_str", b"unlogged"].iter() {
+            let mut builder = RequestBuilder::new(CommandId::Increment);
+            assert!(builder.bytes(*key).is_ok());
+            let req = builder.into_request();
+            let logging = if key.starts_with(b"logged") {
+                &hop
+            } else {
+                // logging is off by default
+                &Hop::new()
+            };
+
+            let _ = logging.dispatch(&req, &mut Vec::new());
+        }
+
+        let records = logger.0.lock().unwrap();
+        let records = records
+            .iter()
+            .filter(|record| record.contains("key=logged") || record.contains("key=unlogged"))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                "command=increment key=logged\\x01 args=1 result=ok",
+                "command=increment key=logged_str args=1 result=KeyTypeDifferent",
+            ],
+            records
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        fn request(
+            command_id: CommandId,
+            key_type: Option<KeyType>,
+            args: &[&[u8]],
+        ) -> Request<'static> {
+            let mut builder = RequestBuilder::new_with_key_type(command_id, key_type);
+
+            for arg in args {
+                assert!(builder.bytes(*arg).is_ok());
+            }
+
+            builder.into_request()
+        }
+
+        let hop = Hop::new();
+        hop.state()
+            .insert(b"foo".to_vec(), Value::String("bar".into()));
+
+        let valid = [
+            request(CommandId::Time, None, &[]),
+            request(CommandId::Get, None, &[b"missing"]),
+            request(CommandId::Increment, Some(KeyType::Integer), &[b"missing"]),
+            request(CommandId::Rename, None, &[b"foo", b"bar"]),
+            request(CommandId::Set, Some(KeyType::Integer), &[b"foo", &[0; 8]]),
+            // only mutating commands have their key types checked
+            request(CommandId::Get, Some(KeyType::Integer), &[b"foo"]),
+        ];
+
+        for req in valid.iter() {
+            assert_eq!(Ok(()), hop.validate(req), "{:?}", req.command_id());
+        }
+
+        let invalid = [
+            (
+                request(CommandId::Rename, None, &[b"foo"]),
+                DispatchError::KeyUnspecified,
+            ),
+            (
+                request(CommandId::Get, None, &[]),
+                DispatchError::KeyUnspecified,
+            ),
+            (
+                request(CommandId::GetRange, None, &[b"foo", &[0; 8]]),
+                DispatchError::ArgumentRetrieval,
+            ),
+            (
+                request(CommandId::Set, None, &[b"foo"]),
+                DispatchError::ArgumentRetrieval,
+            ),
+            (
+                request(CommandId::Increment, Some(KeyType::Integer), &[b"foo"]),
+                DispatchError::KeyTypeDifferent,
+            ),
+        ];
+
+        for (req, error) in invalid.iter() {
+            assert_eq!(Err(*error), hop.validate(req), "{:?}", req.command_id());
+        }
+
+        // nothing was dispatched
+        assert!(!hop.state().contains_key(b"missing"));
+        assert!(!hop.state().contains_key(b"bar"));
+        assert_eq!(Some(KeyType::String), hop.state().key_type(b"foo"));
+    }
+
+    #[test]
+    fn test_keyspace_events() {
+        let hop = Hop::new();
+        let rx = hop.pubsub().watch_keyspace(b"foo*".to_vec());
+        let mut resp = Vec::new();
+
+        let mut builder = RequestBuilder::new(CommandId::Increment);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(hop.dispatch(&builder.into_request(), &mut resp).is_ok());
+
+        let mut builder = RequestBuilder::new(CommandId::Get);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(hop.dispatch(&builder.into_request(), &mut resp).is_ok());
+
+        let mut builder = RequestBuilder::new(CommandId::Rename);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(b"bar".as_ref()).is_ok());
+        assert!(hop.dispatch(&builder.into_request(), &mut resp).is_ok());
+
+        let event = rx.try_receive().unwrap();
+        assert_eq!(CommandId::Increment, event.command_id());
+        assert_eq!(b"foo", event.key());
+
+        let event = rx.try_receive().unwrap();
+        assert_eq!(CommandId::Rename, event.command_id());
+        assert_eq!(b"foo", event.key());
+
+        // "bar" doesn't match the pattern
+        assert!(rx.try_receive().is_err());
+    }
+
+    fn set(hop: &Hop, key: &[u8], value: &[u8]) -> Result<(), DispatchError> {
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::Bytes);
+        assert!(builder.bytes(key).is_ok());
+        assert!(builder.bytes(value).is_ok());
+
+        hop.dispatch(&builder.into_request(), &mut Vec::new())
+    }
+
+    #[test]
+    fn test_eviction_least_recently_used() {
+        static NOW: AtomicU64 = AtomicU64::new(1000);
+
+        // accesses are recorded to the millisecond, so advance the clock
+        // between them to order them
+        let mut builder = Hop::builder();
+        builder
+            .clock(|| NOW.fetch_add(1, Ordering::SeqCst))
+            .memory_max(100);
+        let hop = builder.build();
+
+        for key in [b"a", b"b", b"c", b"d"].iter() {
+            assert!(set(&hop, *key, &[0; 20]).is_ok());
+        }
+
+        // use "a" so that "b" is now the least recently used key
+        assert!(hop.state().key_ref(b"a").is_some());
+        assert!(set(&hop, b"e", &[0; 20]).is_ok());
+
+        assert!(hop.state().memory_used() <= 100);
+        assert!(hop.state().contains_key(b"a"));
+        assert!(!hop.state().contains_key(b"b"));
+        assert!(hop.state().contains_key(b"e"));
+        assert!(hop.metrics().counter(&Metric::KeysEvicted).unwrap() >= 1);
+    }
+
+    #[test]
+    fn test_eviction_random() {
+        let mut builder = Hop::builder();
+        builder
+            .eviction_policy(EvictionPolicy::Random)
+            .memory_max(100);
+        let hop = builder.build();
+
+        for key in [b"a", b"b", b"c", b"d", b"e", b"f"].iter() {
+            assert!(set(&hop, *key, &[0; 20]).is_ok());
+            assert!(hop.state().memory_used() <= 100);
+        }
+
+        assert!(hop.state().contains_key(b"f"));
+        assert!(hop.metrics().counter(&Metric::KeysEvicted).unwrap() >= 2);
+    }
+
+    #[test]
+    fn test_eviction_least_frequently_used() {
+        static NOW: AtomicU64 = AtomicU64::new(1000);
+
+        // keys used equally often are evicted by when they were last used, so
+        // advance the clock between them to order them
+        let mut builder = Hop::builder();
+        builder
+            .clock(|| NOW.fetch_add(1, Ordering::SeqCst))
+            .eviction_policy(EvictionPolicy::LeastFrequentlyUsed)
+            .memory_max(100);
+        let hop = builder.build();
+
+        assert!(set(&hop, b"hot", &[0; 20]).is_ok());
+
+        for key in [b"a", b"b", b"c", b"d", b"e", b"f"].iter() {
+            for _ in 0..5 {
+                assert!(hop.state().key_ref(b"hot").is_some());
+            }
+
+            assert!(set(&hop, *key, &[0; 20]).is_ok());
+        }
+
+        // the frequently used key survives every eviction
+        assert!(hop.state().contains_key(b"hot"));
+        assert!(!hop.state().contains_key(b"a"));
+        assert!(hop.state().contains_key(b"f"));
+    }
+
+    #[test]
+    fn test_eviction_none() {
+        let mut builder = Hop::builder();
+        builder
+            .eviction_policy(EvictionPolicy::None)
+            .memory_max(100);
+        let hop = builder.build();
+
+        assert!(set(&hop, b"foo", &[0; 50]).is_ok());
+        assert_eq!(Err(DispatchError::OutOfMemory), set(&hop, b"bar", &[0; 50]));
+        assert!(hop.state().contains_key(b"foo"));
+    }
+
+    #[test]
+    fn test_eviction_map_set_nx() {
+        let mut builder = Hop::builder();
+        builder
+            .eviction_policy(EvictionPolicy::Random)
+            .memory_max(100);
+        let hop = builder.build();
+
+        assert!(set(&hop, b"foo", &[0; 60]).is_ok());
+
+        // setting a fresh field grows the state, so room is made for it
+        let mut builder = RequestBuilder::new(CommandId::MapSetNx);
+        assert!(builder.bytes(b"bar".as_ref()).is_ok());
+        assert!(builder.bytes(b"field".as_ref()).is_ok());
+        assert!(builder.bytes([0; 40].as_ref()).is_ok());
+        assert!(hop
+            .dispatch(&builder.into_request(), &mut Vec::new())
+            .is_ok());
+
+        assert!(!hop.state().contains_key(b"foo"));
+        assert!(hop.state().contains_key(b"bar"));
+    }
+
+    #[test]
+    fn test_out_of_memory() {
+        let mut builder = Hop::builder();
+        builder.memory_max(100);
+        let hop = builder.build();
+
+        assert_eq!(
+            Err(DispatchError::OutOfMemory),
+            set(&hop, b"foo", &[0; 100])
+        );
+        assert!(!hop.state().contains_key(b"foo"));
+    }
+
+    fn dispatch(hop: &Hop, builder: RequestBuilder) -> Result<(), DispatchError> {
+        hop.dispatch(&builder.into_request(), &mut Vec::new())
+    }
+
+    fn append(hop: &Hop, key_type: KeyType, items: &[&[u8]]) -> Result<(), DispatchError> {
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Append, key_type);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+
+        for item in items {
+            assert!(builder.bytes(*item).is_ok());
+        }
+
+        dispatch(hop, builder)
+    }
+
+    #[test]
+    fn test_bytes_len_max() {
+        let mut builder = Hop::builder();
+        builder.bytes_len_max(4);
+        let hop = builder.build();
+
+        assert_eq!(
+            Err(DispatchError::ValueTooLarge),
+            set(&hop, b"foo", b"abcde")
+        );
+        assert!(!hop.state().contains_key(b"foo"));
+
+        assert!(set(&hop, b"foo", b"abc").is_ok());
+        assert!(append(&hop, KeyType::Bytes, &[b"d"]).is_ok());
+        assert_eq!(
+            Err(DispatchError::ValueTooLarge),
+            append(&hop, KeyType::Bytes, &[b"e"])
+        );
+        assert_eq!(
+            Some(b"abcd".as_ref()),
+            hop.state().key_ref(b"foo").unwrap().as_bytes_ref()
+        );
+
+        // strings are limited by their length in bytes
+        hop.state().remove(b"foo");
+        assert!(append(&hop, KeyType::String, &["é".as_bytes(), b"a"]).is_ok());
+        assert_eq!(
+            Err(DispatchError::ValueTooLarge),
+            append(&hop, KeyType::String, &["é".as_bytes()])
+        );
+        assert_eq!(
+            Some("éa"),
+            hop.state().key_ref(b"foo").unwrap().as_string_ref()
+        );
+    }
+
+    #[test]
+    fn test_list_len_max() {
+        let mut builder = Hop::builder();
+        builder.list_len_max(2);
+        let hop = builder.build();
+
+        let mut builder = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::List);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+
+        for item in [b"a", b"b", b"c"].iter() {
+            assert!(builder.bytes(item.as_ref()).is_ok());
+        }
+
+        assert_eq!(Err(DispatchError::ValueTooLarge), dispatch(&hop, builder));
+        assert!(!hop.state().contains_key(b"foo"));
+
+        assert_eq!(
+            Err(DispatchError::ValueTooLarge),
+            append(&hop, KeyType::List, &[b"a", b"b", b"c"])
+        );
+        assert!(!hop.state().contains_key(b"foo"));
+
+        assert!(append(&hop, KeyType::List, &[b"a"]).is_ok());
+
+        let mut builder = RequestBuilder::new(CommandId::ListInsert);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.bytes(b"a".as_ref()).is_ok());
+        assert!(builder.bytes(b"b".as_ref()).is_ok());
+        assert!(builder.value(true).is_ok());
+        assert!(dispatch(&hop, builder).is_ok());
+
+        assert_eq!(
+            Err(DispatchError::ValueTooLarge),
+            append(&hop, KeyType::List, &[b"c"])
+        );
+
+        // trimming to a cap within the maximum fits
+        let mut builder = RequestBuilder::new(CommandId::AppendCapped);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.value(2).is_ok());
+        assert!(builder.bytes(b"c".as_ref()).is_ok());
+        assert!(dispatch(&hop, builder).is_ok());
+
+        let mut builder = RequestBuilder::new(CommandId::AppendCapped);
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        assert!(builder.value(3).is_ok());
+        assert!(builder.bytes(b"d".as_ref()).is_ok());
+        assert_eq!(Err(DispatchError::ValueTooLarge), dispatch(&hop, builder));
+
+        let list = hop.state().key_ref(b"foo").unwrap();
+        assert_eq!(
+            vec![b"b".to_vec(), b"c".to_vec()],
+            list.as_list_ref()
+                .unwrap()
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_with_capacity() {
+        let hop = Hop::with_capacity(16);
+        assert_eq!(16, hop.config().capacity());
+
+        // the keyspace grows past its capacity
+        for idx in 0..100i64 {
+            let mut builder = RequestBuilder::new(CommandId::Increment);
+            assert!(builder.bytes(idx.to_be_bytes().as_ref()).is_ok());
+            assert!(hop
+                .dispatch(&builder.into_request(), &mut Vec::new())
+                .is_ok());
+        }
+
+        assert!((0..100i64).all(|idx| hop.state().contains_key(&idx.to_be_bytes())));
+    }
+
+    #[test]
+    fn test_dispatch_appends() {
+        let hop = Hop::new();
+        let mut resp = Vec::new();
+        let mut expected = Vec::new();
+
+        for value in 1..=3 {
+            let mut builder = RequestBuilder::new(CommandId::Increment);
+            assert!(builder.bytes(b"foo".as_ref()).is_ok());
+            assert!(hop.dispatch(&builder.into_request(), &mut resp).is_ok());
+
+            expected.extend_from_slice(&Response::from(value).as_bytes());
+        }
+
+        let mut builder = RequestBuilder::new(CommandId::Echo);
+        assert!(builder.bytes(b"bar".as_ref()).is_ok());
+        assert!(hop.dispatch(&builder.into_request(), &mut resp).is_ok());
+        expected.extend_from_slice(&Response::from(vec![b"bar".to_vec()]).as_bytes());
+
+        assert_eq!(expected, resp);
+    }
+
+    fn increment(hop: &Hop, token: &[u8]) -> Vec<u8> {
+        let mut builder = RequestBuilder::new(CommandId::Increment);
+        assert!(builder.token(token).is_ok());
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+
+        let mut resp = Vec::new();
+        assert!(hop.dispatch(&builder.into_request(), &mut resp).is_ok());
+
+        resp
+    }
+
+    #[test]
+    fn test_idempotency_token() {
+        static NOW: AtomicU64 = AtomicU64::new(1000);
+
+        let mut builder = Hop::builder();
+        builder
+            .clock(|| NOW.load(Ordering::SeqCst))
+            .idempotency_ttl(500);
+        let hop = builder.build();
+
+        // replaying the token returns the first response without applying
+        // the increment again
+        let first = increment(&hop, b"a");
+        assert_eq!(first, Response::from(1).as_bytes());
+        assert_eq!(first, increment(&hop, b"a"));
+        assert!(matches!(
+            hop.state().key_ref(b"foo").as_deref(),
+            Some(Value::Integer(1))
+        ));
+
+        // a new token is applied
+        assert_eq!(increment(&hop, b"b"), Response::from(2).as_bytes());
+
+        // and once the token expires it's applied again
+        NOW.store(1500, Ordering::SeqCst);
+        assert_eq!(increment(&hop, b"a"), Response::from(3).as_bytes());
+    }
+
+    #[test]
+    fn test_idempotency_token_errors_not_remembered() {
+        let hop = Hop::new();
+        hop.state().insert(b"foo".to_vec(), Value::Boolean(true));
+
+        let mut builder = RequestBuilder::new(CommandId::Increment);
+        assert!(builder.token(b"a".as_ref()).is_ok());
+        assert!(builder.bytes(b"foo".as_ref()).is_ok());
+        let req = builder.into_request();
+        assert!(hop.dispatch(&req, &mut Vec::new()).is_err());
+
+        // once the error is fixed a retry with the same token is applied
+        hop.state().insert(b"foo".to_vec(), Value::Integer(1));
+        assert_eq!(increment(&hop, b"a"), Response::from(2).as_bytes());
+    }
+
+    #[test]
+    fn test_idempotency_token_concurrent() {
+        use std::{sync::Barrier, thread};
+
+        const ROUNDS: i64 = 2000;
+
+        let hop = Hop::new();
+        let barrier = std::sync::Arc::new(Barrier::new(2));
+
+        let handles = (0..2)
+            .map(|_| {
+                let hop = hop.clone();
+                let barrier = barrier.clone();
+
+                thread::spawn(move || {
+                    (0..ROUNDS)
+                        .map(|round| {
+                            let mut builder = RequestBuilder::new(CommandId::Increment);
+                            builder.token(round.to_be_bytes().as_ref()).unwrap();
+                            builder.bytes(b"foo".as_ref()).unwrap();
+                            let req = builder.into_request();
+
+                            barrier.wait();
+                            let mut resp = Vec::new();
+                            let res = hop.dispatch(&req, &mut resp).map(|()| resp);
+                            barrier.wait();
+
+                            (round, res)
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            // the retry either gets the response of the first dispatch or is
+            // told that it's still pending
+            for (round, res) in handle.join().unwrap() {
+                match res {
+                    Ok(resp) => assert_eq!(resp, Response::from(round + 1).as_bytes()),
+                    Err(why) => assert_eq!(DispatchError::RequestPending, why),
+                }
+            }
+        }
+
+        // each token's increment was applied once
+        assert!(matches!(
+            hop.state().key_ref(b"foo").as_deref(),
+            Some(Value::Integer(ROUNDS))
+        ));
+    }
 }
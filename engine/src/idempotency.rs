@@ -0,0 +1,295 @@
+//! Cache of responses to requests carrying an idempotency token.
+//!
+//! When a request with a token is dispatched successfully its response is
+//! remembered until its TTL passes, so that a client retrying the request
+//! after a lost connection gets the original response back rather than
+//! applying a mutation twice. Refer to [`RequestBuilder::token`] for attaching
+//! a token to a request.
+//!
+//! A token is claimed before its request is dispatched, so that a retry
+//! arriving while the original is still being dispatched is told that it's
+//! pending rather than being applied too.
+//!
+//! [`RequestBuilder::token`]: ../command/request/struct.RequestBuilder.html#method.token
+
+use alloc::vec::Vec;
+use dashmap::{mapref::entry::Entry as MapEntry, DashMap};
+
+#[derive(Clone, Debug)]
+struct Entry {
+    expires_at: u64,
+    /// The response, or `None` while the request is being dispatched.
+    response: Option<Vec<u8>>,
+}
+
+/// The outcome of claiming a token with [`IdempotencyCache::claim`].
+///
+/// [`IdempotencyCache::claim`]: struct.IdempotencyCache.html#method.claim
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Claim {
+    /// The token was claimed, so the request should be dispatched.
+    Claimed,
+    /// A request with the token is still being dispatched.
+    Pending,
+    /// The remembered response of the token's request.
+    Response(Vec<u8>),
+}
+
+/// Bounded cache of responses keyed by idempotency token.
+#[derive(Debug)]
+pub struct IdempotencyCache {
+    entries: DashMap<Vec<u8>, Entry>,
+    len_max: usize,
+    ttl: u64,
+}
+
+impl IdempotencyCache {
+    /// Create a new cache keeping at most the given number of responses, each
+    /// for the given number of milliseconds.
+    pub fn new(len_max: usize, ttl: u64) -> Self {
+        Self {
+            entries: DashMap::new(),
+            len_max,
+            ttl,
+        }
+    }
+
+    /// Retrieve the number of responses currently remembered, including any
+    /// that have expired but not yet been removed.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Retrieve whether no responses are remembered.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Retrieve the response remembered for a token, if it hasn't expired by
+    /// the given Unix time in milliseconds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::idempotency::IdempotencyCache;
+    ///
+    /// let cache = IdempotencyCache::new(16, 1000);
+    /// cache.insert(b"token".to_vec(), b"response".to_vec(), 5000);
+    ///
+    /// assert_eq!(Some(b"response".to_vec()), cache.get(b"token", 5999));
+    /// assert!(cache.get(b"token", 6000).is_none());
+    /// ```
+    pub fn get(&self, token: &[u8], now: u64) -> Option<Vec<u8>> {
+        let entry = self.entries.get(token)?;
+
+        if entry.expires_at > now {
+            return entry.response.clone();
+        }
+
+        drop(entry);
+        self.entries.remove(token);
+
+        None
+    }
+
+    /// Claim a token before dispatching its request as of the given Unix time
+    /// in milliseconds.
+    ///
+    /// The token is checked and claimed under one lock, so only one of any
+    /// requests with the same token arriving at once is dispatched. Once the
+    /// request is dispatched its response must be [inserted], or the claim
+    /// [released] if it failed.
+    ///
+    /// A claim expires after the TTL like a response, so a claim that's never
+    /// inserted or released doesn't hold on to its token forever.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::idempotency::{Claim, IdempotencyCache};
+    ///
+    /// let cache = IdempotencyCache::new(16, 1000);
+    ///
+    /// assert_eq!(Claim::Claimed, cache.claim(b"token", 5000));
+    /// assert_eq!(Claim::Pending, cache.claim(b"token", 5001));
+    ///
+    /// cache.insert(b"token".to_vec(), b"response".to_vec(), 5002);
+    /// assert_eq!(Claim::Response(b"response".to_vec()), cache.claim(b"token", 5003));
+    /// ```
+    ///
+    /// [inserted]: #method.insert
+    /// [released]: #method.release
+    pub fn claim(&self, token: &[u8], now: u64) -> Claim {
+        if self.len_max == 0 {
+            return Claim::Claimed;
+        }
+
+        // Making room locks other shards of the map, so it's done before the
+        // token's shard is locked.
+        if !self.entries.contains_key(token) {
+            self.make_room(now);
+        }
+
+        let claim = Entry {
+            expires_at: now.saturating_add(self.ttl),
+            response: None,
+        };
+
+        match self.entries.entry(token.to_vec()) {
+            MapEntry::Occupied(entry) if entry.get().expires_at > now => {
+                match &entry.get().response {
+                    Some(response) => Claim::Response(response.clone()),
+                    None => Claim::Pending,
+                }
+            }
+            MapEntry::Occupied(mut entry) => {
+                entry.insert(claim);
+
+                Claim::Claimed
+            }
+            MapEntry::Vacant(entry) => {
+                entry.insert(claim);
+
+                Claim::Claimed
+            }
+        }
+    }
+
+    /// Release the claim on a token whose request failed, so that it can be
+    /// dispatched again.
+    ///
+    /// A response already remembered for the token is kept.
+    pub fn release(&self, token: &[u8]) {
+        self.entries
+            .remove_if(token, |_, entry| entry.response.is_none());
+    }
+
+    /// Remember the response to a token as of the given Unix time in
+    /// milliseconds, replacing any claim on it.
+    ///
+    /// If the cache is full then expired responses are removed, and if it's
+    /// still full the response closest to expiring is dropped.
+    pub fn insert(&self, token: Vec<u8>, response: Vec<u8>, now: u64) {
+        if self.len_max == 0 {
+            return;
+        }
+
+        if !self.entries.contains_key(&token) {
+            self.make_room(now);
+        }
+
+        let entry = Entry {
+            expires_at: now.saturating_add(self.ttl),
+            response: Some(response),
+        };
+        self.entries.insert(token, entry);
+    }
+
+    /// Make room for a new entry if the cache is full, removing expired
+    /// entries and then the response closest to expiring.
+    ///
+    /// Claims that haven't expired are kept, since their requests are still
+    /// being dispatched.
+    fn make_room(&self, now: u64) {
+        if self.entries.len() < self.len_max {
+            return;
+        }
+
+        self.entries.retain(|_, entry| entry.expires_at > now);
+
+        if self.entries.len() >= self.len_max {
+            let oldest = self
+                .entries
+                .iter()
+                .filter(|entry| entry.value().response.is_some())
+                .min_by_key(|entry| entry.value().expires_at)
+                .map(|entry| entry.key().clone());
+
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Claim, IdempotencyCache};
+    use core::fmt::Debug;
+    use static_assertions::assert_impl_all;
+
+    assert_impl_all!(IdempotencyCache: Debug, Send, Sync);
+
+    #[test]
+    fn test_expires() {
+        let cache = IdempotencyCache::new(4, 100);
+        cache.insert(b"a".to_vec(), b"1".to_vec(), 1000);
+
+        assert_eq!(Some(b"1".to_vec()), cache.get(b"a", 1099));
+        assert!(cache.get(b"a", 1100).is_none());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_bounded() {
+        let cache = IdempotencyCache::new(2, 100);
+        cache.insert(b"a".to_vec(), b"1".to_vec(), 1000);
+        cache.insert(b"b".to_vec(), b"2".to_vec(), 1001);
+        cache.insert(b"c".to_vec(), b"3".to_vec(), 1002);
+
+        // the response closest to expiring was dropped to make room
+        assert_eq!(2, cache.len());
+        assert!(cache.get(b"a", 1002).is_none());
+        assert_eq!(Some(b"2".to_vec()), cache.get(b"b", 1002));
+        assert_eq!(Some(b"3".to_vec()), cache.get(b"c", 1002));
+
+        // expired responses are removed first
+        cache.insert(b"d".to_vec(), b"4".to_vec(), 1101);
+        assert_eq!(2, cache.len());
+        assert!(cache.get(b"b", 1101).is_none());
+        assert_eq!(Some(b"3".to_vec()), cache.get(b"c", 1101));
+    }
+
+    #[test]
+    fn test_claim() {
+        let cache = IdempotencyCache::new(4, 100);
+        assert_eq!(Claim::Claimed, cache.claim(b"a", 1000));
+
+        // the claim is pending until a response is inserted
+        assert_eq!(Claim::Pending, cache.claim(b"a", 1001));
+        assert!(cache.get(b"a", 1001).is_none());
+        cache.insert(b"a".to_vec(), b"1".to_vec(), 1002);
+        assert_eq!(Claim::Response(b"1".to_vec()), cache.claim(b"a", 1003));
+
+        // releasing keeps responses but drops claims
+        cache.release(b"a");
+        assert_eq!(Some(b"1".to_vec()), cache.get(b"a", 1003));
+        assert_eq!(Claim::Claimed, cache.claim(b"b", 1003));
+        cache.release(b"b");
+        assert_eq!(Claim::Claimed, cache.claim(b"b", 1004));
+
+        // claims expire like responses
+        assert_eq!(Claim::Claimed, cache.claim(b"b", 1104));
+    }
+
+    #[test]
+    fn test_claims_kept_when_full() {
+        let cache = IdempotencyCache::new(2, 100);
+        assert_eq!(Claim::Claimed, cache.claim(b"a", 1000));
+        cache.insert(b"b".to_vec(), b"2".to_vec(), 1001);
+        cache.insert(b"c".to_vec(), b"3".to_vec(), 1002);
+
+        // the response was dropped to make room rather than the claim
+        assert_eq!(Claim::Pending, cache.claim(b"a", 1002));
+        assert!(cache.get(b"b", 1002).is_none());
+        assert_eq!(Some(b"3".to_vec()), cache.get(b"c", 1002));
+    }
+
+    #[test]
+    fn test_disabled() {
+        let cache = IdempotencyCache::new(0, 100);
+        cache.insert(b"a".to_vec(), b"1".to_vec(), 1000);
+
+        assert!(cache.is_empty());
+    }
+}
@@ -1,17 +1,21 @@
 #![deny(clippy::all, clippy::cargo)]
 #![forbid(unsafe_code)]
 #![allow(clippy::multiple_crate_versions)]
-#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
 
 pub extern crate dashmap;
 
 extern crate alloc;
 
 pub mod command;
+pub mod connection;
 pub mod hop;
+pub mod idempotency;
 pub mod metrics;
+pub mod pattern;
 pub mod pubsub;
 pub mod session;
+pub mod slowlog;
 pub mod state;
 
 pub use hop::Hop;
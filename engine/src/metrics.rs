@@ -11,13 +11,23 @@ pub enum Metric {
     CommandsErrored = 1,
     SessionsStarted = 10,
     SessionsEnded = 11,
+    KeysEvicted = 20,
+    ConnectionsOpened = 30,
+    ConnectionsActive = 31,
+    BytesRead = 32,
+    BytesWritten = 33,
 }
 
 impl Metric {
     pub fn name(self) -> &'static str {
         match self {
+            Metric::BytesRead => "bytes_read",
+            Metric::BytesWritten => "bytes_written",
             Metric::CommandsErrored => "commands_errored",
             Metric::CommandsSuccessful => "commands_successful",
+            Metric::ConnectionsActive => "connections_active",
+            Metric::ConnectionsOpened => "connections_opened",
+            Metric::KeysEvicted => "keys_evicted",
             Metric::SessionsEnded => "sessions_ended",
             Metric::SessionsStarted => "sessions_started",
         }
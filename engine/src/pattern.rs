@@ -0,0 +1,123 @@
+//! Glob-style pattern matching over keys.
+//!
+//! Patterns support the following special characters:
+//!
+//! - `*` matches any number of bytes, including none;
+//! - `?` matches exactly one byte;
+//! - `\` escapes the next byte so that it's matched literally.
+//!
+//! Every other byte matches itself.
+
+/// Check whether the input matches a glob-style pattern.
+///
+/// # Examples
+///
+/// ```
+/// use hop_engine::pattern;
+///
+/// assert!(pattern::matches(b"user:*", b"user:1"));
+/// assert!(pattern::matches(b"user:?", b"user:1"));
+/// assert!(!pattern::matches(b"user:?", b"user:10"));
+/// assert!(pattern::matches(br"\*", b"*"));
+/// ```
+pub fn matches(pattern: &[u8], input: &[u8]) -> bool {
+    let (mut p, mut i) = (0, 0);
+
+    // Position in the pattern after the last `*` and the position in the input
+    // that it's currently matching up to, used for backtracking.
+    let mut star: Option<(usize, usize)> = None;
+
+    while i < input.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                p += 1;
+                star = Some((p, i));
+
+                continue;
+            }
+            Some(b'?') => {
+                p += 1;
+                i += 1;
+
+                continue;
+            }
+            Some(b'\\') if pattern.get(p + 1) == Some(&input[i]) => {
+                p += 2;
+                i += 1;
+
+                continue;
+            }
+            Some(byte) if *byte != b'\\' && *byte == input[i] => {
+                p += 1;
+                i += 1;
+
+                continue;
+            }
+            _ => {}
+        }
+
+        // The bytes didn't match, so let the last star consume one more byte
+        // and try again from there.
+        match star {
+            Some((star_p, star_i)) => {
+                p = star_p;
+                i = star_i + 1;
+                star = Some((star_p, i));
+            }
+            None => return false,
+        }
+    }
+
+    // Any remaining pattern can only match if it's entirely stars.
+    pattern[p.min(pattern.len())..]
+        .iter()
+        .all(|byte| *byte == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::matches;
+
+    #[test]
+    fn test_literal() {
+        assert!(matches(b"foo", b"foo"));
+        assert!(!matches(b"foo", b"fo"));
+        assert!(!matches(b"foo", b"fooo"));
+        assert!(matches(b"", b""));
+        assert!(!matches(b"", b"a"));
+    }
+
+    #[test]
+    fn test_star() {
+        assert!(matches(b"*", b""));
+        assert!(matches(b"*", b"anything"));
+        assert!(matches(b"foo*", b"foo"));
+        assert!(matches(b"foo*", b"foobar"));
+        assert!(matches(b"*bar", b"foobar"));
+        assert!(matches(b"f*o*r", b"foobar"));
+        assert!(matches(b"a*b*c", b"aXbYbZc"));
+        assert!(!matches(b"foo*", b"fo"));
+        assert!(!matches(b"*bar", b"barfoo"));
+    }
+
+    #[test]
+    fn test_question_mark() {
+        assert!(matches(b"?", b"a"));
+        assert!(!matches(b"?", b""));
+        assert!(matches(b"f?o", b"foo"));
+        assert!(!matches(b"f?o", b"fooo"));
+        assert!(matches(b"*?", b"a"));
+        assert!(!matches(b"*?", b""));
+    }
+
+    #[test]
+    fn test_escape() {
+        assert!(matches(br"\*", b"*"));
+        assert!(!matches(br"\*", b"a"));
+        assert!(matches(br"\?", b"?"));
+        assert!(!matches(br"\?", b"a"));
+        assert!(matches(br"foo\\", br"foo\"));
+        assert!(matches(br"*\*", b"abc*"));
+        assert!(!matches(br"\", b"a"));
+    }
+}
@@ -0,0 +1,44 @@
+use crate::{command::CommandId, state::Key};
+
+/// An event emitted when a command modifies a key.
+///
+/// Keyspace events are sent to watchers whose pattern matches the key; refer
+/// to [`PubSubManager::watch_keyspace`] for more information.
+///
+/// [`PubSubManager::watch_keyspace`]: struct.PubSubManager.html#method.watch_keyspace
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct KeyspaceEvent {
+    command_id: CommandId,
+    key: Key,
+}
+
+impl KeyspaceEvent {
+    /// Create a new event for a command that modified a key.
+    pub fn new(command_id: CommandId, key: Key) -> Self {
+        Self { command_id, key }
+    }
+
+    /// The ID of the command that modified the key.
+    pub fn command_id(&self) -> CommandId {
+        self.command_id
+    }
+
+    /// Return an immutable reference to the key that was modified.
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+
+    /// Consume the event, returning the key that was modified.
+    pub fn into_key(self) -> Key {
+        self.key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeyspaceEvent;
+    use core::{fmt::Debug, hash::Hash};
+    use static_assertions::assert_impl_all;
+
+    assert_impl_all!(KeyspaceEvent: Clone, Debug, Eq, Hash, PartialEq, Send, Sync);
+}
@@ -1,11 +1,27 @@
-use super::Subscription;
-use crate::{session::SessionId, state::Key};
-use alloc::sync::{Arc, Weak};
+use super::{KeyspaceEvent, KeyspaceReceiver, Subscription};
+use crate::{command::CommandId, pattern, session::SessionId, state::Key};
+use alloc::{
+    sync::{Arc, Weak},
+    vec::Vec,
+};
+use core::sync::atomic::{AtomicU64, Ordering};
 use dashmap::{mapref::entry::Entry, DashMap, DashSet};
+use futures_intrusive::channel::{
+    shared::{self, Sender},
+    TrySendError,
+};
+
+#[derive(Debug)]
+struct KeyspaceWatcher {
+    pattern: Key,
+    tx: Sender<KeyspaceEvent>,
+}
 
 #[derive(Debug, Default)]
 struct PubSubManagerRef {
     keys: DashMap<Key, DashSet<SessionId>>,
+    keyspace_watchers: DashMap<u64, KeyspaceWatcher>,
+    keyspace_watcher_id: AtomicU64,
     sessions: DashMap<SessionId, DashMap<Key, Arc<Subscription>>>,
 }
 
@@ -13,6 +29,10 @@ struct PubSubManagerRef {
 pub struct PubSubManager(Arc<PubSubManagerRef>);
 
 impl PubSubManager {
+    /// Number of keyspace events that may be buffered for a watcher before
+    /// further events are dropped.
+    pub const KEYSPACE_EVENTS_BUFFERED: usize = 1024;
+
     /// Retrieves a session's subscription for a key, if it exists.
     pub fn get(&self, object_key: &[u8], session_id: SessionId) -> Option<Weak<Subscription>> {
         let session = self.0.sessions.get(&session_id)?;
@@ -74,6 +94,61 @@ impl PubSubManager {
         key_unsubbed || session_unsubbed
     }
 
+    /// Watch for modifications to keys matching a glob-style pattern.
+    ///
+    /// A [`KeyspaceEvent`] is sent to the returned receiver each time a
+    /// command modifies a matching key. Refer to the [`pattern`] module for the
+    /// pattern syntax.
+    ///
+    /// Up to [`KEYSPACE_EVENTS_BUFFERED`] events are buffered for the watcher;
+    /// events are dropped for watchers that don't keep up. Dropping the
+    /// receiver stops the watch.
+    ///
+    /// [`KEYSPACE_EVENTS_BUFFERED`]: #associatedconstant.KEYSPACE_EVENTS_BUFFERED
+    /// [`KeyspaceEvent`]: struct.KeyspaceEvent.html
+    /// [`pattern`]: ../pattern/index.html
+    pub fn watch_keyspace(&self, pattern: Key) -> KeyspaceReceiver {
+        let (tx, rx) = shared::channel(Self::KEYSPACE_EVENTS_BUFFERED);
+        let id = self.0.keyspace_watcher_id.fetch_add(1, Ordering::Relaxed);
+
+        self.0
+            .keyspace_watchers
+            .insert(id, KeyspaceWatcher { pattern, tx });
+
+        rx
+    }
+
+    /// Whether there are any keyspace watchers.
+    ///
+    /// This can be used to avoid building events when nobody is watching.
+    pub fn has_keyspace_watchers(&self) -> bool {
+        !self.0.keyspace_watchers.is_empty()
+    }
+
+    /// Notify keyspace watchers with a pattern matching the key that a command
+    /// modified it.
+    ///
+    /// Watchers whose receivers have been dropped are removed.
+    pub fn notify_keyspace(&self, command_id: CommandId, key: &[u8]) {
+        let mut closed = Vec::new();
+
+        for watcher in self.0.keyspace_watchers.iter() {
+            if !pattern::matches(&watcher.pattern, key) {
+                continue;
+            }
+
+            let event = KeyspaceEvent::new(command_id, key.to_vec());
+
+            if let Err(TrySendError::Closed(_)) = watcher.tx.try_send(event) {
+                closed.push(*watcher.key());
+            }
+        }
+
+        for id in closed {
+            self.0.keyspace_watchers.remove(&id);
+        }
+    }
+
     /// Unsubscribes a session from all of its subscriptions.
     ///
     /// Returns whether the session was subscribed to any channels.
@@ -93,9 +168,43 @@ impl PubSubManager {
 #[cfg(test)]
 mod tests {
     use super::{PubSubManager, PubSubManagerRef};
+    use crate::command::CommandId;
     use core::fmt::Debug;
     use static_assertions::assert_impl_all;
 
     assert_impl_all!(PubSubManagerRef: Debug, Default);
     assert_impl_all!(PubSubManager: Clone, Debug, Default);
+
+    #[test]
+    fn test_keyspace_watch() {
+        let manager = PubSubManager::default();
+        assert!(!manager.has_keyspace_watchers());
+
+        let rx = manager.watch_keyspace(b"foo*".to_vec());
+        assert!(manager.has_keyspace_watchers());
+
+        manager.notify_keyspace(CommandId::Set, b"foobar");
+        manager.notify_keyspace(CommandId::Set, b"bar");
+        manager.notify_keyspace(CommandId::Delete, b"foo");
+
+        let event = rx.try_receive().unwrap();
+        assert_eq!(CommandId::Set, event.command_id());
+        assert_eq!(b"foobar", event.key());
+
+        let event = rx.try_receive().unwrap();
+        assert_eq!(CommandId::Delete, event.command_id());
+        assert_eq!(b"foo", event.key());
+
+        assert!(rx.try_receive().is_err());
+    }
+
+    #[test]
+    fn test_keyspace_watch_dropped() {
+        let manager = PubSubManager::default();
+        let rx = manager.watch_keyspace(b"*".to_vec());
+        drop(rx);
+
+        manager.notify_keyspace(CommandId::Set, b"foo");
+        assert!(!manager.has_keyspace_watchers());
+    }
 }
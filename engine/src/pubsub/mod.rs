@@ -1,10 +1,18 @@
 mod key_update;
+mod keyspace_event;
 mod manager;
 
-pub use self::{key_update::KeyUpdate, manager::PubSubManager};
+pub use self::{key_update::KeyUpdate, keyspace_event::KeyspaceEvent, manager::PubSubManager};
 
 use futures_intrusive::channel::shared::{self, Receiver, Sender};
 
+/// Receiver of keyspace events from a keyspace watch.
+///
+/// Refer to [`PubSubManager::watch_keyspace`] for more information.
+///
+/// [`PubSubManager::watch_keyspace`]: struct.PubSubManager.html#method.watch_keyspace
+pub type KeyspaceReceiver = Receiver<KeyspaceEvent>;
+
 #[derive(Debug)]
 pub struct Subscription {
     rx: Receiver<KeyUpdate>,
@@ -0,0 +1,160 @@
+//! Log of commands that took a long time to dispatch.
+//!
+//! The log is a bounded ring buffer: once it's full, recording an entry drops
+//! the oldest one. Refer to [`Builder::slowlog_threshold`] for configuring
+//! which commands are recorded.
+//!
+//! [`Builder::slowlog_threshold`]: ../hop/struct.Builder.html#method.slowlog_threshold
+
+use crate::command::CommandId;
+use alloc::vec::Vec;
+use core::{
+    cmp::Reverse,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use dashmap::DashMap;
+
+/// A command recorded in the slowlog.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SlowLogEntry {
+    command_id: CommandId,
+    duration: u64,
+    timestamp: u64,
+}
+
+impl SlowLogEntry {
+    /// Create a new entry.
+    pub fn new(command_id: CommandId, timestamp: u64, duration: u64) -> Self {
+        Self {
+            command_id,
+            duration,
+            timestamp,
+        }
+    }
+
+    /// Retrieve the ID of the command.
+    pub fn command_id(&self) -> CommandId {
+        self.command_id
+    }
+
+    /// Retrieve how long the command took to dispatch in microseconds.
+    pub fn duration(&self) -> u64 {
+        self.duration
+    }
+
+    /// Retrieve the Unix time in milliseconds when the command was
+    /// dispatched.
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+}
+
+/// Bounded log of slow commands.
+#[derive(Debug)]
+pub struct SlowLog {
+    entries: DashMap<u64, SlowLogEntry>,
+    len_max: usize,
+    next_id: AtomicU64,
+}
+
+impl SlowLog {
+    /// Create a new slowlog keeping at most the given number of entries.
+    pub fn new(len_max: usize) -> Self {
+        Self {
+            entries: DashMap::new(),
+            len_max,
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Retrieve the entries, newest first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::{
+    ///     command::CommandId,
+    ///     slowlog::{SlowLog, SlowLogEntry},
+    /// };
+    ///
+    /// let slowlog = SlowLog::new(2);
+    /// slowlog.record(SlowLogEntry::new(CommandId::Get, 1000, 50));
+    /// slowlog.record(SlowLogEntry::new(CommandId::Set, 1001, 60));
+    /// slowlog.record(SlowLogEntry::new(CommandId::Keys, 1002, 70));
+    ///
+    /// // the oldest entry was dropped to make room
+    /// let entries = slowlog.entries();
+    /// assert_eq!(2, entries.len());
+    /// assert_eq!(CommandId::Keys, entries[0].command_id());
+    /// assert_eq!(CommandId::Set, entries[1].command_id());
+    /// ```
+    pub fn entries(&self) -> Vec<SlowLogEntry> {
+        let mut entries = self
+            .entries
+            .iter()
+            .map(|entry| (*entry.key(), *entry.value()))
+            .collect::<Vec<_>>();
+        entries.sort_unstable_by_key(|(id, _)| Reverse(*id));
+
+        entries.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    /// Retrieve the maximum number of entries kept.
+    pub fn len_max(&self) -> usize {
+        self.len_max
+    }
+
+    /// Record an entry, dropping the oldest entry if the log is full.
+    pub fn record(&self, entry: SlowLogEntry) {
+        if self.len_max == 0 {
+            return;
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        self.entries.insert(id, entry);
+
+        if let Some(oldest) = id.checked_sub(self.len_max as u64) {
+            self.entries.remove(&oldest);
+        }
+    }
+
+    /// Remove all of the entries, returning how many were removed.
+    pub fn reset(&self) -> usize {
+        let len = self.entries.len();
+        self.entries.clear();
+
+        len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SlowLog, SlowLogEntry};
+    use crate::command::CommandId;
+    use core::{fmt::Debug, hash::Hash};
+    use static_assertions::assert_impl_all;
+
+    assert_impl_all!(SlowLog: Debug, Send, Sync);
+    assert_impl_all!(SlowLogEntry: Clone, Copy, Debug, Eq, Hash, PartialEq);
+
+    #[test]
+    fn test_reset() {
+        let slowlog = SlowLog::new(4);
+        slowlog.record(SlowLogEntry::new(CommandId::Get, 1000, 50));
+        slowlog.record(SlowLogEntry::new(CommandId::Set, 1001, 60));
+
+        assert_eq!(2, slowlog.reset());
+        assert!(slowlog.entries().is_empty());
+
+        slowlog.record(SlowLogEntry::new(CommandId::Echo, 1002, 70));
+        assert_eq!(1, slowlog.entries().len());
+    }
+
+    #[test]
+    fn test_disabled() {
+        let slowlog = SlowLog::new(0);
+        slowlog.record(SlowLogEntry::new(CommandId::Get, 1000, 50));
+
+        assert!(slowlog.entries().is_empty());
+    }
+}
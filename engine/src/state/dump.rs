@@ -0,0 +1,460 @@
+//! Serialization of a single key's value into an opaque blob, used to migrate
+//! keys between instances.
+//!
+//! A blob is laid out as:
+//!
+//! - 1 byte for the format [`VERSION`];
+//! - 1 byte for the [`KeyType`] of the value;
+//! - 8 bytes for the remaining time to live in milliseconds, where 0 means
+//!   the key doesn't expire;
+//! - the value's data.
+//!
+//! Booleans are a single byte, floats and integers are 8 bytes and bytes and
+//! strings are their raw bytes. Lists and sets are a 4 byte item count followed
+//! by each item as a 4 byte length and its bytes. Maps are the same but with a
+//! key and value per item, where each value is 1 byte for its [`KeyType`]
+//! followed by its data as an item. Sorted sets are the same as sets but with
+//! each member preceded by its 8 byte score.
+//!
+//! Many keys can be serialized together as a batch of entries, used to load
+//! them in a single request. Each entry is laid out as:
+//!
+//! - 1 byte for the [`KeyType`] of the value;
+//! - a 4 byte key length and the key;
+//! - a 4 byte data length and the value's data.
+//!
+//! All integers are big endian.
+//!
+//! [`KeyType`]: ../enum.KeyType.html
+//! [`VERSION`]: constant.VERSION.html
+
+use super::{KeyType, SortedSet, Value};
+use alloc::{collections::VecDeque, string::String, vec::Vec};
+use core::convert::{TryFrom, TryInto};
+use dashmap::{DashMap, DashSet};
+
+/// Version of the blob format, incremented whenever the format changes.
+pub const VERSION: u8 = 2;
+
+/// Version of the blob format before map values could be of any type, whose
+/// map values are all bytes.
+const VERSION_BYTE_MAPS: u8 = 1;
+
+/// Serialize a value and its remaining time to live in milliseconds into a
+/// blob.
+///
+/// # Examples
+///
+/// ```
+/// use hop_engine::state::{dump, Value};
+///
+/// let blob = dump::dump(&Value::Integer(7), Some(1500));
+/// let (value, ttl) = dump::restore(&blob).unwrap();
+///
+/// assert_eq!(Some(&7), value.as_integer_ref());
+/// assert_eq!(Some(1500), ttl);
+/// ```
+pub fn dump(value: &Value, ttl: Option<u64>) -> Vec<u8> {
+    let mut blob = Vec::with_capacity(1 + 1 + 8 + value.size());
+    blob.push(VERSION);
+    blob.push(value.kind() as u8);
+    blob.extend_from_slice(&ttl.unwrap_or(0).to_be_bytes());
+
+    encode(value, &mut blob);
+
+    blob
+}
+
+/// Deserialize a blob into a value and its remaining time to live in
+/// milliseconds.
+///
+/// Blobs of the previous version are still restored, with their map values
+/// as bytes.
+///
+/// Returns `None` if the blob is malformed or of an unknown version.
+pub fn restore(blob: &[u8]) -> Option<(Value, Option<u64>)> {
+    let (header, data) = (blob.get(..10)?, &blob[10..]);
+    let version = header[0];
+
+    if version != VERSION && version != VERSION_BYTE_MAPS {
+        return None;
+    }
+
+    let kind = KeyType::try_from(header[1]).ok()?;
+    let ttl = match u64::from_be_bytes(header[2..10].try_into().ok()?) {
+        0 => None,
+        ttl => Some(ttl),
+    };
+
+    let value = decode_version(version, kind, data)?;
+
+    Some((value, ttl))
+}
+
+/// Serialize a key and its value as an entry onto the end of a batch.
+///
+/// # Examples
+///
+/// ```
+/// use hop_engine::{
+///     command::{request::RequestBuilder, CommandId},
+///     state::{dump, Value},
+///     Hop,
+/// };
+///
+/// let mut batch = Vec::new();
+/// dump::encode_entry(b"foo", &Value::Integer(1), &mut batch);
+/// dump::encode_entry(b"bar", &Value::Boolean(true), &mut batch);
+///
+/// let mut builder = RequestBuilder::new(CommandId::BulkLoad);
+/// builder.bytes(batch).unwrap();
+///
+/// let hop = Hop::new();
+/// hop.dispatch(&builder.into_request(), &mut Vec::new()).unwrap();
+///
+/// assert!(hop.state().contains_key(b"foo"));
+/// assert!(hop.state().contains_key(b"bar"));
+/// ```
+pub fn encode_entry(key: &[u8], value: &Value, out: &mut Vec<u8>) {
+    let mut data = Vec::with_capacity(value.size());
+    encode(value, &mut data);
+
+    out.push(value.kind() as u8);
+    push_item(out, key);
+    push_item(out, &data);
+}
+
+/// Deserialize every entry of a batch, appending them to a list.
+///
+/// Returns `None` if any entry is malformed, in which case some entries may
+/// already have been appended.
+pub fn decode_entries(batch: &[u8], entries: &mut Vec<(Vec<u8>, Value)>) -> Option<()> {
+    let mut reader = Reader(batch);
+
+    while let Some((kind, rest)) = reader.0.split_first() {
+        let kind = KeyType::try_from(*kind).ok()?;
+        reader.0 = rest;
+
+        let key = reader.item()?;
+        let value = decode(kind, reader.item()?)?;
+
+        entries.push((key.to_vec(), value));
+    }
+
+    Some(())
+}
+
+/// Serialize a value's data, without the blob header, onto the end of a
+/// buffer.
+pub fn encode(value: &Value, out: &mut Vec<u8>) {
+    match value {
+        Value::Boolean(boolean) => out.push(*boolean as u8),
+        Value::Bytes(bytes) => out.extend_from_slice(bytes),
+        Value::Float(float) => out.extend_from_slice(&float.to_be_bytes()),
+        Value::Integer(int) => out.extend_from_slice(&int.to_be_bytes()),
+        // Packed sets are dumped like any other set, and packed again when
+        // they're loaded.
+        Value::IntSet(set) => {
+            push_len(out, set.len());
+
+            for member in set.members() {
+                push_item(out, &member);
+            }
+        }
+        Value::List(list) => {
+            push_len(out, list.len());
+
+            for item in list {
+                push_item(out, item);
+            }
+        }
+        Value::Map(map) => {
+            push_len(out, map.len());
+
+            let mut data = Vec::new();
+
+            for item in map.iter() {
+                push_item(out, item.key());
+
+                data.clear();
+                encode(item.value(), &mut data);
+                out.push(item.value().kind() as u8);
+                push_item(out, &data);
+            }
+        }
+        Value::Set(set) => {
+            push_len(out, set.len());
+
+            for item in set.iter() {
+                push_item(out, item.key());
+            }
+        }
+        Value::SortedSet(set) => {
+            push_len(out, set.len());
+
+            for (member, score) in set.iter() {
+                out.extend_from_slice(&score.to_be_bytes());
+                push_item(out, member);
+            }
+        }
+        Value::String(string) => out.extend_from_slice(string.as_bytes()),
+    }
+}
+
+/// Deserialize a value of the given type from its data, laid out as in a
+/// blob but without the header.
+///
+/// Returns `None` if the data is malformed.
+pub fn decode(kind: KeyType, data: &[u8]) -> Option<Value> {
+    decode_version(VERSION, kind, data)
+}
+
+fn decode_version(version: u8, kind: KeyType, data: &[u8]) -> Option<Value> {
+    let value = match kind {
+        KeyType::Boolean => match data {
+            [byte] => Value::Boolean(*byte > 0),
+            _ => return None,
+        },
+        KeyType::Bytes => Value::Bytes(data.to_vec()),
+        KeyType::Float => Value::Float(f64::from_be_bytes(data.try_into().ok()?)),
+        KeyType::Integer => Value::Integer(i64::from_be_bytes(data.try_into().ok()?)),
+        KeyType::List => {
+            let mut reader = Reader(data);
+            let len = reader.len()?;
+            let mut list = VecDeque::with_capacity(len.min(data.len()));
+
+            for _ in 0..len {
+                list.push_back(reader.item()?.to_vec());
+            }
+
+            reader.finish()?;
+
+            Value::List(list)
+        }
+        KeyType::Map => {
+            let mut reader = Reader(data);
+            let len = reader.len()?;
+            let map = DashMap::new();
+
+            for _ in 0..len {
+                let key = reader.item()?.to_vec();
+                let value = if version == VERSION_BYTE_MAPS {
+                    Value::Bytes(reader.item()?.to_vec())
+                } else {
+                    let kind = reader.kind()?;
+
+                    decode_version(version, kind, reader.item()?)?
+                };
+
+                map.insert(key, value);
+            }
+
+            reader.finish()?;
+
+            Value::Map(map)
+        }
+        KeyType::Set => {
+            let mut reader = Reader(data);
+            let len = reader.len()?;
+            let set = DashSet::new();
+
+            for _ in 0..len {
+                set.insert(reader.item()?.to_vec());
+            }
+
+            reader.finish()?;
+
+            Value::set_from(set)
+        }
+        KeyType::SortedSet => {
+            let mut reader = Reader(data);
+            let len = reader.len()?;
+            let mut set = SortedSet::new();
+
+            for _ in 0..len {
+                let score = reader.score()?;
+                set.insert(reader.item()?.to_vec(), score);
+            }
+
+            reader.finish()?;
+
+            Value::SortedSet(set)
+        }
+        KeyType::String => Value::String(String::from_utf8(data.to_vec()).ok()?),
+    };
+
+    Some(value)
+}
+
+fn push_len(blob: &mut Vec<u8>, len: usize) {
+    blob.extend_from_slice(&(len as u32).to_be_bytes());
+}
+
+fn push_item(blob: &mut Vec<u8>, item: &[u8]) {
+    push_len(blob, item.len());
+    blob.extend_from_slice(item);
+}
+
+/// Reader over the lengths and items of a collection's data.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn kind(&mut self) -> Option<KeyType> {
+        let (kind, rest) = self.0.split_first()?;
+        self.0 = rest;
+
+        KeyType::try_from(*kind).ok()
+    }
+
+    fn len(&mut self) -> Option<usize> {
+        let bytes = self.0.get(..4)?;
+        self.0 = &self.0[4..];
+
+        Some(u32::from_be_bytes(bytes.try_into().ok()?) as usize)
+    }
+
+    fn item(&mut self) -> Option<&'a [u8]> {
+        let len = self.len()?;
+        let item = self.0.get(..len)?;
+        self.0 = &self.0[len..];
+
+        Some(item)
+    }
+
+    fn score(&mut self) -> Option<f64> {
+        let bytes = self.0.get(..8)?;
+        self.0 = &self.0[8..];
+
+        Some(f64::from_be_bytes(bytes.try_into().ok()?)).filter(|score| !score.is_nan())
+    }
+
+    /// Ensure that all of the data was read.
+    fn finish(self) -> Option<()> {
+        if self.0.is_empty() {
+            Some(())
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dump, restore, VERSION};
+    use crate::state::{KeyType, SortedSet, Value};
+    use alloc::{string::String, vec, vec::Vec};
+    use dashmap::{DashMap, DashSet};
+
+    fn roundtrip(value: Value, ttl: Option<u64>) -> Value {
+        let kind = value.kind();
+        let (restored, restored_ttl) = restore(&dump(&value, ttl)).unwrap();
+
+        assert_eq!(kind, restored.kind());
+        assert_eq!(ttl, restored_ttl);
+
+        restored
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let value = roundtrip(Value::Boolean(true), None);
+        assert_eq!(Some(&true), value.as_boolean_ref());
+
+        let value = roundtrip(Value::Bytes(vec![1, 2, 3]), Some(1));
+        assert_eq!(Some([1, 2, 3].as_ref()), value.as_bytes_ref());
+
+        let value = roundtrip(Value::Float(1.5), None);
+        assert_eq!(Some(&1.5), value.as_float_ref());
+
+        let value = roundtrip(Value::Integer(-7), Some(u64::MAX));
+        assert_eq!(Some(&-7), value.as_integer_ref());
+
+        let list = vec![b"foo".to_vec(), Vec::new(), b"bar".to_vec()];
+        let value = roundtrip(Value::from(list.clone()), None);
+        assert_eq!(Some(&list.into()), value.as_list_ref());
+
+        let map = DashMap::new();
+        map.insert(b"foo".to_vec(), Value::Bytes(b"bar".to_vec()));
+        map.insert(b"baz".to_vec(), Value::Bytes(Vec::new()));
+        map.insert(b"qux".to_vec(), Value::Integer(3));
+        map.insert(b"quux".to_vec(), Value::from(vec![b"a".to_vec()]));
+        let value = roundtrip(Value::Map(map.clone()), None);
+        assert_eq!(Value::Map(map), value);
+
+        let set = DashSet::new();
+        set.insert(b"foo".to_vec());
+        set.insert(b"bar".to_vec());
+        let value = roundtrip(Value::Set(set), None);
+        let set = value.as_set_ref().unwrap();
+        assert_eq!(2, set.len());
+        assert!(set.contains(b"foo".as_ref()));
+        assert!(set.contains(b"bar".as_ref()));
+
+        let mut sorted_set = SortedSet::new();
+        sorted_set.insert(b"foo".to_vec(), 2.0);
+        sorted_set.insert(b"bar".to_vec(), -1.0);
+        let value = roundtrip(Value::SortedSet(sorted_set), None);
+        let sorted_set = value.as_sorted_set_ref().unwrap();
+        assert_eq!(
+            vec![(b"bar".as_ref(), -1.0), (b"foo".as_ref(), 2.0)],
+            sorted_set.iter().collect::<Vec<_>>()
+        );
+
+        let value = roundtrip(Value::String(String::from("foo")), None);
+        assert_eq!(Some("foo"), value.as_string_ref());
+    }
+
+    #[test]
+    fn test_byte_maps_version() {
+        let mut blob = vec![1, KeyType::Map as u8];
+        blob.extend_from_slice(&[0; 8]);
+        blob.extend_from_slice(&1u32.to_be_bytes());
+        blob.extend_from_slice(&3u32.to_be_bytes());
+        blob.extend_from_slice(b"foo");
+        blob.extend_from_slice(&3u32.to_be_bytes());
+        blob.extend_from_slice(b"bar");
+
+        let (value, ttl) = restore(&blob).unwrap();
+        let map = value.as_map_ref().unwrap();
+        assert!(ttl.is_none());
+        assert_eq!(1, map.len());
+        assert_eq!(
+            Some(b"bar".as_ref()),
+            map.get(b"foo".as_ref()).unwrap().as_bytes_ref()
+        );
+    }
+
+    #[test]
+    fn test_malformed() {
+        let blob = dump(&Value::from(vec![b"foo".to_vec()]), None);
+
+        // truncated
+        assert!(restore(&blob[..blob.len() - 1]).is_none());
+        assert!(restore(&blob[..5]).is_none());
+
+        // trailing data
+        let mut trailing = blob.clone();
+        trailing.push(0);
+        assert!(restore(&trailing).is_none());
+
+        // different version
+        let mut version = blob.clone();
+        version[0] = VERSION + 1;
+        assert!(restore(&version).is_none());
+
+        // invalid key type
+        let mut kind = blob;
+        kind[1] = 255;
+        assert!(restore(&kind).is_none());
+
+        // invalid utf-8
+        let mut string = dump(&Value::String(String::from("a")), None);
+        string[10] = 0xff;
+        assert!(restore(&string).is_none());
+
+        // wrong size of integer
+        let int = dump(&Value::Integer(1), None);
+        assert!(restore(&int[..int.len() - 1]).is_none());
+        assert_eq!(KeyType::Integer as u8, int[1]);
+    }
+}
@@ -0,0 +1,195 @@
+//! Approximate counting of unique items with a HyperLogLog.
+//!
+//! A HyperLogLog is stored as a plain bytes value of exactly [`SIZE`] bytes,
+//! one per register. Each item is hashed, the first [`PRECISION`] bits of the
+//! hash select a register, and the register keeps the longest run of leading
+//! zeroes seen in the remaining bits. The standard error of the estimate is
+//! about 0.81%.
+//!
+//! [`PRECISION`]: constant.PRECISION.html
+//! [`SIZE`]: constant.SIZE.html
+
+use alloc::{vec, vec::Vec};
+
+/// Number of hash bits used to select a register.
+pub const PRECISION: u32 = 14;
+
+/// Number of registers, and so the size in bytes of a HyperLogLog.
+pub const SIZE: usize = 1 << PRECISION;
+
+/// Create a HyperLogLog with no items.
+pub fn new() -> Vec<u8> {
+    vec![0; SIZE]
+}
+
+/// Whether the bytes are the size of a HyperLogLog.
+pub fn is_valid(registers: &[u8]) -> bool {
+    registers.len() == SIZE
+}
+
+/// Add an item to a HyperLogLog, returning whether a register changed and so
+/// whether the estimate may have changed.
+///
+/// # Panics
+///
+/// Panics if the registers aren't [`SIZE`] bytes long.
+///
+/// [`SIZE`]: constant.SIZE.html
+pub fn add(registers: &mut [u8], item: &[u8]) -> bool {
+    let hash = hash(item);
+    let idx = (hash >> (64 - PRECISION)) as usize;
+
+    // Set a sentinel bit so that the run of zeroes is bounded even when the
+    // remaining bits are all zero.
+    let rest = (hash << PRECISION) | (1 << (PRECISION - 1));
+    let rank = rest.leading_zeros() as u8 + 1;
+
+    if rank > registers[idx] {
+        registers[idx] = rank;
+
+        true
+    } else {
+        false
+    }
+}
+
+/// Estimate the number of unique items added to a HyperLogLog.
+///
+/// # Examples
+///
+/// ```
+/// use hop_engine::state::hyper_log_log;
+///
+/// let mut registers = hyper_log_log::new();
+///
+/// for item in &[b"foo", b"bar", b"foo"] {
+///     hyper_log_log::add(&mut registers, item.as_ref());
+/// }
+///
+/// assert_eq!(2, hyper_log_log::count(&registers));
+/// ```
+pub fn count(registers: &[u8]) -> u64 {
+    let m = SIZE as f64;
+    let alpha = 0.7213 / (1.0 + 1.079 / m);
+
+    let mut sum = 0.0;
+    let mut zeroes = 0;
+
+    for &register in registers {
+        sum += 1.0 / (1u64 << register) as f64;
+
+        if register == 0 {
+            zeroes += 1;
+        }
+    }
+
+    let estimate = alpha * m * m / sum;
+
+    // Small cardinalities are estimated more accurately by linear counting.
+    let estimate = if estimate <= 2.5 * m && zeroes > 0 {
+        m * ln(m / zeroes as f64)
+    } else {
+        estimate
+    };
+
+    (estimate + 0.5) as u64
+}
+
+/// 64-bit FNV-1a hash followed by a finalizer to spread similar items across
+/// all of the bits.
+///
+/// The hash must be stable so that stored HyperLogLogs stay valid.
+fn hash(item: &[u8]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+
+    for &byte in item {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^= hash >> 33;
+
+    hash
+}
+
+/// Natural logarithm of a positive number, since `f64::ln` requires the
+/// standard library.
+fn ln(x: f64) -> f64 {
+    // Split into a mantissa in [1, 2) and an exponent.
+    let bits = x.to_bits();
+    let exponent = ((bits >> 52) & 0x7ff) as i64 - 1023;
+    let mantissa = f64::from_bits((bits & !(0x7ff << 52)) | (1023 << 52));
+
+    // ln(m) = 2 * atanh((m - 1) / (m + 1)), which converges quickly since the
+    // ratio is at most 1/3.
+    let z = (mantissa - 1.0) / (mantissa + 1.0);
+    let z2 = z * z;
+    let mut term = z;
+    let mut sum = 0.0;
+
+    for n in 0..20 {
+        sum += term / f64::from(2 * n + 1);
+        term *= z2;
+    }
+
+    exponent as f64 * core::f64::consts::LN_2 + 2.0 * sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{add, count, is_valid, ln, new, SIZE};
+    use alloc::format;
+
+    fn assert_estimate(len: u64) {
+        let mut registers = new();
+
+        for idx in 0..len {
+            add(&mut registers, format!("item:{}", idx).as_bytes());
+        }
+
+        let estimate = count(&registers);
+        let error = (estimate as f64 - len as f64).abs() / len as f64;
+
+        assert!(
+            error < 0.03,
+            "estimated {} for {} items ({:.2}% error)",
+            estimate,
+            len,
+            error * 100.0
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        let registers = new();
+        assert!(is_valid(&registers));
+        assert_eq!(0, count(&registers));
+        assert!(!is_valid(&registers[..SIZE - 1]));
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let mut registers = new();
+        assert!(add(&mut registers, b"foo"));
+        assert!(!add(&mut registers, b"foo"));
+        assert_eq!(1, count(&registers));
+    }
+
+    #[test]
+    fn test_estimates() {
+        for len in &[100, 1_000, 10_000, 100_000, 1_000_000] {
+            assert_estimate(*len);
+        }
+    }
+
+    #[test]
+    fn test_ln() {
+        for x in &[0.5, 1.0, 2.0, 3.7, 16384.0, 1e9] {
+            assert!((ln(*x) - x.ln()).abs() < 1e-9, "ln({})", x);
+        }
+    }
+}
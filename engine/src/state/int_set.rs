@@ -0,0 +1,164 @@
+//! Sets of integers packed into a sorted array.
+
+use alloc::{string::ToString, vec::Vec};
+use core::{iter::FromIterator, str};
+use dashmap::DashSet;
+
+/// A set whose members are all integers written in decimal, such as `b"-12"`,
+/// packed into a sorted array instead of a hash set of separately allocated
+/// members.
+///
+/// This is only an encoding of a set: the members are written back out as
+/// the same bytes they were parsed from, so commands and responses can't tell
+/// the difference.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct IntSet(Vec<i64>);
+
+impl IntSet {
+    /// Maximum number of members to pack. Inserting into the array is linear,
+    /// so bigger sets are better off as hash sets.
+    pub const MAX_LEN: usize = 512;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pack members, if they're all integers and there aren't too many.
+    pub fn from_members<T: IntoIterator<Item = U>, U: AsRef<[u8]>>(members: T) -> Option<Self> {
+        let set = members
+            .into_iter()
+            .map(|member| Self::parse(member.as_ref()))
+            .collect::<Option<Self>>()?;
+
+        if set.len() <= Self::MAX_LEN {
+            Some(set)
+        } else {
+            None
+        }
+    }
+
+    /// Parse a member as an integer, if it's written in the one form that an
+    /// integer is written back out as.
+    ///
+    /// Members such as `b"007"` or `b"+7"` aren't integers here, since they'd
+    /// be written back out as `b"7"`.
+    pub fn parse(member: &[u8]) -> Option<i64> {
+        let int = str::from_utf8(member).ok()?.parse::<i64>().ok()?;
+
+        if int.to_string().as_bytes() == member {
+            Some(int)
+        } else {
+            None
+        }
+    }
+
+    pub fn contains(&self, member: i64) -> bool {
+        self.0.binary_search(&member).is_ok()
+    }
+
+    /// Insert a member, returning whether it wasn't already in the set.
+    pub fn insert(&mut self, member: i64) -> bool {
+        match self.0.binary_search(&member) {
+            Ok(_) => false,
+            Err(idx) => {
+                self.0.insert(idx, member);
+
+                true
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over the members in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = i64> + '_ {
+        self.0.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Iterate over the members as the bytes they were parsed from.
+    pub fn members(&self) -> impl Iterator<Item = Vec<u8>> + '_ {
+        self.iter().map(|member| member.to_string().into_bytes())
+    }
+
+    /// Unpack the set into a hash set of its members.
+    pub fn to_set(&self) -> DashSet<Vec<u8>> {
+        let set = DashSet::with_capacity(self.len());
+
+        for member in self.members() {
+            set.insert(member);
+        }
+
+        set
+    }
+}
+
+impl FromIterator<i64> for IntSet {
+    fn from_iter<T: IntoIterator<Item = i64>>(iter: T) -> Self {
+        let mut members = iter.into_iter().collect::<Vec<_>>();
+        members.sort_unstable();
+        members.dedup();
+
+        Self(members)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IntSet;
+    use alloc::{vec, vec::Vec};
+    use core::{fmt::Debug, hash::Hash};
+    use static_assertions::assert_impl_all;
+
+    assert_impl_all!(IntSet: Clone, Debug, Default, Eq, Hash, PartialEq);
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Some(7), IntSet::parse(b"7"));
+        assert_eq!(Some(-12), IntSet::parse(b"-12"));
+        assert_eq!(Some(0), IntSet::parse(b"0"));
+        assert_eq!(Some(i64::MIN), IntSet::parse(b"-9223372036854775808"));
+
+        // only the form that's written back out is an integer
+        assert!(IntSet::parse(b"007").is_none());
+        assert!(IntSet::parse(b"+7").is_none());
+        assert!(IntSet::parse(b"-0").is_none());
+        assert!(IntSet::parse(b" 7").is_none());
+        assert!(IntSet::parse(b"").is_none());
+        assert!(IntSet::parse(b"9223372036854775808").is_none());
+        assert!(IntSet::parse(b"foo").is_none());
+    }
+
+    #[test]
+    fn test_from_members() {
+        let set = IntSet::from_members([b"3".as_ref(), b"-1", b"2", b"3"]).unwrap();
+        assert_eq!(vec![-1, 2, 3], set.iter().collect::<Vec<_>>());
+        assert!(set.contains(2));
+        assert!(!set.contains(4));
+        assert_eq!(
+            vec![b"-1".to_vec(), b"2".to_vec(), b"3".to_vec()],
+            set.members().collect::<Vec<_>>()
+        );
+
+        assert!(IntSet::from_members([b"1".as_ref(), b"foo"]).is_none());
+
+        let ints = (0..=IntSet::MAX_LEN).map(|int| int.to_string());
+        assert!(IntSet::from_members(ints).is_none());
+    }
+
+    #[test]
+    fn test_to_set() {
+        let set = IntSet::from_members([b"1".as_ref(), b"20"])
+            .unwrap()
+            .to_set();
+
+        assert_eq!(2, set.len());
+        assert!(set.contains(b"1".as_ref()));
+        assert!(set.contains(b"20".as_ref()));
+    }
+}
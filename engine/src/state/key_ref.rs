@@ -0,0 +1,100 @@
+//! References to a key and its value, holding a lock on the key's shard of
+//! the map.
+
+use super::{Key, Slot, Value};
+use core::{
+    fmt::{Debug, Formatter, Result as FmtResult},
+    ops::{Deref, DerefMut},
+};
+use dashmap::mapref::one::{Ref, RefMut};
+
+/// Immutable reference to a key and its value.
+///
+/// This is created by [`State::key_ref`].
+///
+/// [`State::key_ref`]: struct.State.html#method.key_ref
+pub struct KeyRef<'a>(pub(super) Ref<'a, Key, Slot>);
+
+impl<'a> KeyRef<'a> {
+    pub fn key(&self) -> &Key {
+        self.0.key()
+    }
+
+    pub fn value(&self) -> &Value {
+        &self.0.value().value
+    }
+
+    pub fn pair(&self) -> (&Key, &Value) {
+        (self.key(), self.value())
+    }
+}
+
+impl Debug for KeyRef<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_tuple("KeyRef").field(&self.pair()).finish()
+    }
+}
+
+impl Deref for KeyRef<'_> {
+    type Target = Value;
+
+    fn deref(&self) -> &Self::Target {
+        self.value()
+    }
+}
+
+/// Mutable reference to a key and its value.
+///
+/// This is created by [`State::key_mut`] and [`State::key_or_insert_with`].
+///
+/// [`State::key_mut`]: struct.State.html#method.key_mut
+/// [`State::key_or_insert_with`]: struct.State.html#method.key_or_insert_with
+pub struct KeyRefMut<'a>(pub(super) RefMut<'a, Key, Slot>);
+
+impl<'a> KeyRefMut<'a> {
+    pub fn key(&self) -> &Key {
+        self.0.key()
+    }
+
+    pub fn value(&self) -> &Value {
+        &self.0.value().value
+    }
+
+    pub fn value_mut(&mut self) -> &mut Value {
+        &mut self.0.value_mut().value
+    }
+
+    pub fn pair(&self) -> (&Key, &Value) {
+        (self.key(), self.value())
+    }
+
+    pub fn pair_mut(&mut self) -> (&Key, &mut Value) {
+        let (key, slot) = self.0.pair_mut();
+
+        (key, &mut slot.value)
+    }
+
+    pub fn downgrade(self) -> KeyRef<'a> {
+        KeyRef(self.0.downgrade())
+    }
+}
+
+impl Debug for KeyRefMut<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_tuple("KeyRefMut").field(&self.pair()).finish()
+    }
+}
+
+impl Deref for KeyRefMut<'_> {
+    type Target = Value;
+
+    fn deref(&self) -> &Self::Target {
+        self.value()
+    }
+}
+
+impl DerefMut for KeyRefMut<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.value_mut()
+    }
+}
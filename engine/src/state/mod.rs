@@ -1,16 +1,61 @@
+pub mod dump;
+pub mod hyper_log_log;
+pub mod int_set;
+mod key_ref;
+mod push_waiter;
+pub mod sorted_set;
 pub mod value;
 
-pub use self::value::Value;
+pub use self::{
+    int_set::IntSet,
+    key_ref::{KeyRef, KeyRefMut},
+    push_waiter::PushWaiter,
+    sorted_set::{ScoreRangeFlags, SortedSet},
+    value::Value,
+};
 
-use alloc::{borrow::ToOwned, string::String, sync::Arc, vec::Vec};
-use core::convert::TryFrom;
-use dashmap::{
-    mapref::one::{Ref, RefMut},
-    DashMap,
+use self::push_waiter::Waiters;
+use crate::pattern;
+use alloc::{borrow::ToOwned, collections::VecDeque, string::String, sync::Arc, vec::Vec};
+use core::{
+    convert::TryFrom,
+    fmt::{Display, Formatter, Result as FmtResult},
+    mem,
+    ops::BitOr,
+    sync::atomic::{AtomicU64, AtomicU8, AtomicUsize, Ordering},
 };
+use dashmap::DashMap;
 
 pub type Key = Vec<u8>;
 
+/// Function returning the current Unix time in milliseconds, used to determine
+/// when keys expire.
+pub type Clock = fn() -> u64;
+
+/// Clock returning the system's current Unix time in milliseconds.
+///
+/// Without the `std` feature there's no system clock, so this always returns
+/// 0 and keys only expire when given a timestamp of 0. Provide a clock via
+/// [`State::with_clock`] in that case.
+///
+/// [`State::with_clock`]: struct.State.html#method.with_clock
+pub fn system_clock() -> u64 {
+    #[cfg(feature = "std")]
+    {
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or_default()
+    }
+
+    #[cfg(not(feature = "std"))]
+    {
+        0
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 #[repr(u8)]
 pub enum KeyType {
@@ -22,6 +67,39 @@ pub enum KeyType {
     List = 5,
     Map = 6,
     Set = 7,
+    SortedSet = 8,
+}
+
+impl KeyType {
+    /// Lowercase name of the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::state::KeyType;
+    ///
+    /// assert_eq!("list", KeyType::List.name());
+    /// assert_eq!("sortedset", KeyType::SortedSet.to_string());
+    /// ```
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Boolean => "boolean",
+            Self::Bytes => "bytes",
+            Self::Float => "float",
+            Self::Integer => "integer",
+            Self::List => "list",
+            Self::Map => "map",
+            Self::Set => "set",
+            Self::SortedSet => "sortedset",
+            Self::String => "string",
+        }
+    }
+}
+
+impl Display for KeyType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(self.name())
+    }
 }
 
 impl TryFrom<u8> for KeyType {
@@ -39,21 +117,884 @@ impl TryFrom<u8> for KeyType {
             5 => List,
             6 => Map,
             7 => Set,
+            8 => SortedSet,
             _ => return Err(()),
         })
     }
 }
 
-// The inner map is public to the crate solely for testing purposes.
+/// An end of a list to pop from or push to.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(u8)]
+pub enum ListEnd {
+    Front = 0,
+    Back = 1,
+}
+
+impl ListEnd {
+    /// Pop an item from this end of a list.
+    pub fn pop(self, list: &mut VecDeque<Vec<u8>>) -> Option<Vec<u8>> {
+        match self {
+            Self::Front => list.pop_front(),
+            Self::Back => list.pop_back(),
+        }
+    }
+
+    /// Retrieve the item at this end of a list without removing it.
+    pub fn peek(self, list: &VecDeque<Vec<u8>>) -> Option<&Vec<u8>> {
+        match self {
+            Self::Front => list.front(),
+            Self::Back => list.back(),
+        }
+    }
+
+    /// Push an item to this end of a list.
+    pub fn push(self, list: &mut VecDeque<Vec<u8>>, item: Vec<u8>) {
+        match self {
+            Self::Front => list.push_front(item),
+            Self::Back => list.push_back(item),
+        }
+    }
+}
+
+impl TryFrom<u8> for ListEnd {
+    type Error = ();
+
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        match v {
+            0 => Ok(Self::Front),
+            1 => Ok(Self::Back),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Policy deciding which key to evict when the maximum memory would be
+/// exceeded.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum EvictionPolicy {
+    /// Never evict keys, failing writes that would exceed the maximum memory
+    /// instead.
+    None,
+    /// Evict the key that was least frequently accessed.
+    ///
+    /// Access frequencies decay over time so that keys which were once
+    /// popular are eventually evicted; refer to [`State::frequency`] for more
+    /// information.
+    ///
+    /// [`State::frequency`]: struct.State.html#method.frequency
+    LeastFrequentlyUsed,
+    /// Evict the key that was least recently accessed.
+    LeastRecentlyUsed,
+    /// Evict an arbitrary key.
+    ///
+    /// This is whichever key is found first, which is effectively random since
+    /// keys are stored by their hash.
+    Random,
+}
+
+impl Default for EvictionPolicy {
+    fn default() -> Self {
+        Self::LeastRecentlyUsed
+    }
+}
+
+impl EvictionPolicy {
+    /// Lowercase name of the eviction policy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::state::EvictionPolicy;
+    ///
+    /// assert_eq!("lru", EvictionPolicy::LeastRecentlyUsed.name());
+    /// ```
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::LeastFrequentlyUsed => "lfu",
+            Self::LeastRecentlyUsed => "lru",
+            Self::None => "none",
+            Self::Random => "random",
+        }
+    }
+}
+
+/// Conditions that must hold for a key's expiry to be set.
+///
+/// A key without an expiry is treated as never expiring, so [`GT`] never
+/// applies to it and [`LT`] always does.
+///
+/// # Examples
+///
+/// ```
+/// use hop_engine::state::ExpireFlags;
+///
+/// let flags = ExpireFlags::XX | ExpireFlags::GT;
+/// assert!(flags.is_valid());
+/// assert!(!(ExpireFlags::NX | ExpireFlags::XX).is_valid());
+/// assert_eq!(Some(flags), ExpireFlags::from_bits(flags.bits()));
+/// ```
+///
+/// [`GT`]: #associatedconstant.GT
+/// [`LT`]: #associatedconstant.LT
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ExpireFlags(u8);
+
+impl ExpireFlags {
+    /// Always set the expiry.
+    pub const NONE: Self = Self(0);
+    /// Only set the expiry if the key doesn't already have one.
+    pub const NX: Self = Self(1);
+    /// Only set the expiry if the key already has one.
+    pub const XX: Self = Self(1 << 1);
+    /// Only set the expiry if it's later than the current one.
+    pub const GT: Self = Self(1 << 2);
+    /// Only set the expiry if it's earlier than the current one.
+    pub const LT: Self = Self(1 << 3);
+
+    const ALL: u8 = Self::NX.0 | Self::XX.0 | Self::GT.0 | Self::LT.0;
+
+    /// Create flags from their bits, returning `None` if any bits are unknown.
+    pub fn from_bits(bits: u8) -> Option<Self> {
+        if bits & !Self::ALL == 0 {
+            Some(Self(bits))
+        } else {
+            None
+        }
+    }
+
+    /// Retrieve the bits of the flags.
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Whether all of the given flags are set.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Whether the flags don't conflict with each other.
+    ///
+    /// [`NX`] conflicts with every other flag, and [`GT`] conflicts with
+    /// [`LT`].
+    ///
+    /// [`GT`]: #associatedconstant.GT
+    /// [`LT`]: #associatedconstant.LT
+    /// [`NX`]: #associatedconstant.NX
+    pub fn is_valid(self) -> bool {
+        if self.contains(Self::NX) {
+            return self == Self::NX;
+        }
+
+        !self.contains(Self::GT | Self::LT)
+    }
+
+    /// Whether an expiry may be set given the key's current expiry.
+    fn allows(self, current: Option<u64>, unix_ms: u64) -> bool {
+        if self.contains(Self::NX) && current.is_some() {
+            return false;
+        }
+
+        if self.contains(Self::XX) && current.is_none() {
+            return false;
+        }
+
+        match current {
+            Some(current) if self.contains(Self::GT) && unix_ms <= current => false,
+            Some(current) if self.contains(Self::LT) && unix_ms >= current => false,
+            // A key without an expiry never expires, so no expiry is greater.
+            None if self.contains(Self::GT) => false,
+            _ => true,
+        }
+    }
+}
+
+impl BitOr for ExpireFlags {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self::Output {
+        Self(self.0 | other.0)
+    }
+}
+
+/// Bookkeeping for a key used to decide which keys to evict, stored alongside
+/// its value.
+///
+/// Accesses only update atomics of the key itself, so recording one only
+/// needs a read lock on the key's shard of the map.
+#[derive(Debug, Default)]
+struct KeyMeta {
+    /// Unix time in milliseconds of the last access, as of the last time the
+    /// state's clock was read.
+    accessed_at: AtomicU64,
+    expires_at: Option<u64>,
+    frequency: AtomicU8,
+    size: usize,
+}
+
+impl KeyMeta {
+    /// Number of milliseconds without an access after which a key's access
+    /// frequency is halved.
+    const FREQUENCY_DECAY_MS: u64 = 60_000;
+
+    fn new(now: u64) -> Self {
+        Self {
+            accessed_at: AtomicU64::new(now),
+            ..Self::default()
+        }
+    }
+
+    fn accessed_at(&self) -> u64 {
+        self.accessed_at.load(Ordering::Relaxed)
+    }
+
+    /// The access frequency as of a time, decayed by the time since the key
+    /// was last accessed.
+    fn frequency_at(&self, now: u64) -> u8 {
+        let periods = now.saturating_sub(self.accessed_at()) / Self::FREQUENCY_DECAY_MS;
+
+        self.frequency
+            .load(Ordering::Relaxed)
+            .checked_shr(periods as u32)
+            .unwrap_or(0)
+    }
+
+    /// Rank of the key under an eviction policy, where the key with the lowest
+    /// rank is evicted first.
+    ///
+    /// Access times are only as precise as the reads of the clock, so each
+    /// policy breaks ties with the other's measure.
+    fn eviction_rank(&self, policy: EvictionPolicy, now: u64) -> (u64, u64) {
+        match policy {
+            EvictionPolicy::LeastFrequentlyUsed => {
+                (self.frequency_at(now).into(), self.accessed_at())
+            }
+            EvictionPolicy::LeastRecentlyUsed => {
+                (self.accessed_at(), self.frequency_at(now).into())
+            }
+            EvictionPolicy::None | EvictionPolicy::Random => (0, 0),
+        }
+    }
+
+    /// Record an access at a time.
+    ///
+    /// Concurrent accesses of the same key may only be counted once, which is
+    /// fine for an approximate counter.
+    fn record_access(&self, now: u64) {
+        let frequency = self.frequency_at(now).saturating_add(1);
+        self.frequency.store(frequency, Ordering::Relaxed);
+
+        if now > self.accessed_at() {
+            self.accessed_at.store(now, Ordering::Relaxed);
+        }
+    }
+}
+
+/// A key's value and its bookkeeping.
+#[derive(Debug)]
+struct Slot {
+    meta: KeyMeta,
+    value: Value,
+}
+
+#[derive(Debug)]
+struct StateRef {
+    clock: Clock,
+    /// Index of the shard of the map that the next eviction starts sampling
+    /// keys from.
+    evict_from: AtomicUsize,
+    keys: DashMap<Key, Slot>,
+    memory: AtomicUsize,
+    /// The time returned by the last read of the clock, which accesses are
+    /// recorded at.
+    read_at: AtomicU64,
+    waiters: Waiters,
+}
+
+impl Default for StateRef {
+    fn default() -> Self {
+        Self {
+            clock: system_clock,
+            evict_from: AtomicUsize::new(0),
+            keys: DashMap::new(),
+            memory: AtomicUsize::new(0),
+            read_at: AtomicU64::new(system_clock()),
+            waiters: Waiters::default(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default)]
-pub struct State(pub(crate) Arc<DashMap<Key, Value>>);
+pub struct State(Arc<StateRef>);
+
+impl State {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new state using a clock to determine when keys expire.
+    ///
+    /// By default the [`system_clock`] is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::state::{ExpireFlags, State, Value};
+    ///
+    /// let state = State::with_clock(|| 1000);
+    /// state.insert(b"foo".to_vec(), Value::Integer(1));
+    ///
+    /// // expire "foo" one second from now
+    /// assert!(state.expire_in(b"foo", 1000, ExpireFlags::NONE));
+    /// assert_eq!(Some(2000), state.expires_at(b"foo"));
+    /// ```
+    ///
+    /// [`system_clock`]: fn.system_clock.html
+    pub fn with_clock(clock: Clock) -> Self {
+        Self(Arc::new(StateRef {
+            clock,
+            read_at: AtomicU64::new(clock()),
+            ..StateRef::default()
+        }))
+    }
+
+    /// Create a new state with room for at least the given number of keys
+    /// before it has to grow.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::state::{State, Value};
+    ///
+    /// let state = State::with_capacity(2);
+    ///
+    /// for key in [b"a", b"b", b"c"].iter() {
+    ///     state.insert(key.to_vec(), Value::Integer(1));
+    /// }
+    ///
+    /// assert!(state.contains_key(b"c"));
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_clock_and_capacity(system_clock, capacity)
+    }
+
+    /// Create a new state using a clock to determine when keys expire, with
+    /// room for at least the given number of keys before it has to grow.
+    pub fn with_clock_and_capacity(clock: Clock, capacity: usize) -> Self {
+        Self(Arc::new(StateRef {
+            clock,
+            keys: DashMap::with_capacity(capacity),
+            read_at: AtomicU64::new(clock()),
+            ..StateRef::default()
+        }))
+    }
+
+    /// Register a wait for items to be pushed to a list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::state::{State, Value};
+    ///
+    /// # #[tokio::main] async fn main() {
+    /// let state = State::new();
+    /// let waiter = state.wait_for_push(b"queue");
+    ///
+    /// state.insert(b"queue".to_vec(), Value::from(vec![b"job".to_vec()]));
+    /// state.notify_pushed(b"queue");
+    ///
+    /// waiter.pushed().await;
+    /// # }
+    /// ```
+    pub fn wait_for_push(&self, key: &[u8]) -> PushWaiter {
+        PushWaiter::new(self.clone(), key)
+    }
+
+    /// Wake the waits registered for a key after it's been modified.
+    ///
+    /// Refer to [`wait_for_push`] for more information.
+    ///
+    /// [`wait_for_push`]: #method.wait_for_push
+    pub fn notify_pushed(&self, key: &[u8]) {
+        self.0.waiters.notify(key);
+    }
+
+    /// Retrieve the current Unix time in milliseconds according to the state's
+    /// clock.
+    ///
+    /// Reading the clock for every access of a key would be costly, so
+    /// accesses are recorded at the time of the last read of the clock
+    /// instead. [`Hop`] reads it before dispatching each command.
+    ///
+    /// [`Hop`]: ../struct.Hop.html
+    pub fn now(&self) -> u64 {
+        let now = (self.0.clock)();
+
+        if now > self.0.read_at.load(Ordering::Relaxed) {
+            self.0.read_at.store(now, Ordering::Relaxed);
+        }
+
+        now
+    }
+
+    /// The time of the last read of the clock.
+    fn read_at(&self) -> u64 {
+        self.0.read_at.load(Ordering::Relaxed)
+    }
+
+    /// Retrieve the Unix time in milliseconds at which a key expires.
+    ///
+    /// Returns `None` if the key doesn't exist or doesn't expire.
+    pub fn expires_at(&self, key: &[u8]) -> Option<u64> {
+        self.expire_if_due(key);
+
+        self.0.keys.get(key).and_then(|slot| slot.meta.expires_at)
+    }
+
+    /// Set the Unix time in milliseconds at which a key expires if the flags'
+    /// conditions hold, returning whether the expiry was set.
+    ///
+    /// Returns `false` if the key doesn't exist. If the time has already
+    /// passed then the key is removed immediately.
+    ///
+    /// The flags must be [valid].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::state::{ExpireFlags, State, Value};
+    ///
+    /// let state = State::with_clock(|| 1000);
+    /// state.insert(b"foo".to_vec(), Value::Integer(1));
+    ///
+    /// assert!(!state.expire_at(b"foo", 3000, ExpireFlags::XX));
+    /// assert!(state.expire_at(b"foo", 3000, ExpireFlags::NONE));
+    /// assert!(state.expire_at(b"foo", 2000, ExpireFlags::LT));
+    /// assert!(state.expire_at(b"foo", 500, ExpireFlags::NONE));
+    /// assert!(!state.contains_key(b"foo"));
+    /// assert!(!state.expire_at(b"foo", 2000, ExpireFlags::NONE));
+    /// ```
+    ///
+    /// [valid]: struct.ExpireFlags.html#method.is_valid
+    pub fn expire_at(&self, key: &[u8], unix_ms: u64, flags: ExpireFlags) -> bool {
+        debug_assert!(flags.is_valid());
+
+        self.expire_if_due(key);
+
+        let mut slot = match self.0.keys.get_mut(key) {
+            Some(slot) => slot,
+            None => return false,
+        };
+
+        if !flags.allows(slot.meta.expires_at, unix_ms) {
+            return false;
+        }
+
+        if unix_ms <= self.now() {
+            drop(slot);
+            self.remove(key);
+
+            return true;
+        }
+
+        slot.meta.expires_at = Some(unix_ms);
+
+        true
+    }
+
+    /// Set a key to expire a number of milliseconds from now if the flags'
+    /// conditions hold, returning whether the expiry was set.
+    ///
+    /// If the number of milliseconds isn't positive then the key is removed
+    /// immediately. Refer to [`expire_at`] for more information.
+    ///
+    /// [`expire_at`]: #method.expire_at
+    pub fn expire_in(&self, key: &[u8], ms: i64, flags: ExpireFlags) -> bool {
+        let unix_ms = if ms > 0 {
+            self.now().saturating_add(ms as u64)
+        } else {
+            0
+        };
+
+        self.expire_at(key, unix_ms, flags)
+    }
+
+    /// Remove a key if its expiry has passed, returning whether it was removed.
+    ///
+    /// The expiry is checked with the key locked, so a key that's recreated or
+    /// given a new expiry in the meantime isn't removed.
+    fn expire_if_due(&self, key: &[u8]) -> bool {
+        let now = self.now();
+        let removed = self.0.keys.remove_if(
+            key,
+            |_, slot| matches!(slot.meta.expires_at, Some(at) if at <= now),
+        );
+
+        match removed {
+            Some((_, slot)) => {
+                self.0.memory.fetch_sub(slot.meta.size, Ordering::Relaxed);
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Retrieve the approximate number of bytes used by keys and their values.
+    ///
+    /// Sizes are only recalculated when a key is inserted or when
+    /// [`refresh_size`] is called, so this doesn't account for values modified
+    /// in place until then.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::state::{State, Value};
+    ///
+    /// let state = State::new();
+    /// state.insert(b"foo".to_vec(), Value::Bytes(b"bar".to_vec()));
+    ///
+    /// assert_eq!(6, state.memory_used());
+    /// ```
+    ///
+    /// [`refresh_size`]: #method.refresh_size
+    pub fn memory_used(&self) -> usize {
+        self.0.memory.load(Ordering::Relaxed)
+    }
+
+    /// Recalculate the size of a key and its value after it's been modified.
+    pub fn refresh_size(&self, key: &[u8]) {
+        if let Some(mut slot) = self.0.keys.get_mut(key) {
+            self.resize(key, &mut slot);
+        }
+    }
+
+    /// Recalculate the size of a key's slot.
+    fn resize(&self, key: &[u8], slot: &mut Slot) {
+        let size = key.len() + slot.value.size();
+        let old = mem::replace(&mut slot.meta.size, size);
+
+        self.0.memory.fetch_add(size, Ordering::Relaxed);
+        self.0.memory.fetch_sub(old, Ordering::Relaxed);
+    }
+
+    /// Record an access of a key without retrieving it, returning whether the
+    /// key exists.
+    ///
+    /// This makes the key the most recently used and increases its access
+    /// frequency, lowering its priority for eviction.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::state::{EvictionPolicy, State, Value};
+    ///
+    /// let state = State::new();
+    /// state.insert(b"foo".to_vec(), Value::Integer(1));
+    /// state.insert(b"bar".to_vec(), Value::Integer(2));
+    ///
+    /// assert!(state.touch(b"foo"));
+    /// assert!(!state.touch(b"baz"));
+    ///
+    /// assert_eq!(
+    ///     Some(b"bar".to_vec()),
+    ///     state.evict(EvictionPolicy::LeastRecentlyUsed, &[]),
+    /// );
+    /// ```
+    pub fn touch(&self, key: &[u8]) -> bool {
+        self.expire_if_due(key);
+
+        match self.0.keys.get(key) {
+            Some(slot) => {
+                slot.meta.record_access(self.read_at());
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Retrieve how frequently a key has been accessed, if it exists.
+    ///
+    /// This is a small counter incremented each time the key is accessed. The
+    /// counter is halved for every minute since the key was last accessed, so
+    /// it reflects recent popularity rather than all time popularity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::state::{State, Value};
+    ///
+    /// let state = State::new();
+    /// state.insert(b"foo".to_vec(), Value::Integer(1));
+    /// assert_eq!(Some(1), state.frequency(b"foo"));
+    ///
+    /// state.key_ref(b"foo");
+    /// assert_eq!(Some(2), state.frequency(b"foo"));
+    /// ```
+    pub fn frequency(&self, key: &[u8]) -> Option<u8> {
+        let now = self.now();
+
+        self.0.keys.get(key).map(|slot| slot.meta.frequency_at(now))
+    }
+
+    /// Retrieve the number of milliseconds since a key was last accessed, if it
+    /// exists.
+    ///
+    /// Retrieving the idle time doesn't count as an access.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::state::{State, Value};
+    ///
+    /// let state = State::with_clock(|| 1000);
+    /// state.insert(b"foo".to_vec(), Value::Integer(1));
+    ///
+    /// assert_eq!(Some(0), state.idle_time(b"foo"));
+    /// assert!(state.idle_time(b"bar").is_none());
+    /// ```
+    pub fn idle_time(&self, key: &[u8]) -> Option<u64> {
+        self.expire_if_due(key);
+
+        let accessed_at = self.0.keys.get(key)?.meta.accessed_at();
+
+        Some(self.now().saturating_sub(accessed_at))
+    }
+
+    /// Retrieve the approximate number of bytes used by a key and its value,
+    /// if it exists.
+    ///
+    /// Like [`memory_used`], this is only as current as the last time the
+    /// key's size was recalculated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::state::{State, Value};
+    ///
+    /// let state = State::new();
+    /// state.insert(b"foo".to_vec(), Value::Bytes(b"bar".to_vec()));
+    ///
+    /// assert_eq!(Some(6), state.key_size(b"foo"));
+    /// ```
+    ///
+    /// [`memory_used`]: #method.memory_used
+    pub fn key_size(&self, key: &[u8]) -> Option<usize> {
+        self.expire_if_due(key);
+
+        self.0.keys.get(key).map(|slot| slot.meta.size)
+    }
+
+    /// Evict a key according to a policy, returning the evicted key.
+    ///
+    /// Keys in `exclude` are never evicted. Returns `None` if there are no keys
+    /// that can be evicted or if the policy is [`EvictionPolicy::None`].
+    ///
+    /// Only a bounded sample of keys is compared, so the evicted key is
+    /// approximately the least frequently or recently used one.
+    ///
+    /// [`EvictionPolicy::None`]: enum.EvictionPolicy.html#variant.None
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::state::{EvictionPolicy, State, Value};
+    ///
+    /// let state = State::new();
+    /// state.insert(b"foo".to_vec(), Value::Integer(1));
+    /// state.insert(b"bar".to_vec(), Value::Integer(2));
+    ///
+    /// // "foo" was accessed most recently, so "bar" is evicted
+    /// assert!(state.key_ref(b"foo").is_some());
+    /// assert_eq!(
+    ///     Some(b"bar".to_vec()),
+    ///     state.evict(EvictionPolicy::LeastRecentlyUsed, &[]),
+    /// );
+    /// ```
+    pub fn evict(&self, policy: EvictionPolicy, exclude: &[&[u8]]) -> Option<Key> {
+        /// Maximum number of keys compared to choose the key to evict.
+        const SAMPLES: usize = 16;
+
+        let now = self.read_at();
+
+        let samples = match policy {
+            EvictionPolicy::None => return None,
+            EvictionPolicy::Random => 1,
+            EvictionPolicy::LeastFrequentlyUsed | EvictionPolicy::LeastRecentlyUsed => SAMPLES,
+        };
+
+        // Sampling starts from a different shard each time so that the keys
+        // of every shard are considered.
+        let shards = self.0.keys.shards();
+        let start = self.0.evict_from.fetch_add(1, Ordering::Relaxed);
+        let mut sampled = 0;
+        let mut evict: Option<((u64, u64), Key)> = None;
+
+        for idx in 0..shards.len() {
+            // The shard has to be unlocked before the key is removed, which
+            // happens at the end of each iteration.
+            let shard = shards[start.wrapping_add(idx) % shards.len()].read();
+            let candidates = shard
+                .iter()
+                .filter(|(key, _)| !exclude.contains(&key.as_slice()))
+                .take(samples - sampled);
+
+            for (key, slot) in candidates {
+                sampled += 1;
+                let candidate = slot.get().meta.eviction_rank(policy, now);
+
+                if evict.as_ref().map_or(true, |(rank, _)| candidate < *rank) {
+                    evict = Some((candidate, key.to_owned()));
+                }
+            }
+
+            if sampled == samples {
+                break;
+            }
+        }
+
+        let (_, key) = evict?;
+        self.remove(&key);
+
+        Some(key)
+    }
+
+    /// Check if a key exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::state::{State, Value};
+    ///
+    /// let state = State::new();
+    /// // set a default bytes value to "foo"
+    /// state.insert(b"foo".to_vec(), Value::bytes());
+    ///
+    /// assert!(state.contains_key(b"foo"));
+    /// assert!(!state.contains_key(b"bar"));
+    /// ```
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.expire_if_due(key);
+
+        self.0.keys.contains_key(key)
+    }
+
+    /// Number of keys in the state.
+    ///
+    /// Keys that have expired but haven't been removed yet are counted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::state::{State, Value};
+    ///
+    /// let state = State::new();
+    /// assert!(state.is_empty());
+    ///
+    /// state.insert(b"foo".to_vec(), Value::bytes());
+    /// assert_eq!(1, state.len());
+    /// ```
+    pub fn len(&self) -> usize {
+        self.0.keys.len()
+    }
+
+    /// Whether the state has no keys.
+    pub fn is_empty(&self) -> bool {
+        self.0.keys.is_empty()
+    }
+
+    /// Insert a value by key, replacing and returning the existing value if the
+    /// key was already taken.
+    ///
+    /// Any expiry of the key is cleared.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::state::{State, Value};
+    ///
+    /// let state = State::new();
+    /// assert!(state.insert(b"foo".to_vec(), Value::bytes()).is_none());
+    /// assert!(state.insert(b"foo".to_vec(), Value::boolean()).is_some());
+    /// ```
+    pub fn insert(&self, key: Vec<u8>, value: Value) -> Option<Value> {
+        self.expire_if_due(&key);
+
+        let now = self.now();
+        let size = key.len() + value.size();
+        let slot = Slot {
+            meta: KeyMeta {
+                size,
+                ..KeyMeta::new(now)
+            },
+            value,
+        };
+        slot.meta.record_access(now);
+
+        self.0.memory.fetch_add(size, Ordering::Relaxed);
+        let existing = self.0.keys.insert(key, slot)?;
+        self.0
+            .memory
+            .fetch_sub(existing.meta.size, Ordering::Relaxed);
+
+        Some(existing.value)
+    }
+
+    /// Remove a value by key, returning both the owned key and value if
+    /// present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::state::{State, Value};
+    ///
+    /// let state = State::new();
+    /// // set a default string value to "foo"
+    /// state.key_or_insert_with(b"foo", Value::string);
+    ///
+    /// assert!(state.contains_key(b"foo"));
+    /// assert!(state.remove(b"foo").is_some());
+    /// assert!(!state.contains_key(b"foo"));
+    /// ```
+    pub fn remove(&self, key: &[u8]) -> Option<(Vec<u8>, Value)> {
+        let (key, slot) = self.0.keys.remove(key)?;
+        self.0.memory.fetch_sub(slot.meta.size, Ordering::Relaxed);
+
+        Some((key, slot.value))
+    }
+
+    /// Remove a value by key if the condition holds for its value, returning
+    /// both the owned key and value if removed.
+    ///
+    /// The key is locked while the condition is checked, so the value can't
+    /// be changed in between.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::state::{State, Value};
+    ///
+    /// let state = State::new();
+    /// state.insert(b"foo".to_vec(), Value::Integer(1));
+    ///
+    /// assert!(state.remove_if(b"foo", |value| value.as_integer_ref() == Some(&2)).is_none());
+    /// assert!(state.remove_if(b"foo", |value| value.as_integer_ref() == Some(&1)).is_some());
+    /// assert!(!state.contains_key(b"foo"));
+    /// ```
+    pub fn remove_if(&self, key: &[u8], f: impl FnOnce(&Value) -> bool) -> Option<(Key, Value)> {
+        let (key, slot) = self.0.keys.remove_if(key, |_, slot| f(&slot.value))?;
+        self.0.memory.fetch_sub(slot.meta.size, Ordering::Relaxed);
 
-impl State {
-    pub fn new() -> Self {
-        Self::default()
+        Some((key, slot.value))
     }
 
-    /// Check if a key exists.
+    /// Remove every key matching a glob-style pattern, returning the removed
+    /// keys.
+    ///
+    /// Keys are collected and removed in batches so that a shard of the map
+    /// is never locked for more than a batch at a time. Refer to the
+    /// [`pattern`] module for the pattern syntax.
     ///
     /// # Examples
     ///
@@ -61,50 +1002,166 @@ impl State {
     /// use hop_engine::state::{State, Value};
     ///
     /// let state = State::new();
-    /// // set a default bytes value to "foo"
-    /// state.insert(b"foo".to_vec(), Value::bytes());
+    /// state.insert(b"session:1".to_vec(), Value::bytes());
+    /// state.insert(b"session:2".to_vec(), Value::bytes());
+    /// state.insert(b"user:1".to_vec(), Value::bytes());
     ///
-    /// assert!(state.contains_key(b"foo"));
-    /// assert!(!state.contains_key(b"bar"));
+    /// assert_eq!(2, state.remove_matching(b"session:*").len());
+    /// assert!(state.contains_key(b"user:1"));
     /// ```
-    pub fn contains_key(&self, key: &[u8]) -> bool {
-        self.0.contains_key(key)
+    ///
+    /// [`pattern`]: ../pattern/index.html
+    pub fn remove_matching(&self, pattern: &[u8]) -> Vec<Key> {
+        const BATCH: usize = 128;
+
+        let mut removed = Vec::new();
+
+        loop {
+            // The iterator holds a lock on a shard of the map, so it has to be
+            // dropped before the keys are removed.
+            let batch = self
+                .0
+                .keys
+                .iter()
+                .map(|entry| entry.key().to_owned())
+                .filter(|key| !key.starts_with(b"__hop__:") && pattern::matches(pattern, key))
+                .take(BATCH)
+                .collect::<Vec<_>>();
+
+            let done = batch.len() < BATCH;
+
+            for key in batch {
+                // Keys that have already expired don't count as removed.
+                if !self.expire_if_due(&key) && self.remove(&key).is_some() {
+                    removed.push(key);
+                }
+            }
+
+            if done {
+                return removed;
+            }
+        }
     }
 
-    /// Insert a value by key, replacing and returning the existing value if the
-    /// key was already taken.
+    /// Move a key's value to another key, keeping its expiry.
+    ///
+    /// Any value of the destination key is replaced. Returns `false` if the
+    /// source key doesn't exist.
     ///
     /// # Examples
     ///
     /// ```
-    /// use hop_engine::state::{State, Value};
+    /// use hop_engine::state::{ExpireFlags, State, Value};
     ///
-    /// let state = State::new();
-    /// assert!(state.insert(b"foo".to_vec(), Value::bytes()).is_none());
-    /// assert!(state.insert(b"foo".to_vec(), Value::boolean()).is_some());
+    /// let state = State::with_clock(|| 1000);
+    /// state.insert(b"foo".to_vec(), Value::Integer(1));
+    /// state.expire_in(b"foo", 500, ExpireFlags::NONE);
+    ///
+    /// assert!(state.rename(b"foo", b"bar".to_vec()));
+    /// assert!(!state.contains_key(b"foo"));
+    /// assert_eq!(Some(1500), state.expires_at(b"bar"));
     /// ```
-    pub fn insert(&self, key: Vec<u8>, value: Value) -> Option<Value> {
-        self.0.insert(key, value)
+    pub fn rename(&self, from: &[u8], to: Vec<u8>) -> bool {
+        let expires_at = self.expires_at(from);
+
+        let value = match self.remove(from) {
+            Some((_, value)) => value,
+            None => return false,
+        };
+
+        self.insert(to.clone(), value);
+        self.set_expires_at(&to, expires_at);
+
+        true
     }
 
-    /// Remove a value by key, returning both the owned key and value if
-    /// present.
+    /// Exchange the values of two keys, along with their expiries.
+    ///
+    /// Returns `false` if either key doesn't exist, in which case neither key
+    /// is changed. The keys are exchanged while both are locked, so nothing
+    /// sees one key without the other's value.
     ///
     /// # Examples
     ///
     /// ```
-    /// use hop_engine::state::{State, Value};
+    /// use hop_engine::state::{ExpireFlags, State, Value};
     ///
-    /// let state = State::new();
-    /// // set a default string value to "foo"
-    /// state.key_or_insert_with(b"foo", Value::string);
+    /// let state = State::with_clock(|| 1000);
+    /// state.insert(b"foo".to_vec(), Value::Integer(1));
+    /// state.insert(b"bar".to_vec(), Value::Boolean(true));
+    /// state.expire_in(b"foo", 500, ExpireFlags::NONE);
     ///
-    /// assert!(state.contains_key(b"foo"));
-    /// assert!(state.remove(b"foo").is_some());
-    /// assert!(!state.contains_key(b"foo"));
+    /// assert!(state.swap(b"foo", b"bar"));
+    /// assert_eq!(Some(&true), state.key_ref(b"foo").unwrap().as_boolean_ref());
+    /// assert_eq!(None, state.expires_at(b"foo"));
+    /// assert_eq!(Some(1500), state.expires_at(b"bar"));
+    ///
+    /// assert!(!state.swap(b"foo", b"baz"));
     /// ```
-    pub fn remove(&self, key: &[u8]) -> Option<(Vec<u8>, Value)> {
-        self.0.remove(key)
+    pub fn swap(&self, a: &[u8], b: &[u8]) -> bool {
+        self.expire_if_due(a);
+        self.expire_if_due(b);
+
+        let shards = self.0.keys.shards();
+        let a_idx = self.0.keys.determine_map(a);
+        let b_idx = self.0.keys.determine_map(b);
+
+        if a_idx == b_idx {
+            let mut shard = shards[a_idx].write();
+
+            if !shard.contains_key(b) {
+                return false;
+            }
+
+            // The shard can't lend out two mutable references at once, so one
+            // slot is taken out and put back while the shard is still locked.
+            let (a_key, mut a_slot) = match shard.remove_entry(a) {
+                Some(entry) => entry,
+                None => return false,
+            };
+
+            if let Some(b_slot) = shard.get_mut(b) {
+                self.swap_slots(a, a_slot.get_mut(), b, b_slot.get_mut());
+            }
+
+            shard.insert(a_key, a_slot);
+        } else {
+            // Shards are always locked in the same order so that concurrent
+            // swaps can't deadlock.
+            let (mut a_shard, mut b_shard) = if a_idx < b_idx {
+                let a_shard = shards[a_idx].write();
+
+                (a_shard, shards[b_idx].write())
+            } else {
+                let b_shard = shards[b_idx].write();
+
+                (shards[a_idx].write(), b_shard)
+            };
+
+            match (a_shard.get_mut(a), b_shard.get_mut(b)) {
+                (Some(a_slot), Some(b_slot)) => {
+                    self.swap_slots(a, a_slot.get_mut(), b, b_slot.get_mut())
+                }
+                _ => return false,
+            }
+        }
+
+        true
+    }
+
+    /// Exchange the slots of two keys, recalculating their sizes since each
+    /// size includes its key.
+    fn swap_slots(&self, a: &[u8], a_slot: &mut Slot, b: &[u8], b_slot: &mut Slot) {
+        mem::swap(a_slot, b_slot);
+
+        self.resize(a, a_slot);
+        self.resize(b, b_slot);
+    }
+
+    fn set_expires_at(&self, key: &[u8], expires_at: Option<u64>) {
+        if let Some(mut slot) = self.0.keys.get_mut(key) {
+            slot.meta.expires_at = expires_at;
+        }
     }
 
     /// Retrieve an immutable reference to a key-value pair by key.
@@ -125,28 +1182,38 @@ impl State {
     /// state.insert(b"foo".to_vec(), Value::string());
     /// assert!(state.key_ref(b"foo").is_some());
     /// ```
-    pub fn key_ref<'a>(&'a self, key: &[u8]) -> Option<Ref<'a, Key, Value>> {
+    pub fn key_ref<'a>(&'a self, key: &[u8]) -> Option<KeyRef<'a>> {
         if key.starts_with(b"__hop__:") {
             panic!("Accessed internal key: {}", String::from_utf8_lossy(key));
         }
 
         debug_assert!(!key.is_empty());
 
-        self.0.get(key)
+        self.expire_if_due(key);
+
+        let slot = self.0.keys.get(key)?;
+        slot.meta.record_access(self.read_at());
+
+        Some(KeyRef(slot))
     }
 
     /// Retrieve a mutable reference to a key-value pair by key.
     ///
     /// Returns `None` if the key does not exist.
     /// ```
-    pub fn key_mut<'a>(&'a self, key: &[u8]) -> Option<RefMut<'a, Key, Value>> {
+    pub fn key_mut<'a>(&'a self, key: &[u8]) -> Option<KeyRefMut<'a>> {
         if key.starts_with(b"__hop__:") {
             panic!("Accessed internal key: {}", String::from_utf8_lossy(key));
         }
 
         debug_assert!(!key.is_empty());
 
-        self.0.get_mut(key)
+        self.expire_if_due(key);
+
+        let slot = self.0.keys.get_mut(key)?;
+        slot.meta.record_access(self.read_at());
+
+        Some(KeyRefMut(slot))
     }
 
     /// Retrieve a key's value, providing a function returning the value to
@@ -166,29 +1233,22 @@ impl State {
     ///     _ => println!("it's something else"),
     /// }
     /// ```
-    pub fn key_or_insert_with<'a>(
-        &'a self,
-        key: &[u8],
-        f: impl Fn() -> Value,
-    ) -> RefMut<'a, Key, Value> {
+    pub fn key_or_insert_with<'a>(&'a self, key: &[u8], f: impl Fn() -> Value) -> KeyRefMut<'a> {
         if key.starts_with(b"__hop__:") {
             panic!("Accessed internal key: {}", String::from_utf8_lossy(key));
         }
 
         debug_assert!(!key.is_empty());
 
-        loop {
-            match self.0.get_mut(key) {
-                Some(v) => {
-                    break v;
-                }
-                None => {
-                    self.0.insert(key.to_owned(), f());
+        self.expire_if_due(key);
 
-                    continue;
-                }
-            }
-        }
+        let slot = self.0.keys.entry(key.to_owned()).or_insert_with(|| Slot {
+            meta: KeyMeta::new(self.read_at()),
+            value: f(),
+        });
+        slot.meta.record_access(self.read_at());
+
+        KeyRefMut(slot)
     }
 
     /// Retrieve the key type of a key's value, if it exists.
@@ -205,25 +1265,155 @@ impl State {
     /// assert_eq!(Some(KeyType::Boolean), state.key_type(b"foo"));
     /// ```
     pub fn key_type(&self, key: &[u8]) -> Option<KeyType> {
-        self.0.get(key).map(|r| r.value().kind())
+        self.expire_if_due(key);
+
+        self.0.keys.get(key).map(|slot| slot.value().value.kind())
+    }
+
+    /// Retrieve the name of how a key's value is stored, if it exists.
+    ///
+    /// Like [`key_type`], this doesn't count as an access of the key. Refer
+    /// to [`Value::encoding`] for the names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dashmap::DashSet;
+    /// use hop_engine::state::{State, Value};
+    ///
+    /// let state = State::new();
+    /// let set = DashSet::new();
+    /// set.insert(b"1".to_vec());
+    /// state.insert(b"foo".to_vec(), Value::set_from(set));
+    ///
+    /// assert_eq!(Some("packed"), state.key_encoding(b"foo"));
+    /// ```
+    ///
+    /// [`key_type`]: #method.key_type
+    /// [`Value::encoding`]: value/enum.Value.html#method.encoding
+    pub fn key_encoding(&self, key: &[u8]) -> Option<&'static str> {
+        self.expire_if_due(key);
+
+        self.0
+            .keys
+            .get(key)
+            .map(|slot| slot.value().value.encoding())
+    }
+
+    /// Iterate over every key matching a glob-style pattern.
+    ///
+    /// Keys that have expired but not yet been removed are skipped, as are the
+    /// engine's internal keys. The iterator holds a read lock on one shard of
+    /// the map at a time, so the state mustn't be modified while iterating.
+    /// Refer to the [`pattern`] module for the pattern syntax.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::state::{State, Value};
+    ///
+    /// let state = State::new();
+    /// state.insert(b"session:1".to_vec(), Value::bytes());
+    /// state.insert(b"user:1".to_vec(), Value::bytes());
+    ///
+    /// let sessions = state.keys_matching(b"session:*").collect::<Vec<_>>();
+    /// assert_eq!(vec![b"session:1".to_vec()], sessions);
+    /// ```
+    ///
+    /// [`pattern`]: ../pattern/index.html
+    pub fn keys_matching<'a>(&'a self, pattern: &'a [u8]) -> impl Iterator<Item = Key> + 'a {
+        let now = self.now();
+
+        self.0
+            .keys
+            .iter()
+            .filter(move |entry| {
+                let key = entry.key();
+
+                !key.starts_with(b"__hop__:")
+                    && !matches!(entry.value().meta.expires_at, Some(at) if at <= now)
+                    && pattern::matches(pattern, key)
+            })
+            .map(|entry| entry.key().to_owned())
+    }
+
+    /// Iterate over every key whose value is of a key type.
+    ///
+    /// Keys that have expired but not yet been removed are skipped. The
+    /// iterator holds a read lock on one shard of the map at a time, so the
+    /// state mustn't be modified while iterating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::state::{KeyType, State, Value};
+    ///
+    /// let state = State::new();
+    /// state.insert(b"foo".to_vec(), Value::list());
+    /// state.insert(b"bar".to_vec(), Value::set());
+    ///
+    /// let lists = state.keys_of_type(KeyType::List).collect::<Vec<_>>();
+    /// assert_eq!(vec![b"foo".to_vec()], lists);
+    /// ```
+    pub fn keys_of_type(&self, key_type: KeyType) -> impl Iterator<Item = Key> + '_ {
+        let now = self.now();
+
+        self.0
+            .keys
+            .iter()
+            .filter(move |entry| {
+                let slot = entry.value();
+
+                slot.value.kind() == key_type
+                    && !entry.key().starts_with(b"__hop__:")
+                    && !matches!(slot.meta.expires_at, Some(at) if at <= now)
+            })
+            .map(|entry| entry.key().to_owned())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{KeyType, State, Value};
-    use core::{convert::TryFrom, fmt::Debug, hash::Hash};
+    use super::{EvictionPolicy, ExpireFlags, KeyType, State, Value};
+    use core::{
+        convert::TryFrom,
+        fmt::{Debug, Display},
+        hash::Hash,
+        ops::BitOr,
+        sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    };
     use static_assertions::assert_impl_all;
+    use std::sync::Arc;
 
     assert_impl_all!(
         KeyType: Clone,
         Copy,
         Debug,
+        Display,
         Eq,
         Hash,
         PartialEq,
         TryFrom<u8>
     );
+    assert_impl_all!(
+        EvictionPolicy: Clone,
+        Copy,
+        Debug,
+        Default,
+        Eq,
+        Hash,
+        PartialEq
+    );
+    assert_impl_all!(
+        ExpireFlags: BitOr,
+        Clone,
+        Copy,
+        Debug,
+        Default,
+        Eq,
+        Hash,
+        PartialEq
+    );
     assert_impl_all!(State: Clone, Debug, Default);
 
     #[test]
@@ -241,4 +1431,369 @@ mod tests {
         state.insert(b"bar".to_vec(), Value::Integer(123));
         assert_eq!(Some(KeyType::Integer), state.key_type(b"bar"));
     }
+
+    #[test]
+    fn test_key_type_display() {
+        assert_eq!("bytes", KeyType::Bytes.to_string());
+        assert_eq!("map", KeyType::Map.to_string());
+        assert_eq!("sortedset", KeyType::SortedSet.to_string());
+    }
+
+    #[test]
+    fn test_memory_used() {
+        let state = State::new();
+        state.insert(b"foo".to_vec(), Value::Bytes(b"bar".to_vec()));
+        state.insert(b"baz".to_vec(), Value::Integer(1));
+        assert_eq!(6 + 11, state.memory_used());
+
+        state
+            .key_mut(b"foo")
+            .unwrap()
+            .as_bytes_mut()
+            .unwrap()
+            .extend_from_slice(b"bar");
+        state.refresh_size(b"foo");
+        assert_eq!(9 + 11, state.memory_used());
+
+        state.remove(b"foo");
+        assert_eq!(11, state.memory_used());
+    }
+
+    #[test]
+    fn test_evict_least_recently_used() {
+        let state = State::new();
+        state.insert(b"foo".to_vec(), Value::Integer(1));
+        state.insert(b"bar".to_vec(), Value::Integer(2));
+        state.insert(b"baz".to_vec(), Value::Integer(3));
+        assert!(state.key_ref(b"foo").is_some());
+
+        let policy = EvictionPolicy::LeastRecentlyUsed;
+        assert_eq!(Some(b"baz".to_vec()), state.evict(policy, &[b"bar"]));
+        assert_eq!(Some(b"bar".to_vec()), state.evict(policy, &[]));
+        assert!(state.contains_key(b"foo"));
+        assert_eq!(None, state.evict(policy, &[b"foo"]));
+        assert_eq!(Some(b"foo".to_vec()), state.evict(policy, &[]));
+        assert_eq!(0, state.memory_used());
+    }
+
+    #[test]
+    fn test_evict_least_frequently_used() {
+        let state = State::new();
+        state.insert(b"foo".to_vec(), Value::Integer(1));
+        state.insert(b"bar".to_vec(), Value::Integer(2));
+
+        for _ in 0..10 {
+            assert!(state.key_ref(b"foo").is_some());
+        }
+
+        // "bar" was accessed more recently but less frequently
+        assert!(state.key_ref(b"bar").is_some());
+
+        let policy = EvictionPolicy::LeastFrequentlyUsed;
+        assert_eq!(Some(b"bar".to_vec()), state.evict(policy, &[]));
+        assert_eq!(Some(b"foo".to_vec()), state.evict(policy, &[]));
+    }
+
+    #[test]
+    fn test_frequency_decays() {
+        static NOW: AtomicU64 = AtomicU64::new(1000);
+
+        let state = State::with_clock(|| NOW.load(Ordering::SeqCst));
+        state.insert(b"foo".to_vec(), Value::Integer(1));
+
+        for _ in 0..7 {
+            assert!(state.key_ref(b"foo").is_some());
+        }
+
+        assert_eq!(Some(8), state.frequency(b"foo"));
+
+        NOW.store(61_000, Ordering::SeqCst);
+        assert_eq!(Some(4), state.frequency(b"foo"));
+
+        // accesses are recorded at the time the clock was last read
+        assert!(state.key_ref(b"foo").is_some());
+        assert_eq!(Some(5), state.frequency(b"foo"));
+
+        NOW.store(181_000, Ordering::SeqCst);
+        assert_eq!(Some(1), state.frequency(b"foo"));
+        assert_eq!(None, state.frequency(b"baz"));
+    }
+
+    #[test]
+    fn test_evict_sampled() {
+        let state = State::new();
+
+        for key in 0..1000u16 {
+            state.insert(key.to_be_bytes().to_vec(), Value::Integer(1));
+        }
+
+        // only some keys are sampled at a time, but every key is reached
+        for _ in 0..1000 {
+            assert!(state
+                .evict(EvictionPolicy::LeastRecentlyUsed, &[])
+                .is_some());
+        }
+
+        assert!(state
+            .evict(EvictionPolicy::LeastRecentlyUsed, &[])
+            .is_none());
+        assert_eq!(0, state.memory_used());
+    }
+
+    #[test]
+    fn test_evict_none() {
+        let state = State::new();
+        state.insert(b"foo".to_vec(), Value::Integer(1));
+
+        assert!(state.evict(EvictionPolicy::None, &[]).is_none());
+        assert!(state.contains_key(b"foo"));
+    }
+
+    #[test]
+    fn test_expiry() {
+        static NOW: AtomicU64 = AtomicU64::new(1000);
+
+        let state = State::with_clock(|| NOW.load(Ordering::SeqCst));
+        state.insert(b"foo".to_vec(), Value::Integer(1));
+        assert_eq!(None, state.expires_at(b"foo"));
+
+        assert!(state.expire_in(b"foo", 500, ExpireFlags::NONE));
+        assert_eq!(Some(1500), state.expires_at(b"foo"));
+
+        NOW.store(1499, Ordering::SeqCst);
+        assert!(state.key_ref(b"foo").is_some());
+
+        NOW.store(1500, Ordering::SeqCst);
+        assert!(state.key_ref(b"foo").is_none());
+        assert!(!state.contains_key(b"foo"));
+        assert_eq!(0, state.memory_used());
+
+        // inserting a key clears its expiry
+        state.insert(b"bar".to_vec(), Value::Integer(1));
+        assert!(state.expire_at(b"bar", 2000, ExpireFlags::NONE));
+        state.insert(b"bar".to_vec(), Value::Integer(2));
+        assert_eq!(None, state.expires_at(b"bar"));
+    }
+
+    #[test]
+    fn test_expiry_concurrent() {
+        use std::cell::Cell;
+
+        thread_local! {
+            static NOW: Cell<u64> = Cell::new(1000);
+        }
+
+        let state = State::with_clock(|| NOW.with(Cell::get));
+        let done = Arc::new(AtomicBool::new(false));
+        let expirer = {
+            let state = state.clone();
+            let done = Arc::clone(&done);
+
+            // to this thread the key is always due once it has an expiry
+            std::thread::spawn(move || {
+                NOW.with(|now| now.set(2000));
+
+                while !done.load(Ordering::SeqCst) {
+                    state.expires_at(b"foo");
+                }
+            })
+        };
+
+        // a fresh key without an expiry is never removed
+        for _ in 0..1_000_000 {
+            state.insert(b"foo".to_vec(), Value::Integer(1));
+            assert!(state.contains_key(b"foo"));
+            state.expire_at(b"foo", 1500, ExpireFlags::NONE);
+        }
+
+        done.store(true, Ordering::SeqCst);
+        expirer.join().unwrap();
+    }
+
+    #[test]
+    fn test_idle_time() {
+        static NOW: AtomicU64 = AtomicU64::new(1000);
+
+        let state = State::with_clock(|| NOW.load(Ordering::SeqCst));
+        state.insert(b"foo".to_vec(), Value::Integer(1));
+        assert_eq!(Some(0), state.idle_time(b"foo"));
+
+        NOW.store(1250, Ordering::SeqCst);
+        assert_eq!(Some(250), state.idle_time(b"foo"));
+        assert_eq!(Some(250), state.idle_time(b"foo"));
+
+        // accessing the key resets its idle time
+        assert!(state.touch(b"foo"));
+        assert_eq!(Some(0), state.idle_time(b"foo"));
+    }
+
+    #[test]
+    fn test_expire_flags_valid() {
+        assert!(ExpireFlags::NONE.is_valid());
+        assert!(ExpireFlags::NX.is_valid());
+        assert!((ExpireFlags::XX | ExpireFlags::GT).is_valid());
+        assert!((ExpireFlags::XX | ExpireFlags::LT).is_valid());
+        assert!(!(ExpireFlags::NX | ExpireFlags::XX).is_valid());
+        assert!(!(ExpireFlags::NX | ExpireFlags::GT).is_valid());
+        assert!(!(ExpireFlags::GT | ExpireFlags::LT).is_valid());
+        assert!(ExpireFlags::from_bits(1 << 4).is_none());
+    }
+
+    #[test]
+    fn test_expire_flags() {
+        let state = State::with_clock(|| 1000);
+        state.insert(b"foo".to_vec(), Value::Integer(1));
+
+        // without an existing expiry
+        assert!(!state.expire_at(b"foo", 3000, ExpireFlags::XX));
+        assert!(!state.expire_at(b"foo", 3000, ExpireFlags::GT));
+        assert!(state.expire_at(b"foo", 3000, ExpireFlags::LT));
+        state.insert(b"foo".to_vec(), Value::Integer(1));
+        assert!(state.expire_at(b"foo", 3000, ExpireFlags::NX));
+        assert_eq!(Some(3000), state.expires_at(b"foo"));
+
+        // with an existing expiry
+        assert!(!state.expire_at(b"foo", 4000, ExpireFlags::NX));
+        assert!(!state.expire_at(b"foo", 2000, ExpireFlags::GT));
+        assert!(!state.expire_at(b"foo", 3000, ExpireFlags::GT));
+        assert!(state.expire_at(b"foo", 4000, ExpireFlags::GT));
+        assert!(!state.expire_at(b"foo", 5000, ExpireFlags::LT));
+        assert!(state.expire_at(b"foo", 2000, ExpireFlags::XX | ExpireFlags::LT));
+        assert_eq!(Some(2000), state.expires_at(b"foo"));
+
+        // a past expiry that doesn't meet the conditions leaves the key alone
+        assert!(!state.expire_at(b"foo", 500, ExpireFlags::GT));
+        assert!(state.contains_key(b"foo"));
+    }
+
+    /// Find a key stored in the same shard as "a" and one stored in another
+    /// shard, so that both ways of locking two keys are swapped.
+    fn swap_partners(state: &State) -> (Vec<u8>, Vec<u8>) {
+        let shard = state.0.keys.determine_map(b"a".as_ref());
+        let mut same = None;
+        let mut other = None;
+
+        for idx in 0u32.. {
+            let key = format!("b{}", idx).into_bytes();
+
+            if state.0.keys.determine_map(key.as_slice()) == shard {
+                same = same.or(Some(key));
+            } else {
+                other = other.or(Some(key));
+            }
+
+            if let (Some(same), Some(other)) = (&same, &other) {
+                return (same.clone(), other.clone());
+            }
+        }
+
+        unreachable!()
+    }
+
+    #[test]
+    fn test_swap() {
+        let state = State::new();
+        let (same, other) = swap_partners(&state);
+
+        for b in [same, other].iter() {
+            state.insert(b"a".to_vec(), Value::Bytes(b"foo".to_vec()));
+            state.insert(b.clone(), Value::Integer(1));
+            let memory = state.memory_used();
+
+            assert!(state.swap(b"a", b));
+            assert_eq!(Some(Value::Integer(1)), state.remove(b"a").map(|(_, v)| v));
+            assert_eq!(Some(b.len() + 3), state.key_size(b));
+            assert_eq!(memory - (1 + 8), state.memory_used());
+
+            // neither key is changed if one doesn't exist
+            assert!(!state.swap(b"a", b));
+            assert!(!state.swap(b, b"a"));
+            assert!(state.remove(b).is_some());
+            assert_eq!(0, state.memory_used());
+        }
+    }
+
+    #[test]
+    fn test_swap_concurrent() {
+        let state = State::new();
+        let (same, other) = swap_partners(&state);
+
+        for b in [same, other].iter() {
+            state.insert(b"a".to_vec(), Value::Integer(1));
+            state.insert(b.clone(), Value::Integer(2));
+
+            let done = Arc::new(AtomicBool::new(false));
+            let swapper = {
+                let state = state.clone();
+                let b = b.clone();
+                let done = Arc::clone(&done);
+
+                std::thread::spawn(move || {
+                    for _ in 0..10_000 {
+                        assert!(state.swap(b"a", &b));
+                    }
+
+                    done.store(true, Ordering::SeqCst);
+                })
+            };
+
+            // both keys exist at all times
+            while !done.load(Ordering::SeqCst) {
+                let a = state.key_type(b"a");
+                let b = state.key_type(b);
+
+                assert_eq!((Some(KeyType::Integer), Some(KeyType::Integer)), (a, b));
+            }
+
+            swapper.join().unwrap();
+            state.remove(b"a");
+            state.remove(b);
+        }
+    }
+
+    #[test]
+    fn test_evict_random() {
+        let state = State::new();
+        state.insert(b"foo".to_vec(), Value::Integer(1));
+        state.insert(b"bar".to_vec(), Value::Integer(2));
+
+        assert_eq!(
+            Some(b"bar".to_vec()),
+            state.evict(EvictionPolicy::Random, &[b"foo"])
+        );
+        assert!(state.evict(EvictionPolicy::Random, &[]).is_some());
+        assert!(state.evict(EvictionPolicy::Random, &[]).is_none());
+    }
+
+    #[test]
+    fn test_keys_of_type() {
+        static NOW: AtomicU64 = AtomicU64::new(1000);
+
+        let state = State::with_clock(|| NOW.load(Ordering::SeqCst));
+        state.insert(b"foo".to_vec(), Value::list());
+        state.insert(b"bar".to_vec(), Value::list());
+        state.insert(b"baz".to_vec(), Value::set());
+        state.insert(b"qux".to_vec(), Value::list());
+        state.insert(b"__hop__:internal".to_vec(), Value::list());
+        assert!(state.expire_in(b"qux", 500, ExpireFlags::NONE));
+
+        let mut lists = state.keys_of_type(KeyType::List).collect::<Vec<_>>();
+        lists.sort();
+        assert_eq!(
+            vec![b"bar".to_vec(), b"foo".to_vec(), b"qux".to_vec()],
+            lists
+        );
+
+        // expired keys are skipped
+        NOW.store(1500, Ordering::SeqCst);
+        let mut lists = state.keys_of_type(KeyType::List).collect::<Vec<_>>();
+        lists.sort();
+        assert_eq!(vec![b"bar".to_vec(), b"foo".to_vec()], lists);
+
+        assert_eq!(
+            vec![b"baz".to_vec()],
+            state.keys_of_type(KeyType::Set).collect::<Vec<_>>()
+        );
+        assert_eq!(0, state.keys_of_type(KeyType::Map).count());
+    }
 }
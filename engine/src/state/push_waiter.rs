@@ -0,0 +1,131 @@
+//! Waiting for items to be pushed to lists.
+
+use super::{Key, State};
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+use dashmap::DashMap;
+use futures_intrusive::channel::shared::{self, OneshotReceiver, OneshotSender};
+
+/// Registered waiters, by the key they're waiting on.
+#[derive(Debug, Default)]
+pub(super) struct Waiters {
+    id: AtomicU64,
+    keys: DashMap<Key, Vec<(u64, OneshotSender<()>)>>,
+}
+
+impl Waiters {
+    fn register(&self, key: &[u8]) -> (u64, OneshotReceiver<()>) {
+        let (tx, rx) = shared::oneshot_channel();
+        let id = self.id.fetch_add(1, Ordering::Relaxed);
+
+        self.keys.entry(key.to_vec()).or_default().push((id, tx));
+
+        (id, rx)
+    }
+
+    fn unregister(&self, key: &[u8], id: u64) {
+        if let Some(mut waiters) = self.keys.get_mut(key) {
+            waiters.retain(|(waiter, _)| *waiter != id);
+        }
+
+        self.keys.remove_if(key, |_, waiters| waiters.is_empty());
+    }
+
+    /// Wake every waiter on a key.
+    ///
+    /// All of them are woken since there's no telling how many items were
+    /// pushed; those that find the list empty again wait again.
+    pub(super) fn notify(&self, key: &[u8]) {
+        if self.keys.is_empty() {
+            return;
+        }
+
+        if let Some((_, waiters)) = self.keys.remove(key) {
+            for (_, tx) in waiters {
+                let _ = tx.send(());
+            }
+        }
+    }
+}
+
+/// A registered wait for items to be pushed to a list.
+///
+/// Register the wait *before* checking whether the list is empty, so that a
+/// push between the check and the wait isn't missed. The wait is unregistered
+/// when this is dropped.
+///
+/// This is created by [`State::wait_for_push`].
+///
+/// [`State::wait_for_push`]: struct.State.html#method.wait_for_push
+#[derive(Debug)]
+pub struct PushWaiter {
+    id: u64,
+    key: Key,
+    rx: OneshotReceiver<()>,
+    state: State,
+}
+
+impl PushWaiter {
+    pub(super) fn new(state: State, key: &[u8]) -> Self {
+        let (id, rx) = state.0.waiters.register(key);
+
+        Self {
+            id,
+            key: key.to_vec(),
+            rx,
+            state,
+        }
+    }
+
+    /// Wait until the key may have been pushed to.
+    ///
+    /// This resolves once any command modifies the key, so the list should be
+    /// checked again afterwards.
+    pub async fn pushed(&self) {
+        self.rx.receive().await;
+    }
+}
+
+impl Drop for PushWaiter {
+    fn drop(&mut self) {
+        self.state.0.waiters.unregister(&self.key, self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PushWaiter;
+    use crate::state::State;
+    use core::fmt::Debug;
+    use static_assertions::assert_impl_all;
+
+    assert_impl_all!(PushWaiter: Debug, Send, Sync);
+
+    #[tokio::test]
+    async fn test_notify_wakes_all() {
+        let state = State::new();
+        let a = state.wait_for_push(b"foo");
+        let b = state.wait_for_push(b"foo");
+        let other = state.wait_for_push(b"bar");
+
+        state.notify_pushed(b"foo");
+        a.pushed().await;
+        b.pushed().await;
+
+        // waiters on other keys are left registered
+        assert_eq!(1, state.0.waiters.keys.len());
+        drop(other);
+    }
+
+    #[test]
+    fn test_unregistered_on_drop() {
+        let state = State::new();
+        let a = state.wait_for_push(b"foo");
+        let b = state.wait_for_push(b"foo");
+
+        drop(a);
+        assert_eq!(1, state.0.waiters.keys.get(b"foo".as_ref()).unwrap().len());
+        drop(b);
+        assert!(state.0.waiters.keys.is_empty());
+    }
+}
@@ -0,0 +1,434 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+use core::{
+    cmp::Ordering,
+    ops::{BitOr, Bound},
+};
+
+/// Which bounds of a score range exclude members with exactly that score.
+///
+/// Bounds are inclusive by default.
+///
+/// # Examples
+///
+/// ```
+/// use hop_engine::state::ScoreRangeFlags;
+///
+/// let flags = ScoreRangeFlags::MIN_EXCLUSIVE | ScoreRangeFlags::MAX_EXCLUSIVE;
+/// assert!(flags.contains(ScoreRangeFlags::MIN_EXCLUSIVE));
+/// assert_eq!(Some(flags), ScoreRangeFlags::from_bits(flags.bits()));
+/// assert!(ScoreRangeFlags::from_bits(1 << 2).is_none());
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ScoreRangeFlags(u8);
+
+impl ScoreRangeFlags {
+    /// Both bounds are inclusive.
+    pub const NONE: Self = Self(0);
+    /// Exclude members scored exactly the minimum.
+    pub const MIN_EXCLUSIVE: Self = Self(1);
+    /// Exclude members scored exactly the maximum.
+    pub const MAX_EXCLUSIVE: Self = Self(1 << 1);
+
+    const ALL: u8 = Self::MIN_EXCLUSIVE.0 | Self::MAX_EXCLUSIVE.0;
+
+    /// Create flags from their bits, returning `None` if any bits are unknown.
+    pub fn from_bits(bits: u8) -> Option<Self> {
+        if bits & !Self::ALL == 0 {
+            Some(Self(bits))
+        } else {
+            None
+        }
+    }
+
+    /// Retrieve the bits of the flags.
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Whether all of the given flags are set.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Create the bounds of a range between a minimum and maximum score.
+    pub fn bounds(self, min: f64, max: f64) -> (Bound<f64>, Bound<f64>) {
+        let bound = |score, flag| {
+            if self.contains(flag) {
+                Bound::Excluded(score)
+            } else {
+                Bound::Included(score)
+            }
+        };
+
+        (
+            bound(min, Self::MIN_EXCLUSIVE),
+            bound(max, Self::MAX_EXCLUSIVE),
+        )
+    }
+}
+
+impl BitOr for ScoreRangeFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Score of a sorted set member, totally ordered so that it can be used as a
+/// key.
+#[derive(Clone, Copy, Debug)]
+struct Score(f64);
+
+impl Eq for Score {}
+
+impl Score {
+    /// Map the score's bits to an integer with the same total order as the
+    /// IEEE 754 `totalOrder` predicate.
+    fn ordinal(self) -> i64 {
+        let bits = self.0.to_bits() as i64;
+
+        // Negative scores have their magnitude bits flipped so that larger
+        // magnitudes sort lower.
+        bits ^ (((bits >> 63) as u64) >> 1) as i64
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ordinal().cmp(&other.ordinal())
+    }
+}
+
+impl PartialEq for Score {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Set of unique members ordered by their scores.
+///
+/// Members with equal scores are ordered by their bytes.
+///
+/// # Examples
+///
+/// ```
+/// use hop_engine::state::SortedSet;
+///
+/// let mut set = SortedSet::new();
+/// set.insert(b"bar".to_vec(), 2.0);
+/// set.insert(b"foo".to_vec(), 1.0);
+///
+/// let members = set.iter().map(|(member, _)| member).collect::<Vec<_>>();
+/// assert_eq!([b"foo".as_ref(), b"bar".as_ref()].to_vec(), members);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SortedSet {
+    order: BTreeSet<(Score, Vec<u8>)>,
+    scores: BTreeMap<Vec<u8>, Score>,
+}
+
+impl SortedSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a member with a score, updating its score if it's already a
+    /// member.
+    ///
+    /// Returns whether the member was newly inserted.
+    ///
+    /// Scores must not be NaN.
+    pub fn insert(&mut self, member: Vec<u8>, score: f64) -> bool {
+        debug_assert!(!score.is_nan());
+
+        // Zeroes of either sign compare equal as floats, so store them the same
+        // way to keep score ranges consistent.
+        let score = Score(score + 0.0);
+        let existing = self.scores.insert(member.clone(), score);
+
+        if let Some(existing) = existing {
+            self.order.remove(&(existing, member.clone()));
+        }
+
+        self.order.insert((score, member));
+
+        existing.is_none()
+    }
+
+    /// Whether the set has no members.
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    /// Iterate over the members and their scores in order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&[u8], f64)> + '_ {
+        self.order
+            .iter()
+            .map(|(score, member)| (member.as_slice(), score.0))
+    }
+
+    /// Number of members in the set.
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    /// Iterate over the members and their scores in order between two ranks,
+    /// inclusive.
+    ///
+    /// Negative ranks count back from the last member, so -1 is the last
+    /// member. Ranks outside of the set are clamped to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use hop_engine::state::SortedSet;
+    ///
+    /// let mut set = SortedSet::new();
+    /// set.insert(b"a".to_vec(), 1.0);
+    /// set.insert(b"b".to_vec(), 2.0);
+    /// set.insert(b"c".to_vec(), 3.0);
+    ///
+    /// let members = set.range_by_rank(1, -1).map(|(member, _)| member).collect::<Vec<_>>();
+    /// assert_eq!([b"b".as_ref(), b"c".as_ref()].to_vec(), members);
+    /// ```
+    pub fn range_by_rank(&self, start: i64, stop: i64) -> impl Iterator<Item = (&[u8], f64)> + '_ {
+        let len = self.len() as i64;
+        let resolve = |rank: i64| if rank < 0 { len + rank } else { rank };
+
+        let start = resolve(start).max(0);
+        let stop = resolve(stop).min(len - 1);
+        let count = if start <= stop { stop - start + 1 } else { 0 };
+
+        self.iter().skip(start as usize).take(count as usize)
+    }
+
+    /// Iterate over the members and their scores in order whose scores are
+    /// within the given bounds.
+    ///
+    /// Infinite bounds may be used to leave a side of the range open.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use core::ops::Bound;
+    /// use hop_engine::state::SortedSet;
+    ///
+    /// let mut set = SortedSet::new();
+    /// set.insert(b"a".to_vec(), 1.0);
+    /// set.insert(b"b".to_vec(), 2.0);
+    /// set.insert(b"c".to_vec(), 3.0);
+    ///
+    /// let members = set
+    ///     .range_by_score(Bound::Excluded(1.0), Bound::Included(f64::INFINITY))
+    ///     .map(|(member, _)| member)
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!([b"b".as_ref(), b"c".as_ref()].to_vec(), members);
+    /// ```
+    pub fn range_by_score(
+        &self,
+        min: Bound<f64>,
+        max: Bound<f64>,
+    ) -> impl Iterator<Item = (&[u8], f64)> + '_ {
+        let start = match min {
+            Bound::Included(min) | Bound::Excluded(min) => min + 0.0,
+            Bound::Unbounded => f64::NEG_INFINITY,
+        };
+
+        // The empty member sorts before every other member of the same score.
+        self.order
+            .range((Score(start), Vec::new())..)
+            .map(|(score, member)| (member.as_slice(), score.0))
+            .skip_while(move |(_, score)| matches!(min, Bound::Excluded(min) if *score <= min))
+            .take_while(move |(_, score)| match max {
+                Bound::Included(max) => *score <= max,
+                Bound::Excluded(max) => *score < max,
+                Bound::Unbounded => true,
+            })
+    }
+
+    /// Remove a member, returning whether it was a member.
+    pub fn remove(&mut self, member: &[u8]) -> bool {
+        match self.scores.remove(member) {
+            Some(score) => {
+                self.order.remove(&(score, member.to_vec()));
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Retrieve the score of a member.
+    pub fn score(&self, member: &[u8]) -> Option<f64> {
+        self.scores.get(member).map(|score| score.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ScoreRangeFlags, SortedSet};
+    use alloc::vec::Vec;
+    use core::{
+        fmt::Debug,
+        hash::Hash,
+        ops::{BitOr, Bound},
+    };
+    use static_assertions::assert_impl_all;
+
+    assert_impl_all!(
+        ScoreRangeFlags: BitOr,
+        Clone,
+        Copy,
+        Debug,
+        Default,
+        Eq,
+        Hash,
+        PartialEq,
+    );
+    assert_impl_all!(SortedSet: Clone, Debug, Default, Send, Sync);
+
+    fn members(set: &SortedSet) -> Vec<&[u8]> {
+        set.iter().map(|(member, _)| member).collect()
+    }
+
+    #[test]
+    fn test_insertion_order() {
+        let mut set = SortedSet::new();
+        assert!(set.insert(b"c".to_vec(), 3.0));
+        assert!(set.insert(b"a".to_vec(), -1.5));
+        assert!(set.insert(b"b".to_vec(), 2.0));
+
+        // equal scores are ordered by member
+        assert!(set.insert(b"bb".to_vec(), 2.0));
+
+        assert_eq!([b"a".as_ref(), b"b", b"bb", b"c"].to_vec(), members(&set));
+        assert_eq!(4, set.len());
+    }
+
+    #[test]
+    fn test_score_update() {
+        let mut set = SortedSet::new();
+        set.insert(b"a".to_vec(), 1.0);
+        set.insert(b"b".to_vec(), 2.0);
+
+        assert!(!set.insert(b"a".to_vec(), 3.0));
+        assert_eq!(Some(3.0), set.score(b"a"));
+        assert_eq!([b"b".as_ref(), b"a"].to_vec(), members(&set));
+        assert_eq!(2, set.len());
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut set = SortedSet::new();
+        set.insert(b"a".to_vec(), 1.0);
+
+        assert!(set.remove(b"a"));
+        assert!(!set.remove(b"a"));
+        assert!(set.is_empty());
+        assert!(set.iter().next().is_none());
+        assert_eq!(None, set.score(b"a"));
+    }
+
+    #[test]
+    fn test_range_by_rank() {
+        let mut set = SortedSet::new();
+
+        for (idx, member) in [b"a", b"b", b"c", b"d"].iter().enumerate() {
+            set.insert(member.to_vec(), idx as f64);
+        }
+
+        let range = |start, stop| {
+            set.range_by_rank(start, stop)
+                .map(|(member, _)| member)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!([b"a".as_ref(), b"b"].to_vec(), range(0, 1));
+        assert_eq!([b"c".as_ref(), b"d"].to_vec(), range(-2, -1));
+        assert_eq!([b"a".as_ref(), b"b", b"c", b"d"].to_vec(), range(-100, 100));
+        assert!(range(2, 1).is_empty());
+        assert!(range(4, 10).is_empty());
+        assert!(range(0, -5).is_empty());
+    }
+
+    #[test]
+    fn test_range_by_score() {
+        let mut set = SortedSet::new();
+
+        for (idx, member) in [b"a", b"b", b"c", b"d"].iter().enumerate() {
+            set.insert(member.to_vec(), idx as f64);
+        }
+
+        let range = |min, max| {
+            set.range_by_score(min, max)
+                .map(|(member, _)| member)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            [b"b".as_ref(), b"c"].to_vec(),
+            range(Bound::Included(1.0), Bound::Included(2.0))
+        );
+        assert_eq!(
+            [b"c".as_ref()].to_vec(),
+            range(Bound::Excluded(1.0), Bound::Excluded(3.0))
+        );
+        assert_eq!(
+            [b"a".as_ref(), b"b", b"c", b"d"].to_vec(),
+            range(
+                Bound::Included(f64::NEG_INFINITY),
+                Bound::Included(f64::INFINITY)
+            )
+        );
+        assert_eq!(
+            [b"a".as_ref(), b"b"].to_vec(),
+            range(Bound::Unbounded, Bound::Excluded(2.0))
+        );
+        assert!(range(Bound::Excluded(1.0), Bound::Excluded(2.0)).is_empty());
+        assert!(range(Bound::Included(3.0), Bound::Included(0.0)).is_empty());
+        assert!(range(Bound::Included(10.0), Bound::Unbounded).is_empty());
+    }
+
+    #[test]
+    fn test_range_by_score_signed_zero() {
+        let mut set = SortedSet::new();
+        set.insert(b"a".to_vec(), -0.0);
+
+        assert_eq!(
+            1,
+            set.range_by_score(Bound::Included(0.0), Bound::Unbounded)
+                .count()
+        );
+        assert_eq!(
+            1,
+            set.range_by_score(Bound::Unbounded, Bound::Included(-0.0))
+                .count()
+        );
+    }
+
+    #[test]
+    fn test_score_range_flags_bounds() {
+        assert_eq!(
+            (Bound::Included(1.0), Bound::Included(2.0)),
+            ScoreRangeFlags::NONE.bounds(1.0, 2.0)
+        );
+        assert_eq!(
+            (Bound::Excluded(1.0), Bound::Included(2.0)),
+            ScoreRangeFlags::MIN_EXCLUSIVE.bounds(1.0, 2.0)
+        );
+        assert_eq!(
+            (Bound::Excluded(1.0), Bound::Excluded(2.0)),
+            (ScoreRangeFlags::MIN_EXCLUSIVE | ScoreRangeFlags::MAX_EXCLUSIVE).bounds(1.0, 2.0)
+        );
+    }
+}
@@ -1,33 +1,85 @@
-use super::KeyType;
-use alloc::{string::String, vec::Vec};
+use super::{IntSet, KeyType, SortedSet};
+use alloc::{collections::VecDeque, string::String, vec::Vec};
 use dashmap::{DashMap, DashSet};
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum Value {
     Boolean(bool),
     Bytes(Vec<u8>),
     Float(f64),
     Integer(i64),
-    List(Vec<Vec<u8>>),
-    Map(DashMap<Vec<u8>, Vec<u8>>),
+    /// Set whose members are all integers, packed to use less memory.
+    ///
+    /// This is an encoding of a set rather than a type of its own: its kind is
+    /// [`KeyType::Set`] and it's written in responses and dumps as a set. Sets
+    /// are packed when they're created via [`Value::set_from`] and are
+    /// unpacked by [`Value::insert_set_member`] once a member isn't an
+    /// integer.
+    ///
+    /// [`KeyType::Set`]: ../enum.KeyType.html#variant.Set
+    /// [`Value::set_from`]: #method.set_from
+    /// [`Value::insert_set_member`]: #method.insert_set_member
+    IntSet(IntSet),
+    /// List of items, backed by a ring buffer so that pushing and popping at
+    /// either end is `O(1)`.
+    List(VecDeque<Vec<u8>>),
+    /// Map of fields to values of any type.
+    ///
+    /// Fields set by requests hold bytes, since request arguments don't carry
+    /// types.
+    Map(DashMap<Vec<u8>, Value>),
     Set(DashSet<Vec<u8>>),
+    /// Set of members ordered by their scores.
+    SortedSet(SortedSet),
     String(String),
 }
 
 impl Value {
+    /// Approximate number of bytes used by the value's data.
+    ///
+    /// Collections are measured by the total length of their items, not
+    /// including any overhead of the collection itself.
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Boolean(_) => 1,
+            Self::Bytes(bytes) => bytes.len(),
+            Self::Float(_) => 8,
+            Self::Integer(_) => 8,
+            Self::IntSet(set) => set.len() * 8,
+            Self::List(list) => list.iter().map(Vec::len).sum(),
+            Self::Map(map) => map.iter().map(|r| r.key().len() + r.value().size()).sum(),
+            Self::Set(set) => set.iter().map(|r| r.key().len()).sum(),
+            Self::SortedSet(set) => set.iter().map(|(member, _)| member.len() + 8).sum(),
+            Self::String(string) => string.len(),
+        }
+    }
+
     pub fn kind(&self) -> KeyType {
         match self {
             Self::Boolean(_) => KeyType::Boolean,
             Self::Bytes(_) => KeyType::Bytes,
             Self::Float(_) => KeyType::Float,
             Self::Integer(_) => KeyType::Integer,
+            Self::IntSet(_) => KeyType::Set,
             Self::List(_) => KeyType::List,
             Self::Map(_) => KeyType::Map,
             Self::Set(_) => KeyType::Set,
+            Self::SortedSet(_) => KeyType::SortedSet,
             Self::String(_) => KeyType::String,
         }
     }
 
+    /// Name of how the value is stored, which is `packed` for sets packed as
+    /// an [`IntSet`] and `general` for everything else.
+    ///
+    /// [`IntSet`]: ../int_set/struct.IntSet.html
+    pub fn encoding(&self) -> &'static str {
+        match self {
+            Self::IntSet(_) => "packed",
+            _ => "general",
+        }
+    }
+
     pub fn boolean() -> Self {
         Self::Boolean(false)
     }
@@ -117,17 +169,17 @@ impl Value {
     }
 
     pub fn list() -> Self {
-        Self::List(Vec::new())
+        Self::List(VecDeque::new())
     }
 
-    pub fn as_list_ref(&self) -> Option<&[Vec<u8>]> {
+    pub fn as_list_ref(&self) -> Option<&VecDeque<Vec<u8>>> {
         match self {
             Self::List(inner) => Some(inner),
             _ => None,
         }
     }
 
-    pub fn as_list_mut(&mut self) -> Option<&mut Vec<Vec<u8>>> {
+    pub fn as_list_mut(&mut self) -> Option<&mut VecDeque<Vec<u8>>> {
         match self {
             Self::List(inner) => Some(inner),
             _ => None,
@@ -142,14 +194,14 @@ impl Value {
         Self::Map(DashMap::new())
     }
 
-    pub fn as_map_ref(&self) -> Option<&DashMap<Vec<u8>, Vec<u8>>> {
+    pub fn as_map_ref(&self) -> Option<&DashMap<Vec<u8>, Value>> {
         match self {
             Self::Map(inner) => Some(inner),
             _ => None,
         }
     }
 
-    pub fn as_map_mut(&mut self) -> Option<&mut DashMap<Vec<u8>, Vec<u8>>> {
+    pub fn as_map_mut(&mut self) -> Option<&mut DashMap<Vec<u8>, Value>> {
         match self {
             Self::Map(inner) => Some(inner),
             _ => None,
@@ -164,6 +216,64 @@ impl Value {
         Self::Set(DashSet::new())
     }
 
+    /// Create a set of members, packed as an [`IntSet`] if they're all
+    /// integers.
+    ///
+    /// [`IntSet`]: ../int_set/struct.IntSet.html
+    pub fn set_from(members: DashSet<Vec<u8>>) -> Self {
+        if members.len() > IntSet::MAX_LEN {
+            return Self::Set(members);
+        }
+
+        let ints = members
+            .iter()
+            .map(|member| IntSet::parse(member.key()))
+            .collect::<Option<IntSet>>();
+
+        match ints {
+            Some(set) => Self::IntSet(set),
+            None => Self::Set(members),
+        }
+    }
+
+    /// Insert a member into a set of either encoding, returning whether it
+    /// wasn't already in the set, or `None` if the value isn't a set.
+    ///
+    /// A packed set is unpacked if the member isn't an integer or the set
+    /// would grow past [`IntSet::MAX_LEN`].
+    ///
+    /// [`IntSet::MAX_LEN`]: ../int_set/struct.IntSet.html#associatedconstant.MAX_LEN
+    pub fn insert_set_member(&mut self, member: Vec<u8>) -> Option<bool> {
+        if let Self::IntSet(set) = self {
+            match IntSet::parse(&member) {
+                Some(int) if set.contains(int) => return Some(false),
+                Some(int) if set.len() < IntSet::MAX_LEN => return Some(set.insert(int)),
+                _ => *self = Self::Set(set.to_set()),
+            }
+        }
+
+        self.as_set_mut().map(|set| set.insert(member))
+    }
+
+    /// Number of members if the value is a set of either encoding.
+    pub fn set_len(&self) -> Option<usize> {
+        match self {
+            Self::IntSet(set) => Some(set.len()),
+            Self::Set(set) => Some(set.len()),
+            _ => None,
+        }
+    }
+
+    pub fn as_int_set_ref(&self) -> Option<&IntSet> {
+        match self {
+            Self::IntSet(inner) => Some(inner),
+            _ => None,
+        }
+    }
+
+    /// Retrieve the set if it isn't packed as an [`IntSet`].
+    ///
+    /// [`IntSet`]: ../int_set/struct.IntSet.html
     pub fn as_set_ref(&self) -> Option<&DashSet<Vec<u8>>> {
         match self {
             Self::Set(inner) => Some(inner),
@@ -171,6 +281,9 @@ impl Value {
         }
     }
 
+    /// Retrieve the set if it isn't packed as an [`IntSet`].
+    ///
+    /// [`IntSet`]: ../int_set/struct.IntSet.html
     pub fn as_set_mut(&mut self) -> Option<&mut DashSet<Vec<u8>>> {
         match self {
             Self::Set(inner) => Some(inner),
@@ -179,7 +292,29 @@ impl Value {
     }
 
     pub fn is_set(&self) -> bool {
-        matches!(self, Value::Set(_))
+        matches!(self, Value::IntSet(_) | Value::Set(_))
+    }
+
+    pub fn sorted_set() -> Self {
+        Self::SortedSet(SortedSet::new())
+    }
+
+    pub fn as_sorted_set_ref(&self) -> Option<&SortedSet> {
+        match self {
+            Self::SortedSet(inner) => Some(inner),
+            _ => None,
+        }
+    }
+
+    pub fn as_sorted_set_mut(&mut self) -> Option<&mut SortedSet> {
+        match self {
+            Self::SortedSet(inner) => Some(inner),
+            _ => None,
+        }
+    }
+
+    pub fn is_sorted_set(&self) -> bool {
+        matches!(self, Value::SortedSet(_))
     }
 
     pub fn string() -> Self {
@@ -205,6 +340,41 @@ impl Value {
     }
 }
 
+/// Values are equal if they're the same variant with the same contents.
+///
+/// Maps and sets are compared regardless of the order of their items, and sets
+/// regardless of how they're encoded. Floats,
+/// including the scores of sorted sets, use float equality, so `NaN` is never
+/// equal to itself.
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Boolean(a), Self::Boolean(b)) => a == b,
+            (Self::Bytes(a), Self::Bytes(b)) => a == b,
+            (Self::Float(a), Self::Float(b)) => a == b,
+            (Self::Integer(a), Self::Integer(b)) => a == b,
+            (Self::List(a), Self::List(b)) => a == b,
+            (Self::Map(a), Self::Map(b)) => {
+                a.len() == b.len()
+                    && a.iter().all(|item| match b.get(item.key()) {
+                        Some(value) => *value == *item.value(),
+                        None => false,
+                    })
+            }
+            (Self::IntSet(a), Self::IntSet(b)) => a == b,
+            (Self::IntSet(a), Self::Set(b)) | (Self::Set(b), Self::IntSet(a)) => {
+                a.len() == b.len() && a.members().all(|member| b.contains(&member))
+            }
+            (Self::Set(a), Self::Set(b)) => {
+                a.len() == b.len() && a.iter().all(|item| b.contains(item.key()))
+            }
+            (Self::SortedSet(a), Self::SortedSet(b)) => a.len() == b.len() && a.iter().eq(b.iter()),
+            (Self::String(a), Self::String(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
 impl From<bool> for Value {
     fn from(value: bool) -> Self {
         Self::Boolean(value)
@@ -231,13 +401,31 @@ impl From<i64> for Value {
 
 impl From<Vec<Vec<u8>>> for Value {
     fn from(value: Vec<Vec<u8>>) -> Self {
+        Self::List(value.into())
+    }
+}
+
+impl From<VecDeque<Vec<u8>>> for Value {
+    fn from(value: VecDeque<Vec<u8>>) -> Self {
         Self::List(value)
     }
 }
 
+impl From<DashMap<Vec<u8>, Value>> for Value {
+    fn from(value: DashMap<Vec<u8>, Value>) -> Self {
+        Self::Map(value)
+    }
+}
+
+/// Create a map whose values are bytes.
 impl From<DashMap<Vec<u8>, Vec<u8>>> for Value {
     fn from(value: DashMap<Vec<u8>, Vec<u8>>) -> Self {
-        Self::Map(value)
+        Self::Map(
+            value
+                .into_iter()
+                .map(|(field, value)| (field, Self::Bytes(value)))
+                .collect(),
+        )
     }
 }
 
@@ -247,6 +435,18 @@ impl From<DashSet<Vec<u8>>> for Value {
     }
 }
 
+impl From<IntSet> for Value {
+    fn from(value: IntSet) -> Self {
+        Self::IntSet(value)
+    }
+}
+
+impl From<SortedSet> for Value {
+    fn from(value: SortedSet) -> Self {
+        Self::SortedSet(value)
+    }
+}
+
 impl From<String> for Value {
     fn from(value: String) -> Self {
         Self::String(value)
@@ -256,21 +456,188 @@ impl From<String> for Value {
 #[cfg(test)]
 mod tests {
     use super::Value;
-    use alloc::{string::String, vec::Vec};
+    use crate::state::{IntSet, KeyType, SortedSet};
+    use alloc::{
+        collections::VecDeque,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
     use core::fmt::Debug;
     use dashmap::{DashMap, DashSet};
     use static_assertions::assert_impl_all;
+    use std::time::{Duration, Instant};
 
     assert_impl_all!(
-        Value: Debug,
+        Value: Clone,
+        Debug,
+        PartialEq,
         From<bool>,
         From<Vec<u8>>,
         From<f64>,
         From<i64>,
         From<Vec<Vec<u8>>>,
+        From<VecDeque<Vec<u8>>>,
+        From<DashMap<Vec<u8>, Value>>,
         From<DashMap<Vec<u8>, Vec<u8>>>,
-        From<Vec<u8>>,
         From<DashSet<Vec<u8>>>,
+        From<SortedSet>,
         From<String>,
     );
+
+    /// Time pushing and popping at the front of a list of a given length.
+    fn time_front_operations(len: usize) -> Duration {
+        let mut value = Value::from(vec![b"item".to_vec(); len]);
+        let list = value.as_list_mut().unwrap();
+
+        let start = Instant::now();
+
+        for _ in 0..1000 {
+            list.push_front(b"front".to_vec());
+            assert!(list.pop_front().is_some());
+        }
+
+        start.elapsed()
+    }
+
+    #[test]
+    fn test_list_front_operations_dont_degrade() {
+        let small = time_front_operations(10);
+        let large = time_front_operations(100_000);
+
+        // With a contiguous vector each front operation would shift every
+        // item, making the large list thousands of times slower. The bound is
+        // generous so that timing noise doesn't cause failures.
+        assert!(
+            large < small * 100 + Duration::from_millis(10),
+            "small: {:?}, large: {:?}",
+            small,
+            large
+        );
+    }
+
+    #[test]
+    fn test_list_order() {
+        let mut value = Value::from(vec![b"b".to_vec()]);
+        let list = value.as_list_mut().unwrap();
+        list.push_front(b"a".to_vec());
+        list.push_back(b"c".to_vec());
+
+        let items = value
+            .as_list_ref()
+            .unwrap()
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()], items);
+    }
+
+    #[test]
+    fn test_size() {
+        assert_eq!(1, Value::Boolean(true).size());
+        assert_eq!(3, Value::Bytes(b"foo".to_vec()).size());
+        assert_eq!(8, Value::Integer(1).size());
+        assert_eq!(
+            6,
+            Value::from([b"foo".to_vec(), b"bar".to_vec()].to_vec()).size()
+        );
+        assert_eq!(6, Value::String("foobar".into()).size());
+
+        let map = DashMap::new();
+        map.insert(b"foo".to_vec(), Value::Bytes(b"bar".to_vec()));
+        map.insert(b"baz".to_vec(), Value::Integer(1));
+        assert_eq!(17, Value::Map(map).size());
+
+        let set = DashSet::new();
+        set.insert(b"foo".to_vec());
+        assert_eq!(3, Value::Set(set).size());
+
+        let mut sorted_set = SortedSet::new();
+        sorted_set.insert(b"foo".to_vec(), 1.0);
+        assert_eq!(11, Value::SortedSet(sorted_set).size());
+    }
+
+    #[test]
+    fn test_eq() {
+        let map = DashMap::new();
+        map.insert(b"a".to_vec(), b"1".to_vec());
+        map.insert(b"b".to_vec(), b"2".to_vec());
+        let map = Value::from(map);
+
+        // the clone has the same items, though not necessarily in the same
+        // order
+        assert_eq!(map, map.clone());
+        assert_ne!(map, Value::Map(DashMap::new()));
+
+        // fields of different types aren't equal
+        let typed = DashMap::new();
+        typed.insert(b"a".to_vec(), Value::Integer(1));
+        typed.insert(b"b".to_vec(), Value::Bytes(b"2".to_vec()));
+        assert_ne!(map, Value::Map(typed));
+
+        let mut sorted = SortedSet::new();
+        sorted.insert(b"a".to_vec(), 1.0);
+        let sorted = Value::SortedSet(sorted);
+        assert_eq!(sorted, sorted.clone());
+
+        assert_eq!(Value::Integer(1), Value::Integer(1));
+        assert_ne!(Value::Integer(1), Value::Float(1.0));
+        assert_ne!(Value::Float(f64::NAN), Value::Float(f64::NAN));
+    }
+
+    fn set(members: &[&[u8]]) -> DashSet<Vec<u8>> {
+        members.iter().map(|member| member.to_vec()).collect()
+    }
+
+    #[test]
+    fn test_set_packed() {
+        let packed = Value::set_from(set(&[b"1", b"-20", b"300"]));
+        assert!(packed.as_int_set_ref().is_some());
+        assert_eq!(KeyType::Set, packed.kind());
+        assert_eq!("packed", packed.encoding());
+        assert_eq!(Some(3), packed.set_len());
+        assert_eq!(24, packed.size());
+
+        // sets are equal regardless of their encoding
+        assert_eq!(packed, Value::Set(set(&[b"1", b"-20", b"300"])));
+        assert_eq!(Value::Set(set(&[b"300", b"-20", b"1"])), packed);
+        assert_ne!(packed, Value::Set(set(&[b"1", b"-20", b"301"])));
+
+        let general = Value::set_from(set(&[b"1", b"foo"]));
+        assert!(general.as_set_ref().is_some());
+        assert_eq!("general", general.encoding());
+        assert_eq!("general", Value::set_from(set(&[b"01"])).encoding());
+    }
+
+    #[test]
+    fn test_set_unpacked_on_string_insert() {
+        let mut value = Value::set_from(set(&[b"1", b"2"]));
+
+        assert_eq!(Some(true), value.insert_set_member(b"3".to_vec()));
+        assert_eq!(Some(false), value.insert_set_member(b"2".to_vec()));
+        assert_eq!("packed", value.encoding());
+
+        assert_eq!(Some(true), value.insert_set_member(b"foo".to_vec()));
+        assert_eq!("general", value.encoding());
+        assert_eq!(Value::Set(set(&[b"1", b"2", b"3", b"foo"])), value);
+
+        assert!(Value::Integer(1).insert_set_member(b"1".to_vec()).is_none());
+    }
+
+    #[test]
+    fn test_set_unpacked_past_max_len() {
+        let members = (0..IntSet::MAX_LEN)
+            .map(|int| int.to_string().into_bytes())
+            .collect();
+        let mut value = Value::set_from(members);
+        assert_eq!("packed", value.encoding());
+
+        assert_eq!(Some(false), value.insert_set_member(b"0".to_vec()));
+        assert_eq!("packed", value.encoding());
+
+        let next = IntSet::MAX_LEN.to_string().into_bytes();
+        assert_eq!(Some(true), value.insert_set_member(next));
+        assert_eq!("general", value.encoding());
+        assert_eq!(Some(IntSet::MAX_LEN + 1), value.set_len());
+    }
 }
@@ -216,6 +216,37 @@ pub struct Writer<T: Eq + Hash> {
 }
 
 impl<T: Eq + Hash> Writer<T> {
+    /// Attempt to add an amount to a counter, which may or may not already
+    /// exist.
+    ///
+    /// The counter will be added to if it exists, or a new one will be made in
+    /// its place with the amount as its initial value if it doesn't.
+    ///
+    /// Returns whether the counter could be written. This only fails if the
+    /// metrics instance no longer exists.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use hop_internal_metrics::Metrics;
+    ///
+    /// let metrics = Metrics::new();
+    /// let writer = metrics.writer();
+    ///
+    /// writer.add("bytes", 5);
+    /// writer.add("bytes", 3);
+    ///
+    /// assert_eq!(metrics.reader().counter(&"bytes"), Some(8));
+    /// ```
+    pub fn add(&self, counter: T, amount: u64) -> bool {
+        self.metrics
+            .upgrade()
+            .map(|metrics| {
+                *metrics.counters.entry(counter).or_default() += amount;
+            })
+            .is_some()
+    }
+
     /// Attempt to write a new value to a gauge, which may or may not already
     /// exist.
     ///
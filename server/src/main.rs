@@ -2,27 +2,54 @@
 #![forbid(unsafe_code)]
 #![allow(clippy::multiple_crate_versions)]
 
+mod pool;
+mod pop;
+mod text;
+#[cfg(unix)]
+mod unix;
+mod watch;
+#[cfg(feature = "websocket")]
+mod websocket;
+
 use hop_engine::{
-    command::{request::Context, Response},
+    command::{request::Context, CommandId, Response},
+    connection::Connection,
+    pubsub::KeyspaceReceiver,
+    state::KeyType,
     Hop,
 };
 use log::{debug, warn};
+use pool::Pool;
 use std::{
+    convert::TryFrom,
     env,
     error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    io::Result as IoResult,
     net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::PathBuf,
     str::FromStr as _,
+    time::Duration,
 };
+use text::Magic;
 use tokio::{
-    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader},
     net::{TcpListener, TcpStream},
+    signal,
     stream::StreamExt,
-    task,
+    task, time,
 };
+use watch::{SlowWatchers, WatchPolicy};
 
 struct Config {
     host: IpAddr,
+    idle: Option<Duration>,
+    log_commands: bool,
     port: u16,
+    unix: Option<PathBuf>,
+    watch: WatchPolicy,
+    #[cfg(feature = "websocket")]
+    websocket_port: Option<u16>,
 }
 
 impl Config {
@@ -39,7 +66,55 @@ impl Config {
             Err(_) => Self::PORT_DEFAULT,
         };
 
-        Self { host, port }
+        // The only supported argument is `--unix <path>`, which additionally
+        // listens on a Unix domain socket at the path.
+        let mut args = env::args().skip(1);
+        let mut unix = None;
+
+        while let Some(arg) = args.next() {
+            if arg == "--unix" {
+                unix = args.next().map(PathBuf::from);
+            }
+        }
+
+        // Watchers are disconnected or have events dropped once they fall
+        // behind by this many bytes of events.
+        let watch = WatchPolicy {
+            buffer_max: env::var("WATCH_BUFFER_MAX")
+                .ok()
+                .and_then(|max| max.parse().ok())
+                .unwrap_or(WatchPolicy::BUFFER_MAX_DEFAULT),
+            slow: env::var("SLOW_WATCHERS")
+                .ok()
+                .and_then(|slow| slow.parse().ok())
+                .unwrap_or(SlowWatchers::Drop),
+        };
+
+        // Connections that send nothing for this many seconds are closed. If
+        // it's unset or 0 then they're never closed.
+        let idle = env::var("IDLE_TIMEOUT")
+            .ok()
+            .and_then(|secs| secs.parse().ok())
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs);
+
+        // Every dispatched command is logged at the debug level if this is
+        // `true`.
+        let log_commands = env::var("LOG_COMMANDS")
+            .ok()
+            .and_then(|log| log.parse().ok())
+            .unwrap_or(false);
+
+        Self {
+            host,
+            idle,
+            log_commands,
+            port,
+            unix,
+            watch,
+            #[cfg(feature = "websocket")]
+            websocket_port: env::var("WS_PORT").ok().and_then(|port| port.parse().ok()),
+        }
     }
 }
 
@@ -55,65 +130,1266 @@ async fn main() -> Result<(), Box<dyn Error>> {
     debug!("Binding to {}", addr);
     let mut listener = TcpListener::bind(&addr).await?;
 
-    let hop = Hop::new();
+    let mut builder = Hop::builder();
+    builder.log_commands(config.log_commands);
+    let hop = builder.build();
+    let pool = Pool::new();
+    let watch = config.watch;
+    let idle = config.idle;
+
+    #[cfg(feature = "websocket")]
+    if let Some(port) = config.websocket_port {
+        let addr = SocketAddr::new(config.host, port);
+
+        debug!("Binding websocket listener to {}", addr);
+        let listener = TcpListener::bind(&addr).await?;
+
+        task::spawn(websocket::serve(listener, hop.clone(), pool.clone()));
+    }
+
+    // The socket file is removed when this is dropped on shutdown.
+    #[cfg(unix)]
+    let _unix = match config.unix {
+        Some(path) => {
+            debug!("Binding unix socket to {}", path.display());
+
+            Some(unix::bind(path, hop.clone(), pool.clone(), watch, idle)?)
+        }
+        None => None,
+    };
+
+    #[cfg(not(unix))]
+    if config.unix.is_some() {
+        warn!("Unix sockets aren't supported on this platform");
+    }
 
     let mut incoming = listener.incoming();
 
     debug!("Listening for new connections on {}", addr);
 
-    while let Some(Ok(socket)) = incoming.next().await {
-        task::spawn(handle_socket(socket, hop.clone()));
+    let accept = async {
+        while let Some(Ok(socket)) = incoming.next().await {
+            task::spawn(handle_socket(
+                socket,
+                hop.clone(),
+                pool.clone(),
+                watch,
+                idle,
+            ));
+        }
+    };
+
+    tokio::select! {
+        _ = accept => {}
+        _ = signal::ctrl_c() => debug!("Shutting down"),
     }
 
     Ok(())
 }
 
-async fn handle_socket(socket: TcpStream, hop: Hop) {
+/// The remote end of a connection, identifying it in log lines.
+#[derive(Clone, Copy, Debug)]
+enum Peer {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix,
+}
+
+impl Display for Peer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Tcp(addr) => Display::fmt(addr, f),
+            #[cfg(unix)]
+            Self::Unix => f.write_str("unix peer"),
+        }
+    }
+}
+
+async fn handle_socket(
+    socket: TcpStream,
+    hop: Hop,
+    pool: Pool,
+    watch: WatchPolicy,
+    idle: Option<Duration>,
+) {
     let addr = socket.peer_addr().unwrap();
 
     log::debug!("Connected to peer {}", addr);
 
-    match handle_socket_inner(socket, hop).await {
-        Ok(()) => debug!("Dropping {}", addr),
+    let (reader, writer) = socket.into_split();
+    let peer = Peer::Tcp(addr);
+
+    match handle_socket_inner(reader, writer, hop, pool, watch, idle, peer).await {
+        Ok(conn) => debug!(
+            "Dropping {} after {} commands ({} bytes in, {} bytes out)",
+            addr,
+            conn.commands(),
+            conn.bytes_read(),
+            conn.bytes_written()
+        ),
         Err(why) => warn!("Erroring {}: {:?}", addr, why),
     }
 }
 
-async fn handle_socket_inner(socket: TcpStream, hop: Hop) -> Result<(), Box<dyn Error>> {
-    let mut input = Vec::new();
+/// Read commands from the reader until EOF, writing each command's response to
+/// the writer.
+///
+/// This doesn't care about the transport, so the same loop serves TCP and Unix
+/// sockets given their split halves. Streams that can't be split natively,
+/// such as TLS streams, can be split with [`tokio::io::split`].
+///
+/// Commands run against the database selected by the connection, which is
+/// database 0 until it sends a select command. Requests that don't specify a
+/// key type are given the connection's default key type, which it can set via
+/// a default type command and which is forgotten when the connection ends.
+///
+/// If the connection's first line is `TEXT` then it uses the [text protocol]
+/// instead of the binary protocol. If the connection watches the keyspace then
+/// its events are buffered according to the [watch policy].
+///
+/// The connection is closed after answering a quit command, ignoring anything
+/// sent after it. It's also closed after answering a command that can't be
+/// parsed if where the command ends isn't known, since nothing after it can be
+/// parsed either.
+///
+/// If `idle` is given then the connection is closed once it sends nothing for
+/// that long. Connections watching the keyspace or waiting on a blocking pop
+/// are expected to be quiet, so they aren't closed while doing so.
+///
+/// The connection counts as active in the engine's stats until this returns.
+/// On success the connection's traffic is returned. Log lines about the
+/// connection name the peer so that they can be told apart.
+///
+/// [`tokio::io::split`]: https://docs.rs/tokio/0.2/tokio/io/fn.split.html
+/// [text protocol]: text/index.html
+/// [watch policy]: watch/struct.WatchPolicy.html
+async fn handle_socket_inner(
+    reader: impl AsyncRead + Unpin,
+    mut writer: impl AsyncWrite + Unpin,
+    mut hop: Hop,
+    pool: Pool,
+    policy: WatchPolicy,
+    idle: Option<Duration>,
+    peer: Peer,
+) -> Result<Connection, Box<dyn Error>> {
+    let mut conn = hop.connect();
+
+    // Both buffers are drained after each command and are returned to the pool
+    // when the connection ends.
+    let mut input = pool.get();
     let mut ctx = Context::new();
 
-    let (reader, mut writer) = socket.into_split();
     let mut reader = BufReader::new(reader);
-    let mut resp = Vec::new();
+    let mut resp = pool.get();
+    let mut binary = false;
+
+    loop {
+        // Requests aren't delimited, so read whatever is available and let the
+        // context determine where each command ends.
+        let size = match read_or_idle(&mut reader, &mut input, idle).await? {
+            Some(size) => size,
+            None => {
+                debug!("Closing {} idle for {:?}", peer, idle.unwrap_or_default());
+
+                break;
+            }
+        };
 
-    while let Ok(size) = reader.read_until(b'\n', &mut input).await {
         // If we get no bytes then we're EOF.
         if size == 0 {
             break;
         }
 
-        match ctx.feed(&input) {
-            Ok(Some(req)) => match hop.dispatch(&req, &mut resp) {
-                Ok(()) => {}
-                Err(why) => {
-                    let res = Response::DispatchError(why);
+        conn.record_read(size);
+
+        if !binary {
+            match Magic::detect(&input) {
+                Magic::Binary => binary = true,
+                Magic::Partial => continue,
+                Magic::Text(len) => {
+                    input.drain(..len);
+                    text::serve(
+                        reader, writer, hop, idle, peer, &mut input, &mut resp, &mut conn,
+                    )
+                    .await?;
+
+                    return Ok(conn);
+                }
+            }
+        }
+
+        // The buffer may contain any number of commands, the last of which may
+        // be incomplete. Dispatch every complete command and keep the bytes of
+        // any incomplete one for the next read. Responses are appended to one
+        // buffer so that a pipeline of commands is answered with one write.
+        let mut watch = None;
+        let mut quit = false;
 
-                    res.copy_to(&mut resp);
+        while let Some(processed) = process(&hop, &mut ctx, &mut input, &mut resp) {
+            conn.record_command();
+
+            match processed {
+                Processed::Response => {}
+                Processed::Select(selected) => hop = selected,
+                Processed::DefaultType(key_type) => ctx.set_default_key_type(key_type),
+                Processed::BlockingPop { key, timeout } => {
+                    // Answer the commands before the pop while it waits.
+                    if !resp.is_empty() {
+                        writer.write_all(&resp).await?;
+                        conn.record_written(resp.len());
+                        resp.clear();
+                    }
+
+                    let open = pop::blocking_pop(
+                        &hop,
+                        &key,
+                        timeout,
+                        &mut reader,
+                        &mut input,
+                        &mut resp,
+                        &mut conn,
+                    )
+                    .await?;
+
+                    if !open {
+                        return Ok(conn);
+                    }
+                }
+                Processed::Watch(rx) => {
+                    watch = Some(rx);
+
+                    break;
+                }
+                Processed::Quit => {
+                    quit = true;
+
+                    break;
                 }
-            },
-            Ok(None) => continue,
-            Err(why) => {
-                let res = Response::ParseError(why);
+                Processed::Close => {
+                    debug!("Closing {} after a command that can't be skipped", peer);
+                    quit = true;
+
+                    break;
+                }
+            }
+        }
+
+        if !resp.is_empty() {
+            writer.write_all(&resp).await?;
+            conn.record_written(resp.len());
+            resp.clear();
+        }
+
+        // Anything sent after the quit is ignored.
+        if quit {
+            writer.shutdown().await?;
+
+            return Ok(conn);
+        }
+
+        if let Some(rx) = watch {
+            watch::watch_keyspace(reader, writer, rx, policy, &mut resp, &mut conn).await?;
+
+            return Ok(conn);
+        }
+    }
+
+    Ok(conn)
+}
+
+/// Read whatever is available into the input, returning `None` if nothing
+/// arrives within the idle timeout.
+async fn read_or_idle(
+    reader: &mut (impl AsyncRead + Unpin),
+    input: &mut Vec<u8>,
+    idle: Option<Duration>,
+) -> IoResult<Option<usize>> {
+    let idle = match idle {
+        Some(idle) => idle,
+        None => return reader.read_buf(input).await.map(Some),
+    };
+
+    tokio::select! {
+        size = reader.read_buf(input) => size.map(Some),
+        _ = time::delay_for(idle) => Ok(None),
+    }
+}
+
+/// The outcome of processing a command with [`process`].
+///
+/// [`process`]: fn.process.html
+enum Processed {
+    /// A response was written.
+    Response,
+    /// A response was written and the connection should now use the handle
+    /// to the database it selected.
+    Select(Hop),
+    /// A response was written and the connection should now give requests
+    /// that don't specify a key type this key type.
+    DefaultType(Option<KeyType>),
+    /// A blocking pop found its list empty, so no response was written and
+    /// the connection should wait for an item to be pushed.
+    BlockingPop { key: Vec<u8>, timeout: u64 },
+    /// A response was written and the connection should now watch the
+    /// keyspace using the receiver.
+    Watch(KeyspaceReceiver),
+    /// A response was written and the connection should now be closed.
+    Quit,
+    /// A parse error was written and the connection should now be closed,
+    /// since where the invalid command ends isn't known.
+    Close,
+}
+
+/// Parse and dispatch the command at the start of the input, if the input
+/// contains a complete command.
+///
+/// The response is appended to `resp` and the command's bytes are drained
+/// from the input. Returns `None` if no response was written.
+fn process(
+    hop: &Hop,
+    ctx: &mut Context,
+    input: &mut Vec<u8>,
+    resp: &mut Vec<u8>,
+) -> Option<Processed> {
+    if input.is_empty() {
+        return None;
+    }
+
+    let mut processed = Processed::Response;
+
+    match ctx.feed(input) {
+        Ok(Some(req)) => {
+            let start = resp.len();
+
+            match hop.dispatch(&req, resp) {
+                Ok(()) if req.command_id() == CommandId::Select => {
+                    // The dispatch checked that the database exists.
+                    let selected = req
+                        .typed_arg::<i64>(0)
+                        .and_then(|database| hop.select(database as usize));
+
+                    if let Some(selected) = selected {
+                        processed = Processed::Select(selected);
+                    }
+                }
+                Ok(()) if req.command_id() == CommandId::DefaultType => {
+                    // The dispatch checked that the argument is a key type.
+                    let key_type = req
+                        .arg(0)
+                        .and_then(|arg| arg.first())
+                        .and_then(|byte| KeyType::try_from(*byte).ok());
+
+                    processed = Processed::DefaultType(key_type);
+                }
+                Ok(())
+                    if req.command_id() == CommandId::BlockingPop
+                        && pop::is_nil(&resp[start..]) =>
+                {
+                    // The dispatch checked that there's a timeout.
+                    let key = req.key().unwrap_or_default().to_vec();
+                    let timeout = req.typed_arg::<i64>(1).unwrap_or_default() as u64;
+                    resp.truncate(start);
+
+                    processed = Processed::BlockingPop { key, timeout };
+                }
+                Ok(()) if req.command_id() == CommandId::Watch => {
+                    // The dispatch checked that there's a pattern.
+                    let pattern = req.key().unwrap_or_default().to_vec();
+
+                    processed = Processed::Watch(hop.pubsub().watch_keyspace(pattern));
+                }
+                Ok(()) if req.command_id() == CommandId::Quit => processed = Processed::Quit,
+                Ok(()) => {}
+                Err(why) => Response::DispatchError(why).copy_to(resp),
+            }
+        }
+        // Keep the bytes of the incomplete command for the next read.
+        Ok(None) => return None,
+        Err(why) => {
+            Response::ParseError(why).copy_to(resp);
+
+            // If the context skipped the invalid command then the connection
+            // carries on with the commands after it. Otherwise there's no way
+            // of knowing where the invalid command ends.
+            if !why.is_recoverable() {
+                input.clear();
+
+                return Some(Processed::Close);
+            }
+        }
+    }
+
+    input.drain(..ctx.consumed());
+
+    Some(processed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{handle_socket_inner, Peer, Pool, WatchPolicy};
+    use hop_engine::{
+        command::{
+            request::{ParseError, RequestBuilder},
+            response::{Context, Instruction, Response},
+            CommandId, DispatchError,
+        },
+        metrics::Metric,
+        state::{KeyType, Value},
+        Hop,
+    };
+    use std::{
+        collections::VecDeque,
+        io::Result as IoResult,
+        net::SocketAddr,
+        pin::Pin,
+        task::{Context as TaskContext, Poll},
+        time::Duration,
+    };
+    use tokio::{
+        io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+        time,
+    };
+
+    fn peer() -> Peer {
+        Peer::Tcp(SocketAddr::from(([127, 0, 0, 1], 0)))
+    }
+
+    /// Reader returning at most one of its chunks per read, like a socket
+    /// that a command arrives at in separate segments.
+    struct Chunks(VecDeque<Vec<u8>>);
+
+    impl AsyncRead for Chunks {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _: &mut TaskContext<'_>,
+            buf: &mut [u8],
+        ) -> Poll<IoResult<usize>> {
+            let mut chunk = match self.0.pop_front() {
+                Some(chunk) => chunk,
+                None => return Poll::Ready(Ok(0)),
+            };
+
+            let len = chunk.len().min(buf.len());
+            buf[..len].copy_from_slice(&chunk[..len]);
+
+            if len < chunk.len() {
+                self.0.push_front(chunk.split_off(len));
+            }
+
+            Poll::Ready(Ok(len))
+        }
+    }
+
+    async fn read_response(stream: &mut (impl AsyncRead + Unpin)) -> Response {
+        let mut buf = vec![0; 4];
+        stream.read_exact(&mut buf).await.unwrap();
+        let len = u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]) as usize;
+        buf.resize(4 + len, 0);
+        stream.read_exact(&mut buf[4..]).await.unwrap();
+
+        match Context::new().feed(&buf).unwrap() {
+            Instruction::Concluded(response) => response,
+            Instruction::ReadBytes(_) => panic!("incomplete response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_stream() {
+        let hop = Hop::new();
+
+        let mut builder = RequestBuilder::new(CommandId::Increment);
+        builder.bytes(b"foo".as_ref()).unwrap();
+        let increment = builder.into_request();
+
+        let mut input = Vec::new();
+        input.extend_from_slice(increment.as_bytes());
+        input.extend_from_slice(increment.as_bytes());
+        input.push(CommandId::Stats as u8);
+        // an incomplete command at EOF is dropped without a response
+        input.extend_from_slice(&increment.as_bytes()[..4]);
+
+        let mut output = Vec::new();
+        handle_socket_inner(
+            input.as_slice(),
+            &mut output,
+            hop.clone(),
+            Pool::new(),
+            WatchPolicy::default(),
+            None,
+            peer(),
+        )
+        .await
+        .unwrap();
+
+        let mut output = output.as_slice();
+
+        for expected in 1..=2 {
+            assert!(matches!(
+                read_response(&mut output).await,
+                Response::Value(Value::Integer(int)) if int == expected
+            ));
+        }
+
+        assert!(matches!(
+            read_response(&mut output).await,
+            Response::Value(Value::Map(_))
+        ));
+        assert!(output.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_quit() {
+        let hop = Hop::new();
+
+        let mut builder = RequestBuilder::new(CommandId::Increment);
+        builder.bytes(b"foo".as_ref()).unwrap();
+        let increment = builder.into_request();
+
+        let mut input = Vec::new();
+        input.extend_from_slice(
+            RequestBuilder::new(CommandId::Quit)
+                .into_request()
+                .as_bytes(),
+        );
+        // commands after the quit are never dispatched
+        input.extend_from_slice(increment.as_bytes());
+
+        let mut output = Vec::new();
+        let conn = handle_socket_inner(
+            input.as_slice(),
+            &mut output,
+            hop.clone(),
+            Pool::new(),
+            WatchPolicy::default(),
+            None,
+            peer(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(conn.commands(), 1);
+        assert_eq!(conn.bytes_written(), output.len() as u64);
+
+        let mut output = output.as_slice();
+        assert!(matches!(
+            read_response(&mut output).await,
+            Response::Value(Value::Boolean(true))
+        ));
+        assert!(output.is_empty());
+        assert!(!hop.state().contains_key(b"foo"));
+    }
+
+    #[tokio::test]
+    async fn test_connections_active() {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hop = Hop::new();
+
+        let server = tokio::spawn({
+            let hop = hop.clone();
 
-                res.copy_to(&mut resp);
+            async move {
+                let (socket, _) = listener.accept().await.unwrap();
+                let (reader, writer) = socket.into_split();
+                handle_socket_inner(
+                    reader,
+                    writer,
+                    hop,
+                    Pool::new(),
+                    WatchPolicy::default(),
+                    None,
+                    peer(),
+                )
+                .await
+                .unwrap()
             }
+        });
+
+        assert_eq!(0, hop.connections_active());
+
+        let mut echo = RequestBuilder::new(CommandId::Echo);
+        echo.bytes(b"foo".as_ref()).unwrap();
+        let echo = echo.into_request();
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(echo.as_bytes()).await.unwrap();
+        read_response(&mut stream).await;
+
+        // the response means the connection is being handled
+        assert_eq!(1, hop.connections_active());
+
+        drop(stream);
+        let conn = server.await.unwrap();
+
+        assert_eq!(1, conn.commands());
+        assert_eq!(echo.as_bytes().len() as u64, conn.bytes_read());
+
+        // the connection stays active until its handle is dropped
+        assert_eq!(1, hop.connections_active());
+        drop(conn);
+        assert_eq!(0, hop.connections_active());
+
+        let metrics = hop.metrics();
+        assert_eq!(Some(1), metrics.counter(&Metric::ConnectionsOpened));
+        assert_eq!(
+            Some(echo.as_bytes().len() as u64),
+            metrics.counter(&Metric::BytesRead)
+        );
+        assert!(metrics.counter(&Metric::BytesWritten).unwrap() > 0);
+    }
+
+    #[tokio::test]
+    async fn test_select() {
+        let hop = Hop::new();
+
+        let mut select = RequestBuilder::new(CommandId::Select);
+        select.bytes(1i64.to_be_bytes().as_ref()).unwrap();
+        let mut set = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::Integer);
+        set.bytes(b"foo".as_ref()).unwrap();
+        set.bytes(1i64.to_be_bytes().as_ref()).unwrap();
+        let mut out_of_range = RequestBuilder::new(CommandId::Select);
+        out_of_range.bytes(16i64.to_be_bytes().as_ref()).unwrap();
+
+        let mut input = Vec::new();
+        input.extend_from_slice(select.into_request().as_bytes());
+        input.extend_from_slice(set.into_request().as_bytes());
+        input.extend_from_slice(out_of_range.into_request().as_bytes());
+
+        let mut output = Vec::new();
+        handle_socket_inner(
+            input.as_slice(),
+            &mut output,
+            hop.clone(),
+            Pool::new(),
+            WatchPolicy::default(),
+            None,
+            peer(),
+        )
+        .await
+        .unwrap();
+
+        let mut output = output.as_slice();
+        assert!(matches!(
+            read_response(&mut output).await,
+            Response::Value(Value::Boolean(true))
+        ));
+        assert!(matches!(
+            read_response(&mut output).await,
+            Response::Value(Value::Integer(1))
+        ));
+        assert!(matches!(
+            read_response(&mut output).await,
+            Response::DispatchError(DispatchError::ArgumentRetrieval)
+        ));
+
+        // the key was set in database 1, so it's invisible from database 0
+        assert!(hop.select(1).unwrap().state().contains_key(b"foo"));
+        assert!(!hop.state().contains_key(b"foo"));
+    }
+
+    #[tokio::test]
+    async fn test_default_type() {
+        let hop = Hop::new();
+
+        let set = |key: &[u8], key_type: Option<KeyType>| {
+            let mut set = RequestBuilder::new_with_key_type(CommandId::Set, key_type);
+            set.bytes(key).unwrap();
+            set.bytes(b"bar".as_ref()).unwrap();
+
+            set.into_request().as_bytes().to_vec()
         };
 
-        writer.write_all(&resp).await?;
-        resp.clear();
-        input.clear();
+        let mut default_type = RequestBuilder::new(CommandId::DefaultType);
+        default_type
+            .bytes([KeyType::String as u8].as_ref())
+            .unwrap();
+
+        let mut input = Vec::new();
+        input.extend_from_slice(default_type.into_request().as_bytes());
+        input.extend_from_slice(&set(b"foo", None));
+        input.extend_from_slice(&set(b"baz", None));
+        // the default is overridden by a key type sent with the request
+        input.extend_from_slice(&set(b"qux", Some(KeyType::Bytes)));
+
+        handle_socket_inner(
+            input.as_slice(),
+            &mut Vec::new(),
+            hop.clone(),
+            Pool::new(),
+            WatchPolicy::default(),
+            None,
+            peer(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(Some(KeyType::String), hop.state().key_type(b"foo"));
+        assert_eq!(Some(KeyType::String), hop.state().key_type(b"baz"));
+        assert_eq!(Some(KeyType::Bytes), hop.state().key_type(b"qux"));
+
+        // the default isn't kept once the connection ends
+        handle_socket_inner(
+            set(b"quux", None).as_slice(),
+            &mut Vec::new(),
+            hop.clone(),
+            Pool::new(),
+            WatchPolicy::default(),
+            None,
+            peer(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(Some(KeyType::Bytes), hop.state().key_type(b"quux"));
     }
 
-    Ok(())
+    #[tokio::test]
+    async fn test_idempotency_token_replayed() {
+        let hop = Hop::new();
+
+        let mut builder = RequestBuilder::new(CommandId::Increment);
+        builder.token(b"retry".as_ref()).unwrap();
+        builder.bytes(b"foo".as_ref()).unwrap();
+        let increment = builder.into_request();
+
+        // as if the connection dropped before the first response was read and
+        // the client retried the same request
+        let mut input = Vec::new();
+        input.extend_from_slice(increment.as_bytes());
+        input.extend_from_slice(increment.as_bytes());
+
+        let mut output = Vec::new();
+        handle_socket_inner(
+            input.as_slice(),
+            &mut output,
+            hop.clone(),
+            Pool::new(),
+            WatchPolicy::default(),
+            None,
+            peer(),
+        )
+        .await
+        .unwrap();
+
+        let mut output = output.as_slice();
+
+        for _ in 0..2 {
+            assert!(matches!(
+                read_response(&mut output).await,
+                Response::Value(Value::Integer(1))
+            ));
+        }
+
+        assert!(output.is_empty());
+        assert!(matches!(
+            hop.state().key_ref(b"foo").as_deref(),
+            Some(Value::Integer(1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_text_protocol() {
+        let hop = Hop::new();
+        let input = b"TEXT\r\nSET:str foo bar\r\nget foo\r\n\r\nincrement:int count\nget missing\n";
+
+        let mut output = Vec::new();
+        let conn = handle_socket_inner(
+            input.as_ref(),
+            &mut output,
+            hop.clone(),
+            Pool::new(),
+            WatchPolicy::default(),
+            None,
+            peer(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!("bar\nbar\n1\n(nil)\n", String::from_utf8(output).unwrap());
+        assert_eq!(4, conn.commands());
+        assert!(matches!(
+            hop.state().key_ref(b"foo").as_deref(),
+            Some(Value::String(string)) if string == "bar"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_command_id() {
+        // 99 isn't a command ID, as if a newer client sent a command without
+        // arguments that this build doesn't know about
+        let mut output = Vec::new();
+        handle_socket_inner(
+            [99u8, 0].as_ref(),
+            &mut output,
+            Hop::new(),
+            Pool::new(),
+            WatchPolicy::default(),
+            None,
+            peer(),
+        )
+        .await
+        .unwrap();
+
+        let mut output = output.as_slice();
+        assert!(matches!(
+            read_response(&mut output).await,
+            Response::ParseError(ParseError::CommandIdInvalid)
+        ));
+        assert!(output.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_watch_keyspace() {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hop = Hop::new();
+
+        let server = tokio::spawn({
+            let hop = hop.clone();
+
+            async move {
+                let (socket, _) = listener.accept().await.unwrap();
+                let (reader, writer) = socket.into_split();
+                handle_socket_inner(
+                    reader,
+                    writer,
+                    hop,
+                    Pool::new(),
+                    WatchPolicy::default(),
+                    None,
+                    peer(),
+                )
+                .await
+                .unwrap();
+            }
+        });
+
+        let mut watch = RequestBuilder::new(CommandId::Watch);
+        watch.bytes(b"foo*".as_ref()).unwrap();
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(watch.into_request().as_bytes())
+            .await
+            .unwrap();
+        assert!(matches!(
+            read_response(&mut stream).await,
+            Response::Value(Value::Boolean(true))
+        ));
+
+        let mut resp = Vec::new();
+
+        for key in [b"bar".as_ref(), b"foobar".as_ref()].iter() {
+            let mut set = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::Integer);
+            set.bytes(*key).unwrap();
+            set.bytes(1i64.to_be_bytes().as_ref()).unwrap();
+            hop.dispatch(&set.into_request(), &mut resp).unwrap();
+        }
+
+        assert!(matches!(
+            read_response(&mut stream).await,
+            Response::Value(Value::List(list))
+                if list == [[CommandId::Set as u8].to_vec(), b"foobar".to_vec()]
+        ));
+
+        // closing the connection ends the watch
+        drop(stream);
+        server.await.unwrap();
+    }
+
+    fn blocking_pop(key: &[u8], timeout: i64) -> Vec<u8> {
+        let mut pop = RequestBuilder::new(CommandId::BlockingPop);
+        pop.bytes(key).unwrap();
+        pop.bytes(timeout.to_be_bytes().as_ref()).unwrap();
+
+        pop.into_request().as_bytes().to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_blocking_pop_woken_by_push() {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hop = Hop::new();
+
+        let server = tokio::spawn(async move {
+            for _ in 0..2 {
+                let (socket, _) = listener.accept().await.unwrap();
+                let (reader, writer) = socket.into_split();
+                let hop = hop.clone();
+
+                tokio::spawn(async move {
+                    handle_socket_inner(
+                        reader,
+                        writer,
+                        hop,
+                        Pool::new(),
+                        WatchPolicy::default(),
+                        None,
+                        peer(),
+                    )
+                    .await
+                    .unwrap();
+                });
+            }
+        });
+
+        // the echo after the pop is answered once the pop is
+        let mut echo = RequestBuilder::new(CommandId::Echo);
+        echo.bytes(b"after".as_ref()).unwrap();
+        let mut input = blocking_pop(b"queue", 0);
+        input.extend_from_slice(echo.into_request().as_bytes());
+
+        let mut popper = TcpStream::connect(addr).await.unwrap();
+        popper.write_all(&input).await.unwrap();
+
+        // give the pop time to start waiting
+        time::delay_for(Duration::from_millis(50)).await;
+
+        let mut append = RequestBuilder::new_with_key_type(CommandId::Append, KeyType::List);
+        append.bytes(b"queue".as_ref()).unwrap();
+        append.bytes(b"job".as_ref()).unwrap();
+
+        let mut pusher = TcpStream::connect(addr).await.unwrap();
+        pusher
+            .write_all(append.into_request().as_bytes())
+            .await
+            .unwrap();
+        assert!(matches!(
+            read_response(&mut pusher).await,
+            Response::Value(Value::List(list)) if list == [b"job".to_vec()]
+        ));
+
+        assert!(matches!(
+            read_response(&mut popper).await,
+            Response::Value(Value::Bytes(bytes)) if bytes == b"job"
+        ));
+        assert!(matches!(
+            read_response(&mut popper).await,
+            Response::Value(Value::List(list)) if list == [b"after".to_vec()]
+        ));
+
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_blocking_pop_timeout() {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let (reader, writer) = socket.into_split();
+            handle_socket_inner(
+                reader,
+                writer,
+                Hop::new(),
+                Pool::new(),
+                WatchPolicy::default(),
+                None,
+                peer(),
+            )
+            .await
+            .unwrap();
+        });
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(&blocking_pop(b"queue", 20)).await.unwrap();
+        assert!(read_response(&mut stream).await.is_nil());
+
+        drop(stream);
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_idle_timeout() {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let (reader, writer) = socket.into_split();
+            handle_socket_inner(
+                reader,
+                writer,
+                Hop::new(),
+                Pool::new(),
+                WatchPolicy::default(),
+                Some(Duration::from_millis(20)),
+                peer(),
+            )
+            .await
+            .unwrap()
+        });
+
+        // connect and go silent
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let mut buf = [0; 1];
+        let read = time::timeout(Duration::from_secs(5), stream.read(&mut buf)).await;
+        assert!(matches!(read, Ok(Ok(0))));
+
+        let conn = server.await.unwrap();
+        assert_eq!(0, conn.commands());
+        assert_eq!(0, conn.bytes_read());
+    }
+
+    #[tokio::test]
+    async fn test_argument_containing_newlines() {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hop = Hop::new();
+
+        let server = tokio::spawn({
+            let hop = hop.clone();
+
+            async move {
+                let (socket, _) = listener.accept().await.unwrap();
+                let (reader, writer) = socket.into_split();
+                handle_socket_inner(
+                    reader,
+                    writer,
+                    hop,
+                    Pool::new(),
+                    WatchPolicy::default(),
+                    None,
+                    peer(),
+                )
+                .await
+                .unwrap();
+            }
+        });
+
+        let mut set = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::Bytes);
+        set.bytes(b"foo".as_ref()).unwrap();
+        set.bytes(b"bar\nbaz\n".as_ref()).unwrap();
+        let set = set.into_request();
+        let mut get = RequestBuilder::new(CommandId::Get);
+        get.bytes(b"foo".as_ref()).unwrap();
+        let get = get.into_request();
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+
+        // split the set request right after a newline in its value, and send
+        // the rest of it in the same write as the get request
+        let set_bytes = set.as_bytes();
+        let split = set_bytes.iter().position(|byte| *byte == b'\n').unwrap() + 1;
+        stream.write_all(&set_bytes[..split]).await.unwrap();
+        stream.flush().await.unwrap();
+
+        let mut rest = set_bytes[split..].to_vec();
+        rest.extend_from_slice(get.as_bytes());
+        stream.write_all(&rest).await.unwrap();
+
+        for _ in 0..2 {
+            assert!(matches!(
+                read_response(&mut stream).await,
+                Response::Value(Value::Bytes(bytes)) if bytes == b"bar\nbaz\n"
+            ));
+        }
+
+        drop(stream);
+        server.await.unwrap();
+        assert!(hop.state().contains_key(b"foo"));
+    }
+
+    #[tokio::test]
+    async fn test_command_split_across_reads() {
+        let hop = Hop::new();
+
+        let mut set = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::Bytes);
+        set.bytes(b"foo".as_ref()).unwrap();
+        set.bytes(vec![7; 3000]).unwrap();
+        let set = set.into_request();
+        let mut get = RequestBuilder::new(CommandId::Get);
+        get.bytes(b"foo".as_ref()).unwrap();
+        let get = get.into_request();
+
+        // the set request is split at the size of a TCP segment, with the
+        // rest of it arriving along with the get request
+        let (first, rest) = set.as_bytes().split_at(1460);
+        let mut second = rest.to_vec();
+        second.extend_from_slice(get.as_bytes());
+        let chunks = Chunks(vec![first.to_vec(), second].into());
+
+        let mut output = Vec::new();
+        handle_socket_inner(
+            chunks,
+            &mut output,
+            hop.clone(),
+            Pool::new(),
+            WatchPolicy::default(),
+            None,
+            peer(),
+        )
+        .await
+        .unwrap();
+
+        let mut output = output.as_slice();
+
+        for _ in 0..2 {
+            assert!(matches!(
+                read_response(&mut output).await,
+                Response::Value(Value::Bytes(bytes)) if bytes == vec![7; 3000]
+            ));
+        }
+
+        assert!(output.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_command_in_three_fragments() {
+        let hop = Hop::new();
+
+        let mut builder = RequestBuilder::new(CommandId::IncrementBy);
+        builder.bytes(b"foo".as_ref()).unwrap();
+        builder.bytes(5i64.to_be_bytes().as_ref()).unwrap();
+        let increment = builder.into_request();
+        let bytes = increment.as_bytes();
+
+        // split within the header, within an argument's length, and within
+        // an argument, with the last fragment also starting the next command
+        let mut third = bytes[10..].to_vec();
+        third.extend_from_slice(&bytes[..3]);
+        let fourth = bytes[3..].to_vec();
+        let chunks = Chunks(vec![bytes[..1].to_vec(), bytes[1..10].to_vec(), third, fourth].into());
+
+        let mut output = Vec::new();
+        handle_socket_inner(
+            chunks,
+            &mut output,
+            hop.clone(),
+            Pool::new(),
+            WatchPolicy::default(),
+            None,
+            peer(),
+        )
+        .await
+        .unwrap();
+
+        let mut output = output.as_slice();
+
+        for expected in &[5, 10] {
+            assert!(matches!(
+                read_response(&mut output).await,
+                Response::Value(Value::Integer(int)) if int == *expected
+            ));
+        }
+
+        assert!(output.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_parse_error_recovers() {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let (reader, writer) = socket.into_split();
+            handle_socket_inner(
+                reader,
+                writer,
+                Hop::new(),
+                Pool::new(),
+                WatchPolicy::default(),
+                None,
+                peer(),
+            )
+            .await
+            .unwrap();
+        });
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+
+        stream.write_all(&[99, 0]).await.unwrap();
+        assert!(matches!(
+            read_response(&mut stream).await,
+            Response::ParseError(ParseError::CommandIdInvalid)
+        ));
+
+        // the connection is still usable after the bad command
+        let mut increment = RequestBuilder::new(CommandId::Increment);
+        increment.bytes(b"foo".as_ref()).unwrap();
+        stream
+            .write_all(increment.into_request().as_bytes())
+            .await
+            .unwrap();
+        assert!(matches!(
+            read_response(&mut stream).await,
+            Response::Value(Value::Integer(1))
+        ));
+
+        drop(stream);
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_parse_error_pipelined() {
+        let mut increment = RequestBuilder::new(CommandId::Increment);
+        increment.bytes(b"foo".as_ref()).unwrap();
+        let increment = increment.into_request();
+
+        // an unknown command with an argument is skipped, and the command
+        // after it in the same read is answered
+        let mut input = vec![99, 1, 0, 0, 0, 3, b'f', b'o', b'o'];
+        input.extend_from_slice(increment.as_bytes());
+
+        let mut output = Vec::new();
+        handle_socket_inner(
+            input.as_slice(),
+            &mut output,
+            Hop::new(),
+            Pool::new(),
+            WatchPolicy::default(),
+            None,
+            peer(),
+        )
+        .await
+        .unwrap();
+
+        let mut output = output.as_slice();
+        assert!(matches!(
+            read_response(&mut output).await,
+            Response::ParseError(ParseError::CommandIdInvalid)
+        ));
+        assert!(matches!(
+            read_response(&mut output).await,
+            Response::Value(Value::Integer(1))
+        ));
+        assert!(output.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_parse_error_closes() {
+        let hop = Hop::new();
+        let mut increment = RequestBuilder::new(CommandId::Increment);
+        increment.bytes(b"foo".as_ref()).unwrap();
+        let increment = increment.into_request();
+
+        // where a command with an invalid key type ends isn't known, so
+        // nothing after it is run
+        let mut input = vec![0b1000_0000 | CommandId::Increment as u8, 255, 1, 0, 0, 0, 1];
+        input.extend_from_slice(increment.as_bytes());
+
+        let mut output = Vec::new();
+        handle_socket_inner(
+            input.as_slice(),
+            &mut output,
+            hop.clone(),
+            Pool::new(),
+            WatchPolicy::default(),
+            None,
+            peer(),
+        )
+        .await
+        .unwrap();
+
+        let mut output = output.as_slice();
+        assert!(matches!(
+            read_response(&mut output).await,
+            Response::ParseError(ParseError::KeyTypeInvalid)
+        ));
+        assert!(output.is_empty());
+        assert!(!hop.state().contains_key(b"foo"));
+    }
 }
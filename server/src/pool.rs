@@ -0,0 +1,157 @@
+use std::{
+    mem,
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex},
+};
+
+/// A pool of reusable byte buffers shared between connections.
+///
+/// Buffers are handed out as [`Pooled`] guards which clear the buffer and
+/// return it to the pool when dropped. Buffers that have grown beyond
+/// [`Pool::BUFFER_CAPACITY_MAX`] are dropped instead of being returned so that
+/// a single large response doesn't keep its memory around forever.
+///
+/// [`Pool::BUFFER_CAPACITY_MAX`]: #associatedconstant.BUFFER_CAPACITY_MAX
+/// [`Pooled`]: struct.Pooled.html
+#[derive(Clone, Debug, Default)]
+pub struct Pool(Arc<Mutex<Vec<Vec<u8>>>>);
+
+impl Pool {
+    /// Maximum capacity of a buffer for it to be returned to the pool.
+    pub const BUFFER_CAPACITY_MAX: usize = 64 * 1024;
+
+    /// Maximum number of idle buffers kept in the pool.
+    pub const BUFFERS_MAX: usize = 1024;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take a buffer from the pool, allocating a new one if none are idle.
+    pub fn get(&self) -> Pooled {
+        let buf = self.0.lock().unwrap().pop().unwrap_or_default();
+
+        Pooled {
+            buf,
+            pool: self.clone(),
+        }
+    }
+
+    /// Return the number of idle buffers in the pool.
+    #[cfg(test)]
+    pub fn idle(&self) -> usize {
+        self.0.lock().unwrap().len()
+    }
+
+    fn put(&self, mut buf: Vec<u8>) {
+        if buf.capacity() > Self::BUFFER_CAPACITY_MAX {
+            return;
+        }
+
+        buf.clear();
+
+        let mut buffers = self.0.lock().unwrap();
+
+        if buffers.len() < Self::BUFFERS_MAX {
+            buffers.push(buf);
+        }
+    }
+}
+
+/// A buffer taken from a [`Pool`], returned to it on drop.
+///
+/// [`Pool`]: struct.Pool.html
+#[derive(Debug)]
+pub struct Pooled {
+    buf: Vec<u8>,
+    pool: Pool,
+}
+
+impl Deref for Pooled {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.buf
+    }
+}
+
+impl DerefMut for Pooled {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.buf
+    }
+}
+
+impl Drop for Pooled {
+    fn drop(&mut self) {
+        self.pool.put(mem::take(&mut self.buf));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Pool, Pooled};
+    use hop_engine::{
+        command::{request::RequestBuilder, CommandId},
+        Hop,
+    };
+    use static_assertions::assert_impl_all;
+    use std::fmt::Debug;
+
+    assert_impl_all!(Pool: Clone, Debug, Default, Send, Sync);
+    assert_impl_all!(Pooled: Debug, Send);
+
+    #[test]
+    fn test_reuse() {
+        let pool = Pool::new();
+
+        let ptr = {
+            let mut buf = pool.get();
+            buf.extend_from_slice(b"foo");
+
+            buf.as_ptr()
+        };
+
+        assert_eq!(1, pool.idle());
+
+        let buf = pool.get();
+        assert!(buf.is_empty());
+        assert_eq!(ptr, buf.as_ptr());
+        assert_eq!(0, pool.idle());
+    }
+
+    #[test]
+    fn test_large_buffers_dropped() {
+        let pool = Pool::new();
+
+        {
+            let mut buf = pool.get();
+            buf.resize(Pool::BUFFER_CAPACITY_MAX + 1, 0);
+        }
+
+        assert_eq!(0, pool.idle());
+    }
+
+    #[test]
+    fn test_repeated_dispatch_doesnt_grow() {
+        let hop = Hop::new();
+        let pool = Pool::new();
+
+        let mut builder = RequestBuilder::new(CommandId::Increment);
+        builder.bytes(b"foo".as_ref()).unwrap();
+        let req = builder.into_request();
+
+        let mut capacity = None;
+
+        for _ in 0..1000 {
+            let mut resp = pool.get();
+            hop.dispatch(&req, &mut resp).unwrap();
+
+            match capacity {
+                Some(capacity) => assert_eq!(capacity, resp.capacity()),
+                None => capacity = Some(resp.capacity()),
+            }
+        }
+
+        assert_eq!(1, pool.idle());
+    }
+}
@@ -0,0 +1,113 @@
+//! Parking connections that pop from an empty list until an item is pushed.
+
+use hop_engine::{
+    command::{
+        request::{Request, RequestBuilder},
+        response, CommandId, Response,
+    },
+    connection::Connection,
+    Hop,
+};
+use std::{error::Error, time::Duration};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt},
+    time::{self, Instant},
+};
+
+/// Whether a response is nil.
+pub fn is_nil(resp: &[u8]) -> bool {
+    matches!(Response::parse(resp), Ok(Response::Nil))
+}
+
+/// Create the request for popping from a list, which is dispatched again each
+/// time an item may have been pushed.
+pub fn request(key: &[u8], timeout: u64) -> Request<'static> {
+    // The key came from a valid request, so it fits in one.
+    let mut builder = RequestBuilder::new(CommandId::BlockingPop);
+    builder.bytes(key).expect("key fits in a request");
+    builder
+        .bytes((timeout as i64).to_be_bytes().as_ref())
+        .expect("timeout fits in a request");
+
+    builder.into_request()
+}
+
+/// Retrieve when a blocking pop with the timeout in milliseconds gives up, or
+/// `None` if it waits indefinitely.
+pub fn deadline(timeout: u64) -> Option<Instant> {
+    if timeout == 0 {
+        None
+    } else {
+        Some(Instant::now() + Duration::from_millis(timeout))
+    }
+}
+
+/// Dispatch the pop request, returning whether a response was written. No
+/// response is written if the list is empty.
+pub fn try_pop(hop: &Hop, req: &Request<'_>, resp: &mut Vec<u8>) -> bool {
+    let start = resp.len();
+
+    if let Err(why) = hop.dispatch(req, resp) {
+        Response::DispatchError(why).copy_to(resp);
+
+        return true;
+    }
+
+    if is_nil(&resp[start..]) {
+        resp.truncate(start);
+
+        return false;
+    }
+
+    true
+}
+
+/// Wait for an item to be pushed to a list and pop it, writing nil to the
+/// response if the timeout in milliseconds passes first. A timeout of 0 waits
+/// indefinitely.
+///
+/// The connection is parked without polling the list: the pop is retried only
+/// when a command that can push to the list modifies it. Anything the peer
+/// sends meanwhile is appended to the input for processing afterwards.
+///
+/// Returns `false` if the peer closed the connection while waiting.
+pub async fn blocking_pop(
+    hop: &Hop,
+    key: &[u8],
+    timeout: u64,
+    mut reader: impl AsyncRead + Unpin,
+    input: &mut Vec<u8>,
+    resp: &mut Vec<u8>,
+    conn: &mut Connection,
+) -> Result<bool, Box<dyn Error>> {
+    let req = request(key, timeout);
+    let deadline = deadline(timeout);
+
+    loop {
+        // Register the wait before popping so that a push between the two
+        // isn't missed.
+        let waiter = hop.state().wait_for_push(key);
+
+        if try_pop(hop, &req, resp) {
+            return Ok(true);
+        }
+
+        tokio::select! {
+            _ = waiter.pushed() => {}
+            _ = time::delay_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                response::write_nil(resp);
+
+                return Ok(true);
+            }
+            size = reader.read_buf(input) => {
+                let size = size?;
+
+                if size == 0 {
+                    return Ok(false);
+                }
+
+                conn.record_read(size);
+            }
+        }
+    }
+}
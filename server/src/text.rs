@@ -0,0 +1,235 @@
+//! Human readable text protocol for debugging with tools like `nc` and
+//! `telnet`.
+//!
+//! A connection switches to the text protocol by sending the line `TEXT` as
+//! the very first thing it sends. Each following line is a command in the
+//! same syntax as the CLI, such as `set:str foo bar` or `get foo`, and each
+//! response is a line of plain text. Errors are prefixed with `ERR`.
+
+use super::Peer;
+use hop_engine::{
+    command::{
+        request::{Request, RequestBuilder, RequestBuilderError},
+        CommandId, Response,
+    },
+    connection::Connection,
+    Hop,
+};
+use log::debug;
+use std::{error::Error, time::Duration};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+
+/// Line a connection sends first to use the text protocol, with either line
+/// ending.
+const MAGIC_LF: &[u8] = b"TEXT\n";
+const MAGIC_CRLF: &[u8] = b"TEXT\r\n";
+
+/// Whether the start of a connection's input selects the text protocol.
+#[derive(Debug, Eq, PartialEq)]
+pub enum Magic {
+    /// The input is binary.
+    Binary,
+    /// The input might still become the magic line once more is read.
+    Partial,
+    /// The input starts with the magic line, which is the given number of
+    /// bytes long including its line ending.
+    Text(usize),
+}
+
+impl Magic {
+    pub fn detect(input: &[u8]) -> Self {
+        for line in [MAGIC_LF, MAGIC_CRLF].iter() {
+            if input.starts_with(line) {
+                return Self::Text(line.len());
+            }
+
+            if line.starts_with(input) {
+                return Self::Partial;
+            }
+        }
+
+        Self::Binary
+    }
+}
+
+/// Read lines of commands until EOF, writing each command's response as a
+/// line of text.
+///
+/// The input may already contain commands read along with the magic line.
+#[allow(clippy::too_many_arguments)]
+pub async fn serve(
+    mut reader: impl AsyncRead + Unpin,
+    mut writer: impl AsyncWrite + Unpin,
+    mut hop: Hop,
+    idle: Option<Duration>,
+    peer: Peer,
+    input: &mut Vec<u8>,
+    resp: &mut Vec<u8>,
+    conn: &mut Connection,
+) -> Result<(), Box<dyn Error>> {
+    loop {
+        while let Some(end) = input.iter().position(|byte| *byte == b'\n') {
+            let line = input.drain(..=end).collect::<Vec<_>>();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(selected) = execute(&hop, line, resp) {
+                hop = selected;
+            }
+
+            resp.push(b'\n');
+            writer.write_all(resp).await?;
+            conn.record_command();
+            conn.record_written(resp.len());
+            resp.clear();
+
+            if line.eq_ignore_ascii_case("quit") {
+                writer.shutdown().await?;
+
+                return Ok(());
+            }
+        }
+
+        let size = match super::read_or_idle(&mut reader, input, idle).await? {
+            Some(size) => size,
+            None => {
+                debug!(
+                    "Closing text connection {} idle for {:?}",
+                    peer,
+                    idle.unwrap_or_default()
+                );
+
+                return Ok(());
+            }
+        };
+
+        if size == 0 {
+            return Ok(());
+        }
+
+        conn.record_read(size);
+    }
+}
+
+/// Parse and dispatch a line, writing the response as text.
+///
+/// Returns the handle to switch to if the command selected a database.
+fn execute(hop: &Hop, line: &str, out: &mut Vec<u8>) -> Option<Hop> {
+    // Command names are matched case insensitively so that `SET:str` works.
+    let (name, rest) = line.split_at(line.find(' ').unwrap_or(line.len()));
+    let line = name.to_ascii_lowercase() + rest;
+
+    let req = match hop_cli::parse(&line) {
+        Ok(req) if name.contains(':') => req,
+        // The CLI defaults to bytes keys, but the text protocol leaves the
+        // key type unspecified unless it's given.
+        Ok(req) => match untyped(&req) {
+            Ok(req) => req,
+            Err(why) => {
+                write_error(out, why);
+
+                return None;
+            }
+        },
+        Err(why) => {
+            write_error(out, why);
+
+            return None;
+        }
+    };
+
+    if req.command_id() == CommandId::Watch {
+        write_error(out, "watching the keyspace isn't supported in text mode");
+
+        return None;
+    }
+
+    let mut resp = Vec::new();
+
+    if let Err(why) = hop.dispatch(&req, &mut resp) {
+        write_error(out, why);
+
+        return None;
+    }
+
+    match Response::parse(&resp) {
+        Ok(resp) => write_response(out, resp),
+        Err(why) => write_error(out, why),
+    }
+
+    if req.command_id() == CommandId::Select {
+        // The dispatch checked that the database exists.
+        return req
+            .typed_arg::<i64>(0)
+            .and_then(|database| hop.select(database as usize));
+    }
+
+    None
+}
+
+/// Rebuild a request without its key type.
+fn untyped(req: &Request<'_>) -> Result<Request<'static>, RequestBuilderError> {
+    let mut builder = RequestBuilder::new(req.command_id());
+
+    if let Some(args) = req.args(..) {
+        builder.args(args.map(<[u8]>::to_vec))?;
+    }
+
+    Ok(builder.into_request())
+}
+
+/// Write a response, with each element of an array on its own line.
+fn write_response(out: &mut Vec<u8>, resp: Response) {
+    match resp {
+        Response::Array(items) => {
+            for (idx, item) in items.into_iter().enumerate() {
+                if idx > 0 {
+                    out.push(b'\n');
+                }
+
+                write_response(out, item);
+            }
+        }
+        Response::Value(value) => out.extend_from_slice(hop_cli::print_value(value).as_bytes()),
+        Response::Nil => out.extend_from_slice(b"(nil)"),
+        Response::DispatchError(why) => write_error(out, why),
+        Response::ParseError(why) => write_error(out, format!("{:?}", why)),
+    }
+}
+
+fn write_error(out: &mut Vec<u8>, why: impl ToString) {
+    out.extend_from_slice(b"ERR ");
+    out.extend_from_slice(why.to_string().as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Magic;
+    use hop_engine::command::Response;
+
+    #[test]
+    fn test_magic() {
+        assert_eq!(Magic::Text(5), Magic::detect(b"TEXT\nget foo\n"));
+        assert_eq!(Magic::Text(6), Magic::detect(b"TEXT\r\n"));
+        assert_eq!(Magic::Partial, Magic::detect(b"TE"));
+        assert_eq!(Magic::Partial, Magic::detect(b"TEXT\r"));
+        assert_eq!(Magic::Binary, Magic::detect(b"TEXTS\n"));
+        assert_eq!(Magic::Binary, Magic::detect(b"TEXTS"));
+        assert_eq!(Magic::Binary, Magic::detect(&[0, 1]));
+    }
+
+    #[test]
+    fn test_write_array() {
+        let mut out = Vec::new();
+        super::write_response(
+            &mut out,
+            Response::from(vec![Response::from(1), Response::nil()]),
+        );
+
+        assert_eq!(b"1\n(nil)".as_ref(), out.as_slice());
+    }
+}
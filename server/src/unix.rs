@@ -0,0 +1,139 @@
+use super::{handle_socket_inner, Peer, Pool, WatchPolicy};
+use hop_engine::Hop;
+use log::{debug, warn};
+use std::{fs, io::Result as IoResult, path::PathBuf, time::Duration};
+use tokio::{
+    net::{UnixListener, UnixStream},
+    task,
+};
+
+/// Guard over a bound Unix domain socket, removing the socket file when
+/// dropped.
+#[derive(Debug)]
+pub struct UnixSocket {
+    path: PathBuf,
+}
+
+impl Drop for UnixSocket {
+    fn drop(&mut self) {
+        debug!("Removing unix socket {}", self.path.display());
+
+        if let Err(why) = fs::remove_file(&self.path) {
+            warn!(
+                "Failed to remove unix socket {}: {:?}",
+                self.path.display(),
+                why
+            );
+        }
+    }
+}
+
+/// Bind a Unix domain socket at the path and start accepting connections on
+/// it, dispatching to the shared Hop instance.
+pub fn bind(
+    path: PathBuf,
+    hop: Hop,
+    pool: Pool,
+    watch: WatchPolicy,
+    idle: Option<Duration>,
+) -> IoResult<UnixSocket> {
+    let listener = UnixListener::bind(&path)?;
+
+    debug!("Listening for new connections on {}", path.display());
+
+    task::spawn(serve(listener, hop, pool, watch, idle));
+
+    Ok(UnixSocket { path })
+}
+
+async fn serve(
+    mut listener: UnixListener,
+    hop: Hop,
+    pool: Pool,
+    watch: WatchPolicy,
+    idle: Option<Duration>,
+) {
+    loop {
+        match listener.accept().await {
+            Ok((socket, _)) => {
+                task::spawn(handle_socket(
+                    socket,
+                    hop.clone(),
+                    pool.clone(),
+                    watch,
+                    idle,
+                ));
+            }
+            Err(why) => warn!("Failed to accept unix connection: {:?}", why),
+        }
+    }
+}
+
+async fn handle_socket(
+    mut socket: UnixStream,
+    hop: Hop,
+    pool: Pool,
+    watch: WatchPolicy,
+    idle: Option<Duration>,
+) {
+    let peer = Peer::Unix;
+
+    debug!("Connected to {}", peer);
+
+    let (reader, writer) = socket.split();
+
+    match handle_socket_inner(reader, writer, hop, pool, watch, idle, peer).await {
+        Ok(_) => debug!("Dropping {}", peer),
+        Err(why) => warn!("Erroring {}: {:?}", peer, why),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bind, Pool, WatchPolicy};
+    use hop_engine::{
+        command::{
+            request::RequestBuilder,
+            response::{Context, Instruction, Response},
+            CommandId,
+        },
+        state::Value,
+        Hop,
+    };
+    use std::{env, process};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::UnixStream,
+    };
+
+    #[tokio::test]
+    async fn test_connect() {
+        let path = env::temp_dir().join(format!("hop-test-{}.sock", process::id()));
+        let socket = bind(
+            path.clone(),
+            Hop::new(),
+            Pool::new(),
+            WatchPolicy::default(),
+            None,
+        )
+        .unwrap();
+
+        let mut stream = UnixStream::connect(&path).await.unwrap();
+
+        let mut builder = RequestBuilder::new(CommandId::Increment);
+        builder.bytes(b"foo".as_ref()).unwrap();
+        let req = builder.into_request();
+        stream.write_all(req.as_bytes()).await.unwrap();
+
+        let mut buf = [0; 13];
+        stream.read_exact(&mut buf).await.unwrap();
+
+        assert!(matches!(
+            Context::new().feed(&buf).unwrap(),
+            Instruction::Concluded(Response::Value(Value::Integer(1)))
+        ));
+
+        drop(socket);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,310 @@
+//! Pushing keyspace events to connections watching the keyspace.
+//!
+//! Events are buffered for each watcher while earlier ones are being written,
+//! so a watcher that reads slowly doesn't hold up receiving events. The
+//! buffer is bounded by a [`WatchPolicy`], which also decides what happens to
+//! a watcher that falls far enough behind to fill it.
+//!
+//! [`WatchPolicy`]: struct.WatchPolicy.html
+
+use hop_engine::{command::response, connection::Connection, pubsub::KeyspaceReceiver};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    io::{Error as IoError, ErrorKind},
+    mem,
+    str::FromStr,
+};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// What to do with a watcher whose buffer of unwritten events is full.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SlowWatchers {
+    /// Drop events until the watcher catches up.
+    Drop,
+    /// Disconnect the watcher.
+    Disconnect,
+}
+
+impl FromStr for SlowWatchers {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "drop" => Ok(Self::Drop),
+            "disconnect" => Ok(Self::Disconnect),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Limit on the events buffered for each watcher.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct WatchPolicy {
+    /// Number of bytes of unwritten events a watcher can have before it's
+    /// considered too slow.
+    pub buffer_max: usize,
+    /// What to do with a watcher that's too slow.
+    pub slow: SlowWatchers,
+}
+
+impl WatchPolicy {
+    pub const BUFFER_MAX_DEFAULT: usize = 1024 * 1024;
+}
+
+impl Default for WatchPolicy {
+    fn default() -> Self {
+        Self {
+            buffer_max: Self::BUFFER_MAX_DEFAULT,
+            slow: SlowWatchers::Drop,
+        }
+    }
+}
+
+/// Error for a watcher disconnected for not keeping up with its events.
+#[derive(Debug)]
+pub struct WatcherTooSlow;
+
+impl Display for WatcherTooSlow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("the watcher's buffer of unwritten events is full")
+    }
+}
+
+impl Error for WatcherTooSlow {}
+
+/// Push keyspace events to the writer until the watch ends or the reader
+/// reaches EOF.
+///
+/// Each event is written as a list response of two items: the ID of the
+/// command that modified the key as a single byte, and the key. Anything else
+/// sent by the peer is ignored, since the connection is now only used for
+/// events.
+///
+/// Events received while the writer is busy are appended to `pending`, up to
+/// the policy's maximum. If the watch ends, any events already received are
+/// written before returning.
+pub async fn watch_keyspace(
+    mut reader: impl AsyncRead + Unpin,
+    mut writer: impl AsyncWrite + Unpin,
+    rx: KeyspaceReceiver,
+    policy: WatchPolicy,
+    pending: &mut Vec<u8>,
+    conn: &mut Connection,
+) -> Result<(), Box<dyn Error>> {
+    let mut discard = [0; 64];
+    // Events being written, which are swapped with the pending events once
+    // they've all been written.
+    let mut flushing = Vec::new();
+    let mut flushed = 0;
+
+    loop {
+        if flushed == flushing.len() && !pending.is_empty() {
+            flushing.clear();
+            mem::swap(&mut flushing, pending);
+            flushed = 0;
+        }
+
+        tokio::select! {
+            size = reader.read(&mut discard) => {
+                let size = size?;
+
+                // The peer is gone, so there's nobody to write the rest to.
+                if size == 0 {
+                    return Ok(());
+                }
+
+                conn.record_read(size);
+            }
+            event = rx.receive() => {
+                let event = match event {
+                    Some(event) => event,
+                    None => break,
+                };
+
+                if pending.len() + flushing.len() - flushed >= policy.buffer_max {
+                    match policy.slow {
+                        SlowWatchers::Drop => continue,
+                        SlowWatchers::Disconnect => return Err(WatcherTooSlow.into()),
+                    }
+                }
+
+                response::write_list(pending, [[event.command_id() as u8].as_ref(), event.key()].iter());
+            }
+            size = writer.write(&flushing[flushed..]), if flushed < flushing.len() => {
+                let size = size?;
+
+                if size == 0 {
+                    return Err(IoError::from(ErrorKind::WriteZero).into());
+                }
+
+                flushed += size;
+                conn.record_written(size);
+            }
+        }
+    }
+
+    writer.write_all(&flushing[flushed..]).await?;
+    writer.write_all(pending).await?;
+    conn.record_written(flushing.len() - flushed + pending.len());
+    pending.clear();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{watch_keyspace, SlowWatchers, WatchPolicy, WatcherTooSlow};
+    use hop_engine::{
+        command::{
+            response::{Context, Instruction, Response},
+            CommandId,
+        },
+        pubsub::PubSubManager,
+        state::Value,
+        Hop,
+    };
+    use std::{
+        io::Result as IoResult,
+        pin::Pin,
+        str::FromStr,
+        task::{Context as TaskContext, Poll},
+    };
+    use tokio::io::{AsyncRead, AsyncWrite};
+
+    /// Peer that never sends anything.
+    struct Silent;
+
+    impl AsyncRead for Silent {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _: &mut TaskContext<'_>,
+            _: &mut [u8],
+        ) -> Poll<IoResult<usize>> {
+            Poll::Pending
+        }
+    }
+
+    /// Peer that reads at most one byte at a time, and none at all if it's
+    /// stalled.
+    #[derive(Default)]
+    struct SlowReader {
+        read: Vec<u8>,
+        stalled: bool,
+        yielded: bool,
+    }
+
+    impl AsyncWrite for SlowReader {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut TaskContext<'_>,
+            buf: &[u8],
+        ) -> Poll<IoResult<usize>> {
+            if self.stalled {
+                return Poll::Pending;
+            }
+
+            // Yield before each byte so that the publisher gets ahead.
+            if !self.yielded {
+                self.yielded = true;
+                cx.waker().wake_by_ref();
+
+                return Poll::Pending;
+            }
+
+            self.yielded = false;
+            self.read.push(buf[0]);
+
+            Poll::Ready(Ok(1))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut TaskContext<'_>) -> Poll<IoResult<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _: &mut TaskContext<'_>) -> Poll<IoResult<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn publish(manager: &PubSubManager, count: usize) {
+        for idx in 0..count {
+            let key = format!("foo:{}", idx);
+            manager.notify_keyspace(CommandId::Set, key.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_slow_watchers_from_str() {
+        assert_eq!(Ok(SlowWatchers::Drop), SlowWatchers::from_str("drop"));
+        assert_eq!(
+            Ok(SlowWatchers::Disconnect),
+            SlowWatchers::from_str("disconnect")
+        );
+        assert!(SlowWatchers::from_str("ignore").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_slow_watcher_disconnected() {
+        let manager = PubSubManager::default();
+        let rx = manager.watch_keyspace(b"foo*".to_vec());
+        publish(&manager, 100);
+
+        let policy = WatchPolicy {
+            buffer_max: 64,
+            slow: SlowWatchers::Disconnect,
+        };
+        let writer = SlowReader {
+            stalled: true,
+            ..SlowReader::default()
+        };
+        let mut conn = Hop::new().connect();
+
+        let res = watch_keyspace(Silent, writer, rx, policy, &mut Vec::new(), &mut conn).await;
+        assert!(res.unwrap_err().is::<WatcherTooSlow>());
+    }
+
+    #[tokio::test]
+    async fn test_slow_watcher_events_dropped() {
+        let manager = PubSubManager::default();
+        let rx = manager.watch_keyspace(b"foo*".to_vec());
+        publish(&manager, 100);
+
+        // the watch ends once the published events have been received
+        drop(manager);
+
+        let policy = WatchPolicy {
+            buffer_max: 64,
+            slow: SlowWatchers::Drop,
+        };
+        let mut writer = SlowReader::default();
+        let mut conn = Hop::new().connect();
+        let mut pending = Vec::new();
+
+        watch_keyspace(Silent, &mut writer, rx, policy, &mut pending, &mut conn)
+            .await
+            .unwrap();
+
+        let mut output = writer.read.as_slice();
+        let mut keys = Vec::new();
+
+        // only whole events are written
+        while !output.is_empty() {
+            let mut ctx = Context::new();
+            let len = 4 + u32::from_be_bytes([output[0], output[1], output[2], output[3]]) as usize;
+
+            match ctx.feed(&output[..len]).unwrap() {
+                Instruction::Concluded(Response::Value(Value::List(list))) => {
+                    keys.push(String::from_utf8(list[1].clone()).unwrap())
+                }
+                other => panic!("unexpected event {:?}", other),
+            }
+
+            output = &output[len..];
+        }
+
+        assert!(!keys.is_empty());
+        assert!(keys.len() < 100);
+        assert_eq!("foo:0", keys[0]);
+    }
+}
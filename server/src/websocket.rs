@@ -0,0 +1,374 @@
+use super::{pop, process, Pool, Processed};
+use futures_util::{SinkExt, StreamExt};
+use hop_engine::{
+    command::{request::Context, response},
+    connection::Connection,
+    pubsub::KeyspaceReceiver,
+    Hop,
+};
+use log::{debug, warn};
+use std::error::Error;
+use tokio::{
+    net::{TcpListener, TcpStream},
+    task,
+    time::{self, Instant},
+};
+use tokio_tungstenite::{tungstenite::Message, WebSocketStream};
+
+/// Accept WebSocket connections on the listener, dispatching commands sent
+/// as binary messages to the shared Hop instance.
+///
+/// Each response is sent back as its own binary message. A command may be
+/// split over multiple messages and a message may contain multiple commands.
+///
+/// Connections behave like those of other transports: they have their own
+/// selected database and default key type, blocking pops wait for an item to
+/// be pushed, and a connection watching the keyspace is sent each event as
+/// its own binary message.
+pub async fn serve(mut listener: TcpListener, hop: Hop, pool: Pool) {
+    if let Ok(addr) = listener.local_addr() {
+        debug!("Listening for new websocket connections on {}", addr);
+    }
+
+    loop {
+        match listener.accept().await {
+            Ok((socket, _)) => {
+                task::spawn(handle_socket(socket, hop.clone(), pool.clone()));
+            }
+            Err(why) => warn!("Failed to accept websocket connection: {:?}", why),
+        }
+    }
+}
+
+async fn handle_socket(socket: TcpStream, hop: Hop, pool: Pool) {
+    let addr = socket.peer_addr().unwrap();
+
+    debug!("Connected to websocket peer {}", addr);
+
+    match handle_socket_inner(socket, hop, pool).await {
+        Ok(()) => debug!("Dropping websocket {}", addr),
+        Err(why) => warn!("Erroring websocket {}: {:?}", addr, why),
+    }
+}
+
+async fn handle_socket_inner(
+    socket: TcpStream,
+    mut hop: Hop,
+    pool: Pool,
+) -> Result<(), Box<dyn Error>> {
+    let mut stream = tokio_tungstenite::accept_async(socket).await?;
+    let mut conn = hop.connect();
+
+    let mut input = pool.get();
+    let mut ctx = Context::new();
+    let mut resp = pool.get();
+
+    while let Some(message) = stream.next().await {
+        match message? {
+            Message::Binary(bytes) => {
+                conn.record_read(bytes.len());
+                input.extend_from_slice(&bytes);
+            }
+            Message::Close(_) => break,
+            _ => continue,
+        }
+
+        while let Some(processed) = process(&hop, &mut ctx, &mut input, &mut resp) {
+            conn.record_command();
+
+            let mut watch = None;
+            let mut quit = false;
+
+            match processed {
+                Processed::Response => {}
+                Processed::Select(selected) => hop = selected,
+                Processed::DefaultType(key_type) => ctx.set_default_key_type(key_type),
+                Processed::BlockingPop { key, timeout } => {
+                    let open = blocking_pop(
+                        &mut stream,
+                        &hop,
+                        &key,
+                        timeout,
+                        &mut input,
+                        &mut resp,
+                        &mut conn,
+                    )
+                    .await?;
+
+                    if !open {
+                        return Ok(());
+                    }
+                }
+                Processed::Watch(rx) => watch = Some(rx),
+                Processed::Quit | Processed::Close => quit = true,
+            }
+
+            stream.send(Message::Binary(resp.to_vec())).await?;
+            conn.record_written(resp.len());
+            resp.clear();
+
+            // Anything sent after the quit is ignored.
+            if quit {
+                stream.close(None).await?;
+
+                return Ok(());
+            }
+
+            if let Some(rx) = watch {
+                return watch_keyspace(&mut stream, rx, &mut resp, &mut conn).await;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Wait for an item to be pushed to a list and pop it, like
+/// [`pop::blocking_pop`] does for connections of other transports.
+///
+/// Binary messages the peer sends meanwhile are appended to the input for
+/// processing afterwards. Returns `false` if the peer closed the connection
+/// while waiting.
+///
+/// [`pop::blocking_pop`]: ../pop/fn.blocking_pop.html
+async fn blocking_pop(
+    stream: &mut WebSocketStream<TcpStream>,
+    hop: &Hop,
+    key: &[u8],
+    timeout: u64,
+    input: &mut Vec<u8>,
+    resp: &mut Vec<u8>,
+    conn: &mut Connection,
+) -> Result<bool, Box<dyn Error>> {
+    let req = pop::request(key, timeout);
+    let deadline = pop::deadline(timeout);
+
+    loop {
+        // Register the wait before popping so that a push between the two
+        // isn't missed.
+        let waiter = hop.state().wait_for_push(key);
+
+        if pop::try_pop(hop, &req, resp) {
+            return Ok(true);
+        }
+
+        tokio::select! {
+            _ = waiter.pushed() => {}
+            _ = time::delay_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                response::write_nil(resp);
+
+                return Ok(true);
+            }
+            message = stream.next() => match message {
+                Some(message) => match message? {
+                    Message::Binary(bytes) => {
+                        conn.record_read(bytes.len());
+                        input.extend_from_slice(&bytes);
+                    }
+                    Message::Close(_) => return Ok(false),
+                    _ => {}
+                },
+                None => return Ok(false),
+            },
+        }
+    }
+}
+
+/// Send keyspace events to the peer until the watch ends or the peer closes
+/// the connection.
+///
+/// Each event is sent as its own binary message, written the same as events
+/// sent to connections of other transports. Anything else sent by the peer is
+/// ignored, since the connection is now only used for events.
+async fn watch_keyspace(
+    stream: &mut WebSocketStream<TcpStream>,
+    rx: KeyspaceReceiver,
+    resp: &mut Vec<u8>,
+    conn: &mut Connection,
+) -> Result<(), Box<dyn Error>> {
+    loop {
+        let event = tokio::select! {
+            message = stream.next() => match message {
+                Some(message) => match message? {
+                    Message::Binary(bytes) => {
+                        conn.record_read(bytes.len());
+
+                        continue;
+                    }
+                    Message::Close(_) => return Ok(()),
+                    _ => continue,
+                },
+                None => return Ok(()),
+            },
+            event = rx.receive() => match event {
+                Some(event) => event,
+                None => break,
+            },
+        };
+
+        response::write_list(
+            resp,
+            [[event.command_id() as u8].as_ref(), event.key()].iter(),
+        );
+        stream.send(Message::Binary(resp.to_vec())).await?;
+        conn.record_written(resp.len());
+        resp.clear();
+    }
+
+    stream.close(None).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{serve, Pool};
+    use futures_util::{SinkExt, StreamExt};
+    use hop_engine::{
+        command::{
+            request::RequestBuilder,
+            response::{Context, Instruction, Response},
+            CommandId,
+        },
+        state::{KeyType, Value},
+        Hop,
+    };
+    use std::time::Duration;
+    use tokio::{
+        net::{TcpListener, TcpStream},
+        time,
+    };
+    use tokio_tungstenite::{tungstenite::Message, WebSocketStream};
+
+    async fn connect(hop: Hop) -> WebSocketStream<TcpStream> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, hop, Pool::new()));
+
+        let socket = TcpStream::connect(addr).await.unwrap();
+        let (stream, _) = tokio_tungstenite::client_async("ws://localhost/", socket)
+            .await
+            .unwrap();
+
+        stream
+    }
+
+    async fn receive(stream: &mut WebSocketStream<TcpStream>) -> Response {
+        let resp = match stream.next().await.unwrap().unwrap() {
+            Message::Binary(bytes) => bytes,
+            other => panic!("unexpected message: {:?}", other),
+        };
+
+        match Context::new().feed(&resp).unwrap() {
+            Instruction::Concluded(resp) => resp,
+            _ => panic!("incomplete response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_set_get() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, Hop::new(), Pool::new()));
+
+        let socket = TcpStream::connect(addr).await.unwrap();
+        let (mut stream, _) = tokio_tungstenite::client_async("ws://localhost/", socket)
+            .await
+            .unwrap();
+
+        let mut set = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::String);
+        set.bytes(b"foo".as_ref()).unwrap();
+        set.bytes(b"bar".as_ref()).unwrap();
+        let mut get = RequestBuilder::new(CommandId::Get);
+        get.bytes(b"foo".as_ref()).unwrap();
+
+        for req in [set.into_request(), get.into_request()].iter() {
+            let bytes = req.as_bytes().to_vec();
+            stream.send(Message::Binary(bytes)).await.unwrap();
+
+            let resp = match stream.next().await.unwrap().unwrap() {
+                Message::Binary(bytes) => bytes,
+                other => panic!("unexpected message: {:?}", other),
+            };
+
+            assert!(matches!(
+                Context::new().feed(&resp).unwrap(),
+                Instruction::Concluded(Response::Value(Value::String(string))) if string == "bar"
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_type() {
+        let hop = Hop::new();
+        let mut stream = connect(hop.clone()).await;
+
+        let mut default_type = RequestBuilder::new(CommandId::DefaultType);
+        default_type
+            .bytes([KeyType::String as u8].as_ref())
+            .unwrap();
+        let mut set = RequestBuilder::new(CommandId::Set);
+        set.bytes(b"foo".as_ref()).unwrap();
+        set.bytes(b"bar".as_ref()).unwrap();
+
+        for req in [default_type.into_request(), set.into_request()].iter() {
+            let bytes = req.as_bytes().to_vec();
+            stream.send(Message::Binary(bytes)).await.unwrap();
+            receive(&mut stream).await;
+        }
+
+        assert_eq!(Some(KeyType::String), hop.state().key_type(b"foo"));
+    }
+
+    #[tokio::test]
+    async fn test_blocking_pop_woken_by_push() {
+        let hop = Hop::new();
+        let mut stream = connect(hop.clone()).await;
+
+        let mut pop = RequestBuilder::new(CommandId::BlockingPop);
+        pop.bytes(b"queue".as_ref()).unwrap();
+        pop.bytes(0i64.to_be_bytes().as_ref()).unwrap();
+        let bytes = pop.into_request().as_bytes().to_vec();
+        stream.send(Message::Binary(bytes)).await.unwrap();
+
+        // give the pop time to start waiting
+        time::delay_for(Duration::from_millis(50)).await;
+
+        let mut append = RequestBuilder::new_with_key_type(CommandId::Append, KeyType::List);
+        append.bytes(b"queue".as_ref()).unwrap();
+        append.bytes(b"job".as_ref()).unwrap();
+        hop.dispatch(&append.into_request(), &mut Vec::new())
+            .unwrap();
+
+        assert!(matches!(
+            receive(&mut stream).await,
+            Response::Value(Value::Bytes(bytes)) if bytes == b"job"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_watch_keyspace() {
+        let hop = Hop::new();
+        let mut stream = connect(hop.clone()).await;
+
+        let mut watch = RequestBuilder::new(CommandId::Watch);
+        watch.bytes(b"foo*".as_ref()).unwrap();
+        let bytes = watch.into_request().as_bytes().to_vec();
+        stream.send(Message::Binary(bytes)).await.unwrap();
+        assert!(matches!(
+            receive(&mut stream).await,
+            Response::Value(Value::Boolean(true))
+        ));
+
+        let mut set = RequestBuilder::new_with_key_type(CommandId::Set, KeyType::Bytes);
+        set.bytes(b"foobar".as_ref()).unwrap();
+        set.bytes(b"baz".as_ref()).unwrap();
+        hop.dispatch(&set.into_request(), &mut Vec::new()).unwrap();
+
+        assert!(matches!(
+            receive(&mut stream).await,
+            Response::Value(Value::List(list))
+                if list == [[CommandId::Set as u8].to_vec(), b"foobar".to_vec()]
+        ));
+    }
+}